@@ -56,6 +56,9 @@ async function main(argv: readonly string[]): Promise<void> {
     path.join(sourceRoot, "preprocess"),
     "--proof",
     path.join(sourceRoot, "prove"),
+    // The copied fixtures are proved with the legacy transcript of the Solidity verifier.
+    "--min-fiat-shamir",
+    "legacy",
   ];
   const result = await runCommand("cargo", args, backendWasmRoot);
   const report: NativeVerifierReport = {
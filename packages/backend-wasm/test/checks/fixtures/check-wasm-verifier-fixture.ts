@@ -0,0 +1,132 @@
+import { readFile } from "node:fs/promises";
+import path from "node:path";
+import { fileURLToPath } from "node:url";
+import { loadVerifier } from "../../../tools/verifier-wasm/src/node.js";
+
+interface CopyManifest {
+  readonly schemaVersion: 2;
+  readonly suite: string;
+  readonly workDirectory: string;
+}
+
+interface WasmVerifierReport {
+  readonly suite: string;
+  readonly sourceRoot: string;
+  readonly subcircuitLibrary: string;
+  readonly accepted: boolean;
+  readonly tamperedAccepted: boolean;
+}
+
+async function main(argv: readonly string[]): Promise<void> {
+  if (argv.length !== 1) {
+    throw new Error("Usage: check-wasm-verifier-fixture <copy-manifest.json>");
+  }
+
+  const manifestPath = path.resolve(argv[0]);
+  const manifestDirectory = path.dirname(manifestPath);
+  const backendWasmRoot = path.resolve(manifestDirectory, "../..");
+  const repositoryRoot = path.resolve(backendWasmRoot, "../..");
+  const manifest = parseCopyManifest(JSON.parse(await readFile(manifestPath, "utf8")) as unknown);
+  const sourceRoot = resolveWorkDirectory(repositoryRoot, backendWasmRoot, manifest.workDirectory);
+  const subcircuitLibrary = path.join(
+    backendWasmRoot,
+    "node_modules",
+    "@tokamak-zk-evm",
+    "subcircuit-library",
+    "subcircuits",
+    "library",
+  );
+  const inputs = {
+    setupParams: await readFile(path.join(subcircuitLibrary, "setupParams.json"), "utf8"),
+    sigmaVerify: await readFile(path.join(sourceRoot, "setup", "sigma_verify.json"), "utf8"),
+    instance: await readFile(path.join(sourceRoot, "synthesizer", "instance.json"), "utf8"),
+    preprocess: await readFile(path.join(sourceRoot, "preprocess", "preprocess.json"), "utf8"),
+    proof: await readFile(path.join(sourceRoot, "prove", "proof.json"), "utf8"),
+  };
+  const verifier = await loadVerifier();
+  const report: WasmVerifierReport = {
+    suite: manifest.suite,
+    sourceRoot: path.relative(process.cwd(), sourceRoot),
+    subcircuitLibrary: path.relative(process.cwd(), subcircuitLibrary),
+    accepted: await verifier.verify(inputs),
+    tamperedAccepted: await verifier.verify({ ...inputs, proof: tamperProof(inputs.proof) }),
+  };
+
+  console.log(`${JSON.stringify(report, null, 2)}\n`);
+
+  if (!report.accepted) {
+    throw new Error("WASM verifier rejected the copied fixture.");
+  }
+
+  if (report.tamperedAccepted) {
+    throw new Error("WASM verifier accepted the fixture proof with a modified V_eval.");
+  }
+}
+
+// Adds one to proof3.V_eval, which must make the pairing check fail.
+function tamperProof(proofJson: string): string {
+  const proof = JSON.parse(proofJson) as { proof3?: { V_eval?: unknown } };
+
+  if (typeof proof.proof3?.V_eval !== "string") {
+    throw new Error("Fixture proof has no proof3.V_eval hex string.");
+  }
+
+  const modulus = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001n;
+  const tampered = (BigInt(proof.proof3.V_eval) + 1n) % modulus;
+  proof.proof3.V_eval = `0x${tampered.toString(16).padStart(64, "0")}`;
+  return JSON.stringify(proof);
+}
+
+function parseCopyManifest(raw: unknown): CopyManifest {
+  if (!isRecord(raw)) {
+    throw new Error("Copy manifest must be a JSON object.");
+  }
+
+  if (raw.schemaVersion !== 2) {
+    throw new Error("Copy manifest schemaVersion must be 2.");
+  }
+
+  if (typeof raw.suite !== "string" || raw.suite.trim() === "") {
+    throw new Error("Copy manifest suite must be a non-empty string.");
+  }
+
+  if (typeof raw.workDirectory !== "string" || raw.workDirectory.trim() === "" || path.isAbsolute(raw.workDirectory)) {
+    throw new Error("Copy manifest workDirectory must be a non-empty relative path.");
+  }
+
+  return {
+    schemaVersion: 2,
+    suite: raw.suite,
+    workDirectory: path.normalize(raw.workDirectory),
+  };
+}
+
+function resolveWorkDirectory(repositoryRoot: string, backendWasmRoot: string, workDirectory: string): string {
+  const workDirectoryPath = path.resolve(repositoryRoot, workDirectory);
+  const allowedRoot = path.resolve(backendWasmRoot, "tmp", "fixtures");
+
+  if (!isPathInside(workDirectoryPath, allowedRoot)) {
+    throw new Error(`Copy manifest workDirectory must stay under packages/backend-wasm/tmp/fixtures: ${workDirectory}`);
+  }
+
+  return workDirectoryPath;
+}
+
+function isPathInside(candidate: string, parent: string): boolean {
+  const relative = path.relative(parent, candidate);
+  return relative === "" || (!relative.startsWith("..") && !path.isAbsolute(relative));
+}
+
+function isRecord(value: unknown): value is Record<string, unknown> {
+  return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+
+const entrypoint = fileURLToPath(import.meta.url);
+
+if (process.argv[1] === entrypoint) {
+  main(process.argv.slice(2)).catch((error: unknown) => {
+    const message = error instanceof Error ? error.message : String(error);
+    console.error(`WASM verifier fixture check failed: ${message}`);
+    process.exitCode = 1;
+  });
+}
@@ -0,0 +1,189 @@
+//! Mirrors of the native JSON artifacts read by the verifier. Points accept the `{x, y}` hex
+//! coordinates or the compressed hex string, and are checked on load like the native
+//! `PointValidation::Checked`; scalars must be reduced.
+
+use crate::encoding::{
+    fr_from_hex, g1_from_compressed_hex, g1_from_coords, g2_from_compressed_hex, g2_from_coords,
+};
+use ark_bls12_381::{Fr, G1Affine, G2Affine};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PointRepr {
+    Coords { x: String, y: String },
+    Compressed(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct G1(pub G1Affine);
+
+impl<'de> Deserialize<'de> for G1 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match PointRepr::deserialize(deserializer)? {
+            PointRepr::Coords { x, y } => g1_from_coords(&x, &y),
+            PointRepr::Compressed(hex) => g1_from_compressed_hex(&hex),
+        }
+        .map(G1)
+        .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct G2(pub G2Affine);
+
+impl<'de> Deserialize<'de> for G2 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match PointRepr::deserialize(deserializer)? {
+            PointRepr::Coords { x, y } => g2_from_coords(&x, &y),
+            PointRepr::Compressed(hex) => g2_from_compressed_hex(&hex),
+        }
+        .map(G2)
+        .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scalar(pub Fr);
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        fr_from_hex(&hex).map(Scalar).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SetupParams {
+    pub l_free: usize,
+    pub l: usize,
+    pub l_user_out: usize,
+    pub l_user: usize,
+    pub l_D: usize,
+    pub m_D: usize,
+    pub n: usize,
+    pub s_D: usize,
+    pub s_max: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Instance {
+    pub a_pub_user: Vec<Scalar>,
+    pub a_pub_block: Vec<Scalar>,
+    pub a_pub_function: Vec<Scalar>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PartialSigma1Verify {
+    pub x: G1,
+    pub y: G1,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Sigma2 {
+    pub alpha: G2,
+    pub alpha2: G2,
+    pub alpha3: G2,
+    pub alpha4: G2,
+    pub gamma: G2,
+    pub delta: G2,
+    pub eta: G2,
+    pub x: G2,
+    pub y: G2,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SigmaVerify {
+    pub G: G1,
+    pub H: G2,
+    pub sigma_1: PartialSigma1Verify,
+    pub sigma_2: Sigma2,
+    pub lagrange_KL: G1,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Preprocess {
+    pub s0: G1,
+    pub s1: G1,
+    pub O_pub_fix: G1,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Binding {
+    pub A_free: G1,
+    pub O_pub_free: G1,
+    pub O_mid: G1,
+    pub O_prv: G1,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Proof0 {
+    pub U: G1,
+    pub V: G1,
+    pub W: G1,
+    pub Q_AX: G1,
+    pub Q_AY: G1,
+    pub B: G1,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Proof1 {
+    pub R: G1,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Proof2 {
+    pub Q_CX: G1,
+    pub Q_CY: G1,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Proof3 {
+    pub V_eval: Scalar,
+    pub R_eval: Scalar,
+    pub R_omegaX_eval: Scalar,
+    pub R_omegaX_omegaY_eval: Scalar,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Proof4 {
+    pub Pi_X: G1,
+    pub Pi_Y: G1,
+    pub M_X: G1,
+    pub M_Y: G1,
+    pub N_X: G1,
+    pub N_Y: G1,
+}
+
+/// The native `TranscriptKind`. Only `Keccak` is verified here.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptKind {
+    #[default]
+    Keccak,
+    Poseidon,
+}
+
+/// The native `FiatShamirMode`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FiatShamirMode {
+    #[default]
+    Legacy,
+    StrongV1,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Proof {
+    pub binding: Binding,
+    pub proof0: Proof0,
+    pub proof1: Proof1,
+    pub proof2: Proof2,
+    pub proof3: Proof3,
+    pub proof4: Proof4,
+    #[serde(default)]
+    pub transcript: TranscriptKind,
+    #[serde(default)]
+    pub fiat_shamir: FiatShamirMode,
+}
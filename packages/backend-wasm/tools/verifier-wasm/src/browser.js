@@ -0,0 +1,36 @@
+let wasmModulePromise;
+let initializedPromise;
+
+export async function loadVerifier(options = {}) {
+  const wasmModule = await loadWasmModule();
+
+  if (initializedPromise === undefined) {
+    initializedPromise = wasmModule.default(options.wasmUrl);
+  }
+
+  await initializedPromise;
+
+  return {
+    async verify(inputs) {
+      return wasmModule.verify(
+        toJson(inputs.setupParams),
+        toJson(inputs.sigmaVerify),
+        toJson(inputs.instance),
+        toJson(inputs.preprocess),
+        toJson(inputs.proof),
+      );
+    },
+  };
+}
+
+function toJson(artifact) {
+  return typeof artifact === "string" ? artifact : JSON.stringify(artifact);
+}
+
+async function loadWasmModule() {
+  if (wasmModulePromise === undefined) {
+    wasmModulePromise = import("../pkg/backend_wasm_verifier.js");
+  }
+
+  return wasmModulePromise;
+}
@@ -0,0 +1,160 @@
+use ark_bls12_381::{Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, Compress, Validate};
+
+const FR_BYTES: usize = 32;
+const FQ_BYTES: usize = 48;
+const G1_COMPRESSED_BYTES: usize = 48;
+const G2_COMPRESSED_BYTES: usize = 96;
+
+/// Decodes `0x`-prefixed or bare big-endian hex into exactly `len` bytes, left-padded with
+/// zeros. An odd number of digits reads as if it had a leading zero, as the native `HexString`.
+pub fn hex_to_be_bytes(value: &str, len: usize) -> Result<Vec<u8>, String> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return Err(format!("{value:?} is not a hex string"));
+    }
+    let digits = if digits.len() % 2 == 1 {
+        format!("0{digits}")
+    } else {
+        digits.to_string()
+    };
+    if digits.len() / 2 > len {
+        return Err(format!("{value} does not fit in {len} bytes"));
+    }
+    let mut bytes = vec![0u8; len - digits.len() / 2];
+    for pair in digits.as_bytes().chunks(2) {
+        let pair = std::str::from_utf8(pair).expect("hex digits are ASCII");
+        bytes.push(u8::from_str_radix(pair, 16).expect("hex digits were checked"));
+    }
+    Ok(bytes)
+}
+
+/// A scalar from hex, rejecting values that are not reduced modulo r.
+pub fn fr_from_hex(value: &str) -> Result<Fr, String> {
+    let mut bytes = hex_to_be_bytes(value, FR_BYTES)?;
+    bytes.reverse();
+    fr_from_le_canonical(&bytes).ok_or_else(|| format!("{value} is not reduced modulo r"))
+}
+
+fn fr_from_le_canonical(bytes: &[u8]) -> Option<Fr> {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    Fr::from_bigint(ark_ff::BigInt::new(limbs))
+}
+
+fn fq_from_be_canonical(bytes: &[u8]) -> Option<Fq> {
+    let mut limbs = [0u64; 6];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks_exact(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    Fq::from_bigint(ark_ff::BigInt::new(limbs))
+}
+
+fn fq_from_hex(value: &str) -> Result<Fq, String> {
+    let bytes = hex_to_be_bytes(value, FQ_BYTES)?;
+    fq_from_be_canonical(&bytes).ok_or_else(|| format!("{value} is not reduced modulo p"))
+}
+
+// The native `G2serde` prints an Fq2 coordinate as the big-endian `c1 || c0`.
+fn fq2_from_hex(value: &str) -> Result<Fq2, String> {
+    let bytes = hex_to_be_bytes(value, 2 * FQ_BYTES)?;
+    let c1 = fq_from_be_canonical(&bytes[..FQ_BYTES]);
+    let c0 = fq_from_be_canonical(&bytes[FQ_BYTES..]);
+    match (c0, c1) {
+        (Some(c0), Some(c1)) => Ok(Fq2::new(c0, c1)),
+        _ => Err(format!("{value} is not reduced modulo p")),
+    }
+}
+
+/// A G1 point from its `{x, y}` hex coordinates, checked to be on the curve and in the
+/// prime-order subgroup. The all-zero coordinates are the point at infinity.
+pub fn g1_from_coords(x: &str, y: &str) -> Result<G1Affine, String> {
+    let (x, y) = (fq_from_hex(x)?, fq_from_hex(y)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err("G1 point is not on the curve".to_string());
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err("G1 point is not in the prime-order subgroup".to_string());
+    }
+    Ok(point)
+}
+
+/// A G2 point from its `{x, y}` hex coordinates, with the checks of `g1_from_coords`.
+pub fn g2_from_coords(x: &str, y: &str) -> Result<G2Affine, String> {
+    let (x, y) = (fq2_from_hex(x)?, fq2_from_hex(y)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err("G2 point is not on the twist".to_string());
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err("G2 point is not in the prime-order subgroup".to_string());
+    }
+    Ok(point)
+}
+
+/// A G1 point from the hex of its 48-byte compressed form.
+pub fn g1_from_compressed_hex(value: &str) -> Result<G1Affine, String> {
+    let bytes = hex_to_be_bytes(value, G1_COMPRESSED_BYTES)?;
+    G1Affine::deserialize_with_mode(&bytes[..], Compress::Yes, Validate::Yes)
+        .map_err(|err| format!("compressed G1 point: {err}"))
+}
+
+/// A G2 point from the hex of its 96-byte compressed form.
+pub fn g2_from_compressed_hex(value: &str) -> Result<G2Affine, String> {
+    let bytes = hex_to_be_bytes(value, G2_COMPRESSED_BYTES)?;
+    G2Affine::deserialize_with_mode(&bytes[..], Compress::Yes, Validate::Yes)
+        .map_err(|err| format!("compressed G2 point: {err}"))
+}
+
+/// The big-endian coordinates of `point`, zero for the point at infinity as in icicle.
+pub fn g1_be_coords(point: &G1Affine) -> [[u8; FQ_BYTES]; 2] {
+    let mut coords = [[0u8; FQ_BYTES]; 2];
+    if let Some((x, y)) = point.xy() {
+        for (out, coord) in coords.iter_mut().zip([x, y]) {
+            out.copy_from_slice(&coord.into_bigint().to_bytes_be());
+        }
+    }
+    coords
+}
+
+/// `x || y` in icicle's little-endian layout, zero for the point at infinity.
+pub fn g1_le_bytes(point: &G1Affine) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(2 * FQ_BYTES);
+    for mut coord in g1_be_coords(point) {
+        coord.reverse();
+        bytes.extend_from_slice(&coord);
+    }
+    bytes
+}
+
+/// As `g1_le_bytes`, with each Fq2 coordinate as `c0 || c1`.
+pub fn g2_le_bytes(point: &G2Affine) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4 * FQ_BYTES);
+    match point.xy() {
+        Some((x, y)) => {
+            for coord in [x.c0, x.c1, y.c0, y.c1] {
+                bytes.extend(coord.into_bigint().to_bytes_le());
+            }
+        }
+        None => bytes.resize(4 * FQ_BYTES, 0),
+    }
+    bytes
+}
+
+/// The 32 big-endian bytes of a scalar.
+pub fn fr_be_bytes(scalar: &Fr) -> [u8; FR_BYTES] {
+    let mut bytes = [0u8; FR_BYTES];
+    bytes.copy_from_slice(&scalar.into_bigint().to_bytes_be());
+    bytes
+}
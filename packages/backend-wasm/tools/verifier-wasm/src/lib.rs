@@ -0,0 +1,225 @@
+#![deny(unsafe_code)]
+#![allow(non_snake_case)]
+
+//! The `verify-rust` verifier without icicle, for `wasm32-unknown-unknown`. It reads the same
+//! JSON artifacts as the native verifier (`setupParams.json`, `sigma_verify.json`,
+//! `instance.json`, `preprocess.json` and `proof.json`) and returns the verdict of the final
+//! pairing check, with kappa2 derived from the transcript.
+//!
+//! Only proofs made with the Keccak transcript are supported: the Poseidon transcript depends on
+//! icicle's Poseidon parameters.
+
+pub mod artifacts;
+pub mod encoding;
+pub mod transcript;
+pub mod verifier;
+
+use artifacts::TranscriptKind;
+use serde::de::DeserializeOwned;
+pub use verifier::Verifier;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// An artifact is not valid JSON of the expected shape, or holds an invalid point or scalar.
+    Parse {
+        artifact: &'static str,
+        message: String,
+    },
+    /// The setup parameters or the instance do not have the shape the verifier needs.
+    Shape(String),
+    /// The proof was made with an option this verifier does not implement.
+    Unsupported(String),
+}
+
+impl core::fmt::Display for VerifyError {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VerifyError::Parse { artifact, message } => {
+                write!(formatter, "failed to parse {artifact}: {message}")
+            }
+            VerifyError::Shape(message) => write!(formatter, "invalid shape: {message}"),
+            VerifyError::Unsupported(message) => write!(formatter, "unsupported: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+fn parse<T: DeserializeOwned>(artifact: &'static str, json: &str) -> Result<T, VerifyError> {
+    serde_json::from_str(json).map_err(|err| VerifyError::Parse {
+        artifact,
+        message: err.to_string(),
+    })
+}
+
+impl Verifier {
+    /// Parses the artifacts from their JSON text and checks their shapes.
+    pub fn from_json(
+        setup_params: &str,
+        sigma_verify: &str,
+        instance: &str,
+        preprocess: &str,
+        proof: &str,
+    ) -> Result<Self, VerifyError> {
+        let proof: artifacts::Proof = parse("proof.json", proof)?;
+        if proof.transcript != TranscriptKind::Keccak {
+            return Err(VerifyError::Unsupported(format!(
+                "the {:?} transcript",
+                proof.transcript
+            )));
+        }
+        Verifier::new(
+            parse("setupParams.json", setup_params)?,
+            parse("sigma_verify.json", sigma_verify)?,
+            parse("instance.json", instance)?,
+            parse("preprocess.json", preprocess)?,
+            proof,
+        )
+    }
+}
+
+/// Verifies a proof from the JSON text of its artifacts. Returns the verdict, or throws if an
+/// artifact cannot be parsed or the proof uses an unsupported transcript.
+#[wasm_bindgen(js_name = verify)]
+pub fn verify_wasm(
+    setup_params: &str,
+    sigma_verify: &str,
+    instance: &str,
+    preprocess: &str,
+    proof: &str,
+) -> Result<bool, JsValue> {
+    Verifier::from_json(setup_params, sigma_verify, instance, preprocess, proof)
+        .map(|verifier| verifier.verify_snark())
+        .map_err(|error| JsValue::from_str(&error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{g1_from_coords, g2_from_coords, hex_to_be_bytes};
+    use crate::verifier::{eval_on_roots_of_unity, root_of_unity};
+    use ark_bls12_381::{Fq, Fr, G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, Field, One, PrimeField, UniformRand, Zero};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn roots_of_unity_match_icicle() {
+        // The 2^32-th root icicle's and ffjavascript's NTT domains are built from.
+        let expected = "0212d79e5b416b6f0fd56dc8d168d6c0c4024ff270b3e0941b788f500b912f1f";
+        let bytes = hex_to_be_bytes(expected, 32).unwrap();
+        assert_eq!(root_of_unity(1 << 32), Fr::from_be_bytes_mod_order(&bytes));
+        for log_size in 0..12 {
+            let size = 1usize << log_size;
+            let root = root_of_unity(size);
+            assert_eq!(root.pow([size as u64]), Fr::one());
+            if size > 1 {
+                assert_ne!(root.pow([size as u64 / 2]), Fr::one());
+            }
+        }
+    }
+
+    #[test]
+    fn barycentric_evaluation_matches_interpolation() {
+        let mut rng = ark_std::test_rng();
+        for size in [1usize, 2, 8, 32] {
+            let evals: Vec<Fr> = (0..size).map(|_| Fr::rand(&mut rng)).collect();
+            // coeffs[j] = 1/N * sum_i evals[i] * omega^(-ij)
+            let omega_inv = root_of_unity(size).inverse().unwrap();
+            let size_inv = Fr::from(size as u64).inverse().unwrap();
+            let coeffs: Vec<Fr> = (0..size)
+                .map(|j| {
+                    let step = omega_inv.pow([j as u64]);
+                    let mut power = Fr::one();
+                    let mut sum = Fr::zero();
+                    for eval in &evals {
+                        sum += *eval * power;
+                        power *= step;
+                    }
+                    sum * size_inv
+                })
+                .collect();
+            let point = Fr::rand(&mut rng);
+            let horner = coeffs
+                .iter()
+                .rev()
+                .fold(Fr::zero(), |acc, coeff| acc * point + coeff);
+            assert_eq!(eval_on_roots_of_unity(&evals, point), horner);
+            let last_root = root_of_unity(size).pow([size as u64 - 1]);
+            assert_eq!(eval_on_roots_of_unity(&evals, last_root), evals[size - 1]);
+        }
+    }
+
+    #[test]
+    fn parses_native_point_coordinates() {
+        let hex = |field: &Fq| {
+            let digits: String = field
+                .into_bigint()
+                .to_bytes_be()
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect();
+            digits
+        };
+        let g1 = G1Affine::generator();
+        let (x, y) = g1.xy().unwrap();
+        assert_eq!(
+            g1_from_coords(&format!("0x{}", hex(&x)), &format!("0x{}", hex(&y))),
+            Ok(g1)
+        );
+        assert_eq!(g1_from_coords("0x0", "0x00"), Ok(G1Affine::zero()));
+        assert!(g1_from_coords(&format!("0x{}", hex(&x)), &format!("0x{}", hex(&x))).is_err());
+        let modulus: String = Fq::MODULUS
+            .to_bytes_be()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        assert!(g1_from_coords(&format!("0x{modulus}"), "0x0").is_err());
+
+        // Fq2 coordinates are printed as c1 || c0.
+        let g2 = G2Affine::generator();
+        let (x, y) = g2.xy().unwrap();
+        let coordinate = |c0: &Fq, c1: &Fq| format!("0x{}{}", hex(c1), hex(c0));
+        assert_eq!(
+            g2_from_coords(&coordinate(&x.c0, &x.c1), &coordinate(&y.c0, &y.c1)),
+            Ok(g2)
+        );
+        assert!(g2_from_coords(&coordinate(&x.c1, &x.c0), &coordinate(&y.c0, &y.c1)).is_err());
+    }
+
+    // Verifies the checked-in fixture, laid out as the backend-wasm fixture suites
+    // (`setup/`, `synthesizer/`, `preprocess/` and `prove/`), and rejects the proof once one of
+    // its evaluations is changed. `verifier::tests::writes_fixture` rewrites the fixture.
+    #[test]
+    fn verifies_checked_in_fixture() {
+        let fixture_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test/checks/fixtures/verifier");
+        let read = |path: PathBuf| std::fs::read_to_string(&path).expect("read fixture");
+        let setup_params = read(fixture_dir.join("setupParams.json"));
+        let sigma_verify = read(fixture_dir.join("setup/sigma_verify.json"));
+        let instance = read(fixture_dir.join("synthesizer/instance.json"));
+        let preprocess = read(fixture_dir.join("preprocess/preprocess.json"));
+        let proof = read(fixture_dir.join("prove/proof.json"));
+
+        let verify = |proof: &str| {
+            Verifier::from_json(&setup_params, &sigma_verify, &instance, &preprocess, proof)
+                .expect("parse fixture")
+                .verify_snark()
+        };
+        assert!(verify(&proof));
+
+        let mut tampered: serde_json::Value = serde_json::from_str(&proof).unwrap();
+        let eval = &mut tampered["proof3"]["V_eval"];
+        let value = crate::encoding::fr_from_hex(eval.as_str().unwrap()).unwrap() + Fr::one();
+        let digits: String = value
+            .into_bigint()
+            .to_bytes_be()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        *eval = serde_json::Value::String(format!("0x{digits}"));
+        assert!(!verify(&tampered.to_string()));
+    }
+}
@@ -0,0 +1,20 @@
+/**
+ * The JSON artifacts read by the native verifier, as text or as parsed JSON.
+ */
+export interface VerifierInputs {
+  setupParams: string | object;
+  sigmaVerify: string | object;
+  instance: string | object;
+  preprocess: string | object;
+  proof: string | object;
+}
+
+export interface Verifier {
+  /**
+   * Resolves to the verdict. Rejects if an artifact cannot be parsed or the proof was made
+   * with an unsupported transcript.
+   */
+  verify(inputs: VerifierInputs): Promise<boolean>;
+}
+
+export function loadVerifier(): Promise<Verifier>;
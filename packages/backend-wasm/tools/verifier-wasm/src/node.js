@@ -0,0 +1,59 @@
+import { readFile } from "node:fs/promises";
+import { dirname, join } from "node:path";
+import { fileURLToPath, pathToFileURL } from "node:url";
+
+const packageRoot = dirname(dirname(fileURLToPath(import.meta.url)));
+const generatedJsPath = join(packageRoot, "pkg", "backend_wasm_verifier.js");
+const generatedWasmPath = join(packageRoot, "pkg", "backend_wasm_verifier_bg.wasm");
+
+let wasmModulePromise;
+let initializedPromise;
+
+export async function loadVerifier() {
+  const wasmModule = await loadWasmModule();
+
+  if (initializedPromise === undefined) {
+    initializedPromise = initializeWasmModule(wasmModule);
+  }
+
+  await initializedPromise;
+
+  return {
+    async verify(inputs) {
+      return wasmModule.verify(
+        toJson(inputs.setupParams),
+        toJson(inputs.sigmaVerify),
+        toJson(inputs.instance),
+        toJson(inputs.preprocess),
+        toJson(inputs.proof),
+      );
+    },
+  };
+}
+
+function toJson(artifact) {
+  return typeof artifact === "string" ? artifact : JSON.stringify(artifact);
+}
+
+async function loadWasmModule() {
+  if (wasmModulePromise === undefined) {
+    wasmModulePromise = import(pathToFileURL(generatedJsPath).href).catch((error) => {
+      const message = error instanceof Error ? error.message : String(error);
+      throw new Error(`Failed to load generated verifier package. Run npm run verifier-wasm:build first. ${message}`);
+    });
+  }
+
+  return wasmModulePromise;
+}
+
+async function initializeWasmModule(wasmModule) {
+  let wasmBytes;
+  try {
+    wasmBytes = await readFile(generatedWasmPath);
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    throw new Error(`Failed to read generated verifier WASM. Run npm run verifier-wasm:build first. ${message}`);
+  }
+
+  wasmModule.initSync({ module: wasmBytes });
+}
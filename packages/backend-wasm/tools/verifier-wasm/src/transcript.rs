@@ -0,0 +1,75 @@
+use crate::encoding::{fr_be_bytes, g1_be_coords};
+use ark_bls12_381::{Fr, G1Affine};
+use ark_ff::{One, PrimeField, Zero};
+use tiny_keccak::{Hasher, Keccak};
+
+const DST_0_TAG: u8 = 0;
+const DST_1_TAG: u8 = 1;
+const CHALLENGE_DST_TAG: u8 = 2;
+
+/// The rolling Keccak-256 transcript of the native prover and the Solidity verifier. Every
+/// absorbed 32-byte word replaces the two state words with the hashes of
+/// `tag || state_0 || state_1 || word` under tags 0 and 1, and the `i`-th challenge is the hash of
+/// `2 || state_0 || state_1 || i` masked to 253 bits.
+#[derive(Default)]
+pub struct RollingKeccakTranscript {
+    state_0: [u8; 32],
+    state_1: [u8; 32],
+    challenge_counter: u32,
+}
+
+impl RollingKeccakTranscript {
+    fn update(&mut self, word: &[u8; 32]) {
+        // The Solidity memory layout: the tag in the last byte of a 4-byte slot, then the states
+        // and the word.
+        let mut input = [0u8; 100];
+        input[4..36].copy_from_slice(&self.state_0);
+        input[36..68].copy_from_slice(&self.state_1);
+        input[68..100].copy_from_slice(word);
+        input[3] = DST_0_TAG;
+        self.state_0 = keccak256(&input);
+        input[3] = DST_1_TAG;
+        self.state_1 = keccak256(&input);
+    }
+
+    /// Absorbs each coordinate as its top 16 bytes, left-padded to a word, then its low 32 bytes.
+    /// The point at infinity is absorbed as zero coordinates.
+    pub fn absorb_g1(&mut self, point: &G1Affine) {
+        for coord in g1_be_coords(point) {
+            let mut high = [0u8; 32];
+            high[16..].copy_from_slice(&coord[..16]);
+            self.update(&high);
+            self.update(coord[16..].try_into().unwrap());
+        }
+    }
+
+    pub fn absorb_scalar(&mut self, scalar: &Fr) {
+        self.update(&fr_be_bytes(scalar));
+    }
+
+    /// Squeezes one challenge, never zero.
+    pub fn squeeze_challenge(&mut self) -> Fr {
+        let mut input = [0u8; 72];
+        input[3] = CHALLENGE_DST_TAG;
+        input[4..36].copy_from_slice(&self.state_0);
+        input[36..68].copy_from_slice(&self.state_1);
+        input[68..72].copy_from_slice(&self.challenge_counter.to_be_bytes());
+        self.challenge_counter += 1;
+
+        let mut value = keccak256(&input);
+        value[0] &= 0x1f;
+        let challenge = Fr::from_be_bytes_mod_order(&value);
+        if challenge.is_zero() {
+            return Fr::one();
+        }
+        challenge
+    }
+}
+
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
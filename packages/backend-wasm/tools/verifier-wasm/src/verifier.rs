@@ -0,0 +1,735 @@
+use crate::artifacts::{FiatShamirMode, Instance, Preprocess, Proof, SetupParams, SigmaVerify};
+use crate::encoding::{g1_le_bytes, g2_le_bytes};
+use crate::transcript::{keccak256, RollingKeccakTranscript};
+use crate::VerifyError;
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_ff::{batch_inversion, FftField, Field, One, PrimeField, Zero};
+
+/// Absorbed first in `FiatShamirMode::StrongV1`, as a big-endian scalar.
+const FIAT_SHAMIR_PROTOCOL_TAG: &[u8] = b"tokamak-zk-evm/fiat-shamir/v1";
+
+/// The verifier of `verify-rust` on arkworks: the same challenges, domain evaluations and final
+/// pairing check, with `a_pub` evaluated barycentrically from the public inputs instead of
+/// interpolated.
+pub struct Verifier {
+    pub setup_params: SetupParams,
+    pub sigma: SigmaVerify,
+    pub instance: Instance,
+    pub preprocess: Preprocess,
+    pub proof: Proof,
+    /// The public inputs `a_pub` interpolates on the `l_free`-th roots of unity.
+    a_pub_evals: Vec<Fr>,
+}
+
+#[derive(Clone)]
+struct VerificationChallenges {
+    thetas: [Fr; 3],
+    kappa0: Fr,
+    chi: Fr,
+    zeta: Fr,
+    kappa1: Fr,
+    kappa2: Fr,
+}
+
+struct VerificationDomainContext {
+    m_i: usize,
+    omega_m_i: Fr,
+    omega_s_max: Fr,
+    t_n_eval: Fr,
+    t_mi_eval: Fr,
+    t_smax_eval: Fr,
+}
+
+/// icicle's primitive `size`-th root of unity, a power of `5^t` with `r - 1 = 2^32 t`. This is
+/// not arkworks' `TWO_ADIC_ROOT_OF_UNITY`, which is `7^t`.
+pub fn root_of_unity(size: usize) -> Fr {
+    assert!(
+        size.is_power_of_two() && size.trailing_zeros() <= Fr::TWO_ADICITY,
+        "no root of unity of order {size}"
+    );
+    let root = Fr::from(5u64).pow(Fr::TRACE);
+    root.pow([1u64 << (Fr::TWO_ADICITY - size.trailing_zeros())])
+}
+
+/// Evaluates at `point` the polynomial of degree below `evals.len()` that takes `evals[i]` at
+/// `omega^i`, as `(point^N - 1) / N * sum_i evals[i] * omega^i / (point - omega^i)`.
+pub fn eval_on_roots_of_unity(evals: &[Fr], point: Fr) -> Fr {
+    let size = evals.len();
+    let omega = root_of_unity(size);
+    let roots: Vec<Fr> = std::iter::successors(Some(Fr::one()), |root| Some(*root * omega))
+        .take(size)
+        .collect();
+    let mut denoms: Vec<Fr> = roots.iter().map(|root| point - root).collect();
+    if let Some(i) = denoms.iter().position(Zero::is_zero) {
+        return evals[i];
+    }
+    batch_inversion(&mut denoms);
+    let sum: Fr = evals
+        .iter()
+        .zip(&roots)
+        .zip(&denoms)
+        .map(|((eval, root), denom)| *eval * root * denom)
+        .sum();
+    let size_inv = Fr::from(size as u64).inverse().unwrap();
+    sum * (point.pow([size as u64]) - Fr::one()) * size_inv
+}
+
+impl Verifier {
+    /// Checks the setup shape as `Verifier::init` does and collects the public inputs: the first
+    /// `l_user` user values followed by the first `l_free - l_user` block values.
+    pub fn new(
+        setup_params: SetupParams,
+        sigma: SigmaVerify,
+        instance: Instance,
+        preprocess: Preprocess,
+        proof: Proof,
+    ) -> Result<Self, VerifyError> {
+        let m_i = setup_params
+            .l_D
+            .checked_sub(setup_params.l)
+            .ok_or_else(|| VerifyError::Shape("l_D must be >= l".to_string()))?;
+        for (name, value) in [
+            ("n", setup_params.n),
+            ("s_max", setup_params.s_max),
+            ("m_I", m_i),
+            ("l_free", setup_params.l_free),
+        ] {
+            if !value.is_power_of_two() {
+                return Err(VerifyError::Shape(format!("{name} is not a power of two.")));
+            }
+        }
+        let l_user = setup_params.l_user;
+        let m_block = setup_params
+            .l_free
+            .checked_sub(l_user)
+            .ok_or_else(|| VerifyError::Shape("l_free must be >= l_user".to_string()))?;
+        if instance.a_pub_user.len() < l_user || instance.a_pub_block.len() < m_block {
+            return Err(VerifyError::Shape(format!(
+                "the instance has fewer than l_user = {l_user} user or {m_block} block values"
+            )));
+        }
+        let a_pub_evals = instance.a_pub_user[..l_user]
+            .iter()
+            .chain(&instance.a_pub_block[..m_block])
+            .map(|value| value.0)
+            .collect();
+        Ok(Self {
+            setup_params,
+            sigma,
+            instance,
+            preprocess,
+            proof,
+            a_pub_evals,
+        })
+    }
+
+    pub fn verify_snark(&self) -> bool {
+        let challenges = self.collect_challenges();
+        let (lhs, rhs) = self.snark_pairing_terms(&challenges);
+        let (lhs_bases, rhs_bases) = self.snark_pairing_bases();
+        let g1: Vec<G1Affine> = lhs
+            .into_iter()
+            .chain(rhs.into_iter().map(|term| -term))
+            .map(|term| term.into_affine())
+            .collect();
+        let g2: Vec<G2Affine> = lhs_bases.into_iter().chain(rhs_bases).collect();
+        Bls12_381::multi_pairing(g1, g2).is_zero()
+    }
+
+    /// Keccak-256 of the little-endian coordinates of `G`, `sigma_1.x`, `sigma_1.y`,
+    /// `lagrange_KL`, `H` and the sigma_2 points, masked to 254 bits, as `SigmaVerify::digest`.
+    fn sigma_digest(&self) -> Fr {
+        let sigma = &self.sigma;
+        let mut bytes = Vec::new();
+        for point in [
+            &sigma.G,
+            &sigma.sigma_1.x,
+            &sigma.sigma_1.y,
+            &sigma.lagrange_KL,
+        ] {
+            bytes.extend(g1_le_bytes(&point.0));
+        }
+        let sigma_2 = &sigma.sigma_2;
+        for point in [
+            &sigma.H,
+            &sigma_2.alpha,
+            &sigma_2.alpha2,
+            &sigma_2.alpha3,
+            &sigma_2.alpha4,
+            &sigma_2.gamma,
+            &sigma_2.delta,
+            &sigma_2.eta,
+            &sigma_2.x,
+            &sigma_2.y,
+        ] {
+            bytes.extend(g2_le_bytes(&point.0));
+        }
+        let mut digest = keccak256(&bytes);
+        digest[31] &= 0b0011_1111;
+        Fr::from_le_bytes_mod_order(&digest)
+    }
+
+    // The `ProtocolStatement` absorbed in `FiatShamirMode::StrongV1`.
+    fn absorb_statement(&self, transcript: &mut RollingKeccakTranscript) {
+        transcript.absorb_scalar(&Fr::from_be_bytes_mod_order(FIAT_SHAMIR_PROTOCOL_TAG));
+        transcript.absorb_scalar(&self.sigma_digest());
+        let params = &self.setup_params;
+        for dim in [
+            params.l_free,
+            params.l,
+            params.l_user_out,
+            params.l_user,
+            params.l_D,
+            params.m_D,
+            params.n,
+            params.s_D,
+            params.s_max,
+        ] {
+            transcript.absorb_scalar(&Fr::from(dim as u32));
+        }
+        for point in [
+            &self.preprocess.s0,
+            &self.preprocess.s1,
+            &self.preprocess.O_pub_fix,
+        ] {
+            transcript.absorb_g1(&point.0);
+        }
+        let instance = &self.instance;
+        for values in [
+            &instance.a_pub_user,
+            &instance.a_pub_block,
+            &instance.a_pub_function,
+        ] {
+            transcript.absorb_scalar(&Fr::from(values.len() as u32));
+            for value in values {
+                transcript.absorb_scalar(&value.0);
+            }
+        }
+    }
+
+    // The native `Kappa2Mode::Transcript` derivation.
+    fn collect_challenges(&self) -> VerificationChallenges {
+        let proof = &self.proof;
+        let mut transcript = RollingKeccakTranscript::default();
+        if proof.fiat_shamir == FiatShamirMode::StrongV1 {
+            self.absorb_statement(&mut transcript);
+        }
+        let proof0 = &proof.proof0;
+        for point in [
+            &proof0.U,
+            &proof0.V,
+            &proof0.W,
+            &proof0.Q_AX,
+            &proof0.Q_AY,
+            &proof0.B,
+        ] {
+            transcript.absorb_g1(&point.0);
+        }
+        let thetas = [(); 3].map(|_| transcript.squeeze_challenge());
+        transcript.absorb_g1(&proof.proof1.R.0);
+        let kappa0 = transcript.squeeze_challenge();
+        transcript.absorb_g1(&proof.proof2.Q_CX.0);
+        transcript.absorb_g1(&proof.proof2.Q_CY.0);
+        let chi = transcript.squeeze_challenge();
+        let zeta = transcript.squeeze_challenge();
+        let proof3 = &proof.proof3;
+        for eval in [
+            &proof3.V_eval,
+            &proof3.R_eval,
+            &proof3.R_omegaX_eval,
+            &proof3.R_omegaX_omegaY_eval,
+        ] {
+            transcript.absorb_scalar(&eval.0);
+        }
+        let kappa1 = transcript.squeeze_challenge();
+        let proof4 = &proof.proof4;
+        let binding = &proof.binding;
+        for point in [
+            &proof4.Pi_X,
+            &proof4.Pi_Y,
+            &proof4.M_X,
+            &proof4.M_Y,
+            &proof4.N_X,
+            &proof4.N_Y,
+            &binding.A_free,
+            &binding.O_pub_free,
+            &binding.O_mid,
+            &binding.O_prv,
+            &self.preprocess.s0,
+            &self.preprocess.s1,
+            &self.preprocess.O_pub_fix,
+        ] {
+            transcript.absorb_g1(&point.0);
+        }
+        let kappa2 = transcript.squeeze_challenge();
+        VerificationChallenges {
+            thetas,
+            kappa0,
+            chi,
+            zeta,
+            kappa1,
+            kappa2,
+        }
+    }
+
+    fn build_domain_context(
+        &self,
+        challenges: &VerificationChallenges,
+    ) -> VerificationDomainContext {
+        let m_i = self.setup_params.l_D - self.setup_params.l;
+        let s_max = self.setup_params.s_max;
+        VerificationDomainContext {
+            m_i,
+            omega_m_i: root_of_unity(m_i),
+            omega_s_max: root_of_unity(s_max),
+            t_n_eval: challenges.chi.pow([self.setup_params.n as u64]) - Fr::one(),
+            t_mi_eval: challenges.chi.pow([m_i as u64]) - Fr::one(),
+            t_smax_eval: challenges.zeta.pow([s_max as u64]) - Fr::one(),
+        }
+    }
+
+    fn eval_lagrange_k0(
+        &self,
+        domain: &VerificationDomainContext,
+        challenges: &VerificationChallenges,
+    ) -> Fr {
+        if challenges.chi == Fr::one() {
+            return Fr::one();
+        }
+        domain.t_mi_eval
+            * Fr::from(domain.m_i as u64).inverse().unwrap()
+            * (challenges.chi - Fr::one()).inverse().unwrap()
+    }
+
+    fn lhs_arith(
+        &self,
+        domain: &VerificationDomainContext,
+        challenges: &VerificationChallenges,
+    ) -> G1Projective {
+        let proof0 = &self.proof.proof0;
+        let v_eval = self.proof.proof3.V_eval.0;
+        let g = self.sigma.G.0;
+        proof0.U.0 * v_eval - proof0.W.0 + (proof0.V.0 - g * v_eval) * challenges.kappa1
+            - proof0.Q_AX.0 * domain.t_n_eval
+            - proof0.Q_AY.0 * domain.t_smax_eval
+    }
+
+    fn lhs_copy(
+        &self,
+        domain: &VerificationDomainContext,
+        challenges: &VerificationChallenges,
+        lagrange_k0_eval: Fr,
+    ) -> G1Projective {
+        let proof0 = &self.proof.proof0;
+        let proof1 = &self.proof.proof1;
+        let proof2 = &self.proof.proof2;
+        let proof3 = &self.proof.proof3;
+        let (r_eval, r_omega_x_eval, r_omega_x_omega_y_eval) = (
+            proof3.R_eval.0,
+            proof3.R_omegaX_eval.0,
+            proof3.R_omegaX_omegaY_eval.0,
+        );
+        let g = self.sigma.G.0;
+        let thetas = &challenges.thetas;
+        let f = proof0.B.0
+            + self.preprocess.s0.0 * thetas[0]
+            + self.preprocess.s1.0 * thetas[1]
+            + g * thetas[2];
+        let g_term = proof0.B.0
+            + self.sigma.sigma_1.x.0 * thetas[0]
+            + self.sigma.sigma_1.y.0 * thetas[1]
+            + g * thetas[2];
+        let lhs_c_term1 = self.sigma.lagrange_KL.0 * (r_eval - Fr::one())
+            + (g_term * r_eval - f * r_omega_x_eval)
+                * (challenges.kappa0 * (challenges.chi - Fr::one()))
+            + (g_term * r_eval - f * r_omega_x_omega_y_eval)
+                * (challenges.kappa0.square() * lagrange_k0_eval)
+            - proof2.Q_CX.0 * domain.t_mi_eval
+            - proof2.Q_CY.0 * domain.t_smax_eval;
+        let kappa1 = challenges.kappa1;
+        let kappa2 = challenges.kappa2;
+        lhs_c_term1 * kappa1.square()
+            + (proof1.R.0 - g * r_eval) * kappa1.pow([3])
+            + (proof1.R.0 - g * r_omega_x_eval) * kappa2
+            + (proof1.R.0 - g * r_omega_x_omega_y_eval) * kappa2.square()
+    }
+
+    fn lhs_binding(&self, challenges: &VerificationChallenges, a_eval: Fr) -> G1Projective {
+        let scale = challenges.kappa2 * challenges.kappa1.pow([4]);
+        self.proof.binding.A_free.0 * (Fr::one() + scale) - self.sigma.G.0 * (scale * a_eval)
+    }
+
+    fn snark_aux(
+        &self,
+        domain: &VerificationDomainContext,
+        challenges: &VerificationChallenges,
+    ) -> (G1Projective, G1Projective, G1Projective) {
+        let proof4 = &self.proof.proof4;
+        let (chi, zeta) = (challenges.chi, challenges.zeta);
+        let kappa2 = challenges.kappa2;
+        let omega_m_i_inv = domain.omega_m_i.inverse().unwrap();
+        let omega_s_max_inv = domain.omega_s_max.inverse().unwrap();
+        let aux = proof4.Pi_X.0 * (kappa2 * chi)
+            + proof4.Pi_Y.0 * (kappa2 * zeta)
+            + proof4.M_X.0 * (kappa2.square() * omega_m_i_inv * chi)
+            + proof4.M_Y.0 * (kappa2.square() * zeta)
+            + proof4.N_X.0 * (kappa2.pow([3]) * omega_m_i_inv * chi)
+            + proof4.N_Y.0 * (kappa2.pow([3]) * omega_s_max_inv * zeta);
+        let aux_x = proof4.Pi_X.0 * kappa2
+            + proof4.M_X.0 * kappa2.square()
+            + proof4.N_X.0 * kappa2.pow([3]);
+        let aux_y = proof4.Pi_Y.0 * kappa2
+            + proof4.M_Y.0 * kappa2.square()
+            + proof4.N_Y.0 * kappa2.pow([3]);
+        (aux, aux_x, aux_y)
+    }
+
+    /// G1 sides of `e(lhs, lhs_bases) == e(rhs, rhs_bases)`, as the native
+    /// `snark_pairing_terms`.
+    fn snark_pairing_terms(
+        &self,
+        challenges: &VerificationChallenges,
+    ) -> ([G1Projective; 5], [G1Projective; 5]) {
+        let binding = &self.proof.binding;
+        let proof0 = &self.proof.proof0;
+        let domain = self.build_domain_context(challenges);
+        let lagrange_k0_eval = self.eval_lagrange_k0(&domain, challenges);
+        let a_eval = eval_on_roots_of_unity(&self.a_pub_evals, challenges.chi);
+        let lhs_a = self.lhs_arith(&domain, challenges);
+        let lhs_c = self.lhs_copy(&domain, challenges, lagrange_k0_eval);
+        let lhs_b = self.lhs_binding(challenges, a_eval);
+        let lhs = lhs_b + (lhs_a + lhs_c) * challenges.kappa2;
+        let (aux, aux_x, aux_y) = self.snark_aux(&domain, challenges);
+        (
+            [
+                lhs + aux,
+                proof0.B.0.into(),
+                proof0.U.0.into(),
+                proof0.V.0.into(),
+                proof0.W.0.into(),
+            ],
+            [
+                self.preprocess.O_pub_fix.0 + binding.O_pub_free.0,
+                binding.O_mid.0.into(),
+                binding.O_prv.0.into(),
+                aux_x,
+                aux_y,
+            ],
+        )
+    }
+
+    fn snark_pairing_bases(&self) -> ([G2Affine; 5], [G2Affine; 5]) {
+        let sigma_2 = &self.sigma.sigma_2;
+        (
+            [
+                self.sigma.H.0,
+                sigma_2.alpha4.0,
+                sigma_2.alpha.0,
+                sigma_2.alpha2.0,
+                sigma_2.alpha3.0,
+            ],
+            [
+                sigma_2.gamma.0,
+                sigma_2.eta.0,
+                sigma_2.delta.0,
+                sigma_2.x.0,
+                sigma_2.y.0,
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifacts::{
+        Binding, PartialSigma1Verify, Proof0, Proof1, Proof2, Proof3, Proof4, Scalar, Sigma2,
+        TranscriptKind, G1, G2,
+    };
+    use crate::encoding::{fr_be_bytes, g1_be_coords};
+    use ark_bls12_381::Fq;
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, UniformRand};
+    use serde_json::{json, Value};
+    use std::path::Path;
+
+    fn hex(bytes: &[u8]) -> String {
+        let digits: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!("0x{digits}")
+    }
+
+    fn fr_json(scalar: &Fr) -> Value {
+        json!(hex(&fr_be_bytes(scalar)))
+    }
+
+    fn g1_json(point: &G1) -> Value {
+        let [x, y] = g1_be_coords(&point.0);
+        json!({ "x": hex(&x), "y": hex(&y) })
+    }
+
+    // Fq2 coordinates as the native `G2serde` prints them, `c1 || c0`.
+    fn g2_json(point: &G2) -> Value {
+        let (x, y) = point.0.xy().expect("no point at infinity in the setup");
+        let coordinate = |c0: Fq, c1: Fq| {
+            let mut bytes = c1.into_bigint().to_bytes_be();
+            bytes.extend(c0.into_bigint().to_bytes_be());
+            hex(&bytes)
+        };
+        json!({ "x": coordinate(x.c0, x.c1), "y": coordinate(y.c0, y.c1) })
+    }
+
+    // Builds a `StrongV1` proof that passes `verify_snark` from a setup whose trapdoors are
+    // known, and writes it with its setup in the layout `verifies_checked_in_fixture` reads.
+    //
+    // With the bases `[1, alpha^4, alpha, alpha^2, alpha^3]` and `[gamma, eta, delta, x, y]`
+    // multiples of `H`, the pairing check holds when one G1 combination of the terms is zero.
+    // That combination is a cubic in kappa2 whose coefficients only depend on the challenges up
+    // to kappa1, so `O_prv`, `Pi_X`, `M_X` and `N_X` are solved to zero each coefficient and the
+    // proof verifies whatever kappa2 the transcript derives.
+    #[test]
+    #[ignore = "rewrites the checked-in fixture"]
+    fn writes_fixture() {
+        let mut rng = ark_std::test_rng();
+        let [alpha, gamma, delta, eta, x, y] = [(); 6].map(|_| Fr::rand(&mut rng));
+        let evals = [(); 4].map(|_| Scalar(Fr::rand(&mut rng)));
+        let g = G1Affine::generator();
+        let h = G2Affine::generator();
+        let mut random_g1 = || G1((g * Fr::rand(&mut rng)).into_affine());
+        let g2 = |trapdoor: Fr| G2((h * trapdoor).into_affine());
+
+        let setup_params = SetupParams {
+            l_free: 4,
+            l: 8,
+            l_user_out: 1,
+            l_user: 2,
+            l_D: 16,
+            m_D: 32,
+            n: 8,
+            s_D: 4,
+            s_max: 4,
+        };
+        let sigma = SigmaVerify {
+            G: G1(g),
+            H: G2(h),
+            sigma_1: PartialSigma1Verify {
+                x: G1((g * x).into_affine()),
+                y: G1((g * y).into_affine()),
+            },
+            sigma_2: Sigma2 {
+                alpha: g2(alpha),
+                alpha2: g2(alpha.square()),
+                alpha3: g2(alpha.pow([3])),
+                alpha4: g2(alpha.pow([4])),
+                gamma: g2(gamma),
+                delta: g2(delta),
+                eta: g2(eta),
+                x: g2(x),
+                y: g2(y),
+            },
+            lagrange_KL: random_g1(),
+        };
+        let instance = Instance {
+            a_pub_user: (1..=2)
+                .map(|value| Scalar(Fr::from(value as u64)))
+                .collect(),
+            a_pub_block: (3..=4)
+                .map(|value| Scalar(Fr::from(value as u64)))
+                .collect(),
+            a_pub_function: (5..=6)
+                .map(|value| Scalar(Fr::from(value as u64)))
+                .collect(),
+        };
+        let preprocess = Preprocess {
+            s0: random_g1(),
+            s1: random_g1(),
+            O_pub_fix: random_g1(),
+        };
+        let identity = G1(G1Affine::zero());
+        let proof = Proof {
+            binding: Binding {
+                A_free: random_g1(),
+                O_pub_free: random_g1(),
+                O_mid: random_g1(),
+                O_prv: identity,
+            },
+            proof0: Proof0 {
+                U: random_g1(),
+                V: random_g1(),
+                W: random_g1(),
+                Q_AX: random_g1(),
+                Q_AY: random_g1(),
+                B: random_g1(),
+            },
+            proof1: Proof1 { R: random_g1() },
+            proof2: Proof2 {
+                Q_CX: random_g1(),
+                Q_CY: random_g1(),
+            },
+            proof3: Proof3 {
+                V_eval: evals[0],
+                R_eval: evals[1],
+                R_omegaX_eval: evals[2],
+                R_omegaX_omegaY_eval: evals[3],
+            },
+            proof4: Proof4 {
+                Pi_X: identity,
+                Pi_Y: random_g1(),
+                M_X: identity,
+                M_Y: random_g1(),
+                N_X: identity,
+                N_Y: random_g1(),
+            },
+            transcript: TranscriptKind::Keccak,
+            fiat_shamir: FiatShamirMode::StrongV1,
+        };
+        let mut verifier = Verifier::new(setup_params, sigma, instance, preprocess, proof).unwrap();
+
+        let challenges = verifier.collect_challenges();
+        let lhs_scalars = [
+            Fr::one(),
+            alpha.pow([4]),
+            alpha,
+            alpha.square(),
+            alpha.pow([3]),
+        ];
+        let rhs_scalars = [gamma, eta, delta, x, y];
+        let combination = |kappa2: u64| {
+            let challenges = VerificationChallenges {
+                kappa2: Fr::from(kappa2),
+                ..challenges.clone()
+            };
+            let (lhs, rhs) = verifier.snark_pairing_terms(&challenges);
+            let lhs: G1Projective = lhs.iter().zip(&lhs_scalars).map(|(t, s)| *t * s).sum();
+            let rhs: G1Projective = rhs.iter().zip(&rhs_scalars).map(|(t, s)| *t * s).sum();
+            lhs - rhs
+        };
+        let [c_0, c_1, c_2, c_3] = [0, 1, 2, 3].map(combination);
+        // The coefficients of the cubic from its values at 0, 1, 2 and 3.
+        let coeff3 = (c_3 - c_2 * Fr::from(3u64) + c_1 * Fr::from(3u64) - c_0)
+            * Fr::from(6u64).inverse().unwrap();
+        let coeff2 = (c_2 - c_1 * Fr::from(2u64) + c_0) * Fr::from(2u64).inverse().unwrap()
+            - coeff3 * Fr::from(3u64);
+        let coeff1 = c_1 - c_0 - coeff2 - coeff3;
+
+        let domain = verifier.build_domain_context(&challenges);
+        let shifted_chi = domain.omega_m_i.inverse().unwrap() * challenges.chi;
+        let binding = &mut verifier.proof.binding;
+        binding.O_prv = G1((c_0 * delta.inverse().unwrap()).into_affine());
+        let proof4 = &mut verifier.proof.proof4;
+        proof4.Pi_X = G1((-coeff1 * (challenges.chi - x).inverse().unwrap()).into_affine());
+        proof4.M_X = G1((-coeff2 * (shifted_chi - x).inverse().unwrap()).into_affine());
+        proof4.N_X = G1((-coeff3 * (shifted_chi - x).inverse().unwrap()).into_affine());
+        assert!(verifier.verify_snark());
+
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test/checks/fixtures/verifier");
+        let params = &verifier.setup_params;
+        let sigma = &verifier.sigma;
+        let sigma_2 = &sigma.sigma_2;
+        let instance = &verifier.instance;
+        let preprocess = &verifier.preprocess;
+        let proof = &verifier.proof;
+        let scalars = |values: &[Scalar]| Value::from_iter(values.iter().map(|v| fr_json(&v.0)));
+        for (path, value) in [
+            (
+                "setupParams.json",
+                json!({
+                    "l_free": params.l_free,
+                    "l": params.l,
+                    "l_user_out": params.l_user_out,
+                    "l_user": params.l_user,
+                    "l_D": params.l_D,
+                    "m_D": params.m_D,
+                    "n": params.n,
+                    "s_D": params.s_D,
+                    "s_max": params.s_max,
+                }),
+            ),
+            (
+                "setup/sigma_verify.json",
+                json!({
+                    "G": g1_json(&sigma.G),
+                    "H": g2_json(&sigma.H),
+                    "sigma_1": {
+                        "x": g1_json(&sigma.sigma_1.x),
+                        "y": g1_json(&sigma.sigma_1.y),
+                    },
+                    "sigma_2": {
+                        "alpha": g2_json(&sigma_2.alpha),
+                        "alpha2": g2_json(&sigma_2.alpha2),
+                        "alpha3": g2_json(&sigma_2.alpha3),
+                        "alpha4": g2_json(&sigma_2.alpha4),
+                        "gamma": g2_json(&sigma_2.gamma),
+                        "delta": g2_json(&sigma_2.delta),
+                        "eta": g2_json(&sigma_2.eta),
+                        "x": g2_json(&sigma_2.x),
+                        "y": g2_json(&sigma_2.y),
+                    },
+                    "lagrange_KL": g1_json(&sigma.lagrange_KL),
+                }),
+            ),
+            (
+                "synthesizer/instance.json",
+                json!({
+                    "a_pub_user": scalars(&instance.a_pub_user),
+                    "a_pub_block": scalars(&instance.a_pub_block),
+                    "a_pub_function": scalars(&instance.a_pub_function),
+                }),
+            ),
+            (
+                "preprocess/preprocess.json",
+                json!({
+                    "s0": g1_json(&preprocess.s0),
+                    "s1": g1_json(&preprocess.s1),
+                    "O_pub_fix": g1_json(&preprocess.O_pub_fix),
+                }),
+            ),
+            (
+                "prove/proof.json",
+                json!({
+                    "binding": {
+                        "A_free": g1_json(&proof.binding.A_free),
+                        "O_pub_free": g1_json(&proof.binding.O_pub_free),
+                        "O_mid": g1_json(&proof.binding.O_mid),
+                        "O_prv": g1_json(&proof.binding.O_prv),
+                    },
+                    "proof0": {
+                        "U": g1_json(&proof.proof0.U),
+                        "V": g1_json(&proof.proof0.V),
+                        "W": g1_json(&proof.proof0.W),
+                        "Q_AX": g1_json(&proof.proof0.Q_AX),
+                        "Q_AY": g1_json(&proof.proof0.Q_AY),
+                        "B": g1_json(&proof.proof0.B),
+                    },
+                    "proof1": { "R": g1_json(&proof.proof1.R) },
+                    "proof2": {
+                        "Q_CX": g1_json(&proof.proof2.Q_CX),
+                        "Q_CY": g1_json(&proof.proof2.Q_CY),
+                    },
+                    "proof3": {
+                        "V_eval": fr_json(&proof.proof3.V_eval.0),
+                        "R_eval": fr_json(&proof.proof3.R_eval.0),
+                        "R_omegaX_eval": fr_json(&proof.proof3.R_omegaX_eval.0),
+                        "R_omegaX_omegaY_eval": fr_json(&proof.proof3.R_omegaX_omegaY_eval.0),
+                    },
+                    "proof4": {
+                        "Pi_X": g1_json(&proof.proof4.Pi_X),
+                        "Pi_Y": g1_json(&proof.proof4.Pi_Y),
+                        "M_X": g1_json(&proof.proof4.M_X),
+                        "M_Y": g1_json(&proof.proof4.M_Y),
+                        "N_X": g1_json(&proof.proof4.N_X),
+                        "N_Y": g1_json(&proof.proof4.N_Y),
+                    },
+                    "transcript": "keccak",
+                    "fiat_shamir": "strong_v1",
+                }),
+            ),
+        ] {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let text = serde_json::to_string_pretty(&value).unwrap();
+            std::fs::write(path, text + "\n").unwrap();
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use super::{BivariatePolynomial, DensePolynomialExt};
+use crate::compute_backend;
+use icicle_bls12_381::curve::ScalarField;
+use icicle_core::ntt::{self, NTTDir};
+use icicle_core::traits::{Arithmetic, FieldImpl};
+use icicle_core::vec_ops::VecOpsConfig;
+use icicle_runtime::memory::HostSlice;
+use std::ops::{Add, Mul, Sub};
+
+/// A bivariate polynomial kept as its evaluations on the coset domain
+/// `(coset_x * omega_x^i, coset_y * omega_y^j)`, where `omega_x` and `omega_y` are the `x_size`-th
+/// and `y_size`-th roots of unity. The evaluations are stored as `evals[i * y_size + j]`, the
+/// layout of `to_rou_evals`.
+///
+/// Sums and products are pointwise, and `div_by_vanishing` divides on the coset, so a chain such
+/// as `(uXY * vXY - wXY) / (t_n, t_smax)` needs no conversion to coefficients until `to_poly` is
+/// called for the commitment. The domain must be large enough for the degree of every result.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CosetPolynomialExt {
+    pub x_size: usize,
+    pub y_size: usize,
+    pub coset_x: ScalarField,
+    pub coset_y: ScalarField,
+    evals: Box<[ScalarField]>,
+}
+
+impl CosetPolynomialExt {
+    pub fn from_evals(
+        evals: impl Into<Box<[ScalarField]>>,
+        x_size: usize,
+        y_size: usize,
+        coset_x: ScalarField,
+        coset_y: ScalarField,
+    ) -> Self {
+        let evals = evals.into();
+        if !x_size.is_power_of_two() || !y_size.is_power_of_two() {
+            panic!("The sizes of a coset domain must be powers of two.");
+        }
+        if evals.len() != x_size * y_size {
+            panic!("Mismatch between the evaluations and the coset domain size");
+        }
+        Self {
+            x_size,
+            y_size,
+            coset_x,
+            coset_y,
+            evals,
+        }
+    }
+
+    /// Evaluates `poly` on the coset domain of `x_size` by `y_size`.
+    pub fn from_poly(
+        poly: &DensePolynomialExt,
+        x_size: usize,
+        y_size: usize,
+        coset_x: ScalarField,
+        coset_y: ScalarField,
+    ) -> Self {
+        let (x_degree, y_degree) = poly.find_degree();
+        if x_degree >= x_size as i64 || y_degree >= y_size as i64 {
+            panic!("The coset domain is too small for the polynomial degree.");
+        }
+        let mut resized = poly.clone();
+        resized.resize(x_size, y_size);
+        let mut evals = vec![ScalarField::zero(); x_size * y_size];
+        resized.to_rou_evals(
+            Some(&coset_x),
+            Some(&coset_y),
+            HostSlice::from_mut_slice(&mut evals),
+        );
+        Self::from_evals(evals, x_size, y_size, coset_x, coset_y)
+    }
+
+    /// Interpolates the coefficients, with the size trimmed to the degree.
+    pub fn to_poly(&self) -> DensePolynomialExt {
+        let mut poly = DensePolynomialExt::from_rou_evals(
+            HostSlice::from_slice(&self.evals),
+            self.x_size,
+            self.y_size,
+            Some(&self.coset_x),
+            Some(&self.coset_y),
+        );
+        poly.optimize_size();
+        poly
+    }
+
+    pub fn evals(&self) -> &[ScalarField] {
+        &self.evals
+    }
+
+    /// Divides by `X^x_degree - 1` and `Y^y_degree - 1` and returns `(quo_x, quo_y)` with
+    /// `self = quo_x * (X^x_degree - 1) + quo_y * (Y^y_degree - 1)`, both on the domain of `self`.
+    ///
+    /// `quo_y` is the one of X-degree below `x_degree`, as in `div_by_vanishing_opt`: folding the
+    /// X coefficients modulo `X^x_degree - 1` leaves `quo_y * (Y^y_degree - 1)`, which is divided
+    /// pointwise on the Y coset. `quo_x` is then `(self - quo_y * (Y^y_degree - 1)) /
+    /// (X^x_degree - 1)` pointwise. This costs one X-axis NTT each way, and neither quotient is
+    /// interpolated. The result is only meaningful if `self` vanishes on the product of the two
+    /// root-of-unity domains.
+    pub fn div_by_vanishing(&self, x_degree: usize, y_degree: usize) -> (Self, Self) {
+        if !x_degree.is_power_of_two() || !y_degree.is_power_of_two() {
+            panic!("The denominators must have degrees as powers of two.");
+        }
+        if x_degree > self.x_size || y_degree > self.y_size {
+            panic!("The coset domain must be larger than the denominators.");
+        }
+        let (x_size, y_size) = (self.x_size, self.y_size);
+        let t_x_inv = vanishing_inverses(x_size, x_degree, &self.coset_x);
+        let t_y = vanishing_values(y_size, y_degree, &self.coset_y);
+        let t_y_inv = vanishing_inverses(y_size, y_degree, &self.coset_y);
+
+        // X coefficients against Y evaluations, folded modulo X^x_degree - 1.
+        let mut mixed = vec![ScalarField::zero(); x_size * y_size];
+        x_axis_ntt(
+            &self.evals,
+            y_size,
+            NTTDir::kInverse,
+            &self.coset_x,
+            &mut mixed,
+        );
+        let mut quo_y_mixed = vec![ScalarField::zero(); x_size * y_size];
+        for (row, coeffs) in mixed.chunks(y_size).enumerate() {
+            let folded = &mut quo_y_mixed[(row % x_degree) * y_size..][..y_size];
+            for (acc, coeff) in folded.iter_mut().zip(coeffs) {
+                *acc = *acc + *coeff;
+            }
+        }
+        for row in quo_y_mixed[..x_degree * y_size].chunks_mut(y_size) {
+            for (value, inv) in row.iter_mut().zip(&t_y_inv) {
+                *value = *value * *inv;
+            }
+        }
+        drop(mixed);
+
+        let mut quo_y = vec![ScalarField::zero(); x_size * y_size];
+        x_axis_ntt(
+            &quo_y_mixed,
+            y_size,
+            NTTDir::kForward,
+            &self.coset_x,
+            &mut quo_y,
+        );
+        drop(quo_y_mixed);
+
+        let mut quo_x = vec![ScalarField::zero(); x_size * y_size];
+        for (i, row) in quo_x.chunks_mut(y_size).enumerate() {
+            let offset = i * y_size;
+            for (j, value) in row.iter_mut().enumerate() {
+                let numer = self.evals[offset + j] - quo_y[offset + j] * t_y[j];
+                *value = numer * t_x_inv[i];
+            }
+        }
+
+        (
+            Self::from_evals(quo_x, x_size, y_size, self.coset_x, self.coset_y),
+            Self::from_evals(quo_y, x_size, y_size, self.coset_x, self.coset_y),
+        )
+    }
+
+    fn assert_same_domain(&self, rhs: &Self) {
+        if self.x_size != rhs.x_size
+            || self.y_size != rhs.y_size
+            || self.coset_x != rhs.coset_x
+            || self.coset_y != rhs.coset_y
+        {
+            panic!("Coset polynomials on different domains.");
+        }
+    }
+
+    fn zip_evals(&self, rhs: &Self, f: impl Fn(ScalarField, ScalarField) -> ScalarField) -> Self {
+        self.assert_same_domain(rhs);
+        let evals: Vec<ScalarField> = self
+            .evals
+            .iter()
+            .zip(rhs.evals.iter())
+            .map(|(a, b)| f(*a, *b))
+            .collect();
+        Self::from_evals(evals, self.x_size, self.y_size, self.coset_x, self.coset_y)
+    }
+}
+
+impl Add for &CosetPolynomialExt {
+    type Output = CosetPolynomialExt;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip_evals(rhs, |a, b| a + b)
+    }
+}
+
+impl Sub for &CosetPolynomialExt {
+    type Output = CosetPolynomialExt;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_evals(rhs, |a, b| a - b)
+    }
+}
+
+impl Mul for &CosetPolynomialExt {
+    type Output = CosetPolynomialExt;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.zip_evals(rhs, |a, b| a * b)
+    }
+}
+
+impl Mul<&ScalarField> for &CosetPolynomialExt {
+    type Output = CosetPolynomialExt;
+
+    fn mul(self, rhs: &ScalarField) -> Self::Output {
+        let evals: Vec<ScalarField> = self.evals.iter().map(|eval| *eval * *rhs).collect();
+        CosetPolynomialExt::from_evals(evals, self.x_size, self.y_size, self.coset_x, self.coset_y)
+    }
+}
+
+// NTT along X of every Y column of an `x_size` by `y_size` matrix, with the X coset.
+fn x_axis_ntt(
+    input: &[ScalarField],
+    y_size: usize,
+    dir: NTTDir,
+    coset_x: &ScalarField,
+    output: &mut [ScalarField],
+) {
+    let mut cfg = ntt::NTTConfig::<ScalarField>::default();
+    cfg.batch_size = y_size as i32;
+    cfg.columns_batch = true;
+    cfg.coset_gen = *coset_x;
+    compute_backend::ntt(
+        HostSlice::from_slice(input),
+        dir,
+        &cfg,
+        HostSlice::from_mut_slice(output),
+    )
+    .expect("NTT domain is not initialized. Call init_ntt_domain_for_size first.");
+}
+
+// `(coset * omega^i)^degree - 1` for the `size` points of the coset.
+fn vanishing_values(size: usize, degree: usize, coset: &ScalarField) -> Vec<ScalarField> {
+    let omega = ntt::get_root_of_unity::<ScalarField>(size as u64).pow(degree);
+    let mut point = coset.pow(degree);
+    let mut values = Vec::with_capacity(size);
+    for _ in 0..size {
+        values.push(point - ScalarField::one());
+        point = point * omega;
+    }
+    values
+}
+
+fn vanishing_inverses(size: usize, degree: usize, coset: &ScalarField) -> Vec<ScalarField> {
+    let values = vanishing_values(size, degree, coset);
+    if values.iter().any(|value| *value == ScalarField::zero()) {
+        panic!("The coset meets the roots of the vanishing polynomial.");
+    }
+    let mut inverses = vec![ScalarField::zero(); size];
+    compute_backend::inv(
+        HostSlice::from_slice(&values),
+        HostSlice::from_mut_slice(&mut inverses),
+        &VecOpsConfig::default(),
+    )
+    .unwrap();
+    inverses
+}
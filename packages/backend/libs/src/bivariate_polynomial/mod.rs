@@ -1,10 +1,11 @@
 use super::vector_operations::*;
+use crate::compute_backend;
 use icicle_bls12_381::curve::{ScalarCfg, ScalarField};
 use icicle_bls12_381::polynomials::DensePolynomial;
 use icicle_core::ntt::{self, NTTDir};
 use icicle_core::polynomials::UnivariatePolynomial;
 use icicle_core::traits::{Arithmetic, FieldConfig, FieldImpl, GenerateRandom};
-use icicle_core::vec_ops::{VecOps, VecOpsConfig};
+use icicle_core::vec_ops::VecOpsConfig;
 use icicle_runtime::memory::{DeviceSlice, DeviceVec, HostOrDeviceSlice, HostSlice};
 use rayon::prelude::*;
 #[cfg(feature = "timing")]
@@ -19,6 +20,11 @@ use std::{
 #[cfg(feature = "timing")]
 use crate::timing::{record_detail, SizeInfo};
 
+mod coset;
+mod sparse;
+pub use coset::CosetPolynomialExt;
+pub use sparse::SparsePolynomialExt;
+
 #[cfg(feature = "timing")]
 fn record_detail_step(op: &'static str, start: Instant, label: &'static str, dims: Vec<usize>) {
     record_detail(op, start.elapsed(), vec![SizeInfo { label, dims }]);
@@ -229,17 +235,9 @@ impl<'a> PolyExpr<'a> {
         target_x_size: usize,
         target_y_size: usize,
     ) -> DensePolynomialExt {
-        if !target_x_size.is_power_of_two() || !target_y_size.is_power_of_two() {
-            panic!("Fused polynomial expression domains must be powers of two.");
-        }
-        let (x_degree, y_degree) = self.degree_bound();
-        if domain_size_for_degree(x_degree) > target_x_size
-            || domain_size_for_degree(y_degree) > target_y_size
-        {
-            panic!("Fused polynomial expression domain is too small for the expression degree.");
-        }
+        self.assert_domain_fits(target_x_size, target_y_size);
         let mut leaf_cache = HashMap::new();
-        let evals = self.evaluate_on_domain(target_x_size, target_y_size, &mut leaf_cache);
+        let evals = self.evaluate_on_domain(target_x_size, target_y_size, None, &mut leaf_cache);
         #[cfg(feature = "timing")]
         let start = Instant::now();
         let result = DensePolynomialExt::from_rou_evals(
@@ -259,6 +257,69 @@ impl<'a> PolyExpr<'a> {
         result
     }
 
+    /// Evaluates the expression on the coset `coset_x * omega^i, coset_y * omega^j` of the domain
+    /// the expression degree needs, and keeps the result in evaluation form.
+    pub fn evaluate_on_coset(
+        &self,
+        coset_x: &ScalarField,
+        coset_y: &ScalarField,
+    ) -> CosetPolynomialExt {
+        let (x_degree, y_degree) = self.degree_bound();
+        let x_size = domain_size_for_degree(x_degree);
+        let y_size = domain_size_for_degree(y_degree);
+        self.evaluate_on_coset_with_domain(x_size, y_size, coset_x, coset_y)
+    }
+
+    pub fn evaluate_on_coset_with_domain(
+        &self,
+        target_x_size: usize,
+        target_y_size: usize,
+        coset_x: &ScalarField,
+        coset_y: &ScalarField,
+    ) -> CosetPolynomialExt {
+        self.assert_domain_fits(target_x_size, target_y_size);
+        let mut leaf_cache = HashMap::new();
+        let evals = self.evaluate_on_domain(
+            target_x_size,
+            target_y_size,
+            Some((*coset_x, *coset_y)),
+            &mut leaf_cache,
+        );
+        let mut host_evals = vec![ScalarField::zero(); target_x_size * target_y_size];
+        evals
+            .copy_to_host(HostSlice::from_mut_slice(&mut host_evals))
+            .unwrap();
+        CosetPolynomialExt::from_evals(host_evals, target_x_size, target_y_size, *coset_x, *coset_y)
+    }
+
+    /// Evaluates the expression at one point from the evaluations of its leaves.
+    pub fn eval(&self, x: &ScalarField, y: &ScalarField) -> ScalarField {
+        match self {
+            Self::Poly(poly) => poly.eval(x, y),
+            Self::Scalar(scalar) => *scalar,
+            Self::Add(lhs, rhs) => lhs.eval(x, y) + rhs.eval(x, y),
+            Self::Sub(lhs, rhs) => lhs.eval(x, y) - rhs.eval(x, y),
+            Self::Mul(lhs, rhs) => lhs.eval(x, y) * rhs.eval(x, y),
+            Self::Scale(scalar, expr) => *scalar * expr.eval(x, y),
+            Self::MulXMinusOne(expr) => (*x - ScalarField::one()) * expr.eval(x, y),
+            Self::Sum(terms) => terms
+                .iter()
+                .fold(ScalarField::zero(), |acc, term| acc + term.eval(x, y)),
+        }
+    }
+
+    fn assert_domain_fits(&self, target_x_size: usize, target_y_size: usize) {
+        if !target_x_size.is_power_of_two() || !target_y_size.is_power_of_two() {
+            panic!("Fused polynomial expression domains must be powers of two.");
+        }
+        let (x_degree, y_degree) = self.degree_bound();
+        if domain_size_for_degree(x_degree) > target_x_size
+            || domain_size_for_degree(y_degree) > target_y_size
+        {
+            panic!("Fused polynomial expression domain is too small for the expression degree.");
+        }
+    }
+
     fn degree_bound(&self) -> (i64, i64) {
         match self {
             Self::Poly(poly) => poly.find_degree(),
@@ -308,20 +369,22 @@ impl<'a> PolyExpr<'a> {
         }
     }
 
+    // `coset` shifts the X and Y domains to `coset_x * omega^i` and `coset_y * omega^j`.
     fn evaluate_on_domain(
         &self,
         x_size: usize,
         y_size: usize,
+        coset: Option<(ScalarField, ScalarField)>,
         leaf_cache: &mut HashMap<(usize, usize, usize), DeviceVec<ScalarField>>,
     ) -> DeviceVec<ScalarField> {
         let size = x_size * y_size;
         let vec_ops_cfg = VecOpsConfig::default();
         match self {
-            Self::Poly(poly) => eval_poly_leaf(poly, x_size, y_size, leaf_cache),
+            Self::Poly(poly) => eval_poly_leaf(poly, x_size, y_size, coset, leaf_cache),
             Self::Scalar(scalar) => device_vec_from_scalar(*scalar, size),
             Self::Add(lhs, rhs) => {
-                let lhs_evals = lhs.evaluate_on_domain(x_size, y_size, leaf_cache);
-                let rhs_evals = rhs.evaluate_on_domain(x_size, y_size, leaf_cache);
+                let lhs_evals = lhs.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
+                let rhs_evals = rhs.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
                 let mut out = DeviceVec::<ScalarField>::device_malloc(size).unwrap();
@@ -329,14 +392,14 @@ impl<'a> PolyExpr<'a> {
                 record_detail_step("fused_add_alloc", start, "evals", vec![x_size, y_size]);
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
-                ScalarCfg::add(&lhs_evals, &rhs_evals, &mut out, &vec_ops_cfg).unwrap();
+                compute_backend::add(&lhs_evals, &rhs_evals, &mut out, &vec_ops_cfg).unwrap();
                 #[cfg(feature = "timing")]
                 record_detail_step("fused_add_pointwise", start, "evals", vec![x_size, y_size]);
                 out
             }
             Self::Sub(lhs, rhs) => {
-                let lhs_evals = lhs.evaluate_on_domain(x_size, y_size, leaf_cache);
-                let rhs_evals = rhs.evaluate_on_domain(x_size, y_size, leaf_cache);
+                let lhs_evals = lhs.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
+                let rhs_evals = rhs.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
                 let mut out = DeviceVec::<ScalarField>::device_malloc(size).unwrap();
@@ -344,14 +407,14 @@ impl<'a> PolyExpr<'a> {
                 record_detail_step("fused_sub_alloc", start, "evals", vec![x_size, y_size]);
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
-                ScalarCfg::sub(&lhs_evals, &rhs_evals, &mut out, &vec_ops_cfg).unwrap();
+                compute_backend::sub(&lhs_evals, &rhs_evals, &mut out, &vec_ops_cfg).unwrap();
                 #[cfg(feature = "timing")]
                 record_detail_step("fused_sub_pointwise", start, "evals", vec![x_size, y_size]);
                 out
             }
             Self::Mul(lhs, rhs) => {
-                let lhs_evals = lhs.evaluate_on_domain(x_size, y_size, leaf_cache);
-                let rhs_evals = rhs.evaluate_on_domain(x_size, y_size, leaf_cache);
+                let lhs_evals = lhs.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
+                let rhs_evals = rhs.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
                 let mut out = DeviceVec::<ScalarField>::device_malloc(size).unwrap();
@@ -359,13 +422,13 @@ impl<'a> PolyExpr<'a> {
                 record_detail_step("fused_mul_alloc", start, "evals", vec![x_size, y_size]);
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
-                ScalarCfg::mul(&lhs_evals, &rhs_evals, &mut out, &vec_ops_cfg).unwrap();
+                compute_backend::mul(&lhs_evals, &rhs_evals, &mut out, &vec_ops_cfg).unwrap();
                 #[cfg(feature = "timing")]
                 record_detail_step("fused_mul_pointwise", start, "evals", vec![x_size, y_size]);
                 out
             }
             Self::Scale(scalar, expr) => {
-                let expr_evals = expr.evaluate_on_domain(x_size, y_size, leaf_cache);
+                let expr_evals = expr.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
                 if *scalar == ScalarField::one() {
                     return expr_evals;
                 }
@@ -377,7 +440,7 @@ impl<'a> PolyExpr<'a> {
                 let scaler = [*scalar];
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
-                ScalarCfg::scalar_mul(
+                compute_backend::scalar_mul(
                     HostSlice::from_slice(&scaler),
                     &expr_evals,
                     &mut out,
@@ -389,8 +452,8 @@ impl<'a> PolyExpr<'a> {
                 out
             }
             Self::MulXMinusOne(expr) => {
-                let expr_evals = expr.evaluate_on_domain(x_size, y_size, leaf_cache);
-                let x_minus_one = x_minus_one_evals(x_size, y_size);
+                let expr_evals = expr.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
+                let x_minus_one = x_minus_one_evals(x_size, y_size, coset);
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
                 let mut out = DeviceVec::<ScalarField>::device_malloc(size).unwrap();
@@ -403,7 +466,7 @@ impl<'a> PolyExpr<'a> {
                 );
                 #[cfg(feature = "timing")]
                 let start = Instant::now();
-                ScalarCfg::mul(&expr_evals, &x_minus_one, &mut out, &vec_ops_cfg).unwrap();
+                compute_backend::mul(&expr_evals, &x_minus_one, &mut out, &vec_ops_cfg).unwrap();
                 #[cfg(feature = "timing")]
                 record_detail_step(
                     "fused_x_minus_one_pointwise",
@@ -416,7 +479,7 @@ impl<'a> PolyExpr<'a> {
             Self::Sum(terms) => {
                 let mut out = device_vec_from_scalar(ScalarField::zero(), size);
                 for term in terms {
-                    let term_evals = term.evaluate_on_domain(x_size, y_size, leaf_cache);
+                    let term_evals = term.evaluate_on_domain(x_size, y_size, coset, leaf_cache);
                     #[cfg(feature = "timing")]
                     let start = Instant::now();
                     let mut next = DeviceVec::<ScalarField>::device_malloc(size).unwrap();
@@ -424,7 +487,7 @@ impl<'a> PolyExpr<'a> {
                     record_detail_step("fused_sum_alloc", start, "evals", vec![x_size, y_size]);
                     #[cfg(feature = "timing")]
                     let start = Instant::now();
-                    ScalarCfg::add(&out, &term_evals, &mut next, &vec_ops_cfg).unwrap();
+                    compute_backend::add(&out, &term_evals, &mut next, &vec_ops_cfg).unwrap();
                     #[cfg(feature = "timing")]
                     record_detail_step("fused_sum_pointwise", start, "evals", vec![x_size, y_size]);
                     out = next;
@@ -460,6 +523,7 @@ fn eval_poly_leaf(
     poly: &DensePolynomialExt,
     x_size: usize,
     y_size: usize,
+    coset: Option<(ScalarField, ScalarField)>,
     leaf_cache: &mut HashMap<(usize, usize, usize), DeviceVec<ScalarField>>,
 ) -> DeviceVec<ScalarField> {
     let len = x_size * y_size;
@@ -489,7 +553,12 @@ fn eval_poly_leaf(
     record_detail_step("fused_leaf_alloc", start, "evals", vec![x_size, y_size]);
     #[cfg(feature = "timing")]
     let start = Instant::now();
-    resized.to_rou_evals(None, None, &mut evals);
+    match coset {
+        Some((coset_x, coset_y)) => {
+            resized.to_rou_evals(Some(&coset_x), Some(&coset_y), &mut evals)
+        }
+        None => resized.to_rou_evals(None, None, &mut evals),
+    }
     #[cfg(feature = "timing")]
     record_detail_step("fused_leaf_to_rou_evals", start, "evals", vec![x_size, y_size]);
     #[cfg(feature = "timing")]
@@ -501,10 +570,14 @@ fn eval_poly_leaf(
     out
 }
 
-fn x_minus_one_evals(x_size: usize, y_size: usize) -> DeviceVec<ScalarField> {
+fn x_minus_one_evals(
+    x_size: usize,
+    y_size: usize,
+    coset: Option<(ScalarField, ScalarField)>,
+) -> DeviceVec<ScalarField> {
     let omega_x = ntt::get_root_of_unity::<ScalarField>(x_size as u64);
     let mut values = vec![ScalarField::zero(); x_size * y_size];
-    let mut x = ScalarField::one();
+    let mut x = coset.map_or(ScalarField::one(), |(coset_x, _)| coset_x);
     for x_idx in 0..x_size {
         let factor = x - ScalarField::one();
         for y_idx in 0..y_size {
@@ -562,7 +635,11 @@ impl Add for &DensePolynomialExt {
         }
         #[cfg(feature = "timing")]
         let step_start = Instant::now();
-        let out_poly = &lhs_ext.poly + &rhs_ext.poly;
+        let out_poly = compute_backend::poly_add(
+            &lhs_ext.poly,
+            &rhs_ext.poly,
+            lhs_ext.x_size * lhs_ext.y_size,
+        );
         #[cfg(feature = "timing")]
         record_detail_step(
             "add_icicle_add",
@@ -634,7 +711,11 @@ impl AddAssign<&DensePolynomialExt> for DensePolynomialExt {
         }
         #[cfg(feature = "timing")]
         let step_start = Instant::now();
-        self.poly = &lhs_ext.poly + &rhs_ext.poly;
+        self.poly = compute_backend::poly_add(
+            &lhs_ext.poly,
+            &rhs_ext.poly,
+            lhs_ext.x_size * lhs_ext.y_size,
+        );
         #[cfg(feature = "timing")]
         record_detail_step(
             "addassign_icicle_add",
@@ -702,7 +783,11 @@ impl Sub for &DensePolynomialExt {
         }
         #[cfg(feature = "timing")]
         let step_start = Instant::now();
-        let out_poly = &lhs_ext.poly - &rhs_ext.poly;
+        let out_poly = compute_backend::poly_sub(
+            &lhs_ext.poly,
+            &rhs_ext.poly,
+            lhs_ext.x_size * lhs_ext.y_size,
+        );
         #[cfg(feature = "timing")]
         record_detail_step(
             "sub_icicle_sub",
@@ -832,7 +917,7 @@ impl Mul<&ScalarField> for &DensePolynomialExt {
         );
         #[cfg(feature = "timing")]
         let step_start = Instant::now();
-        ScalarCfg::scalar_mul(scaler, &coeffs, &mut res_coeffs, &vec_ops_cfg).unwrap();
+        compute_backend::scalar_mul(scaler, &coeffs, &mut res_coeffs, &vec_ops_cfg).unwrap();
         #[cfg(feature = "timing")]
         record_detail_step(
             "scalar_mul_icicle_scalar_mul",
@@ -932,7 +1017,7 @@ impl Mul<&DensePolynomialExt> for &ScalarField {
         );
         #[cfg(feature = "timing")]
         let step_start = Instant::now();
-        ScalarCfg::scalar_mul(scaler, &coeffs, &mut res_coeffs, &vec_ops_cfg).unwrap();
+        compute_backend::scalar_mul(scaler, &coeffs, &mut res_coeffs, &vec_ops_cfg).unwrap();
         #[cfg(feature = "timing")]
         record_detail_step(
             "scalar_mul_icicle_scalar_mul",
@@ -1450,7 +1535,7 @@ impl BivariatePolynomial for DensePolynomialExt {
             cfg.batch_size = 1;
             cfg.columns_batch = false;
             cfg.coset_gen = coset_gen_y.copied().unwrap_or(Self::Field::one());
-            ntt::ntt(in_mat, dir, &cfg, out_mat).unwrap();
+            compute_backend::ntt(in_mat, dir, &cfg, out_mat).unwrap();
             return;
         }
 
@@ -1458,7 +1543,7 @@ impl BivariatePolynomial for DensePolynomialExt {
             cfg.batch_size = 1;
             cfg.columns_batch = false;
             cfg.coset_gen = coset_gen_x.copied().unwrap_or(Self::Field::one());
-            ntt::ntt(in_mat, dir, &cfg, out_mat).unwrap();
+            compute_backend::ntt(in_mat, dir, &cfg, out_mat).unwrap();
             return;
         }
 
@@ -1468,12 +1553,12 @@ impl BivariatePolynomial for DensePolynomialExt {
             cfg.batch_size = x_size as i32;
             cfg.columns_batch = false;
             cfg.coset_gen = coset_gen_y.copied().unwrap_or(Self::Field::one());
-            ntt::ntt(in_mat, dir, &cfg, &mut out_y).unwrap();
+            compute_backend::ntt(in_mat, dir, &cfg, &mut out_y).unwrap();
 
             cfg.batch_size = y_size as i32;
             cfg.columns_batch = true;
             cfg.coset_gen = coset_gen_x.copied().unwrap_or(Self::Field::one());
-            ntt::ntt(&out_y, dir, &cfg, out_mat).unwrap();
+            compute_backend::ntt(&out_y, dir, &cfg, out_mat).unwrap();
         }
     }
 
@@ -1722,7 +1807,7 @@ impl BivariatePolynomial for DensePolynomialExt {
 
         for offset in 0..(self.y_degree + 1) as usize {
             let sub_xpoly = self.get_univariate_polynomial_x(offset as u64);
-            result[offset] = sub_xpoly.poly.eval(x);
+            result[offset] = compute_backend::poly_eval(&sub_xpoly.poly, self.x_size, x);
         }
 
         DensePolynomialExt::from_coeffs(result, 1, self.y_size)
@@ -1734,7 +1819,7 @@ impl BivariatePolynomial for DensePolynomialExt {
 
         for offset in 0..(self.x_degree + 1) as usize {
             let sub_ypoly = self.get_univariate_polynomial_y(offset as u64);
-            result[offset] = sub_ypoly.poly.eval(y);
+            result[offset] = compute_backend::poly_eval(&sub_ypoly.poly, self.y_size, y);
         }
         DensePolynomialExt::from_coeffs(result, self.x_size, 1)
     }
@@ -1971,7 +2056,7 @@ impl BivariatePolynomial for DensePolynomialExt {
         );
         #[cfg(feature = "timing")]
         let step_start = Instant::now();
-        ScalarCfg::mul(&lhs_evals, &rhs_evals, &mut out_evals, &cfg_vec_ops).unwrap();
+        compute_backend::mul(&lhs_evals, &rhs_evals, &mut out_evals, &cfg_vec_ops).unwrap();
         #[cfg(feature = "timing")]
         record_detail_step(
             "mul_icicle_eval_mul",
@@ -2067,13 +2152,16 @@ impl BivariatePolynomial for DensePolynomialExt {
             } else {
                 self.get_univariate_polynomial_x(offset as u64)
             };
-            let (sub_quo_poly, sub_rem_poly) = sub_poly.poly.divide(&denominator.poly);
             let mut sub_quo_coeffs_vec = vec![Self::Field::zero(); quo_size];
             let mut sub_rem_coeffs_vec = vec![Self::Field::zero(); rem_size];
-            let sub_quo_coeffs = HostSlice::from_mut_slice(&mut sub_quo_coeffs_vec);
-            let sub_rem_coeffs = HostSlice::from_mut_slice(&mut sub_rem_coeffs_vec);
-            sub_quo_poly.copy_coeffs(0, sub_quo_coeffs);
-            sub_rem_poly.copy_coeffs(0, sub_rem_coeffs);
+            compute_backend::poly_divide(
+                &sub_poly.poly,
+                quo_size,
+                &denominator.poly,
+                denominator.x_size * denominator.y_size,
+                &mut sub_quo_coeffs_vec,
+                &mut sub_rem_coeffs_vec,
+            );
             quo_coeffs_vec[offset * quo_size..(offset + 1) * quo_size]
                 .copy_from_slice(&sub_quo_coeffs_vec);
             rem_coeffs_vec[offset * rem_size..(offset + 1) * rem_size]
@@ -0,0 +1,274 @@
+use super::{BivariatePolynomial, DensePolynomialExt};
+use crate::compute_backend;
+use icicle_bls12_381::curve::ScalarField;
+use icicle_core::ntt::{self, NTTDir};
+use icicle_core::traits::{Arithmetic, FieldImpl};
+use icicle_runtime::memory::HostSlice;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Add, Mul, Sub};
+
+/// A bivariate polynomial stored as its non-zero columns in the Lagrange basis of Y.
+///
+/// The polynomial is `sum_j c_j(X) L_j(Y)`, where `L_j` is the Lagrange polynomial of `omega^j`
+/// for the `y_size`-th root of unity `omega`, and `c_j` is kept as its `x_size` coefficients. The
+/// witness polynomials have one column per placement, so the `s_max` slots beyond the placements
+/// are never stored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparsePolynomialExt {
+    pub x_size: usize,
+    pub y_size: usize,
+    columns: BTreeMap<usize, Box<[ScalarField]>>,
+}
+
+impl SparsePolynomialExt {
+    pub fn zero(x_size: usize, y_size: usize) -> Self {
+        if !x_size.is_power_of_two() || !y_size.is_power_of_two() {
+            panic!("The sizes of a sparse polynomial must be powers of two.");
+        }
+        Self {
+            x_size,
+            y_size,
+            columns: BTreeMap::new(),
+        }
+    }
+
+    /// Builds the polynomial from the evaluations of its columns on the `x_size`-th roots of
+    /// unity, the layout `gen_bXY` and `read_R1CS_gen_uvwXY` fill for each placement.
+    pub fn from_column_evals(
+        x_size: usize,
+        y_size: usize,
+        columns: impl IntoIterator<Item = (usize, Vec<ScalarField>)>,
+    ) -> Self {
+        let mut res = Self::zero(x_size, y_size);
+        for (j, evals) in columns {
+            if evals.len() != x_size {
+                panic!("Mismatch between the column evaluations and the polynomial size");
+            }
+            let mut coeffs = vec![ScalarField::zero(); x_size];
+            column_ntt(&evals, NTTDir::kInverse, &mut coeffs);
+            res.insert(j, coeffs.into_boxed_slice());
+        }
+        res
+    }
+
+    /// Converts a dense polynomial, keeping the non-zero columns. Only Y is transformed: the
+    /// forward NTTs of the coefficient rows give the columns' X coefficients directly.
+    pub fn from_dense(poly: &DensePolynomialExt) -> Self {
+        let (x_size, y_size) = (poly.x_size, poly.y_size);
+        let mut coeffs = vec![ScalarField::zero(); x_size * y_size];
+        poly.copy_coeffs(0, HostSlice::from_mut_slice(&mut coeffs));
+        let columns = rows_ntt(&coeffs, x_size, NTTDir::kForward);
+        let mut res = Self::zero(x_size, y_size);
+        for j in 0..y_size {
+            res.insert(j, (0..x_size).map(|i| columns[i * y_size + j]).collect());
+        }
+        res
+    }
+
+    /// Expands the columns with one batch of inverse NTTs over the Y rows, skipping the X
+    /// transforms a round trip through `from_rou_evals` would take.
+    pub fn to_dense(&self) -> DensePolynomialExt {
+        let mut columns = vec![ScalarField::zero(); self.x_size * self.y_size];
+        for (&j, coeffs) in &self.columns {
+            for (i, coeff) in coeffs.iter().enumerate() {
+                columns[i * self.y_size + j] = *coeff;
+            }
+        }
+        let coeffs = rows_ntt(&columns, self.x_size, NTTDir::kInverse);
+        DensePolynomialExt::from_coeffs(HostSlice::from_slice(&coeffs), self.x_size, self.y_size)
+    }
+
+    /// Indices of the stored, non-zero columns in ascending order.
+    pub fn occupied(&self) -> impl Iterator<Item = usize> + '_ {
+        self.columns.keys().copied()
+    }
+
+    /// X coefficients of the `j`-th column, or `None` if the column is zero.
+    pub fn column(&self, j: usize) -> Option<&[ScalarField]> {
+        self.columns.get(&j).map(|coeffs| &coeffs[..])
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn eval(&self, x: &ScalarField, y: &ScalarField) -> ScalarField {
+        let omega = ntt::get_root_of_unity::<ScalarField>(self.y_size as u64);
+        let vanishing = y.pow(self.y_size) - ScalarField::one();
+        let scale = vanishing * ScalarField::from_u32(self.y_size as u32).inv();
+        let mut res = ScalarField::zero();
+        for (&j, coeffs) in &self.columns {
+            let omega_j = omega.pow(j);
+            // L_j(y) = omega^j (y^n - 1) / (n (y - omega^j)), which is 1 or 0 on the domain.
+            let lagrange = if vanishing == ScalarField::zero() {
+                if *y != omega_j {
+                    continue;
+                }
+                ScalarField::one()
+            } else {
+                omega_j * scale * (*y - omega_j).inv()
+            };
+            let column = coeffs
+                .iter()
+                .rev()
+                .fold(ScalarField::zero(), |acc, coeff| acc * *x + *coeff);
+            res = res + lagrange * column;
+        }
+        res
+    }
+
+    /// Multiplies the occupied columns pairwise with NTTs of twice the X size. The result is the
+    /// product reduced modulo `Y^y_size - 1`, i.e. the product on the Y domain where the prover's
+    /// per-placement constraints are checked, and is zero on columns either factor leaves empty.
+    /// The full product, whose Y-degree overflows the domain, needs `to_dense`.
+    pub fn mul_reduced(&self, rhs: &Self) -> Self {
+        self.assert_same_size(rhs);
+        let x_size = 2 * self.x_size;
+        let mut res = Self::zero(x_size, self.y_size);
+        let mut lhs_evals = vec![ScalarField::zero(); x_size];
+        let mut rhs_evals = vec![ScalarField::zero(); x_size];
+        for (&j, lhs_coeffs) in &self.columns {
+            let Some(rhs_coeffs) = rhs.columns.get(&j) else {
+                continue;
+            };
+            column_ntt(
+                &padded(lhs_coeffs, x_size),
+                NTTDir::kForward,
+                &mut lhs_evals,
+            );
+            column_ntt(
+                &padded(rhs_coeffs, x_size),
+                NTTDir::kForward,
+                &mut rhs_evals,
+            );
+            let products: Vec<ScalarField> = lhs_evals
+                .iter()
+                .zip(&rhs_evals)
+                .map(|(lhs, rhs)| *lhs * *rhs)
+                .collect();
+            let mut coeffs = vec![ScalarField::zero(); x_size];
+            column_ntt(&products, NTTDir::kInverse, &mut coeffs);
+            res.insert(j, coeffs.into_boxed_slice());
+        }
+        res
+    }
+
+    /// Divides every column by `X^x_degree - 1` and returns the quotient and the remainder. The
+    /// Y-degree is below `y_size`, so nothing is left to divide by `Y^y_size - 1`; a polynomial
+    /// that vanishes on the whole domain has a zero remainder.
+    pub fn div_by_vanishing(&self, x_degree: usize) -> (Self, Self) {
+        if x_degree == 0 {
+            panic!("The vanishing polynomial must have a positive degree.");
+        }
+        let mut quotient = Self::zero(self.x_size, self.y_size);
+        let mut remainder = Self::zero(self.x_size, self.y_size);
+        for (&j, coeffs) in &self.columns {
+            let mut rem = coeffs.to_vec();
+            let mut quo = vec![ScalarField::zero(); self.x_size];
+            // X^k = X^(k-d) (X^d - 1) + X^(k-d), folded from the top coefficient down.
+            for k in (x_degree..self.x_size).rev() {
+                let coeff = rem[k];
+                rem[k] = ScalarField::zero();
+                quo[k - x_degree] = quo[k - x_degree] + coeff;
+                rem[k - x_degree] = rem[k - x_degree] + coeff;
+            }
+            quotient.insert(j, quo.into_boxed_slice());
+            remainder.insert(j, rem.into_boxed_slice());
+        }
+        (quotient, remainder)
+    }
+
+    // Stores a column unless it is zero.
+    fn insert(&mut self, j: usize, coeffs: Box<[ScalarField]>) {
+        if j >= self.y_size {
+            panic!("The column index exceeds the polynomial size.");
+        }
+        if coeffs.iter().any(|coeff| *coeff != ScalarField::zero()) {
+            self.columns.insert(j, coeffs);
+        } else {
+            self.columns.remove(&j);
+        }
+    }
+
+    fn assert_same_size(&self, rhs: &Self) {
+        if self.x_size != rhs.x_size || self.y_size != rhs.y_size {
+            panic!("Sparse polynomials of different sizes.");
+        }
+    }
+
+    fn zip_columns(&self, rhs: &Self, f: impl Fn(ScalarField, ScalarField) -> ScalarField) -> Self {
+        self.assert_same_size(rhs);
+        let zero_column = vec![ScalarField::zero(); self.x_size].into_boxed_slice();
+        let mut res = Self::zero(self.x_size, self.y_size);
+        let occupied: BTreeSet<usize> = self.occupied().chain(rhs.occupied()).collect();
+        for j in occupied {
+            let lhs = self.columns.get(&j).unwrap_or(&zero_column);
+            let rhs = rhs.columns.get(&j).unwrap_or(&zero_column);
+            let coeffs = lhs.iter().zip(rhs.iter()).map(|(a, b)| f(*a, *b)).collect();
+            res.insert(j, coeffs);
+        }
+        res
+    }
+}
+
+impl Add for &SparsePolynomialExt {
+    type Output = SparsePolynomialExt;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip_columns(rhs, |a, b| a + b)
+    }
+}
+
+impl Sub for &SparsePolynomialExt {
+    type Output = SparsePolynomialExt;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_columns(rhs, |a, b| a - b)
+    }
+}
+
+impl Mul<&ScalarField> for &SparsePolynomialExt {
+    type Output = SparsePolynomialExt;
+
+    fn mul(self, rhs: &ScalarField) -> Self::Output {
+        let mut res = SparsePolynomialExt::zero(self.x_size, self.y_size);
+        for (&j, coeffs) in &self.columns {
+            res.insert(j, coeffs.iter().map(|coeff| *coeff * *rhs).collect());
+        }
+        res
+    }
+}
+
+// Univariate NTT of one column over the roots of unity of its length.
+fn column_ntt(input: &[ScalarField], dir: NTTDir, output: &mut [ScalarField]) {
+    DensePolynomialExt::_biNTT(
+        HostSlice::from_slice(input),
+        input.len(),
+        1,
+        dir,
+        None,
+        None,
+        HostSlice::from_mut_slice(output),
+    );
+}
+
+// Univariate NTTs of the `rows` rows of a row-major matrix, in one batch.
+fn rows_ntt(input: &[ScalarField], rows: usize, dir: NTTDir) -> Vec<ScalarField> {
+    let mut output = vec![ScalarField::zero(); input.len()];
+    let mut cfg = ntt::NTTConfig::<ScalarField>::default();
+    cfg.batch_size = rows as i32;
+    compute_backend::ntt(
+        HostSlice::from_slice(input),
+        dir,
+        &cfg,
+        HostSlice::from_mut_slice(&mut output),
+    )
+    .unwrap();
+    output
+}
+
+fn padded(coeffs: &[ScalarField], size: usize) -> Vec<ScalarField> {
+    let mut res = coeffs.to_vec();
+    res.resize(size, ScalarField::zero());
+    res
+}
@@ -0,0 +1,196 @@
+use crate::group_structures::{ark_g1_affine_to_icicle, icicle_g1_affine_to_ark};
+use ark_bls12_381::{Fr, G1Affine as ArkG1Affine, G1Projective as ArkG1Projective};
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_poly::univariate::{DenseOrSparsePolynomial, DensePolynomial as ArkDensePolynomial};
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain};
+use icicle_bls12_381::curve::{G1Affine, G1Projective, ScalarField};
+use icicle_bls12_381::polynomials::DensePolynomial;
+use icicle_core::ntt::{self, NTTDir};
+use icicle_core::polynomials::UnivariatePolynomial;
+use icicle_core::traits::FieldImpl;
+use icicle_runtime::memory::HostSlice;
+use rayon::prelude::*;
+
+// `batch_size` transforms of `input.len() / batch_size` elements in natural order. With
+// `columns_batch`, element `k` of batch `b` is at `k * batch_size + b`, otherwise at
+// `b * size + k`.
+pub(super) fn ntt(
+    input: &[ScalarField],
+    dir: NTTDir,
+    batch_size: usize,
+    columns_batch: bool,
+    coset_gen: &ScalarField,
+    output: &mut [ScalarField],
+) {
+    let size = input.len() / batch_size;
+    if size == 0 {
+        return;
+    }
+    let position = |b: usize, k: usize| {
+        if columns_batch {
+            k * batch_size + b
+        } else {
+            b * size + k
+        }
+    };
+    let domain = domain(size, coset_gen);
+    let batches: Vec<Vec<Fr>> = (0..batch_size)
+        .into_par_iter()
+        .map(|b| {
+            let mut values: Vec<Fr> = (0..size)
+                .map(|k| scalar_to_ark(&input[position(b, k)]))
+                .collect();
+            match dir {
+                NTTDir::kForward => domain.fft_in_place(&mut values),
+                NTTDir::kInverse => domain.ifft_in_place(&mut values),
+            }
+            values
+        })
+        .collect();
+    output.par_iter_mut().enumerate().for_each(|(i, out)| {
+        let (b, k) = if columns_batch {
+            (i % batch_size, i / batch_size)
+        } else {
+            (i / size, i % size)
+        };
+        *out = scalar_from_ark(&batches[b][k]);
+    });
+}
+
+// The ark-poly domain of `size` on the coset of `coset_gen`, with the generator replaced by
+// icicle's root of unity so the evaluations come out in the same order as icicle's NTT.
+fn domain(size: usize, coset_gen: &ScalarField) -> Radix2EvaluationDomain<Fr> {
+    let mut domain =
+        Radix2EvaluationDomain::<Fr>::new(size).expect("NTT size exceeds the two-adicity of Fr");
+    let root = scalar_to_ark(&ntt::get_root_of_unity::<ScalarField>(size as u64));
+    domain.group_gen = root;
+    domain.group_gen_inv = root.inverse().unwrap();
+    domain
+        .get_coset(scalar_to_ark(coset_gen))
+        .expect("The coset generator must be non-zero.")
+}
+
+pub(super) fn msm(scalars: &[ScalarField], bases: &[G1Affine]) -> G1Projective {
+    let scalars: Vec<Fr> = scalars.par_iter().map(scalar_to_ark).collect();
+    let bases: Vec<ArkG1Affine> = bases
+        .par_iter()
+        .map(|base| {
+            // icicle encodes the point at infinity as (0, 0), which is not on the curve.
+            if *base == G1Affine::zero() {
+                ArkG1Affine::identity()
+            } else {
+                icicle_g1_affine_to_ark(base)
+            }
+        })
+        .collect();
+    let res = ArkG1Projective::msm(&bases, &scalars).expect("msm input length mismatch");
+    G1Projective::from(ark_g1_affine_to_icicle(&res.into_affine()))
+}
+
+pub(super) fn msm_batch(scalars: &[ScalarField], bases: &[G1Affine], results: &mut [G1Projective]) {
+    let size = scalars.len() / results.len();
+    results
+        .par_iter_mut()
+        .zip(scalars.par_chunks(size).zip(bases.par_chunks(size)))
+        .for_each(|(result, (scalars, bases))| *result = msm(scalars, bases));
+}
+
+// The first `size` coefficients of `poly`.
+pub(super) fn coeffs(poly: &DensePolynomial, size: usize) -> Vec<ScalarField> {
+    let mut coeffs = vec![ScalarField::zero(); size];
+    poly.copy_coeffs(0, HostSlice::from_mut_slice(&mut coeffs));
+    coeffs
+}
+
+pub(super) fn poly_zip(
+    lhs: &DensePolynomial,
+    rhs: &DensePolynomial,
+    size: usize,
+    op: impl Fn(Fr, Fr) -> Fr + Sync,
+) -> DensePolynomial {
+    let mut out = vec![ScalarField::zero(); size];
+    zip_map(&coeffs(lhs, size), &coeffs(rhs, size), &mut out, op);
+    DensePolynomial::from_coeffs(HostSlice::from_slice(&out), size)
+}
+
+// Horner's rule over `coeffs`, lowest degree first.
+pub(super) fn eval(coeffs: &[ScalarField], x: &ScalarField) -> ScalarField {
+    let x = scalar_to_ark(x);
+    let value = coeffs
+        .iter()
+        .rev()
+        .fold(Fr::zero(), |acc, coeff| acc * x + scalar_to_ark(coeff));
+    scalar_from_ark(&value)
+}
+
+pub(super) fn divide(
+    numerator: &[ScalarField],
+    denominator: &[ScalarField],
+    quotient: &mut [ScalarField],
+    remainder: &mut [ScalarField],
+) {
+    let to_ark = |coeffs: &[ScalarField]| {
+        DenseOrSparsePolynomial::from(ArkDensePolynomial::from_coefficients_vec(
+            coeffs.iter().map(scalar_to_ark).collect(),
+        ))
+    };
+    let (quo, rem) = to_ark(numerator)
+        .divide_with_q_and_r(&to_ark(denominator))
+        .expect("Division by the zero polynomial");
+    for (out, coeffs) in [(quotient, quo.coeffs), (remainder, rem.coeffs)] {
+        out.fill(ScalarField::zero());
+        for (out, coeff) in out.iter_mut().zip(&coeffs) {
+            *out = scalar_from_ark(coeff);
+        }
+    }
+}
+
+// `out[i] = op(a[i], b[i])`, with a single-element `b` applied to every element of `a`.
+pub(super) fn zip_map(
+    a: &[ScalarField],
+    b: &[ScalarField],
+    out: &mut [ScalarField],
+    op: impl Fn(Fr, Fr) -> Fr + Sync,
+) {
+    let b: Vec<Fr> = b.par_iter().map(scalar_to_ark).collect();
+    out.par_iter_mut().enumerate().for_each(|(i, out)| {
+        let rhs = if b.len() == 1 { b[0] } else { b[i] };
+        *out = scalar_from_ark(&op(scalar_to_ark(&a[i]), rhs));
+    });
+}
+
+// One sum per element of `out`, over the batches laid out as in `ntt`.
+pub(super) fn sum(a: &[ScalarField], columns_batch: bool, out: &mut [ScalarField]) {
+    let batch_size = out.len();
+    let size = a.len() / batch_size;
+    out.par_iter_mut().enumerate().for_each(|(b, out)| {
+        let sum: Fr = (0..size)
+            .map(|k| {
+                let i = if columns_batch {
+                    k * batch_size + b
+                } else {
+                    b * size + k
+                };
+                scalar_to_ark(&a[i])
+            })
+            .sum();
+        *out = scalar_from_ark(&sum);
+    });
+}
+
+pub(super) fn inv(a: &[ScalarField], out: &mut [ScalarField]) {
+    let mut values: Vec<Fr> = a.par_iter().map(scalar_to_ark).collect();
+    ark_ff::batch_inversion(&mut values);
+    out.par_iter_mut()
+        .zip(values.par_iter())
+        .for_each(|(out, value)| *out = scalar_from_ark(value));
+}
+
+fn scalar_to_ark(scalar: &ScalarField) -> Fr {
+    Fr::from_le_bytes_mod_order(&scalar.to_bytes_le())
+}
+
+fn scalar_from_ark(scalar: &Fr) -> ScalarField {
+    ScalarField::from_bytes_le(&scalar.into_bigint().to_bytes_le())
+}
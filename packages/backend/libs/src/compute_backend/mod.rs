@@ -0,0 +1,396 @@
+#[cfg(feature = "arkworks")]
+mod arkworks;
+
+use icicle_bls12_381::curve::{G1Affine, G1Projective, ScalarCfg, ScalarField};
+use icicle_bls12_381::polynomials::DensePolynomial;
+use icicle_core::msm::{self, MSMConfig};
+use icicle_core::ntt::{self, NTTConfig, NTTDir};
+use icicle_core::polynomials::UnivariatePolynomial;
+use icicle_core::vec_ops::{VecOps, VecOpsConfig};
+use icicle_runtime::errors::eIcicleError;
+use icicle_runtime::memory::{HostOrDeviceSlice, HostSlice};
+use std::cell::Cell;
+
+/// Library that runs the NTTs, MSMs, field vector operations and polynomial arithmetic (addition,
+/// subtraction, evaluation and division) of `bivariate_polynomial`, `vector_operations`,
+/// `encode_poly` and the reference string generators of `iotools`.
+///
+/// Field elements, points and polynomial storage are icicle's types under either backend, so
+/// icicle is always linked. The arkworks backend converts them to `ark-bls12-381` at the boundary
+/// of each operation. Storing, copying and slicing polynomial coefficients still goes through
+/// icicle's polynomial API, which on the CPU device is a host memory copy. Both backends return
+/// the same values: the arkworks NTTs use icicle's roots of unity, and commitments are compared
+/// as affine points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComputeBackend {
+    /// icicle's kernels on the active device.
+    Icicle,
+    /// `ark-poly` NTTs, `ark-ec` MSMs and `ark-ff` arithmetic on the host. Device buffers are
+    /// read in place, so icicle must be on its CPU device, which `check_device` selects when the
+    /// feature is enabled.
+    #[cfg(feature = "arkworks")]
+    Arkworks,
+}
+
+#[cfg(feature = "arkworks")]
+const DEFAULT_COMPUTE_BACKEND: ComputeBackend = ComputeBackend::Arkworks;
+#[cfg(not(feature = "arkworks"))]
+const DEFAULT_COMPUTE_BACKEND: ComputeBackend = ComputeBackend::Icicle;
+
+impl Default for ComputeBackend {
+    /// `Arkworks` if libs is built with the `arkworks` feature, `Icicle` otherwise.
+    fn default() -> Self {
+        DEFAULT_COMPUTE_BACKEND
+    }
+}
+
+thread_local! {
+    static COMPUTE_BACKEND: Cell<ComputeBackend> = const { Cell::new(DEFAULT_COMPUTE_BACKEND) };
+}
+
+/// Runs `f` with `backend` selected on the current thread, and restores the previous backend
+/// afterwards.
+pub fn with_compute_backend<T>(backend: ComputeBackend, f: impl FnOnce() -> T) -> T {
+    struct Restore(ComputeBackend);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            COMPUTE_BACKEND.with(|cell| cell.set(self.0));
+        }
+    }
+    let _restore = Restore(COMPUTE_BACKEND.with(|cell| cell.replace(backend)));
+    f()
+}
+
+/// The backend in effect on the current thread, the default unless overridden by
+/// `with_compute_backend`.
+pub fn compute_backend() -> ComputeBackend {
+    COMPUTE_BACKEND.with(Cell::get)
+}
+
+/// `ntt::ntt` on the selected backend. The arkworks backend reads `batch_size`, `columns_batch`
+/// and `coset_gen` from `cfg` and assumes the natural orderings of the default config.
+pub fn ntt<In, Out>(
+    input: &In,
+    dir: NTTDir,
+    cfg: &NTTConfig<ScalarField>,
+    output: &mut Out,
+) -> Result<(), eIcicleError>
+where
+    In: HostOrDeviceSlice<ScalarField> + ?Sized,
+    Out: HostOrDeviceSlice<ScalarField> + ?Sized,
+{
+    match compute_backend() {
+        ComputeBackend::Icicle => ntt::ntt(input, dir, cfg, output),
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => {
+            let batch_size = cfg.batch_size.max(1) as usize;
+            if input.len() != output.len() || input.len() % batch_size != 0 {
+                return Err(eIcicleError::InvalidArgument);
+            }
+            arkworks::ntt(
+                host_view(input),
+                dir,
+                batch_size,
+                cfg.columns_batch,
+                &cfg.coset_gen,
+                host_view_mut(output),
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Multi-scalar multiplication of host buffers of equal length.
+pub fn msm(scalars: &[ScalarField], bases: &[G1Affine]) -> G1Projective {
+    if scalars.len() != bases.len() {
+        panic!("msm input length mismatch");
+    }
+    match compute_backend() {
+        ComputeBackend::Icicle => {
+            let mut msm_res = vec![G1Projective::zero(); 1];
+            msm::msm(
+                HostSlice::from_slice(scalars),
+                HostSlice::from_slice(bases),
+                &MSMConfig::default(),
+                HostSlice::from_mut_slice(&mut msm_res),
+            )
+            .unwrap();
+            msm_res[0]
+        }
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => arkworks::msm(scalars, bases),
+    }
+}
+
+/// `results.len()` multi-scalar multiplications over consecutive equal chunks of `scalars` and
+/// `bases`.
+pub fn msm_batch(scalars: &[ScalarField], bases: &[G1Affine], results: &mut [G1Projective]) {
+    if scalars.len() != bases.len() || results.is_empty() || scalars.len() % results.len() != 0 {
+        panic!("msm input length mismatch");
+    }
+    match compute_backend() {
+        ComputeBackend::Icicle => {
+            let mut cfg = MSMConfig::default();
+            cfg.batch_size = results.len() as i32;
+            msm::msm(
+                HostSlice::from_slice(scalars),
+                HostSlice::from_slice(bases),
+                &cfg,
+                HostSlice::from_mut_slice(results),
+            )
+            .unwrap();
+        }
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => arkworks::msm_batch(scalars, bases, results),
+    }
+}
+
+/// `lhs + rhs` for polynomials of `size` coefficients each.
+#[cfg_attr(not(feature = "arkworks"), allow(unused_variables))]
+pub fn poly_add(lhs: &DensePolynomial, rhs: &DensePolynomial, size: usize) -> DensePolynomial {
+    match compute_backend() {
+        ComputeBackend::Icicle => lhs + rhs,
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => arkworks::poly_zip(lhs, rhs, size, |a, b| a + b),
+    }
+}
+
+/// `lhs - rhs` for polynomials of `size` coefficients each.
+#[cfg_attr(not(feature = "arkworks"), allow(unused_variables))]
+pub fn poly_sub(lhs: &DensePolynomial, rhs: &DensePolynomial, size: usize) -> DensePolynomial {
+    match compute_backend() {
+        ComputeBackend::Icicle => lhs - rhs,
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => arkworks::poly_zip(lhs, rhs, size, |a, b| a - b),
+    }
+}
+
+/// `poly.eval(x)` for a polynomial of `size` coefficients.
+#[cfg_attr(not(feature = "arkworks"), allow(unused_variables))]
+pub fn poly_eval(poly: &DensePolynomial, size: usize, x: &ScalarField) -> ScalarField {
+    match compute_backend() {
+        ComputeBackend::Icicle => poly.eval(x),
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => arkworks::eval(&arkworks::coeffs(poly, size), x),
+    }
+}
+
+/// `numerator.divide(denominator)` for polynomials of `numerator_size` and `denominator_size`
+/// coefficients. The quotient and the remainder fill `quotient` and `remainder` from the lowest
+/// degree, zero-padded.
+#[cfg_attr(not(feature = "arkworks"), allow(unused_variables))]
+pub fn poly_divide(
+    numerator: &DensePolynomial,
+    numerator_size: usize,
+    denominator: &DensePolynomial,
+    denominator_size: usize,
+    quotient: &mut [ScalarField],
+    remainder: &mut [ScalarField],
+) {
+    match compute_backend() {
+        ComputeBackend::Icicle => {
+            let (quo, rem) = numerator.divide(denominator);
+            quo.copy_coeffs(0, HostSlice::from_mut_slice(quotient));
+            rem.copy_coeffs(0, HostSlice::from_mut_slice(remainder));
+        }
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => arkworks::divide(
+            &arkworks::coeffs(numerator, numerator_size),
+            &arkworks::coeffs(denominator, denominator_size),
+            quotient,
+            remainder,
+        ),
+    }
+}
+
+// Element-wise operations with the signatures of `VecOps`. Batching does not change their
+// result, so the arkworks backend ignores it.
+macro_rules! binary_vec_op {
+    ($(#[$doc:meta])* $name:ident, $ark_op:expr) => {
+        $(#[$doc])*
+        pub fn $name<A, B, R>(
+            a: &A,
+            b: &B,
+            result: &mut R,
+            cfg: &VecOpsConfig,
+        ) -> Result<(), eIcicleError>
+        where
+            A: HostOrDeviceSlice<ScalarField> + ?Sized,
+            B: HostOrDeviceSlice<ScalarField> + ?Sized,
+            R: HostOrDeviceSlice<ScalarField> + ?Sized,
+        {
+            match compute_backend() {
+                ComputeBackend::Icicle => ScalarCfg::$name(a, b, result, cfg),
+                #[cfg(feature = "arkworks")]
+                ComputeBackend::Arkworks => {
+                    if a.len() != result.len() || b.len() != result.len() {
+                        return Err(eIcicleError::InvalidArgument);
+                    }
+                    arkworks::zip_map(host_view(a), host_view(b), host_view_mut(result), $ark_op);
+                    Ok(())
+                }
+            }
+        }
+    };
+}
+
+binary_vec_op!(add, |a, b| a + b);
+binary_vec_op!(sub, |a, b| a - b);
+binary_vec_op!(mul, |a, b| a * b);
+binary_vec_op!(
+    /// The arkworks backend divides by zero as multiplication by zero.
+    div,
+    |a, b| a * ark_ff::Field::inverse(&b).unwrap_or_default()
+);
+
+// Scalar-vector operations of `VecOps`, where `a` holds the single scalar.
+macro_rules! scalar_vec_op {
+    ($(#[$doc:meta])* $name:ident, $ark_op:expr) => {
+        $(#[$doc])*
+        pub fn $name<A, B, R>(
+            a: &A,
+            b: &B,
+            result: &mut R,
+            cfg: &VecOpsConfig,
+        ) -> Result<(), eIcicleError>
+        where
+            A: HostOrDeviceSlice<ScalarField> + ?Sized,
+            B: HostOrDeviceSlice<ScalarField> + ?Sized,
+            R: HostOrDeviceSlice<ScalarField> + ?Sized,
+        {
+            match compute_backend() {
+                ComputeBackend::Icicle => ScalarCfg::$name(a, b, result, cfg),
+                #[cfg(feature = "arkworks")]
+                ComputeBackend::Arkworks => {
+                    if a.len() != 1 || b.len() != result.len() {
+                        return Err(eIcicleError::InvalidArgument);
+                    }
+                    arkworks::zip_map(host_view(b), host_view(a), host_view_mut(result), $ark_op);
+                    Ok(())
+                }
+            }
+        }
+    };
+}
+
+scalar_vec_op!(scalar_add, |b, a| a + b);
+scalar_vec_op!(
+    /// `a - b[i]` for every element of `b`.
+    scalar_sub,
+    |b, a| a - b
+);
+scalar_vec_op!(scalar_mul, |b, a| a * b);
+
+/// Sums each of the `cfg.batch_size` batches of `a` into one element of `result`.
+pub fn sum<A, R>(a: &A, result: &mut R, cfg: &VecOpsConfig) -> Result<(), eIcicleError>
+where
+    A: HostOrDeviceSlice<ScalarField> + ?Sized,
+    R: HostOrDeviceSlice<ScalarField> + ?Sized,
+{
+    match compute_backend() {
+        ComputeBackend::Icicle => ScalarCfg::sum(a, result, cfg),
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => {
+            let batch_size = cfg.batch_size.max(1) as usize;
+            if result.len() != batch_size || a.len() % batch_size != 0 {
+                return Err(eIcicleError::InvalidArgument);
+            }
+            arkworks::sum(host_view(a), cfg.columns_batch, host_view_mut(result));
+            Ok(())
+        }
+    }
+}
+
+/// Inverts every element of `a`. The arkworks backend maps zero to zero.
+pub fn inv<A, R>(a: &A, result: &mut R, cfg: &VecOpsConfig) -> Result<(), eIcicleError>
+where
+    A: HostOrDeviceSlice<ScalarField> + ?Sized,
+    R: HostOrDeviceSlice<ScalarField> + ?Sized,
+{
+    match compute_backend() {
+        ComputeBackend::Icicle => ScalarCfg::inv(a, result, cfg),
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => {
+            if a.len() != result.len() {
+                return Err(eIcicleError::InvalidArgument);
+            }
+            arkworks::inv(host_view(a), host_view_mut(result));
+            Ok(())
+        }
+    }
+}
+
+/// Transposes the `nof_rows` by `nof_cols` row-major matrix `a` into `result`.
+pub fn transpose<A, R>(
+    a: &A,
+    nof_rows: u32,
+    nof_cols: u32,
+    result: &mut R,
+    cfg: &VecOpsConfig,
+) -> Result<(), eIcicleError>
+where
+    A: HostOrDeviceSlice<ScalarField> + ?Sized,
+    R: HostOrDeviceSlice<ScalarField> + ?Sized,
+{
+    match compute_backend() {
+        ComputeBackend::Icicle => ScalarCfg::transpose(a, nof_rows, nof_cols, result, cfg),
+        #[cfg(feature = "arkworks")]
+        ComputeBackend::Arkworks => {
+            let (rows, cols) = (nof_rows as usize, nof_cols as usize);
+            if a.len() != rows * cols || result.len() != rows * cols {
+                return Err(eIcicleError::InvalidArgument);
+            }
+            let (a, result) = (host_view(a), host_view_mut(result));
+            for (r, row) in a.chunks(cols).enumerate() {
+                for (c, value) in row.iter().enumerate() {
+                    result[c * rows + r] = *value;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// Host or CPU-device memory as a slice, read in place so that the arkworks backend adds no copy
+// to the icicle buffers it is handed. icicle's CPU device allocates device buffers in host
+// memory; buffers of any other device are rejected.
+#[cfg(feature = "arkworks")]
+fn host_view<S>(slice: &S) -> &[ScalarField]
+where
+    S: HostOrDeviceSlice<ScalarField> + ?Sized,
+{
+    if slice.is_empty() {
+        return &[];
+    }
+    assert_host_memory(slice);
+    // SAFETY: `as_ptr` and `len` describe one allocation of initialized field elements in host
+    // memory (checked above) that lives, unaliased by writers, as long as the `&S` borrow.
+    unsafe { std::slice::from_raw_parts(slice.as_ptr(), slice.len()) }
+}
+
+#[cfg(feature = "arkworks")]
+fn host_view_mut<S>(slice: &mut S) -> &mut [ScalarField]
+where
+    S: HostOrDeviceSlice<ScalarField> + ?Sized,
+{
+    if slice.is_empty() {
+        return &mut [];
+    }
+    assert_host_memory(slice);
+    // SAFETY: as in `host_view`, and the `&mut S` borrow makes the view the only access.
+    unsafe { std::slice::from_raw_parts_mut(slice.as_mut_ptr(), slice.len()) }
+}
+
+#[cfg(feature = "arkworks")]
+fn assert_host_memory<S>(slice: &S)
+where
+    S: HostOrDeviceSlice<ScalarField> + ?Sized,
+{
+    if slice.is_on_device() {
+        let device = icicle_runtime::get_active_device().expect("No active icicle device");
+        assert!(
+            slice.is_on_active_device() && device.get_device_type() == "CPU",
+            "The arkworks backend reads device buffers only on icicle's CPU device"
+        );
+    }
+}
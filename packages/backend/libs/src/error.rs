@@ -0,0 +1,68 @@
+use crate::group_structures::PointCheck;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors surfaced by the library entry points of the prover, verifier and preprocessor.
+#[derive(Error, Debug)]
+pub enum TokamakError {
+    #[error("IO error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Failed to parse {what}: {reason}")]
+    Parse { what: String, reason: String },
+    #[error("Invalid setup shape: {0}")]
+    Shape(String),
+    #[error("Invalid archive {path}: {reason}")]
+    Archive { path: PathBuf, reason: String },
+    #[error("Failed to initialize the NTT domain: {0}")]
+    NttDomain(String),
+    #[error("Invalid {group} point: {check:?}")]
+    InvalidPoint {
+        group: &'static str,
+        check: PointCheck,
+    },
+    #[error("Missing input: {0}")]
+    MissingInput(&'static str),
+    #[error("Checkpoint does not match the inputs: {0}")]
+    CheckpointMismatch(String),
+    #[error("Accumulator mismatch: {0}")]
+    AccumulatorMismatch(String),
+}
+
+pub type TokamakResult<T> = Result<T, TokamakError>;
+
+impl TokamakError {
+    /// Classifies an error returned by the `read_*_from_json` helpers. `serde_json` failures arrive
+    /// wrapped as `InvalidData`/`UnexpectedEof` I/O errors and are reported as parse errors.
+    pub fn from_read(path: impl AsRef<Path>, err: io::Error) -> Self {
+        let path = path.as_ref();
+        match err.kind() {
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => TokamakError::Parse {
+                what: path.display().to_string(),
+                reason: err.to_string(),
+            },
+            _ => TokamakError::Io {
+                path: path.to_path_buf(),
+                source: err,
+            },
+        }
+    }
+
+    pub fn parse(what: impl Into<String>, reason: impl Into<String>) -> Self {
+        TokamakError::Parse {
+            what: what.into(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn archive(path: impl AsRef<Path>, reason: impl Into<String>) -> Self {
+        TokamakError::Archive {
+            path: path.as_ref().to_path_buf(),
+            reason: reason.into(),
+        }
+    }
+}
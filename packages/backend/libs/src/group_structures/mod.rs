@@ -1,5 +1,7 @@
-use crate::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt};
-use crate::field_structures::{FieldSerde, Tau};
+use crate::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt, SparsePolynomialExt};
+use crate::compute_backend;
+use crate::error::{TokamakError, TokamakResult};
+use crate::field_structures::{hashing, FieldSerde, Tau};
 use crate::iotools::{
     from_coef_vec_to_g1serde_mat, from_coef_vec_to_g1serde_vec, scaled_outer_product_1d,
     scaled_outer_product_2d, HexString, PlacementVariables, SetupParams, SubcircuitInfo,
@@ -8,13 +10,15 @@ use crate::vector_operations::*;
 use ark_bls12_381::{Bls12_381, G1Affine as ArkG1Affine, G2Affine as ArkG2Affine};
 use ark_ec::pairing::Pairing;
 use ark_ec::pairing::PairingOutput;
-use ark_ff::Field;
-use icicle_bls12_381::curve::{G1Affine, G1Projective, G2Affine, ScalarField};
-use icicle_core::msm::{self, MSMConfig};
+use ark_ff::{BigInt, BigInteger, Field, PrimeField};
+use icicle_bls12_381::curve::{
+    BaseField, G1Affine, G1Projective, G2Affine, G2BaseField, ScalarField,
+};
 use icicle_core::traits::{Arithmetic, FieldImpl};
 use icicle_runtime::memory::HostSlice;
 
 use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::ops::{Add, Mul, Sub};
 
 macro_rules! extend_monomial_vec {
@@ -64,66 +68,161 @@ macro_rules! impl_encode_poly {
                 poly: &mut DensePolynomialExt,
                 params: &SetupParams,
             ) -> G1serde {
-                poly.optimize_size();
-                let x_size = poly.x_size;
-                let y_size = poly.y_size;
-                let rs_x_size = std::cmp::max(2 * params.n, 2 * (params.l_D - params.l));
-                let rs_y_size = params.s_max * 2;
-                let target_x_size = (poly.x_degree + 1) as usize;
-                let target_y_size = (poly.y_degree + 1) as usize;
-                if target_x_size > rs_x_size || target_y_size > rs_y_size {
-                    panic!("Insufficient length of sigma.sigma_1.xy_powers");
-                }
-                if target_x_size * target_y_size == 0 {
-                    return G1serde::zero();
-                }
-                let poly_coeffs_vec_compact = {
-                    let mut poly_coeffs_vec = vec![ScalarField::zero(); x_size * y_size];
-                    let poly_coeffs = HostSlice::from_mut_slice(&mut poly_coeffs_vec);
-                    poly.copy_coeffs(0, poly_coeffs);
-                    resize(
-                        &poly_coeffs_vec,
-                        x_size,
-                        y_size,
-                        target_x_size,
-                        target_y_size,
-                        ScalarField::zero(),
-                    )
-                };
-
-                let rs_unpacked: Vec<G1Affine> = {
-                    let rs_resized = resize(
-                        &self.xy_powers,
-                        rs_x_size,
-                        rs_y_size,
-                        target_x_size,
-                        target_y_size,
-                        G1serde::zero(),
-                    );
-                    rs_resized.iter().map(|x| x.0).collect()
-                };
-
-                let mut msm_res = vec![G1Projective::zero(); 1];
-
-                msm::msm(
-                    HostSlice::from_slice(&poly_coeffs_vec_compact),
-                    HostSlice::from_slice(&rs_unpacked),
-                    &MSMConfig::default(),
-                    HostSlice::from_mut_slice(&mut msm_res),
-                )
-                .unwrap();
-                G1serde(G1Affine::from(msm_res[0]))
+                encode_poly_with_bases(poly, params, |idx| self.xy_powers[idx].0)
+            }
+
+            /// Commits to a sparse polynomial. The reference string is in the monomial basis, so
+            /// the columns are expanded to the dense form first.
+            pub fn encode_sparse_poly(
+                &self,
+                poly: &SparsePolynomialExt,
+                params: &SetupParams,
+            ) -> G1serde {
+                self.encode_poly(&mut poly.to_dense(), params)
             }
         }
     };
 }
 
+/// How much memory one `encode_poly` commitment may hold for its MSM inputs. `Unbounded` builds
+/// the whole coefficient matrix and basis at once. `Bytes` tiles the matrix into bands of X rows,
+/// each covering every power of Y, that fit in the budget and sums one partial MSM per band; when
+/// not even one row fits, each row is split into runs of powers of Y instead. Both give the same
+/// commitment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MsmMemoryBudget {
+    #[default]
+    Unbounded,
+    Bytes(usize),
+}
+
+impl MsmMemoryBudget {
+    /// Number of the `rows` rows of `row_bytes` bytes each that one tile holds, at least one.
+    pub fn rows_per_tile(&self, row_bytes: usize, rows: usize) -> usize {
+        match self {
+            MsmMemoryBudget::Unbounded => rows,
+            MsmMemoryBudget::Bytes(bytes) => (bytes / row_bytes.max(1)).clamp(1, rows.max(1)),
+        }
+    }
+
+    /// Whether `bytes` fit in the budget.
+    pub fn holds(&self, bytes: usize) -> bool {
+        match self {
+            MsmMemoryBudget::Unbounded => true,
+            MsmMemoryBudget::Bytes(budget) => bytes <= *budget,
+        }
+    }
+}
+
+thread_local! {
+    static MSM_MEMORY_BUDGET: Cell<MsmMemoryBudget> =
+        const { Cell::new(MsmMemoryBudget::Unbounded) };
+}
+
+/// Runs `f` with the given budget applied to every `encode_poly` on the current thread, and
+/// restores the previous budget afterwards.
+pub fn with_msm_memory_budget<T>(budget: MsmMemoryBudget, f: impl FnOnce() -> T) -> T {
+    struct Restore(MsmMemoryBudget);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            MSM_MEMORY_BUDGET.with(|cell| cell.set(self.0));
+        }
+    }
+    let _restore = Restore(MSM_MEMORY_BUDGET.with(|cell| cell.replace(budget)));
+    f()
+}
+
+/// The budget in effect on the current thread, `Unbounded` unless overridden by
+/// `with_msm_memory_budget`.
+pub fn msm_memory_budget() -> MsmMemoryBudget {
+    MSM_MEMORY_BUDGET.with(Cell::get)
+}
+
+/// Commits to `poly` against the `xy_powers` table, whose entry for `x^i y^j` is `base(i *
+/// 2 * s_max + j)`. Rows of the coefficient matrix are fetched and converted one tile at a time, so
+/// with a memory-mapped table only the current tile is resident.
+pub(crate) fn encode_poly_with_bases(
+    poly: &mut DensePolynomialExt,
+    params: &SetupParams,
+    base: impl Fn(usize) -> G1Affine,
+) -> G1serde {
+    poly.optimize_size();
+    let y_size = poly.y_size;
+    let rs_x_size = std::cmp::max(2 * params.n, 2 * (params.l_D - params.l));
+    let rs_y_size = params.s_max * 2;
+    let target_x_size = (poly.x_degree + 1) as usize;
+    let target_y_size = (poly.y_degree + 1) as usize;
+    if target_x_size > rs_x_size || target_y_size > rs_y_size {
+        panic!("Insufficient length of sigma.sigma_1.xy_powers");
+    }
+    if target_x_size * target_y_size == 0 {
+        return G1serde::zero();
+    }
+
+    let coeff_bytes = std::mem::size_of::<ScalarField>();
+    let base_bytes = std::mem::size_of::<G1Affine>();
+    let row_bytes = y_size * coeff_bytes + target_y_size * base_bytes;
+    let budget = msm_memory_budget();
+    let mut acc = G1Projective::zero();
+    if !budget.holds(row_bytes) {
+        // Fetches only the used coefficients of a row, a run of powers of Y at a time.
+        let tile_cols = budget.rows_per_tile(coeff_bytes + base_bytes, target_y_size);
+        for i in 0..target_x_size {
+            for col_start in (0..target_y_size).step_by(tile_cols) {
+                let cols = std::cmp::min(tile_cols, target_y_size - col_start);
+                let mut coeffs = vec![ScalarField::zero(); cols];
+                poly.copy_coeffs(
+                    (i * y_size + col_start) as u64,
+                    HostSlice::from_mut_slice(&mut coeffs),
+                );
+                let bases: Vec<G1Affine> = (col_start..col_start + cols)
+                    .map(|j| base(rs_y_size * i + j))
+                    .collect();
+                acc = acc + compute_backend::msm(&coeffs, &bases);
+            }
+        }
+        return G1serde(G1Affine::from(acc));
+    }
+
+    let tile_rows = budget.rows_per_tile(row_bytes, target_x_size);
+    for row_start in (0..target_x_size).step_by(tile_rows) {
+        let rows = std::cmp::min(tile_rows, target_x_size - row_start);
+        let coeffs = {
+            let mut tile = vec![ScalarField::zero(); rows * y_size];
+            poly.copy_coeffs(
+                (row_start * y_size) as u64,
+                HostSlice::from_mut_slice(&mut tile),
+            );
+            resize(
+                &tile,
+                rows,
+                y_size,
+                rows,
+                target_y_size,
+                ScalarField::zero(),
+            )
+        };
+        let bases: Vec<G1Affine> = (row_start..row_start + rows)
+            .flat_map(|i| (0..target_y_size).map(move |j| (i, j)))
+            .map(|(i, j)| base(rs_y_size * i + j))
+            .collect();
+
+        acc = acc + compute_backend::msm(&coeffs, &bases);
+    }
+    G1serde(G1Affine::from(acc))
+}
+
 pub fn pairing(lhs: &[G1serde], rhs: &[G2serde]) -> PairingOutput<Bls12_381> {
     let lhs_ark: Vec<ArkG1Affine> = lhs.iter().map(|x| icicle_g1_affine_to_ark(&x.0)).collect();
     let rhs_ark: Vec<ArkG2Affine> = rhs.iter().map(|x| icicle_g2_affine_to_ark(&x.0)).collect();
     Bls12_381::multi_pairing(lhs_ark, rhs_ark)
 }
 
+/// Checks that the product of the pairings `e(lhs[i], rhs[i])` is the identity, with one multi-pairing.
+pub fn pairing_product_is_identity(lhs: &[G1serde], rhs: &[G2serde]) -> bool {
+    ark_ff::Zero::is_zero(&pairing(lhs, rhs))
+}
+
 pub(crate) fn msm_g1_bases(scalars: &[ScalarField], bases: &[G1Affine]) -> G1serde {
     if scalars.len() != bases.len() {
         panic!("msm input length mismatch");
@@ -131,15 +230,7 @@ pub(crate) fn msm_g1_bases(scalars: &[ScalarField], bases: &[G1Affine]) -> G1ser
     if scalars.is_empty() {
         return G1serde::zero();
     }
-    let mut msm_res = vec![G1Projective::zero(); 1];
-    msm::msm(
-        HostSlice::from_slice(scalars),
-        HostSlice::from_slice(bases),
-        &MSMConfig::default(),
-        HostSlice::from_mut_slice(&mut msm_res),
-    )
-    .unwrap();
-    G1serde(G1Affine::from(msm_res[0]))
+    G1serde(G1Affine::from(compute_backend::msm(scalars, bases)))
 }
 
 pub(crate) fn encode_o_pub_fix_common<F>(
@@ -845,12 +936,12 @@ impl PartialSigma1 {
 //     }
 // }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PartialSigma1Verify {
     pub x: G1serde,
     pub y: G1serde,
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SigmaVerify {
     pub G: G1serde,
     pub H: G2serde,
@@ -883,6 +974,103 @@ impl SigmaVerify {
     pub fn lagrange_kl(&self) -> G1serde {
         self.lagrange_KL
     }
+
+    /// Keccak-256 of the little-endian coordinates of `G`, `sigma_1.x`, `sigma_1.y`,
+    /// `lagrange_KL`, `H` and the sigma_2 points, reduced to a scalar by `hashing`. Identifies the
+    /// reference string in the strong Fiat-Shamir statement.
+    pub fn digest(&self) -> ScalarField {
+        let mut bytes = Vec::new();
+        for point in [&self.G, &self.sigma_1.x, &self.sigma_1.y, &self.lagrange_KL] {
+            bytes.extend(point.0.x.to_bytes_le());
+            bytes.extend(point.0.y.to_bytes_le());
+        }
+        let sigma_2 = &self.sigma_2;
+        for point in [
+            &self.H,
+            &sigma_2.alpha,
+            &sigma_2.alpha2,
+            &sigma_2.alpha3,
+            &sigma_2.alpha4,
+            &sigma_2.gamma,
+            &sigma_2.delta,
+            &sigma_2.eta,
+            &sigma_2.x,
+            &sigma_2.y,
+        ] {
+            bytes.extend(point.0.x.to_bytes_le());
+            bytes.extend(point.0.y.to_bytes_le());
+        }
+        hashing(&bytes)
+    }
+}
+
+/// Outcome of checking that a point built from raw coordinates is an element of the prime-order group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum PointCheck {
+    Valid,
+    NonCanonical,
+    NotOnCurve,
+    NotInSubgroup,
+}
+
+impl PointCheck {
+    pub fn is_valid(&self) -> bool {
+        *self == PointCheck::Valid
+    }
+}
+
+/// Whether points read from proofs, preprocess files and CRS artifacts are checked as they are
+/// loaded. `Trusted` skips the checks and is meant only for artifacts produced locally.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointValidation {
+    #[default]
+    Checked,
+    Trusted,
+}
+
+thread_local! {
+    static POINT_VALIDATION: Cell<PointValidation> = const { Cell::new(PointValidation::Checked) };
+}
+
+/// Runs `f` with the given point validation applied to every load on the current thread, including
+/// serde deserialization of `G1serde`/`G2serde`, and restores the previous setting afterwards.
+pub fn with_point_validation<T>(validation: PointValidation, f: impl FnOnce() -> T) -> T {
+    struct Restore(PointValidation);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            POINT_VALIDATION.with(|cell| cell.set(self.0));
+        }
+    }
+    let _restore = Restore(POINT_VALIDATION.with(|cell| cell.replace(validation)));
+    f()
+}
+
+/// The point validation in effect on the current thread, `Checked` unless overridden by
+/// `with_point_validation`.
+pub fn point_validation() -> PointValidation {
+    POINT_VALIDATION.with(Cell::get)
+}
+
+// Rejects coordinates that are not reduced modulo p instead of silently masking them.
+fn canonical_fq_from_le(bytes: &[u8]) -> Option<ark_bls12_381::Fq> {
+    if bytes.len() != 48 {
+        return None;
+    }
+    let mut limbs = [0u64; 6];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    ark_bls12_381::Fq::from_bigint(BigInt::new(limbs))
+}
+
+fn canonical_fq2_from_le(bytes: &[u8]) -> Option<ark_bls12_381::Fq2> {
+    if bytes.len() != 96 {
+        return None;
+    }
+    Some(ark_bls12_381::Fq2::new(
+        canonical_fq_from_le(&bytes[0..48])?,
+        canonical_fq_from_le(&bytes[48..96])?,
+    ))
 }
 
 #[derive(Clone, Debug, Copy, PartialEq)]
@@ -891,6 +1079,45 @@ impl G1serde {
     pub fn zero() -> Self {
         Self(G1Affine::zero())
     }
+
+    /// Wraps `point` after checking it is canonical, on the curve and in the prime-order subgroup.
+    pub fn checked(point: G1Affine) -> TokamakResult<Self> {
+        let point = Self(point);
+        match point.check() {
+            PointCheck::Valid => Ok(point),
+            check => Err(TokamakError::InvalidPoint { group: "G1", check }),
+        }
+    }
+
+    /// Wraps a point read from an artifact, checking it unless `validation` is `Trusted`.
+    pub fn load(point: G1Affine, validation: PointValidation) -> TokamakResult<Self> {
+        match validation {
+            PointValidation::Checked => Self::checked(point),
+            PointValidation::Trusted => Ok(Self(point)),
+        }
+    }
+
+    /// Checks the point is canonical, on BLS12-381 and in the prime-order subgroup.
+    /// The all-zero encoding is accepted as the identity.
+    pub fn check(&self) -> PointCheck {
+        if self.0 == G1Affine::zero() {
+            return PointCheck::Valid;
+        }
+        let (Some(x), Some(y)) = (
+            canonical_fq_from_le(&self.0.x.to_bytes_le()),
+            canonical_fq_from_le(&self.0.y.to_bytes_le()),
+        ) else {
+            return PointCheck::NonCanonical;
+        };
+        let point = ArkG1Affine::new_unchecked(x, y);
+        if !point.is_on_curve() {
+            PointCheck::NotOnCurve
+        } else if !point.is_in_correct_subgroup_assuming_on_curve() {
+            PointCheck::NotInSubgroup
+        } else {
+            PointCheck::Valid
+        }
+    }
 }
 impl Add for G1serde {
     type Output = Self;
@@ -952,6 +1179,45 @@ impl G2serde {
     pub fn zero() -> Self {
         Self(G2Affine::zero())
     }
+
+    /// Wraps `point` after checking it is canonical, on the twist and in the prime-order subgroup.
+    pub fn checked(point: G2Affine) -> TokamakResult<Self> {
+        let point = Self(point);
+        match point.check() {
+            PointCheck::Valid => Ok(point),
+            check => Err(TokamakError::InvalidPoint { group: "G2", check }),
+        }
+    }
+
+    /// Wraps a point read from an artifact, checking it unless `validation` is `Trusted`.
+    pub fn load(point: G2Affine, validation: PointValidation) -> TokamakResult<Self> {
+        match validation {
+            PointValidation::Checked => Self::checked(point),
+            PointValidation::Trusted => Ok(Self(point)),
+        }
+    }
+
+    /// Checks the point is canonical, on the BLS12-381 twist and in the prime-order subgroup.
+    /// The all-zero encoding is accepted as the identity.
+    pub fn check(&self) -> PointCheck {
+        if self.0 == G2Affine::zero() {
+            return PointCheck::Valid;
+        }
+        let (Some(x), Some(y)) = (
+            canonical_fq2_from_le(&self.0.x.to_bytes_le()),
+            canonical_fq2_from_le(&self.0.y.to_bytes_le()),
+        ) else {
+            return PointCheck::NonCanonical;
+        };
+        let point = ArkG2Affine::new_unchecked(x, y);
+        if !point.is_on_curve() {
+            PointCheck::NotOnCurve
+        } else if !point.is_in_correct_subgroup_assuming_on_curve() {
+            PointCheck::NotInSubgroup
+        } else {
+            PointCheck::Valid
+        }
+    }
 }
 //new added for G2Serde
 impl Add for G2serde {
@@ -1018,6 +1284,17 @@ pub fn icicle_g1_affine_to_ark(g: &G1Affine) -> ArkG1Affine {
     ArkG1Affine::new_unchecked(x, y)
 }
 
+/// Inverse of `icicle_g1_affine_to_ark`. The point at infinity maps to icicle's all-zero encoding.
+pub fn ark_g1_affine_to_icicle(g: &ArkG1Affine) -> G1Affine {
+    if g.infinity {
+        return G1Affine::zero();
+    }
+    G1Affine {
+        x: BaseField::from_bytes_le(&g.x.into_bigint().to_bytes_le()),
+        y: BaseField::from_bytes_le(&g.y.into_bigint().to_bytes_le()),
+    }
+}
+
 pub fn icicle_g2_affine_to_ark(g: &G2Affine) -> ArkG2Affine {
     let x_bytes = g.x.to_bytes_le();
     let y_bytes = g.y.to_bytes_le();
@@ -1029,3 +1306,20 @@ pub fn icicle_g2_affine_to_ark(g: &G2Affine) -> ArkG2Affine {
 
     ArkG2Affine::new_unchecked(x, y)
 }
+
+/// Inverse of `icicle_g2_affine_to_ark`, with the same treatment of the point at infinity as
+/// `ark_g1_affine_to_icicle`.
+pub fn ark_g2_affine_to_icicle(g: &ArkG2Affine) -> G2Affine {
+    if g.infinity {
+        return G2Affine::zero();
+    }
+    let fq2_bytes = |c: &ark_bls12_381::Fq2| {
+        let mut bytes = c.c0.into_bigint().to_bytes_le();
+        bytes.extend(c.c1.into_bigint().to_bytes_le());
+        bytes
+    };
+    G2Affine {
+        x: G2BaseField::from_bytes_le(&fq2_bytes(&g.x)),
+        y: G2BaseField::from_bytes_le(&fq2_bytes(&g.y)),
+    }
+}
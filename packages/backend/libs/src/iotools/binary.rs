@@ -0,0 +1,278 @@
+use crate::error::{TokamakError, TokamakResult};
+use crate::group_structures::{point_validation, G1serde};
+use crate::iotools::compressed::G1_COMPRESSED_LEN;
+use ark_ff::{BigInt, PrimeField};
+use clap::ValueEnum;
+use icicle_bls12_381::curve::{BaseField, G1Affine, ScalarField};
+use icicle_core::traits::FieldImpl;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Version written after the magic of every binary artifact. Bump it whenever the layout changes.
+/// Version 2 appends the transcript and Fiat-Shamir mode tags to proofs.
+pub const BINARY_FORMAT_VERSION: u8 = 2;
+
+/// Oldest version `BinaryReader` still accepts; readers branch on `BinaryReader::version`.
+pub const MIN_BINARY_FORMAT_VERSION: u8 = 1;
+
+const FLAG_COMPRESSED: u8 = 0b0000_0001;
+const HEADER_LEN: usize = 6;
+const BASE_FIELD_LEN: usize = 48;
+const SCALAR_LEN: usize = 32;
+
+/// How G1 points are laid out in a binary artifact.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointEncoding {
+    /// Big-endian `x || y`, 96 bytes. Decoding checks the point unless validation is turned off
+    /// with `with_point_validation`.
+    #[default]
+    Uncompressed,
+    /// The 48-byte zcash/arkworks compressed form. Decoding checks the point is on the curve and
+    /// in the prime-order subgroup.
+    Compressed,
+}
+
+/// On-disk encoding of the proof and preprocess artifacts, selectable from the CLI.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ArtifactEncoding {
+    /// Part1/part2 hex-string JSON consumed by the Solidity verifier
+    #[default]
+    Json,
+    /// Versioned binary with uncompressed points
+    Binary,
+    /// Versioned binary with compressed points
+    BinaryCompressed,
+}
+
+impl ArtifactEncoding {
+    /// File name of the artifact `stem` in this encoding, e.g. `proof.json` or `proof.bin`.
+    pub fn file_name(&self, stem: &str) -> String {
+        match self {
+            ArtifactEncoding::Json => format!("{stem}.json"),
+            ArtifactEncoding::Binary | ArtifactEncoding::BinaryCompressed => format!("{stem}.bin"),
+        }
+    }
+
+    /// The point encoding of a binary artifact, or `None` for JSON.
+    pub fn point_encoding(&self) -> Option<PointEncoding> {
+        match self {
+            ArtifactEncoding::Json => None,
+            ArtifactEncoding::Binary => Some(PointEncoding::Uncompressed),
+            ArtifactEncoding::BinaryCompressed => Some(PointEncoding::Compressed),
+        }
+    }
+}
+
+/// Writes the `magic || version || flags` header followed by fixed-width elements.
+pub struct BinaryWriter {
+    bytes: Vec<u8>,
+    encoding: PointEncoding,
+}
+
+impl BinaryWriter {
+    pub fn new(magic: &[u8; 4], encoding: PointEncoding) -> Self {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(magic);
+        bytes.push(BINARY_FORMAT_VERSION);
+        bytes.push(match encoding {
+            PointEncoding::Uncompressed => 0,
+            PointEncoding::Compressed => FLAG_COMPRESSED,
+        });
+        Self { bytes, encoding }
+    }
+
+    pub fn g1(&mut self, point: &G1serde) -> &mut Self {
+        match self.encoding {
+            PointEncoding::Uncompressed => {
+                self.bytes.extend(be_bytes(&point.0.x));
+                self.bytes.extend(be_bytes(&point.0.y));
+            }
+            PointEncoding::Compressed => self.bytes.extend(point.to_compressed()),
+        }
+        self
+    }
+
+    pub fn scalar(&mut self, scalar: &ScalarField) -> &mut Self {
+        self.bytes.extend(be_bytes(scalar));
+        self
+    }
+
+    pub fn u8(&mut self, value: u8) -> &mut Self {
+        self.bytes.push(value);
+        self
+    }
+
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.bytes.extend(value.to_be_bytes());
+        self
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads back what `BinaryWriter` produced, checking the header and that no bytes are left over.
+pub struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    version: u8,
+    encoding: PointEncoding,
+    what: &'static str,
+}
+
+impl<'a> BinaryReader<'a> {
+    pub fn new(bytes: &'a [u8], magic: &[u8; 4], what: &'static str) -> TokamakResult<Self> {
+        if bytes.len() < HEADER_LEN || &bytes[..4] != magic {
+            return Err(TokamakError::parse(what, "missing or wrong magic header"));
+        }
+        let version = bytes[4];
+        if !(MIN_BINARY_FORMAT_VERSION..=BINARY_FORMAT_VERSION).contains(&version) {
+            return Err(TokamakError::parse(
+                what,
+                format!(
+                    "unsupported format version {version} (expected {MIN_BINARY_FORMAT_VERSION} to {BINARY_FORMAT_VERSION})"
+                ),
+            ));
+        }
+        let encoding = match bytes[5] {
+            0 => PointEncoding::Uncompressed,
+            FLAG_COMPRESSED => PointEncoding::Compressed,
+            flags => {
+                return Err(TokamakError::parse(
+                    what,
+                    format!("unknown flags 0x{flags:02x}"),
+                ))
+            }
+        };
+        Ok(Self {
+            bytes,
+            pos: HEADER_LEN,
+            version,
+            encoding,
+            what,
+        })
+    }
+
+    /// The format version in the header, between `MIN_BINARY_FORMAT_VERSION` and
+    /// `BINARY_FORMAT_VERSION`.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn encoding(&self) -> PointEncoding {
+        self.encoding
+    }
+
+    fn take(&mut self, len: usize) -> TokamakResult<&'a [u8]> {
+        if self.bytes.len() - self.pos < len {
+            return Err(TokamakError::parse(
+                self.what,
+                format!("truncated at byte {}", self.pos),
+            ));
+        }
+        let chunk = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(chunk)
+    }
+
+    pub fn g1(&mut self) -> TokamakResult<G1serde> {
+        match self.encoding {
+            PointEncoding::Uncompressed => {
+                let pos = self.pos;
+                let x = BaseField::from_bytes_le(&le_bytes(self.take(BASE_FIELD_LEN)?));
+                let y = BaseField::from_bytes_le(&le_bytes(self.take(BASE_FIELD_LEN)?));
+                G1serde::load(G1Affine { x, y }, point_validation()).map_err(|err| {
+                    TokamakError::parse(self.what, format!("invalid G1 point at byte {pos}: {err}"))
+                })
+            }
+            PointEncoding::Compressed => {
+                let pos = self.pos;
+                let chunk = self.take(G1_COMPRESSED_LEN)?;
+                G1serde::from_compressed(chunk).map_err(|err| {
+                    TokamakError::parse(
+                        self.what,
+                        format!("invalid compressed G1 point at byte {pos}: {err}"),
+                    )
+                })
+            }
+        }
+    }
+
+    /// Reads a scalar, rejecting values that are not reduced modulo r so that every artifact
+    /// has exactly one encoding.
+    pub fn scalar(&mut self) -> TokamakResult<ScalarField> {
+        let pos = self.pos;
+        let le = le_bytes(self.take(SCALAR_LEN)?);
+        if !is_canonical_scalar(&le) {
+            return Err(TokamakError::parse(
+                self.what,
+                format!("non-canonical scalar at byte {pos}"),
+            ));
+        }
+        Ok(ScalarField::from_bytes_le(&le))
+    }
+
+    pub fn u8(&mut self) -> TokamakResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u64(&mut self) -> TokamakResult<u64> {
+        let mut value = [0u8; 8];
+        value.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(value))
+    }
+
+    pub fn bytes(&mut self, len: usize) -> TokamakResult<&'a [u8]> {
+        self.take(len)
+    }
+
+    pub fn finish(self) -> TokamakResult<()> {
+        if self.pos != self.bytes.len() {
+            return Err(TokamakError::parse(
+                self.what,
+                format!("{} trailing bytes", self.bytes.len() - self.pos),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Writes a binary artifact, creating the parent directory like `write_into_json` does.
+pub fn write_binary(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes)
+}
+
+pub fn read_binary(path: &Path) -> TokamakResult<Vec<u8>> {
+    fs::read(path).map_err(|source| TokamakError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn be_bytes<T: FieldImpl>(field: &T) -> Vec<u8> {
+    let mut bytes = field.to_bytes_le();
+    bytes.reverse();
+    bytes
+}
+
+fn le_bytes(be: &[u8]) -> Vec<u8> {
+    be.iter().rev().copied().collect()
+}
+
+fn is_canonical_scalar(le: &[u8]) -> bool {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(le.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    ark_bls12_381::Fr::from_bigint(BigInt::new(limbs)).is_some()
+}
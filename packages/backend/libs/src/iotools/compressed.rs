@@ -0,0 +1,410 @@
+use super::{G1SerdeRkyv, G2SerdeRkyv, Sigma1Rkyv, Sigma2Rkyv, SigmaRkyv};
+use crate::error::{TokamakError, TokamakResult};
+use crate::group_structures::{
+    ark_g1_affine_to_icicle, ark_g2_affine_to_icicle, icicle_g1_affine_to_ark,
+    icicle_g2_affine_to_ark, G1serde, G2serde, Sigma, Sigma1, Sigma2,
+};
+use ark_bls12_381::{G1Affine as ArkG1Affine, G2Affine as ArkG2Affine};
+use ark_ec::AffineRepr;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use icicle_bls12_381::curve::{G1Affine, G2Affine};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::io;
+
+/// Length of a compressed G1 point: the big-endian x coordinate with the compression, infinity
+/// and sign-of-y flags in the top three bits (zcash/arkworks layout).
+pub const G1_COMPRESSED_LEN: usize = 48;
+/// Length of a compressed G2 point: x.c1 then x.c0, flags as for G1.
+pub const G2_COMPRESSED_LEN: usize = 96;
+
+impl G1serde {
+    pub fn to_compressed(&self) -> [u8; G1_COMPRESSED_LEN] {
+        let point = if self.0 == G1Affine::zero() {
+            ArkG1Affine::zero()
+        } else {
+            icicle_g1_affine_to_ark(&self.0)
+        };
+        let mut bytes = [0u8; G1_COMPRESSED_LEN];
+        point
+            .serialize_with_mode(&mut bytes[..], Compress::Yes)
+            .expect("a compressed G1 point is 48 bytes");
+        bytes
+    }
+
+    /// Decompresses a point, rejecting x coordinates off the curve and points outside the
+    /// prime-order subgroup.
+    pub fn from_compressed(bytes: &[u8]) -> TokamakResult<Self> {
+        if bytes.len() != G1_COMPRESSED_LEN {
+            return Err(TokamakError::parse(
+                "compressed G1 point",
+                format!("expected {G1_COMPRESSED_LEN} bytes, got {}", bytes.len()),
+            ));
+        }
+        let point = ArkG1Affine::deserialize_with_mode(bytes, Compress::Yes, Validate::Yes)
+            .map_err(|err| TokamakError::parse("compressed G1 point", err.to_string()))?;
+        Ok(G1serde(ark_g1_affine_to_icicle(&point)))
+    }
+}
+
+impl G2serde {
+    pub fn to_compressed(&self) -> [u8; G2_COMPRESSED_LEN] {
+        let point = if self.0 == G2Affine::zero() {
+            ArkG2Affine::zero()
+        } else {
+            icicle_g2_affine_to_ark(&self.0)
+        };
+        let mut bytes = [0u8; G2_COMPRESSED_LEN];
+        point
+            .serialize_with_mode(&mut bytes[..], Compress::Yes)
+            .expect("a compressed G2 point is 96 bytes");
+        bytes
+    }
+
+    /// Decompresses a point, rejecting x coordinates off the twist and points outside the
+    /// prime-order subgroup.
+    pub fn from_compressed(bytes: &[u8]) -> TokamakResult<Self> {
+        if bytes.len() != G2_COMPRESSED_LEN {
+            return Err(TokamakError::parse(
+                "compressed G2 point",
+                format!("expected {G2_COMPRESSED_LEN} bytes, got {}", bytes.len()),
+            ));
+        }
+        let point = ArkG2Affine::deserialize_with_mode(bytes, Compress::Yes, Validate::Yes)
+            .map_err(|err| TokamakError::parse("compressed G2 point", err.to_string()))?;
+        Ok(G2serde(ark_g2_affine_to_icicle(&point)))
+    }
+}
+
+/// `0x`-prefixed hex of a compressed point, as written into JSON.
+pub fn compressed_to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+pub fn compressed_from_hex(value: &str) -> TokamakResult<Vec<u8>> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|err| TokamakError::parse("compressed point hex", err.to_string()))
+}
+
+/// Serde helpers writing a `G1serde` field as a compressed hex string, for use with
+/// `#[serde(with = "compressed::g1_hex")]`. Reading also accepts the `{x, y}` form.
+pub mod g1_hex {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(point: &G1serde, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&compressed_to_hex(&point.to_compressed()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<G1serde, D::Error> {
+        G1serde::deserialize(deserializer)
+    }
+}
+
+/// Serde helpers writing a `G2serde` field as a compressed hex string.
+pub mod g2_hex {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(point: &G2serde, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&compressed_to_hex(&point.to_compressed()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<G2serde, D::Error> {
+        G2serde::deserialize(deserializer)
+    }
+}
+
+#[derive(Debug, Clone, Copy, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[archive(check_bytes)]
+pub struct G1CompressedRkyv {
+    pub bytes: [u8; G1_COMPRESSED_LEN],
+}
+
+#[derive(Debug, Clone, Copy, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[archive(check_bytes)]
+pub struct G2CompressedRkyv {
+    pub bytes: [u8; G2_COMPRESSED_LEN],
+}
+
+impl G1CompressedRkyv {
+    pub fn from_g1serde(value: &G1serde) -> Self {
+        Self {
+            bytes: value.to_compressed(),
+        }
+    }
+}
+
+impl ArchivedG1CompressedRkyv {
+    pub fn to_g1serde(&self) -> TokamakResult<G1serde> {
+        G1serde::from_compressed(&self.bytes)
+    }
+}
+
+impl G2CompressedRkyv {
+    pub fn from_g2serde(value: &G2serde) -> Self {
+        Self {
+            bytes: value.to_compressed(),
+        }
+    }
+}
+
+impl ArchivedG2CompressedRkyv {
+    pub fn to_g2serde(&self) -> TokamakResult<G2serde> {
+        G2serde::from_compressed(&self.bytes)
+    }
+}
+
+/// Prefix of a `combined_sigma.rkyv` holding a `CompressedSigmaRkyv` instead of a `SigmaRkyv`.
+/// Eight bytes keep the archive that follows aligned.
+pub const COMPRESSED_SIGMA_MAGIC: [u8; 8] = *b"TKSIGC01";
+
+#[derive(Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[archive(check_bytes)]
+pub struct CompressedSigmaRkyv {
+    pub G: G1CompressedRkyv,
+    pub H: G2CompressedRkyv,
+    pub sigma_1: CompressedSigma1Rkyv,
+    pub sigma_2: CompressedSigma2Rkyv,
+    pub lagrange_KL: G1CompressedRkyv,
+}
+
+#[derive(Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[archive(check_bytes)]
+pub struct CompressedSigma1Rkyv {
+    pub xy_powers: Vec<G1CompressedRkyv>,
+    pub x: G1CompressedRkyv,
+    pub y: G1CompressedRkyv,
+    pub delta: G1CompressedRkyv,
+    pub eta: G1CompressedRkyv,
+    pub gamma_inv_o_inst: Vec<G1CompressedRkyv>,
+    pub eta_inv_li_o_inter_alpha4_kj: Vec<Vec<G1CompressedRkyv>>,
+    pub delta_inv_li_o_prv: Vec<Vec<G1CompressedRkyv>>,
+    pub delta_inv_alphak_xh_tx: Vec<Vec<G1CompressedRkyv>>,
+    pub delta_inv_alpha4_xj_tx: Vec<G1CompressedRkyv>,
+    pub delta_inv_alphak_yi_ty: Vec<Vec<G1CompressedRkyv>>,
+}
+
+#[derive(Debug, Clone, Copy, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[archive(check_bytes)]
+pub struct CompressedSigma2Rkyv {
+    pub alpha: G2CompressedRkyv,
+    pub alpha2: G2CompressedRkyv,
+    pub alpha3: G2CompressedRkyv,
+    pub alpha4: G2CompressedRkyv,
+    pub gamma: G2CompressedRkyv,
+    pub delta: G2CompressedRkyv,
+    pub eta: G2CompressedRkyv,
+    pub x: G2CompressedRkyv,
+    pub y: G2CompressedRkyv,
+}
+
+fn compress_g1_vec(points: &[G1serde]) -> Vec<G1CompressedRkyv> {
+    points
+        .par_iter()
+        .map(G1CompressedRkyv::from_g1serde)
+        .collect()
+}
+
+fn compress_g1_mat(rows: &[Box<[G1serde]>]) -> Vec<Vec<G1CompressedRkyv>> {
+    rows.iter().map(|row| compress_g1_vec(row)).collect()
+}
+
+fn decompress_g1(point: &ArchivedG1CompressedRkyv) -> TokamakResult<G1SerdeRkyv> {
+    Ok(G1SerdeRkyv::from_g1serde(&point.to_g1serde()?))
+}
+
+fn decompress_g2(point: &ArchivedG2CompressedRkyv) -> TokamakResult<G2SerdeRkyv> {
+    Ok(G2SerdeRkyv::from_g2serde(&point.to_g2serde()?))
+}
+
+fn decompress_g1_vec(points: &[ArchivedG1CompressedRkyv]) -> TokamakResult<Vec<G1SerdeRkyv>> {
+    points.par_iter().map(decompress_g1).collect()
+}
+
+fn decompress_g1_mat(
+    rows: &[rkyv::vec::ArchivedVec<ArchivedG1CompressedRkyv>],
+) -> TokamakResult<Vec<Vec<G1SerdeRkyv>>> {
+    rows.iter().map(|row| decompress_g1_vec(row)).collect()
+}
+
+impl CompressedSigmaRkyv {
+    pub fn from_sigma(sigma: &Sigma) -> Self {
+        Self {
+            G: G1CompressedRkyv::from_g1serde(&sigma.G),
+            H: G2CompressedRkyv::from_g2serde(&sigma.H),
+            sigma_1: CompressedSigma1Rkyv::from_sigma(&sigma.sigma_1),
+            sigma_2: CompressedSigma2Rkyv::from_sigma(&sigma.sigma_2),
+            lagrange_KL: G1CompressedRkyv::from_g1serde(&sigma.lagrange_KL),
+        }
+    }
+}
+
+impl CompressedSigma1Rkyv {
+    fn from_sigma(sigma: &Sigma1) -> Self {
+        Self {
+            xy_powers: compress_g1_vec(&sigma.xy_powers),
+            x: G1CompressedRkyv::from_g1serde(&sigma.x),
+            y: G1CompressedRkyv::from_g1serde(&sigma.y),
+            delta: G1CompressedRkyv::from_g1serde(&sigma.delta),
+            eta: G1CompressedRkyv::from_g1serde(&sigma.eta),
+            gamma_inv_o_inst: compress_g1_vec(&sigma.gamma_inv_o_inst),
+            eta_inv_li_o_inter_alpha4_kj: compress_g1_mat(&sigma.eta_inv_li_o_inter_alpha4_kj),
+            delta_inv_li_o_prv: compress_g1_mat(&sigma.delta_inv_li_o_prv),
+            delta_inv_alphak_xh_tx: compress_g1_mat(&sigma.delta_inv_alphak_xh_tx),
+            delta_inv_alpha4_xj_tx: compress_g1_vec(&sigma.delta_inv_alpha4_xj_tx),
+            delta_inv_alphak_yi_ty: compress_g1_mat(&sigma.delta_inv_alphak_yi_ty),
+        }
+    }
+}
+
+impl CompressedSigma2Rkyv {
+    fn from_sigma(sigma: &Sigma2) -> Self {
+        Self {
+            alpha: G2CompressedRkyv::from_g2serde(&sigma.alpha),
+            alpha2: G2CompressedRkyv::from_g2serde(&sigma.alpha2),
+            alpha3: G2CompressedRkyv::from_g2serde(&sigma.alpha3),
+            alpha4: G2CompressedRkyv::from_g2serde(&sigma.alpha4),
+            gamma: G2CompressedRkyv::from_g2serde(&sigma.gamma),
+            delta: G2CompressedRkyv::from_g2serde(&sigma.delta),
+            eta: G2CompressedRkyv::from_g2serde(&sigma.eta),
+            x: G2CompressedRkyv::from_g2serde(&sigma.x),
+            y: G2CompressedRkyv::from_g2serde(&sigma.y),
+        }
+    }
+}
+
+impl ArchivedCompressedSigmaRkyv {
+    /// Decompresses every point, checking each one, into the layout the prover reads in place.
+    pub fn decompress(&self) -> TokamakResult<SigmaRkyv> {
+        let sigma_1 = &self.sigma_1;
+        let sigma_2 = &self.sigma_2;
+        Ok(SigmaRkyv {
+            G: decompress_g1(&self.G)?,
+            H: decompress_g2(&self.H)?,
+            sigma_1: Sigma1Rkyv {
+                xy_powers: decompress_g1_vec(&sigma_1.xy_powers)?,
+                x: decompress_g1(&sigma_1.x)?,
+                y: decompress_g1(&sigma_1.y)?,
+                delta: decompress_g1(&sigma_1.delta)?,
+                eta: decompress_g1(&sigma_1.eta)?,
+                gamma_inv_o_inst: decompress_g1_vec(&sigma_1.gamma_inv_o_inst)?,
+                eta_inv_li_o_inter_alpha4_kj: decompress_g1_mat(
+                    &sigma_1.eta_inv_li_o_inter_alpha4_kj,
+                )?,
+                delta_inv_li_o_prv: decompress_g1_mat(&sigma_1.delta_inv_li_o_prv)?,
+                delta_inv_alphak_xh_tx: decompress_g1_mat(&sigma_1.delta_inv_alphak_xh_tx)?,
+                delta_inv_alpha4_xj_tx: decompress_g1_vec(&sigma_1.delta_inv_alpha4_xj_tx)?,
+                delta_inv_alphak_yi_ty: decompress_g1_mat(&sigma_1.delta_inv_alphak_yi_ty)?,
+            },
+            sigma_2: Sigma2Rkyv {
+                alpha: decompress_g2(&sigma_2.alpha)?,
+                alpha2: decompress_g2(&sigma_2.alpha2)?,
+                alpha3: decompress_g2(&sigma_2.alpha3)?,
+                alpha4: decompress_g2(&sigma_2.alpha4)?,
+                gamma: decompress_g2(&sigma_2.gamma)?,
+                delta: decompress_g2(&sigma_2.delta)?,
+                eta: decompress_g2(&sigma_2.eta)?,
+                x: decompress_g2(&sigma_2.x)?,
+                y: decompress_g2(&sigma_2.y)?,
+            },
+            lagrange_KL: decompress_g1(&self.lagrange_KL)?,
+        })
+    }
+}
+
+/// Encodes `sigma` as `COMPRESSED_SIGMA_MAGIC || rkyv(CompressedSigmaRkyv)`.
+pub fn compressed_sigma_bytes(sigma: &Sigma) -> io::Result<Vec<u8>> {
+    let archive = rkyv::to_bytes::<_, 256>(&CompressedSigmaRkyv::from_sigma(sigma))
+        .map_err(io::Error::other)?;
+    let mut bytes = Vec::with_capacity(COMPRESSED_SIGMA_MAGIC.len() + archive.len());
+    bytes.extend_from_slice(&COMPRESSED_SIGMA_MAGIC);
+    bytes.extend_from_slice(&archive);
+    Ok(bytes)
+}
+
+pub fn is_compressed_sigma(bytes: &[u8]) -> bool {
+    bytes.starts_with(&COMPRESSED_SIGMA_MAGIC)
+}
+
+/// Validates a compressed `combined_sigma.rkyv` and decompresses it into a `SigmaRkyv`.
+pub fn decompress_sigma_bytes(bytes: &[u8]) -> TokamakResult<SigmaRkyv> {
+    const WHAT: &str = "compressed sigma archive";
+    if !is_compressed_sigma(bytes) {
+        return Err(TokamakError::parse(WHAT, "missing magic header"));
+    }
+    // Copy so the archive is aligned regardless of where `bytes` lives.
+    let mut aligned = rkyv::AlignedVec::with_capacity(bytes.len() - COMPRESSED_SIGMA_MAGIC.len());
+    aligned.extend_from_slice(&bytes[COMPRESSED_SIGMA_MAGIC.len()..]);
+    let archived = rkyv::check_archived_root::<CompressedSigmaRkyv>(&aligned)
+        .map_err(|err| TokamakError::parse(WHAT, format!("{err:?}")))?;
+    archived.decompress()
+}
+
+#[derive(Serialize)]
+struct CompressedSigma2Json {
+    #[serde(with = "g2_hex")]
+    alpha: G2serde,
+    #[serde(with = "g2_hex")]
+    alpha2: G2serde,
+    #[serde(with = "g2_hex")]
+    alpha3: G2serde,
+    #[serde(with = "g2_hex")]
+    alpha4: G2serde,
+    #[serde(with = "g2_hex")]
+    gamma: G2serde,
+    #[serde(with = "g2_hex")]
+    delta: G2serde,
+    #[serde(with = "g2_hex")]
+    eta: G2serde,
+    #[serde(with = "g2_hex")]
+    x: G2serde,
+    #[serde(with = "g2_hex")]
+    y: G2serde,
+}
+
+#[derive(Serialize)]
+struct CompressedPartialSigma1VerifyJson {
+    #[serde(with = "g1_hex")]
+    x: G1serde,
+    #[serde(with = "g1_hex")]
+    y: G1serde,
+}
+
+// Same field names as `SigmaVerify`, so `SigmaVerify::read_from_json` reads it back.
+#[derive(Serialize)]
+struct CompressedSigmaVerifyJson {
+    #[serde(with = "g1_hex")]
+    G: G1serde,
+    #[serde(with = "g2_hex")]
+    H: G2serde,
+    sigma_1: CompressedPartialSigma1VerifyJson,
+    sigma_2: CompressedSigma2Json,
+    #[serde(with = "g1_hex")]
+    lagrange_KL: G1serde,
+}
+
+/// `sigma_verify.json` with every point written as a compressed hex string.
+pub fn compressed_sigma_verify_json(sigma: &Sigma) -> serde_json::Result<Vec<u8>> {
+    let sigma_2 = &sigma.sigma_2;
+    serde_json::to_vec_pretty(&CompressedSigmaVerifyJson {
+        G: sigma.G,
+        H: sigma.H,
+        sigma_1: CompressedPartialSigma1VerifyJson {
+            x: sigma.sigma_1.x,
+            y: sigma.sigma_1.y,
+        },
+        sigma_2: CompressedSigma2Json {
+            alpha: sigma_2.alpha,
+            alpha2: sigma_2.alpha2,
+            alpha3: sigma_2.alpha3,
+            alpha4: sigma_2.alpha4,
+            gamma: sigma_2.gamma,
+            delta: sigma_2.delta,
+            eta: sigma_2.eta,
+            x: sigma_2.x,
+            y: sigma_2.y,
+        },
+        lagrange_KL: sigma.lagrange_KL,
+    })
+}
@@ -0,0 +1,38 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// What the transcript absorbs before the first round message. Recorded in the proof, so that
+/// the verifier replays the mode the prover used. Ordered from weakest to strongest.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FiatShamirMode {
+    /// Only the round messages, as the original Solidity verifier expects
+    #[default]
+    Legacy,
+    /// First the `ProtocolStatement`: protocol tag, reference string, setup parameters,
+    /// preprocess commitments and public instance
+    StrongV1,
+}
+
+impl FiatShamirMode {
+    /// Byte identifying the mode in binary artifacts.
+    pub fn tag(&self) -> u8 {
+        match self {
+            FiatShamirMode::Legacy => 0,
+            FiatShamirMode::StrongV1 => 1,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(FiatShamirMode::Legacy),
+            1 => Some(FiatShamirMode::StrongV1),
+            _ => None,
+        }
+    }
+}
+
+/// Absorbed first in `FiatShamirMode::StrongV1`, as a big-endian scalar.
+pub const FIAT_SHAMIR_PROTOCOL_TAG: &[u8] = b"tokamak-zk-evm/fiat-shamir/v1";
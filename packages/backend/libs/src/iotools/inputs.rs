@@ -0,0 +1,286 @@
+use std::borrow::Cow;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{
+    Instance, InstanceDescription, Permutation, PlacementVariables, SetupParams, SubcircuitInfo,
+};
+use crate::error::{TokamakError, TokamakResult};
+use crate::group_structures::PointValidation;
+
+/// Binary R1CS constraints of the subcircuit library, one file per subcircuit.
+pub trait R1csSource {
+    fn r1cs_bytes(&self, subcircuit_id: usize) -> io::Result<Cow<'_, [u8]>>;
+}
+
+/// A subcircuit library directory, read from `r1cs/subcircuit<id>.r1cs`.
+impl R1csSource for Path {
+    fn r1cs_bytes(&self, subcircuit_id: usize) -> io::Result<Cow<'_, [u8]>> {
+        let path = self.join(format!("r1cs/subcircuit{subcircuit_id}.r1cs"));
+        std::fs::read(&path)
+            .map(Cow::Owned)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))
+    }
+}
+
+impl R1csSource for PathBuf {
+    fn r1cs_bytes(&self, subcircuit_id: usize) -> io::Result<Cow<'_, [u8]>> {
+        self.as_path().r1cs_bytes(subcircuit_id)
+    }
+}
+
+/// Binary R1CS files held in memory, indexed by subcircuit id.
+impl R1csSource for Vec<Vec<u8>> {
+    fn r1cs_bytes(&self, subcircuit_id: usize) -> io::Result<Cow<'_, [u8]>> {
+        self.get(subcircuit_id)
+            .map(|data| Cow::Borrowed(data.as_slice()))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no R1CS for subcircuit {subcircuit_id}"),
+                )
+            })
+    }
+}
+
+/// Circuit and synthesizer inputs of one transaction. `Prover::init`, `Verifier::init` and
+/// `Preprocess::gen` each take an extension of this trait that adds the reference string
+/// artifacts they consume. The `*InputPaths` structs of those crates read the usual files from
+/// the subcircuit library and synthesizer output directories; `MemoryInputs` borrows structures
+/// the caller already holds.
+pub trait CircuitInputs: R1csSource {
+    fn setup_params(&self) -> TokamakResult<Cow<'_, SetupParams>>;
+    fn subcircuit_infos(&self) -> TokamakResult<Cow<'_, [SubcircuitInfo]>>;
+    fn placement_variables(&self) -> TokamakResult<Cow<'_, [PlacementVariables]>>;
+    fn permutation(&self) -> TokamakResult<Cow<'_, [Permutation]>>;
+    fn instance(&self) -> TokamakResult<Cow<'_, Instance>>;
+
+    /// The synthesizer's descriptions of the instance elements, if available. Only the verifier
+    /// reads them, to report the public values a proof attests to.
+    fn instance_description(&self) -> TokamakResult<Option<Cow<'_, InstanceDescription>>> {
+        Ok(None)
+    }
+
+    /// How the points of the reference string artifacts are checked on load.
+    fn point_validation(&self) -> PointValidation {
+        PointValidation::Checked
+    }
+
+    /// Size of the file `file_name` (e.g. `instance.json`) an input is read from, for the timing
+    /// report. Inputs that are not read from files report 0.
+    fn file_bytes(&self, _file_name: &str) -> usize {
+        0
+    }
+}
+
+/// Size of `dir/file_name`, or 0 if it cannot be read.
+pub fn file_bytes(dir: &str, file_name: &str) -> usize {
+    std::fs::metadata(Path::new(dir).join(file_name))
+        .map(|metadata| metadata.len() as usize)
+        .unwrap_or(0)
+}
+
+pub fn load_subcircuit_infos(qap_path: &str) -> TokamakResult<Box<[SubcircuitInfo]>> {
+    let path = PathBuf::from(qap_path).join("subcircuitInfo.json");
+    SubcircuitInfo::read_box_from_json(path.clone())
+        .map_err(|err| TokamakError::from_read(&path, err))
+}
+
+pub fn load_placement_variables(
+    synthesizer_path: &str,
+) -> TokamakResult<Box<[PlacementVariables]>> {
+    let path = PathBuf::from(synthesizer_path).join("placementVariables.json");
+    PlacementVariables::read_box_from_json(path.clone())
+        .map_err(|err| TokamakError::from_read(&path, err))
+}
+
+pub fn load_permutation(synthesizer_path: &str) -> TokamakResult<Box<[Permutation]>> {
+    let path = PathBuf::from(synthesizer_path).join("permutation.json");
+    Permutation::read_box_from_json(path.clone()).map_err(|err| TokamakError::from_read(&path, err))
+}
+
+pub fn load_instance(synthesizer_path: &str) -> TokamakResult<Instance> {
+    let path = PathBuf::from(synthesizer_path).join("instance.json");
+    Instance::read_from_json(path.clone()).map_err(|err| TokamakError::from_read(&path, err))
+}
+
+/// Reads `instance_description.json`, which synthesizer outputs that predate it do not have.
+pub fn load_instance_description(
+    synthesizer_path: &str,
+) -> TokamakResult<Option<InstanceDescription>> {
+    let path = PathBuf::from(synthesizer_path).join("instance_description.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    InstanceDescription::read_from_json(path.clone())
+        .map(Some)
+        .map_err(|err| TokamakError::from_read(&path, err))
+}
+
+/// Implements `CircuitInputs` for a struct with `qap_path`, `synthesizer_path` and
+/// `point_validation` fields, reading `setupParams.json`, `subcircuitInfo.json` and the R1CS
+/// files from the former and `placementVariables.json`, `permutation.json`, `instance.json` and
+/// `instance_description.json` from the latter.
+#[macro_export]
+macro_rules! impl_circuit_inputs_for_paths {
+    ($t:ty) => {
+        impl $crate::iotools::inputs::R1csSource for $t {
+            fn r1cs_bytes(
+                &self,
+                subcircuit_id: usize,
+            ) -> std::io::Result<std::borrow::Cow<'_, [u8]>> {
+                $crate::iotools::inputs::R1csSource::r1cs_bytes(
+                    std::path::Path::new(self.qap_path),
+                    subcircuit_id,
+                )
+            }
+        }
+
+        impl $crate::iotools::inputs::CircuitInputs for $t {
+            fn setup_params(
+                &self,
+            ) -> $crate::error::TokamakResult<std::borrow::Cow<'_, $crate::iotools::SetupParams>>
+            {
+                $crate::utils::load_setup_params_from_qap_path(self.qap_path)
+                    .map(std::borrow::Cow::Owned)
+            }
+
+            fn subcircuit_infos(
+                &self,
+            ) -> $crate::error::TokamakResult<std::borrow::Cow<'_, [$crate::iotools::SubcircuitInfo]>>
+            {
+                $crate::iotools::inputs::load_subcircuit_infos(self.qap_path)
+                    .map(|infos| std::borrow::Cow::Owned(infos.into_vec()))
+            }
+
+            fn placement_variables(
+                &self,
+            ) -> $crate::error::TokamakResult<
+                std::borrow::Cow<'_, [$crate::iotools::PlacementVariables]>,
+            > {
+                $crate::iotools::inputs::load_placement_variables(self.synthesizer_path)
+                    .map(|variables| std::borrow::Cow::Owned(variables.into_vec()))
+            }
+
+            fn permutation(
+                &self,
+            ) -> $crate::error::TokamakResult<std::borrow::Cow<'_, [$crate::iotools::Permutation]>>
+            {
+                $crate::iotools::inputs::load_permutation(self.synthesizer_path)
+                    .map(|permutation| std::borrow::Cow::Owned(permutation.into_vec()))
+            }
+
+            fn instance(
+                &self,
+            ) -> $crate::error::TokamakResult<std::borrow::Cow<'_, $crate::iotools::Instance>>
+            {
+                $crate::iotools::inputs::load_instance(self.synthesizer_path)
+                    .map(std::borrow::Cow::Owned)
+            }
+
+            fn instance_description(
+                &self,
+            ) -> $crate::error::TokamakResult<
+                Option<std::borrow::Cow<'_, $crate::iotools::InstanceDescription>>,
+            > {
+                $crate::iotools::inputs::load_instance_description(self.synthesizer_path)
+                    .map(|description| description.map(std::borrow::Cow::Owned))
+            }
+
+            fn point_validation(&self) -> $crate::group_structures::PointValidation {
+                self.point_validation
+            }
+
+            fn file_bytes(&self, file_name: &str) -> usize {
+                let dir = match file_name {
+                    "setupParams.json" | "subcircuitInfo.json" => self.qap_path,
+                    _ => self.synthesizer_path,
+                };
+                $crate::iotools::inputs::file_bytes(dir, file_name)
+            }
+        }
+    };
+}
+
+/// Inputs borrowed from structures the caller already holds, e.g. a synthesizer running in the
+/// same process. `crs` carries the reference string artifacts of the consumer. Inputs left as
+/// `None` fail with `TokamakError::MissingInput` when the consumer asks for them, so a verifier
+/// only needs `setup_params` and `instance`. `instance_description` is optional.
+pub struct MemoryInputs<'a, C> {
+    pub setup_params: Option<&'a SetupParams>,
+    pub subcircuit_infos: Option<&'a [SubcircuitInfo]>,
+    pub placement_variables: Option<&'a [PlacementVariables]>,
+    pub permutation: Option<&'a [Permutation]>,
+    pub instance: Option<&'a Instance>,
+    pub instance_description: Option<&'a InstanceDescription>,
+    pub r1cs: Option<&'a dyn R1csSource>,
+    pub crs: C,
+    pub point_validation: PointValidation,
+}
+
+impl<'a, C> MemoryInputs<'a, C> {
+    pub fn new(crs: C) -> Self {
+        Self {
+            setup_params: None,
+            subcircuit_infos: None,
+            placement_variables: None,
+            permutation: None,
+            instance: None,
+            instance_description: None,
+            r1cs: None,
+            crs,
+            point_validation: PointValidation::default(),
+        }
+    }
+}
+
+fn borrowed<'a, T: ToOwned + ?Sized>(
+    value: Option<&'a T>,
+    name: &'static str,
+) -> TokamakResult<Cow<'a, T>> {
+    value
+        .map(Cow::Borrowed)
+        .ok_or(TokamakError::MissingInput(name))
+}
+
+impl<C> R1csSource for MemoryInputs<'_, C> {
+    fn r1cs_bytes(&self, subcircuit_id: usize) -> io::Result<Cow<'_, [u8]>> {
+        match self.r1cs {
+            Some(r1cs) => r1cs.r1cs_bytes(subcircuit_id),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no R1CS source was given",
+            )),
+        }
+    }
+}
+
+impl<C> CircuitInputs for MemoryInputs<'_, C> {
+    fn setup_params(&self) -> TokamakResult<Cow<'_, SetupParams>> {
+        borrowed(self.setup_params, "setup_params")
+    }
+
+    fn subcircuit_infos(&self) -> TokamakResult<Cow<'_, [SubcircuitInfo]>> {
+        borrowed(self.subcircuit_infos, "subcircuit_infos")
+    }
+
+    fn placement_variables(&self) -> TokamakResult<Cow<'_, [PlacementVariables]>> {
+        borrowed(self.placement_variables, "placement_variables")
+    }
+
+    fn permutation(&self) -> TokamakResult<Cow<'_, [Permutation]>> {
+        borrowed(self.permutation, "permutation")
+    }
+
+    fn instance(&self) -> TokamakResult<Cow<'_, Instance>> {
+        borrowed(self.instance, "instance")
+    }
+
+    fn instance_description(&self) -> TokamakResult<Option<Cow<'_, InstanceDescription>>> {
+        Ok(self.instance_description.map(Cow::Borrowed))
+    }
+
+    fn point_validation(&self) -> PointValidation {
+        self.point_validation
+    }
+}
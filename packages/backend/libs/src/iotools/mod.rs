@@ -1,27 +1,29 @@
-use crate::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt};
+use crate::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt, SparsePolynomialExt};
+use crate::compute_backend;
+use crate::error::{TokamakError, TokamakResult};
 use crate::field_structures::FieldSerde;
 use crate::group_structures::{
     count_o_mid_nvar, count_o_prv_nvar, encode_o_pub_fix_common, encode_o_pub_free_common,
-    encode_statement_common, G1serde, G2serde, PartialSigma1, PartialSigma1Verify, Sigma, Sigma1,
-    Sigma2, SigmaPreprocess, SigmaVerify,
+    encode_poly_with_bases, encode_statement_common, point_validation, G1serde, G2serde,
+    PartialSigma1, PartialSigma1Verify, PointValidation, Sigma, Sigma1, Sigma2, SigmaPreprocess,
+    SigmaVerify,
 };
 use crate::polynomial_structures::{from_subcircuit_to_QAP, QAP};
 #[cfg(feature = "timing")]
 use crate::timing::{record as record_timing, SizeInfo};
 use crate::utils::check_gpu;
 use crate::vector_operations::transpose_inplace;
+use binary::PointEncoding;
 use icicle_bls12_381::curve::{
     BaseField, G1Affine, G1Projective, G2Affine, G2BaseField, ScalarField,
 };
-use icicle_core::msm::{self, MSMConfig};
 use icicle_core::ntt;
 use icicle_core::traits::{Arithmetic, FieldImpl};
-use icicle_runtime::{
-    self,
-    memory::{DeviceVec, HostSlice},
-};
+use icicle_runtime::memory::HostSlice;
+use inputs::R1csSource;
 use rayon::iter::{
-    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelIterator,
 };
 
 use super::vector_operations::*;
@@ -41,6 +43,15 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 use std::{env, fmt};
 
+pub mod binary;
+pub mod compressed;
+pub mod fiat_shamir;
+pub mod inputs;
+pub mod permutation;
+pub mod public_io;
+pub mod solidity;
+pub mod witness;
+
 #[macro_export]
 macro_rules! impl_read_from_json {
     ($t:ty) => {
@@ -163,7 +174,7 @@ pub struct PublicWireInfo {
     pub numPubWires: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct SetupParams {
     pub l_free: usize,
     pub l: usize,
@@ -274,15 +285,34 @@ pub struct FinalCrsDigests {
 pub fn write_final_crs_artifacts(
     output_dir: &PathBuf,
     sigma: &Sigma,
+) -> io::Result<FinalCrsDigests> {
+    write_final_crs_artifacts_with_encoding(output_dir, sigma, PointEncoding::Uncompressed)
+}
+
+/// Like `write_final_crs_artifacts`, but with `PointEncoding::Compressed` the points of
+/// `combined_sigma.rkyv` and `sigma_verify.json` are stored compressed. `sigma_preprocess.rkyv` is
+/// always uncompressed since the preprocessor reads it in place.
+pub fn write_final_crs_artifacts_with_encoding(
+    output_dir: &PathBuf,
+    sigma: &Sigma,
+    encoding: PointEncoding,
 ) -> io::Result<FinalCrsDigests> {
     fs::create_dir_all(output_dir)?;
 
-    let sigma_rkyv = SigmaRkyv::from_sigma(sigma);
-    let combined_sigma_bytes = rkyv::to_bytes::<_, 256>(&sigma_rkyv).map_err(io::Error::other)?;
-    fs::write(
-        output_dir.join("combined_sigma.rkyv"),
-        combined_sigma_bytes.as_ref(),
-    )?;
+    let combined_sigma_path = output_dir.join("combined_sigma.rkyv");
+    let combined_sigma_sha256 = match encoding {
+        PointEncoding::Uncompressed => {
+            let sigma_rkyv = SigmaRkyv::from_sigma(sigma);
+            let bytes = rkyv::to_bytes::<_, 256>(&sigma_rkyv).map_err(io::Error::other)?;
+            fs::write(&combined_sigma_path, bytes.as_ref())?;
+            sha256_hex(bytes.as_ref())
+        }
+        PointEncoding::Compressed => {
+            let bytes = compressed::compressed_sigma_bytes(sigma)?;
+            fs::write(&combined_sigma_path, &bytes)?;
+            sha256_hex(&bytes)
+        }
+    };
 
     let sigma_preprocess_rkyv = SigmaPreprocessRkyv::from_sigma(sigma);
     let sigma_preprocess_bytes =
@@ -292,12 +322,15 @@ pub fn write_final_crs_artifacts(
         sigma_preprocess_bytes.as_ref(),
     )?;
 
-    let sigma_verify = sigma.sigma_verify();
-    let sigma_verify_bytes = serde_json::to_vec_pretty(&sigma_verify).map_err(io::Error::other)?;
+    let sigma_verify_bytes = match encoding {
+        PointEncoding::Uncompressed => serde_json::to_vec_pretty(&sigma.sigma_verify()),
+        PointEncoding::Compressed => compressed::compressed_sigma_verify_json(sigma),
+    }
+    .map_err(io::Error::other)?;
     fs::write(output_dir.join("sigma_verify.json"), &sigma_verify_bytes)?;
 
     Ok(FinalCrsDigests {
-        combined_sigma_sha256: sha256_hex(combined_sigma_bytes.as_ref()),
+        combined_sigma_sha256,
         sigma_preprocess_sha256: sha256_hex(sigma_preprocess_bytes.as_ref()),
         sigma_verify_sha256: sha256_hex(&sigma_verify_bytes),
     })
@@ -396,7 +429,7 @@ pub struct PublicInputBuffer {
     pub inPts: Box<[InPts]>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Instance {
     pub a_pub_user: Box<[HexString]>,
     pub a_pub_block: Box<[HexString]>,
@@ -405,7 +438,20 @@ pub struct Instance {
 
 impl_read_from_json!(Instance);
 
-#[derive(Debug, Deserialize)]
+/// `instance_description.json` of the synthesizer: for each element of `instance.json`, the
+/// `extDest` of the public output or the `extSource` of the input it carries, or an empty string
+/// for padding. A value wider than 128 bits takes two elements, described with a
+/// ` (lower 16 bytes)` and then a ` (upper 16 bytes)` suffix.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceDescription {
+    pub a_pub_user_description: Box<[String]>,
+    pub a_pub_block_description: Box<[String]>,
+    pub a_pub_function_description: Box<[String]>,
+}
+
+impl_read_from_json!(InstanceDescription);
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Permutation {
     pub row: usize,
     pub col: usize,
@@ -455,7 +501,7 @@ impl Permutation {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct SubcircuitInfo {
     pub id: usize,
     pub name: String,
@@ -516,7 +562,10 @@ impl R1csBinary {
         let mut file = File::open(path)?;
         let mut data = Vec::new();
         file.read_to_end(&mut data)?;
+        Self::parse(data)
+    }
 
+    fn parse(data: Vec<u8>) -> io::Result<Self> {
         let mut offset = 0usize;
         let magic = read_bytes(&data, &mut offset, 4)?;
         if magic != b"r1cs" {
@@ -691,18 +740,49 @@ impl SubcircuitR1CS {
         Self::from_r1cs_with_mode(path, setup_params, subcircuit_info, false, true)
     }
 
+    /// Same as `from_r1cs_sparse_only`, for the contents of a binary R1CS file already in memory.
+    pub fn from_r1cs_bytes_sparse_only(
+        data: Vec<u8>,
+        setup_params: &SetupParams,
+        subcircuit_info: &SubcircuitInfo,
+    ) -> io::Result<Self> {
+        Self::from_r1cs_binary_with_mode(
+            || R1csBinary::parse(data),
+            setup_params,
+            subcircuit_info,
+            false,
+            true,
+        )
+    }
+
     fn from_r1cs_with_mode(
         path: PathBuf,
         setup_params: &SetupParams,
         subcircuit_info: &SubcircuitInfo,
         include_compact_matrices: bool,
         include_sparse_rows: bool,
+    ) -> io::Result<Self> {
+        Self::from_r1cs_binary_with_mode(
+            || R1csBinary::read(path),
+            setup_params,
+            subcircuit_info,
+            include_compact_matrices,
+            include_sparse_rows,
+        )
+    }
+
+    fn from_r1cs_binary_with_mode(
+        read_binary: impl FnOnce() -> io::Result<R1csBinary>,
+        setup_params: &SetupParams,
+        subcircuit_info: &SubcircuitInfo,
+        include_compact_matrices: bool,
+        include_sparse_rows: bool,
     ) -> io::Result<Self> {
         let phase_profile = env::var("TOKAMAK_UVWXY_PHASE_PROFILE").ok().as_deref() == Some("1");
         let total_start = phase_profile.then(Instant::now);
 
         let read_binary_start = phase_profile.then(Instant::now);
-        let binary = R1csBinary::read(path)?;
+        let binary = read_binary()?;
         if let Some(start) = read_binary_start {
             print_r1cs_binary_phase(
                 subcircuit_info.id,
@@ -730,7 +810,13 @@ impl SubcircuitR1CS {
             ));
         }
         if setup_params.n < subcircuit_info.Nconsts {
-            panic!("n is smaller than the actual number of constraints.");
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "n={} is smaller than the {} constraints of subcircuit {}",
+                    setup_params.n, subcircuit_info.Nconsts, subcircuit_info.id
+                ),
+            ));
         }
 
         let active_wire_scan_start = phase_profile.then(Instant::now);
@@ -1001,16 +1087,24 @@ impl<'de> Deserialize<'de> for G1serde {
     where
         D: serde::Deserializer<'de>,
     {
+        // Either the `{x, y}` coordinates or the compressed point as one hex string.
         #[derive(Deserialize)]
-        struct G1Coords {
-            x: String,
-            y: String,
+        #[serde(untagged)]
+        enum G1Repr {
+            Coords { x: String, y: String },
+            Compressed(String),
+        }
+        match G1Repr::deserialize(deserializer)? {
+            G1Repr::Coords { x, y } => {
+                let x_field = BaseField::from_hex(&x).into();
+                let y_field = BaseField::from_hex(&y).into();
+                G1serde::load(G1Affine::from_limbs(x_field, y_field), point_validation())
+                    .map_err(D::Error::custom)
+            }
+            G1Repr::Compressed(hex) => compressed::compressed_from_hex(&hex)
+                .and_then(|bytes| G1serde::from_compressed(&bytes))
+                .map_err(D::Error::custom),
         }
-        let G1Coords { x, y } = G1Coords::deserialize(deserializer)?;
-        let x_field = BaseField::from_hex(&x).into();
-        let y_field = BaseField::from_hex(&y).into();
-        let point = G1Affine::from_limbs(x_field, y_field);
-        Ok(G1serde(point))
     }
 }
 impl G1serde {
@@ -1044,16 +1138,24 @@ impl<'de> Deserialize<'de> for G2serde {
     where
         D: serde::Deserializer<'de>,
     {
+        // Either the `{x, y}` coordinates or the compressed point as one hex string.
         #[derive(Deserialize)]
-        struct G2Coords {
-            x: String,
-            y: String,
+        #[serde(untagged)]
+        enum G2Repr {
+            Coords { x: String, y: String },
+            Compressed(String),
+        }
+        match G2Repr::deserialize(deserializer)? {
+            G2Repr::Coords { x, y } => {
+                let x_field = G2BaseField::from_hex(&x).into();
+                let y_field = G2BaseField::from_hex(&y).into();
+                G2serde::load(G2Affine::from_limbs(x_field, y_field), point_validation())
+                    .map_err(D::Error::custom)
+            }
+            G2Repr::Compressed(hex) => compressed::compressed_from_hex(&hex)
+                .and_then(|bytes| G2serde::from_compressed(&bytes))
+                .map_err(D::Error::custom),
         }
-        let G2Coords { x, y } = G2Coords::deserialize(deserializer)?;
-        let x_field = G2BaseField::from_hex(&x).into();
-        let y_field = G2BaseField::from_hex(&y).into();
-        let point = G2Affine::from_limbs(x_field, y_field);
-        Ok(G2serde(point))
     }
 }
 impl G2serde {
@@ -1114,33 +1216,9 @@ pub fn from_coef_vec_to_g1serde_vec_msm(coef: &[ScalarField], gen: &G1Affine, re
     println!("msm");
     let n = coef.len();
 
-    let scalars_host = HostSlice::from_slice(coef.as_ref());
-
-    let mut pts = Vec::with_capacity(n);
-    pts.resize(n, *gen);
-
-    let points_host = HostSlice::from_slice(&pts);
-
-    let mut result_dev = DeviceVec::<G1Projective>::device_malloc(n).expect("device_malloc failed");
-
-    let cfg = msm::MSMConfig::default();
-
-    msm::msm(
-        scalars_host, // &[ScalarField]
-        points_host,  // &[G1Affine]
-        &cfg,
-        &mut result_dev[..], // &mut DeviceSlice<G1Projective>
-    )
-    .expect("msm failed");
-
+    let pts = vec![*gen; n];
     let mut host_out = vec![G1Projective::zero(); n];
-    result_dev
-        .copy_to_host(HostSlice::from_mut_slice(&mut host_out))
-        .expect("copy_to_host failed");
-
-    drop(result_dev);
-    // drop(points_host);
-    // drop(scalars_host);
+    compute_backend::msm_batch(coef, &pts, &mut host_out);
 
     host_out
         .into_par_iter()
@@ -1236,20 +1314,16 @@ pub fn gen_g1serde_vec_of_xy_monomials(
     drop(base_vec);
 
     let acc_multiplier = if is_row_base { x } else { y };
-    let mut msm_cfg = MSMConfig::default();
-    msm_cfg.batch_size = inner_loop_len.try_into().unwrap();
     for i in 1..outer_loop_len {
         let (head, tail) = res_projective.split_at_mut(i * inner_loop_len);
         let prev_vec = &head[(i - 1) * inner_loop_len..i * inner_loop_len];
         let prev_vec_affine: Vec<G1Affine> = prev_vec.iter().map(|&x| G1Affine::from(x)).collect();
         let curr_vec = &mut tail[0..inner_loop_len];
-        msm::msm(
-            HostSlice::from_slice(&vec![acc_multiplier; inner_loop_len]),
-            HostSlice::from_slice(&prev_vec_affine),
-            &msm_cfg,
-            HostSlice::from_mut_slice(curr_vec),
-        )
-        .unwrap();
+        compute_backend::msm_batch(
+            &vec![acc_multiplier; inner_loop_len],
+            &prev_vec_affine,
+            curr_vec,
+        );
     }
     for i in 0..x_size {
         for j in 0..y_size {
@@ -1285,11 +1359,11 @@ pub fn from_coef_vec_to_g1serde_mat(
 }
 
 pub fn read_R1CS_gen_uvwXY(
-    qap_path: &str,
-    placement_variables: &Box<[PlacementVariables]>,
-    subcircuit_infos: &Box<[SubcircuitInfo]>,
+    r1cs: &(impl R1csSource + ?Sized),
+    placement_variables: &[PlacementVariables],
+    subcircuit_infos: &[SubcircuitInfo],
     setup_params: &SetupParams,
-) -> (DensePolynomialExt, DensePolynomialExt, DensePolynomialExt) {
+) -> TokamakResult<(DensePolynomialExt, DensePolynomialExt, DensePolynomialExt)> {
     let phase_profile = env::var("TOKAMAK_UVWXY_PHASE_PROFILE").ok().as_deref() == Some("1");
     let uvwxy_total_start = phase_profile.then(Instant::now);
 
@@ -1304,51 +1378,13 @@ pub fn read_R1CS_gen_uvwXY(
         print_uvwxy_phase("alloc_eval_buffers", start.elapsed().as_nanos());
     }
 
-    let usage_scan_start = phase_profile.then(Instant::now);
-    if placement_variables.len() > s_max {
-        panic!("placement_variables length exceeds s_max.");
-    }
-
-    // Collect usage stats and placement indices per subcircuit
-    let mut usage_counts = vec![0usize; subcircuit_infos.len()];
-    let mut unique_ids = HashSet::<usize>::new();
-    let mut indices_by_subcircuit: Vec<Vec<usize>> = vec![Vec::new(); subcircuit_infos.len()];
-    for (i, placement) in placement_variables.iter().enumerate() {
-        let subcircuit_id = placement.subcircuitId;
-        if subcircuit_id >= subcircuit_infos.len() {
-            panic!("Invalid subcircuit id in placement_variables.");
-        }
-        usage_counts[subcircuit_id] += 1;
-        unique_ids.insert(subcircuit_id);
-        indices_by_subcircuit[subcircuit_id].push(i);
-    }
-    if let Some(start) = usage_scan_start {
-        print_uvwxy_phase("usage_scan", start.elapsed().as_nanos());
-    }
-
-    // Preload all unique subcircuit R1CS (no incremental cache)
-    let r1cs_preload_start = phase_profile.then(Instant::now);
-    let mut r1cs_by_id: Vec<Option<SubcircuitR1CS>> =
-        (0..subcircuit_infos.len()).map(|_| None).collect();
-    for &subcircuit_id in unique_ids.iter() {
-        let binary_r1cs_path =
-            PathBuf::from(qap_path).join(format!("r1cs/subcircuit{subcircuit_id}.r1cs"));
-        let loaded_r1cs = SubcircuitR1CS::from_r1cs_sparse_only(
-            binary_r1cs_path.clone(),
-            &setup_params,
-            &subcircuit_infos[subcircuit_id],
-        )
-        .unwrap_or_else(|err| {
-            panic!(
-                "failed to load required binary R1CS file {}: {err}",
-                binary_r1cs_path.display()
-            )
-        });
-        r1cs_by_id[subcircuit_id] = Some(loaded_r1cs);
-    }
-    if let Some(start) = r1cs_preload_start {
-        print_uvwxy_phase("r1cs_preload_sparse", start.elapsed().as_nanos());
-    }
+    let (usage_counts, r1cs_by_id) = preload_subcircuit_r1cs(
+        r1cs,
+        placement_variables,
+        subcircuit_infos,
+        setup_params,
+        phase_profile,
+    )?;
 
     println!("Using sparse R1CS uvwXY generation.");
 
@@ -1367,7 +1403,7 @@ pub fn read_R1CS_gen_uvwXY(
     }
 
     let usage_report_start = phase_profile.then(Instant::now);
-    let unique_subcircuits = unique_ids.len();
+    let unique_subcircuits = usage_counts.iter().filter(|&&count| count > 0).count();
     let total_subcircuit_uses = placement_variables.len();
     println!(
         "📊 Subcircuit uses: {} unique, {} total",
@@ -1406,8 +1442,6 @@ pub fn read_R1CS_gen_uvwXY(
     drop(v_eval);
     drop(w_eval);
     drop(r1cs_by_id);
-    drop(indices_by_subcircuit);
-    drop(unique_ids);
     drop(usage_counts);
     if let Some(start) = cleanup_start {
         print_uvwxy_phase("cleanup_local_buffers", start.elapsed().as_nanos());
@@ -1416,7 +1450,153 @@ pub fn read_R1CS_gen_uvwXY(
         print_uvwxy_phase("uvwxy_total_function", start.elapsed().as_nanos());
     }
 
-    return (uXY, vXY, wXY);
+    Ok((uXY, vXY, wXY))
+}
+
+/// `read_R1CS_gen_uvwXY` as sparse polynomials with one column per placement, skipping the
+/// transpose and the `s_max - placement_variables.len()` empty columns of the dense version.
+pub fn read_R1CS_gen_uvwXY_sparse(
+    r1cs: &(impl R1csSource + ?Sized),
+    placement_variables: &[PlacementVariables],
+    subcircuit_infos: &[SubcircuitInfo],
+    setup_params: &SetupParams,
+) -> TokamakResult<(
+    SparsePolynomialExt,
+    SparsePolynomialExt,
+    SparsePolynomialExt,
+)> {
+    let n = setup_params.n;
+    let s_max = setup_params.s_max;
+    let (usage_counts, r1cs_by_id) = preload_subcircuit_r1cs(
+        r1cs,
+        placement_variables,
+        subcircuit_infos,
+        setup_params,
+        false,
+    )?;
+
+    let p = placement_variables.len();
+    let mut u_eval = vec![ScalarField::zero(); p * n];
+    let mut v_eval = vec![ScalarField::zero(); p * n];
+    let mut w_eval = vec![ScalarField::zero(); p * n];
+    eval_uvwxy_sparse_rows(
+        placement_variables,
+        &r1cs_by_id,
+        &usage_counts,
+        n,
+        &mut u_eval,
+        &mut v_eval,
+        &mut w_eval,
+    );
+
+    let to_sparse = |eval: Vec<ScalarField>| {
+        let columns = eval.chunks(n).map(<[ScalarField]>::to_vec).enumerate();
+        SparsePolynomialExt::from_column_evals(n, s_max, columns)
+    };
+    Ok((to_sparse(u_eval), to_sparse(v_eval), to_sparse(w_eval)))
+}
+
+// Counts the uses of each subcircuit and loads the compact R1CS of every used one.
+fn preload_subcircuit_r1cs(
+    r1cs: &(impl R1csSource + ?Sized),
+    placement_variables: &[PlacementVariables],
+    subcircuit_infos: &[SubcircuitInfo],
+    setup_params: &SetupParams,
+    phase_profile: bool,
+) -> TokamakResult<(Vec<usize>, Vec<Option<SubcircuitR1CS>>)> {
+    let usage_scan_start = phase_profile.then(Instant::now);
+    let usage_counts = count_subcircuit_uses(placement_variables, subcircuit_infos, setup_params)?;
+    for (placement_idx, placement) in placement_variables.iter().enumerate() {
+        let info = &subcircuit_infos[placement.subcircuitId];
+        if placement.variables.len() != info.flattenMap.len() {
+            return Err(TokamakError::parse(
+                "placementVariables.json",
+                format!(
+                    "placement {placement_idx} has {} variables, but subcircuit {} has {} wires",
+                    placement.variables.len(),
+                    info.id,
+                    info.flattenMap.len()
+                ),
+            ));
+        }
+    }
+    if let Some(start) = usage_scan_start {
+        print_uvwxy_phase("usage_scan", start.elapsed().as_nanos());
+    }
+
+    // Preload all unique subcircuit R1CS (no incremental cache)
+    let r1cs_preload_start = phase_profile.then(Instant::now);
+    let r1cs_by_id = usage_counts
+        .iter()
+        .zip(subcircuit_infos)
+        .map(|(&count, info)| {
+            (count > 0)
+                .then(|| load_subcircuit_r1cs(r1cs, info, setup_params))
+                .transpose()
+        })
+        .collect::<TokamakResult<Vec<_>>>()?;
+    if let Some(start) = r1cs_preload_start {
+        print_uvwxy_phase("r1cs_preload_sparse", start.elapsed().as_nanos());
+    }
+    Ok((usage_counts, r1cs_by_id))
+}
+
+// Counts the uses of each subcircuit, rejecting more placements than `s_max` and subcircuit ids
+// outside the library.
+pub(crate) fn count_subcircuit_uses(
+    placement_variables: &[PlacementVariables],
+    subcircuit_infos: &[SubcircuitInfo],
+    setup_params: &SetupParams,
+) -> TokamakResult<Vec<usize>> {
+    if placement_variables.len() > setup_params.s_max {
+        return Err(TokamakError::Shape(format!(
+            "{} placements exceed s_max = {}.",
+            placement_variables.len(),
+            setup_params.s_max
+        )));
+    }
+    let mut usage_counts = vec![0usize; subcircuit_infos.len()];
+    for (placement_idx, placement) in placement_variables.iter().enumerate() {
+        match usage_counts.get_mut(placement.subcircuitId) {
+            Some(count) => *count += 1,
+            None => {
+                return Err(TokamakError::parse(
+                    "placementVariables.json",
+                    format!(
+                        "placement {placement_idx} uses unknown subcircuit {}",
+                        placement.subcircuitId
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(usage_counts)
+}
+
+// Loads the compact R1CS of one subcircuit. A source without the subcircuit's R1CS is a missing
+// input; one whose R1CS does not parse or does not match `info` is a parse error.
+pub(crate) fn load_subcircuit_r1cs(
+    r1cs: &(impl R1csSource + ?Sized),
+    info: &SubcircuitInfo,
+    setup_params: &SetupParams,
+) -> TokamakResult<SubcircuitR1CS> {
+    if info.Nconsts > setup_params.n {
+        return Err(TokamakError::Shape(format!(
+            "subcircuit {} ({}) has {} constraints, more than n = {}.",
+            info.id, info.name, info.Nconsts, setup_params.n
+        )));
+    }
+    r1cs.r1cs_bytes(info.id)
+        .and_then(|data| {
+            SubcircuitR1CS::from_r1cs_bytes_sparse_only(data.into_owned(), setup_params, info)
+        })
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => TokamakError::MissingInput("subcircuit R1CS"),
+            _ => TokamakError::parse(
+                format!("the R1CS of subcircuit {}", info.id),
+                err.to_string(),
+            ),
+        })
 }
 
 fn print_uvwxy_phase(name: &str, nanos: u128) {
@@ -1424,7 +1604,7 @@ fn print_uvwxy_phase(name: &str, nanos: u128) {
 }
 
 fn eval_uvwxy_sparse_rows(
-    placement_variables: &Box<[PlacementVariables]>,
+    placement_variables: &[PlacementVariables],
     r1cs_by_id: &[Option<SubcircuitR1CS>],
     usage_counts: &[usize],
     n: usize,
@@ -1669,30 +1849,67 @@ macro_rules! split_push {
     };
 }
 
+// Helper function to decode a hex string (with or without 0x) into a fixed-size big-endian slot
+fn decode_hex_part(part: &str, out: &mut [u8]) -> TokamakResult<()> {
+    decode_to_slice(part.trim_start_matches("0x"), out).map_err(|err| {
+        TokamakError::parse(
+            format!("hex entry {part}"),
+            format!("expected {} bytes: {err}", out.len()),
+        )
+    })
+}
+
 // Helper function to recover a BaseField from part1 (16 bytes) and part2 (32 bytes)
-fn recover_basefield(part1: &String, part2: &String) -> BaseField {
+fn recover_basefield(part1: &String, part2: &String) -> TokamakResult<BaseField> {
     let mut bytes = [0u8; 48];
 
-    decode_to_slice(part1.trim_start_matches("0x"), &mut bytes[0..16]).expect("Invalid format");
+    decode_hex_part(part1, &mut bytes[0..16])?;
 
-    decode_to_slice(part2.trim_start_matches("0x"), &mut bytes[16..48]).expect("Invalid format");
+    decode_hex_part(part2, &mut bytes[16..48])?;
     bytes.reverse(); // to little Edian
 
-    return BaseField::from_bytes_le(&bytes);
+    return Ok(BaseField::from_bytes_le(&bytes));
 }
 
-pub fn next_point(idx: usize, part1: &Vec<String>, part2: &Vec<String>) -> G1serde {
-    let bx = recover_basefield(&part1[idx], &part2[idx]);
-    let by = recover_basefield(&part1[idx + 1], &part2[idx + 1]);
+pub fn next_point(idx: usize, part1: &Vec<String>, part2: &Vec<String>) -> TokamakResult<G1serde> {
+    if idx + 1 >= part1.len() || idx + 1 >= part2.len() {
+        return Err(TokamakError::parse(
+            "formatted G1 point",
+            format!("entry index {idx} is out of range"),
+        ));
+    }
+    let bx = recover_basefield(&part1[idx], &part2[idx])?;
+    let by = recover_basefield(&part1[idx + 1], &part2[idx + 1])?;
 
-    return G1serde(G1Affine { x: bx, y: by });
+    G1serde::load(G1Affine { x: bx, y: by }, point_validation())
+}
+
+// Helper function to parse a 256-bit hex string (as produced by scalar_to_hex) into a ScalarField
+pub fn scalar_from_hex(value: &str) -> TokamakResult<ScalarField> {
+    let hex = value.trim_start_matches("0x");
+    let padded = if hex.len() % 2 == 1 {
+        format!("0{hex}")
+    } else {
+        hex.to_string()
+    };
+    let mut bytes = hex::decode(&padded)
+        .map_err(|err| TokamakError::parse(format!("scalar {value}"), err.to_string()))?;
+    if bytes.len() > 32 {
+        return Err(TokamakError::parse(
+            format!("scalar {value}"),
+            "longer than 32 bytes",
+        ));
+    }
+    bytes.reverse(); // to little Edian
+    bytes.resize(32, 0);
+    Ok(ScalarField::from_bytes_le(&bytes))
 }
 
 #[macro_export]
 macro_rules! pop_recover {
     ($idx: ident, $part1: expr, $part2: expr, $( $point: ident),+ $(,)?) => {
         $(
-            let $point = next_point($idx, $part1, $part2);
+            let $point = next_point($idx, $part1, $part2)?;
             $idx += 2;
         )+
     };
@@ -1980,6 +2197,59 @@ impl ArchivedG1SerdeRkyv {
     }
 }
 
+fn validate_g1_archive(points: &[ArchivedG1SerdeRkyv]) -> TokamakResult<()> {
+    points
+        .par_iter()
+        .try_for_each(|point| G1serde::checked(point.to_g1_affine()).map(|_| ()))
+}
+
+fn validate_g1_archive_rows(
+    rows: &[rkyv::vec::ArchivedVec<ArchivedG1SerdeRkyv>],
+) -> TokamakResult<()> {
+    rows.iter().try_for_each(|row| validate_g1_archive(row))
+}
+
+impl ArchivedSigmaRkyv {
+    /// Checks every point of the archive unless `validation` is `Trusted`. The accessors of an
+    /// archived sigma convert points without checks, so loaders call this once up front.
+    pub fn validate_points(&self, validation: PointValidation) -> TokamakResult<()> {
+        if validation == PointValidation::Trusted {
+            return Ok(());
+        }
+        let sigma_1 = &self.sigma_1;
+        for point in [
+            &self.G,
+            &self.lagrange_KL,
+            &sigma_1.x,
+            &sigma_1.y,
+            &sigma_1.delta,
+            &sigma_1.eta,
+        ] {
+            G1serde::checked(point.to_g1_affine())?;
+        }
+        G2serde::checked(self.H.to_g2serde().0)?;
+        self.sigma_2.validate_points()?;
+        validate_g1_archive(&sigma_1.xy_powers)?;
+        validate_g1_archive(&sigma_1.gamma_inv_o_inst)?;
+        validate_g1_archive_rows(&sigma_1.eta_inv_li_o_inter_alpha4_kj)?;
+        validate_g1_archive_rows(&sigma_1.delta_inv_li_o_prv)?;
+        validate_g1_archive_rows(&sigma_1.delta_inv_alphak_xh_tx)?;
+        validate_g1_archive(&sigma_1.delta_inv_alpha4_xj_tx)?;
+        validate_g1_archive_rows(&sigma_1.delta_inv_alphak_yi_ty)
+    }
+}
+
+impl ArchivedSigmaPreprocessRkyv {
+    /// Same as `ArchivedSigmaRkyv::validate_points` for the preprocess subset.
+    pub fn validate_points(&self, validation: PointValidation) -> TokamakResult<()> {
+        if validation == PointValidation::Trusted {
+            return Ok(());
+        }
+        validate_g1_archive(&self.sigma_1.xy_powers)?;
+        validate_g1_archive(&self.sigma_1.gamma_inv_o_inst)
+    }
+}
+
 impl ArchivedG2SerdeRkyv {
     pub fn to_g2serde(&self) -> G2serde {
         let x_field = G2BaseField::from_bytes_le(&self.x).into();
@@ -1989,6 +2259,22 @@ impl ArchivedG2SerdeRkyv {
 }
 
 impl ArchivedSigma2Rkyv {
+    fn validate_points(&self) -> TokamakResult<()> {
+        [
+            &self.alpha,
+            &self.alpha2,
+            &self.alpha3,
+            &self.alpha4,
+            &self.gamma,
+            &self.delta,
+            &self.eta,
+            &self.x,
+            &self.y,
+        ]
+        .into_iter()
+        .try_for_each(|point| G2serde::checked(point.to_g2serde().0).map(|_| ()))
+    }
+
     pub fn to_sigma2(&self) -> Sigma2 {
         Sigma2 {
             alpha: self.alpha.to_g2serde(),
@@ -2044,59 +2330,9 @@ fn encode_poly_from_xy_powers_with_timing(
     xy_powers: &[ArchivedG1SerdeRkyv],
     _timing_name: Option<&'static str>,
 ) -> G1serde {
-    poly.optimize_size();
-    let x_size = poly.x_size;
-    let y_size = poly.y_size;
-    let rs_x_size = std::cmp::max(2 * params.n, 2 * (params.l_D - params.l));
-    let rs_y_size = params.s_max * 2;
-    let target_x_size = (poly.x_degree + 1) as usize;
-    let target_y_size = (poly.y_degree + 1) as usize;
-    if target_x_size > rs_x_size || target_y_size > rs_y_size {
-        panic!("Insufficient length of sigma.sigma_1.xy_powers");
-    }
-    if target_x_size * target_y_size == 0 {
-        return G1serde::zero();
-    }
-
-    let poly_coeffs_vec_compact = {
-        let mut poly_coeffs_vec = vec![ScalarField::zero(); x_size * y_size];
-        let poly_coeffs = HostSlice::from_mut_slice(&mut poly_coeffs_vec);
-        poly.copy_coeffs(0, poly_coeffs);
-        resize(
-            &poly_coeffs_vec,
-            x_size,
-            y_size,
-            target_x_size,
-            target_y_size,
-            ScalarField::zero(),
-        )
-    };
-
-    let rs_unpacked: Vec<G1Affine> = {
-        let mut res = Vec::with_capacity(target_x_size * target_y_size);
-        for i in 0..target_x_size {
-            for j in 0..target_y_size {
-                if i < rs_x_size && j < rs_y_size {
-                    let idx = rs_y_size * i + j;
-                    res.push(xy_powers[idx].to_g1_affine());
-                } else {
-                    res.push(G1Affine::zero());
-                }
-            }
-        }
-        res
-    };
-
-    let mut msm_res = vec![G1Projective::zero(); 1];
     #[cfg(feature = "timing")]
     let msm_start = Instant::now();
-    msm::msm(
-        HostSlice::from_slice(&poly_coeffs_vec_compact),
-        HostSlice::from_slice(&rs_unpacked),
-        &MSMConfig::default(),
-        HostSlice::from_mut_slice(&mut msm_res),
-    )
-    .unwrap();
+    let res = encode_poly_with_bases(poly, params, |idx| xy_powers[idx].to_g1_affine());
     #[cfg(feature = "timing")]
     if let Some(name) = _timing_name {
         record_timing(
@@ -2105,11 +2341,11 @@ fn encode_poly_from_xy_powers_with_timing(
             msm_start.elapsed(),
             vec![SizeInfo {
                 label: "msm",
-                dims: vec![target_x_size, target_y_size],
+                dims: vec![(poly.x_degree + 1) as usize, (poly.y_degree + 1) as usize],
             }],
         );
     }
-    G1serde(G1Affine::from(msm_res[0]))
+    res
 }
 
 impl ArchivedSigma1Rkyv {
@@ -2117,6 +2353,10 @@ impl ArchivedSigma1Rkyv {
         encode_poly_from_xy_powers(poly, params, self.xy_powers.as_slice())
     }
 
+    pub fn encode_sparse_poly(&self, poly: &SparsePolynomialExt, params: &SetupParams) -> G1serde {
+        self.encode_poly(&mut poly.to_dense(), params)
+    }
+
     pub fn encode_poly_timed(
         &self,
         poly: &mut DensePolynomialExt,
@@ -2218,6 +2458,10 @@ impl ArchivedPartialSigma1Rkyv {
         encode_poly_from_xy_powers(poly, params, self.xy_powers.as_slice())
     }
 
+    pub fn encode_sparse_poly(&self, poly: &SparsePolynomialExt, params: &SetupParams) -> G1serde {
+        self.encode_poly(&mut poly.to_dense(), params)
+    }
+
     pub fn encode_poly_timed(
         &self,
         poly: &mut DensePolynomialExt,
@@ -0,0 +1,225 @@
+use super::{
+    scalar_from_hex, scalar_to_hex, Permutation, PlacementVariables, SetupParams, SubcircuitInfo,
+};
+use crate::error::{TokamakError, TokamakResult};
+use icicle_bls12_381::curve::ScalarField;
+use icicle_core::traits::FieldImpl;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Cycle structure of a valid permutation. Cells without an entry are fixed points and are not
+/// counted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PermutationSummary {
+    pub entries: usize,
+    /// Cycles of length two or more.
+    pub cycles: usize,
+    pub longest_cycle: usize,
+}
+
+/// Checks that `permutation.json` describes a permutation of the `m_i x s_max` grid of interface
+/// wires, as `Permutation::to_poly` assumes: every `row` and `X` below `m_i`, every `col` and `Y`
+/// below `s_max`, no cell listed twice as a source or as a target, and every target also listed
+/// as a source, since a cell without an entry maps to itself.
+pub fn validate_permutation(
+    permutation: &[Permutation],
+    m_i: usize,
+    s_max: usize,
+) -> TokamakResult<PermutationSummary> {
+    let invalid = |reason: String| TokamakError::parse("permutation.json", reason);
+    let mut targets_by_source = HashMap::with_capacity(permutation.len());
+    let mut targets = HashSet::with_capacity(permutation.len());
+    for (entry, perm) in permutation.iter().enumerate() {
+        if perm.row >= m_i || perm.X >= m_i || perm.col >= s_max || perm.Y >= s_max {
+            return Err(invalid(format!(
+                "entry {entry} links ({}, {}) to ({}, {}) outside the {m_i} x {s_max} grid",
+                perm.row, perm.col, perm.X, perm.Y
+            )));
+        }
+        if targets_by_source
+            .insert((perm.row, perm.col), (perm.X, perm.Y))
+            .is_some()
+        {
+            return Err(invalid(format!(
+                "entry {entry} lists the source ({}, {}) again",
+                perm.row, perm.col
+            )));
+        }
+        if !targets.insert((perm.X, perm.Y)) {
+            return Err(invalid(format!(
+                "entry {entry} lists the target ({}, {}) again",
+                perm.X, perm.Y
+            )));
+        }
+    }
+    if let Some(entry) = permutation
+        .iter()
+        .position(|perm| !targets_by_source.contains_key(&(perm.X, perm.Y)))
+    {
+        let perm = &permutation[entry];
+        return Err(invalid(format!(
+            "entry {entry} maps ({}, {}) to ({}, {}), which has no entry and so also maps to itself",
+            perm.row, perm.col, perm.X, perm.Y
+        )));
+    }
+
+    let mut summary = PermutationSummary {
+        entries: permutation.len(),
+        ..PermutationSummary::default()
+    };
+    let mut visited = HashSet::with_capacity(permutation.len());
+    for perm in permutation {
+        let start = (perm.row, perm.col);
+        if visited.contains(&start) {
+            continue;
+        }
+        let mut length = 0;
+        let mut cell = start;
+        while visited.insert(cell) {
+            length += 1;
+            cell = targets_by_source[&cell];
+        }
+        if length > 1 {
+            summary.cycles += 1;
+            summary.longest_cycle = summary.longest_cycle.max(length);
+        }
+    }
+    Ok(summary)
+}
+
+/// One end of a copy constraint: a cell of the interface grid and the wire that occupies it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkedWire {
+    pub row: usize,
+    /// Column of the cell, which is also the index of the placement.
+    pub col: usize,
+    /// The subcircuit id and name and the local wire index of the placement's variable in this
+    /// cell, or `None` if its subcircuit has no wire in this row and the cell holds zero.
+    pub wire: Option<(usize, String, usize)>,
+    pub value: ScalarField,
+}
+
+impl fmt::Display for LinkedWire {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}) ", self.row, self.col)?;
+        match &self.wire {
+            Some((subcircuit_id, subcircuit_name, local_wire)) => write!(
+                f,
+                "placement {} subcircuit {subcircuit_id} {subcircuit_name} wire {local_wire}",
+                self.col
+            )?,
+            None => write!(f, "unoccupied")?,
+        }
+        write!(f, " = {}", scalar_to_hex(&self.value))
+    }
+}
+
+/// The first permutation entry whose two cells carry different values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CopyConstraintMismatch {
+    pub entry: usize,
+    pub source: LinkedWire,
+    pub target: LinkedWire,
+}
+
+impl fmt::Display for CopyConstraintMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "permutation entry {} links {} to {}",
+            self.entry, self.source, self.target
+        )
+    }
+}
+
+/// Validates the permutation with [`validate_permutation`], then checks that every entry links
+/// two cells with equal values. A cell holds the variable of the placement in its column whose
+/// global wire, `l + row`, belongs to that placement's subcircuit in `globalWireList.json`, and
+/// zero otherwise, as in `gen_bXY`. Returns the first entry, in file order, whose cells differ.
+pub fn check_copy_constraints(
+    permutation: &[Permutation],
+    placement_variables: &[PlacementVariables],
+    subcircuit_infos: &[SubcircuitInfo],
+    global_wire_list: &[Box<[usize]>],
+    setup_params: &SetupParams,
+) -> TokamakResult<(PermutationSummary, Option<CopyConstraintMismatch>)> {
+    let l = setup_params.l;
+    let m_i = setup_params.l_D.checked_sub(l).ok_or_else(|| {
+        TokamakError::Shape(format!(
+            "l_D must be >= l (l_D = {}, l = {}).",
+            setup_params.l_D, l
+        ))
+    })?;
+    let summary = validate_permutation(permutation, m_i, setup_params.s_max)?;
+    if global_wire_list.len() < setup_params.l_D {
+        return Err(TokamakError::parse(
+            "globalWireList.json",
+            format!(
+                "{} wires, fewer than l_D = {}",
+                global_wire_list.len(),
+                setup_params.l_D
+            ),
+        ));
+    }
+
+    let linked_wire = |row: usize, col: usize| -> TokamakResult<LinkedWire> {
+        let unoccupied = LinkedWire {
+            row,
+            col,
+            wire: None,
+            value: ScalarField::zero(),
+        };
+        let Some(placement) = placement_variables.get(col) else {
+            return Ok(unoccupied);
+        };
+        let owner = &global_wire_list[l + row];
+        let (subcircuit_id, local_wire) = match owner[..] {
+            [subcircuit_id, local_wire, ..] => (subcircuit_id, local_wire),
+            _ => {
+                return Err(TokamakError::parse(
+                    "globalWireList.json",
+                    format!("wire {} is not a [subcircuit, wire] pair", l + row),
+                ))
+            }
+        };
+        if subcircuit_id != placement.subcircuitId {
+            return Ok(unoccupied);
+        }
+        let info = subcircuit_infos.get(subcircuit_id).ok_or_else(|| {
+            TokamakError::parse(
+                "placementVariables.json",
+                format!("placement {col} uses unknown subcircuit {subcircuit_id}"),
+            )
+        })?;
+        let value = placement.variables.get(local_wire).ok_or_else(|| {
+            TokamakError::parse(
+                "placementVariables.json",
+                format!(
+                    "placement {col} has {} variables, fewer than wire {local_wire} of {}",
+                    placement.variables.len(),
+                    info.name
+                ),
+            )
+        })?;
+        Ok(LinkedWire {
+            row,
+            col,
+            wire: Some((subcircuit_id, info.name.clone(), local_wire)),
+            value: scalar_from_hex(&value.0)?,
+        })
+    };
+
+    for (entry, perm) in permutation.iter().enumerate() {
+        let source = linked_wire(perm.row, perm.col)?;
+        let target = linked_wire(perm.X, perm.Y)?;
+        if source.value != target.value {
+            let mismatch = CopyConstraintMismatch {
+                entry,
+                source,
+                target,
+            };
+            return Ok((summary, Some(mismatch)));
+        }
+    }
+    Ok((summary, None))
+}
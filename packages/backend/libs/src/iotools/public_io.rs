@@ -0,0 +1,212 @@
+use super::{scalar_to_hex, Instance, InstanceDescription, SetupParams};
+use crate::error::TokamakError;
+use icicle_bls12_381::curve::ScalarField;
+use icicle_core::traits::FieldImpl;
+use serde::Serialize;
+
+const LOWER_HALF: &str = " (lower 16 bytes)";
+const UPPER_HALF: &str = " (upper 16 bytes)";
+
+/// The part of the public instance an element belongs to, in the order of the synthesizer's
+/// `BUFFER_LIST`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublicRegion {
+    /// `a_pub_user[..l_user_out]`, written by the public output buffer.
+    UserOutput,
+    /// `a_pub_user[l_user_out..l_user]`, read by the public input buffer.
+    UserInput,
+    /// `a_pub_block[..l_free - l_user]`, read by the block buffer.
+    Block,
+}
+
+/// A described value among the public field elements.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PublicValue {
+    /// The synthesizer's description, without the half suffix of a split value.
+    pub description: String,
+    /// The value as `0x` followed by 64 hex digits.
+    pub value: String,
+    pub region: PublicRegion,
+    /// Index of the value's first field element in `a_pub_user` or `a_pub_block`.
+    pub index: usize,
+    /// 2 for a value split into its lower and upper 16 bytes, else 1.
+    pub limbs: usize,
+}
+
+/// Why part of the description could not be matched to the verified field elements. The values
+/// it concerns are left out of `PublicIo`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PublicIoMismatch {
+    /// `instance_description.json` could not be read.
+    Unreadable { reason: String },
+    /// The description vector does not have one entry per element of the instance vector.
+    Length {
+        vector: &'static str,
+        elements: usize,
+        descriptions: usize,
+    },
+    /// A lower half that is not followed by its upper half, or an upper half that does not
+    /// follow its lower half.
+    UnpairedHalf {
+        vector: &'static str,
+        index: usize,
+        description: String,
+    },
+    /// A half of a split value that does not fit in 16 bytes.
+    WideHalf {
+        vector: &'static str,
+        index: usize,
+        description: String,
+    },
+}
+
+/// The decoded public outputs and inputs of an instance, with every mismatch found on the way.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PublicIo {
+    pub outputs: Vec<PublicValue>,
+    /// Public inputs followed by block values.
+    pub inputs: Vec<PublicValue>,
+    pub mismatches: Vec<PublicIoMismatch>,
+}
+
+impl PublicIo {
+    /// A result that only records that the description could not be read.
+    pub fn unreadable(err: &TokamakError) -> Self {
+        Self {
+            mismatches: vec![PublicIoMismatch::Unreadable {
+                reason: err.to_string(),
+            }],
+            ..Self::default()
+        }
+    }
+}
+
+/// Labels the field elements the verifier interpolates, `Instance::public_inputs`, with the
+/// synthesizer's `instance_description.json`.
+///
+/// Every element with a nonempty description is a value, except that a ` (lower 16 bytes)`
+/// element followed by the ` (upper 16 bytes)` element of the same description is one value of up
+/// to 256 bits, as the synthesizer splits values wider than 128 bits. Elements described as
+/// padding are skipped. Descriptions that do not fit the elements are reported in `mismatches`
+/// rather than failing, since they are not part of the verified statement.
+pub fn decode_public_io(
+    instance: &Instance,
+    description: &InstanceDescription,
+    setup_params: &SetupParams,
+) -> PublicIo {
+    let elements = instance.public_inputs(setup_params);
+    let (user, block) = elements.split_at(setup_params.l_user);
+    let mut public_io = PublicIo::default();
+    decode_vector(
+        &mut public_io,
+        "a_pub_user",
+        instance.a_pub_user.len(),
+        user,
+        &description.a_pub_user_description,
+        |index| {
+            if index < setup_params.l_user_out {
+                PublicRegion::UserOutput
+            } else {
+                PublicRegion::UserInput
+            }
+        },
+    );
+    decode_vector(
+        &mut public_io,
+        "a_pub_block",
+        instance.a_pub_block.len(),
+        block,
+        &description.a_pub_block_description,
+        |_| PublicRegion::Block,
+    );
+    public_io
+}
+
+// Decodes the interpolated `elements` of the instance vector `vector`, which has `length`
+// elements in `instance.json`, into `public_io`.
+fn decode_vector(
+    public_io: &mut PublicIo,
+    vector: &'static str,
+    length: usize,
+    elements: &[ScalarField],
+    descriptions: &[String],
+    region: impl Fn(usize) -> PublicRegion,
+) {
+    if descriptions.len() != length {
+        public_io.mismatches.push(PublicIoMismatch::Length {
+            vector,
+            elements: length,
+            descriptions: descriptions.len(),
+        });
+    }
+    let unpaired = |index: usize, description: &str| PublicIoMismatch::UnpairedHalf {
+        vector,
+        index,
+        description: description.to_string(),
+    };
+
+    let described = elements.len().min(descriptions.len());
+    let mut index = 0;
+    while index < described {
+        let text = descriptions[index].as_str();
+        let (description, limbs, value) = if text.is_empty() {
+            index += 1;
+            continue;
+        } else if let Some(base) = text.strip_suffix(UPPER_HALF) {
+            public_io.mismatches.push(unpaired(index, base));
+            index += 1;
+            continue;
+        } else if let Some(base) = text.strip_suffix(LOWER_HALF) {
+            let upper = format!("{base}{UPPER_HALF}");
+            if index + 1 == described || descriptions[index + 1] != upper {
+                public_io.mismatches.push(unpaired(index, base));
+                index += 1;
+                continue;
+            }
+            match join_halves(&elements[index], &elements[index + 1]) {
+                Ok(value) => (base, 2, value),
+                Err(half) => {
+                    public_io.mismatches.push(PublicIoMismatch::WideHalf {
+                        vector,
+                        index: index + half,
+                        description: base.to_string(),
+                    });
+                    index += 2;
+                    continue;
+                }
+            }
+        } else {
+            (text, 1, scalar_to_hex(&elements[index]))
+        };
+
+        let decoded = PublicValue {
+            description: description.to_string(),
+            value,
+            region: region(index),
+            index,
+            limbs,
+        };
+        match decoded.region {
+            PublicRegion::UserOutput => public_io.outputs.push(decoded),
+            PublicRegion::UserInput | PublicRegion::Block => public_io.inputs.push(decoded),
+        }
+        index += limbs;
+    }
+}
+
+// `upper * 2^128 + lower` as `0x` followed by 64 hex digits, or the offset of the first half
+// wider than 128 bits.
+fn join_halves(lower: &ScalarField, upper: &ScalarField) -> Result<String, usize> {
+    let mut le = Vec::with_capacity(32);
+    for (offset, half) in [lower, upper].into_iter().enumerate() {
+        let bytes = half.to_bytes_le();
+        if bytes[16..].iter().any(|&byte| byte != 0) {
+            return Err(offset);
+        }
+        le.extend_from_slice(&bytes[..16]);
+    }
+    le.reverse();
+    Ok(format!("0x{}", hex::encode(le)))
+}
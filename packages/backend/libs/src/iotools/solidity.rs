@@ -0,0 +1,278 @@
+use super::fiat_shamir::{FiatShamirMode, FIAT_SHAMIR_PROTOCOL_TAG};
+use super::{hex_encode, split_g1, SetupParams};
+use crate::group_structures::{G1serde, G2serde, SigmaVerify};
+use icicle_core::traits::FieldImpl;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Name of the generated library unless the caller picks another.
+pub const DEFAULT_LIBRARY_NAME: &str = "TokamakVerifierConstants";
+
+/// Order in which the Solidity verifier's rolling Keccak transcript absorbs the proof, preprocess
+/// and binding elements, each group followed by the challenges it squeezes. G1 points are
+/// absorbed as the four words `x.part1, x.part2, y.part1, y.part2`, and evaluations as one word.
+pub const TRANSCRIPT_LAYOUT: &[(&[&str], &[&str])] = &[
+    (
+        &["U", "V", "W", "Q_AX", "Q_AY", "B"],
+        &["theta0", "theta1", "theta2"],
+    ),
+    (&["R"], &["kappa0"]),
+    (&["Q_CX", "Q_CY"], &["chi", "zeta"]),
+    (
+        &["V_eval", "R_eval", "R_omegaX_eval", "R_omegaX_omegaY_eval"],
+        &["kappa1"],
+    ),
+    (
+        &[
+            "Pi_X",
+            "Pi_Y",
+            "M_X",
+            "M_Y",
+            "N_X",
+            "N_Y",
+            "A_free",
+            "O_pub_free",
+            "O_mid",
+            "O_prv",
+            "s0",
+            "s1",
+            "O_pub_fix",
+        ],
+        &["kappa2"],
+    ),
+];
+
+/// What the transcript absorbs before `TRANSCRIPT_LAYOUT` in `FiatShamirMode::StrongV1`: the
+/// protocol tag, the CRS digest, the setup parameters, the preprocess commitments and the public
+/// instance vectors, each preceded by its length. `name[..]` stands for every element in order.
+/// Points take four words as in `TRANSCRIPT_LAYOUT`, everything else one.
+pub const STATEMENT_LAYOUT: &[&str] = &[
+    "protocol_tag",
+    "sigma_digest",
+    "l_free",
+    "l",
+    "l_user_out",
+    "l_user",
+    "l_D",
+    "m_D",
+    "n",
+    "s_D",
+    "s_max",
+    "s0",
+    "s1",
+    "O_pub_fix",
+    "a_pub_user.len",
+    "a_pub_user[..]",
+    "a_pub_block.len",
+    "a_pub_block[..]",
+    "a_pub_function.len",
+    "a_pub_function[..]",
+];
+
+/// The constants an on-chain verifier needs for one CRS and one preprocess: the `sigma_verify`
+/// points, the preprocess commitments and the transcript layout of `fiat_shamir`, rendered as a
+/// Solidity library. In `FiatShamirMode::StrongV1` it also holds the protocol tag and the setup
+/// parameters the statement absorbs.
+pub struct SolidityVerifierConstants<'a> {
+    pub library_name: &'a str,
+    pub sigma: &'a SigmaVerify,
+    pub s0: &'a G1serde,
+    pub s1: &'a G1serde,
+    pub O_pub_fix: &'a G1serde,
+    pub setup_params: &'a SetupParams,
+    pub fiat_shamir: FiatShamirMode,
+}
+
+impl SolidityVerifierConstants<'_> {
+    /// Renders the library. Base field elements use the part1/part2 split of the proof calldata,
+    /// so the contract can copy them into precompile inputs unchanged.
+    pub fn render(&self) -> String {
+        let sigma = self.sigma;
+        let sigma_2 = &sigma.sigma_2;
+        let mut out = String::new();
+        push_lines(
+            &mut out,
+            &[
+                "// SPDX-License-Identifier: MIT",
+                "// This file is auto-generated from sigma_verify.json and the preprocess output. Do not edit.",
+                "pragma solidity ^0.8.23;",
+                "",
+                "/// @notice CRS and preprocess constants of the Tokamak zk-SNARK verifier.",
+                "/// @dev A BLS12-381 base field element is split into PART1, its top 16 bytes, and PART2,",
+                "/// its low 32 bytes, as in the proof calldata. G2 coordinates are Fp2 elements c0 + c1 * u,",
+                "/// written as X0/X1 and Y0/Y1.",
+            ],
+        );
+        let _ = writeln!(out, "library {} {{", self.library_name);
+        push_lines(
+            &mut out,
+            &[
+                "    // Fiat-Shamir transcript: every 32-byte word w updates the state as",
+                "    // state_i = keccak256(uint32(DST_i) || state_0 || state_1 || w) for i = 0, 1, and the",
+                "    // n-th challenge is hashed with CHALLENGE_DST from the state and n, then masked with",
+                "    // FR_MASK. A zero challenge is replaced by one.",
+                "    uint8 internal constant TRANSCRIPT_DST_0 = 0;",
+                "    uint8 internal constant TRANSCRIPT_DST_1 = 1;",
+                "    uint8 internal constant TRANSCRIPT_CHALLENGE_DST = 2;",
+                "    uint256 internal constant FR_MASK =",
+                "        0x1fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff;",
+                "    // FiatShamirMode::tag() of the proofs this library verifies: 0 is legacy, which absorbs",
+                "    // only the round messages, and 1 is strong-v1, which absorbs the statement first.",
+            ],
+        );
+        let _ = writeln!(
+            out,
+            "    uint8 internal constant FIAT_SHAMIR_MODE = {};",
+            self.fiat_shamir.tag()
+        );
+        if self.fiat_shamir == FiatShamirMode::StrongV1 {
+            let mut tag = [0u8; 32];
+            tag[32 - FIAT_SHAMIR_PROTOCOL_TAG.len()..].copy_from_slice(FIAT_SHAMIR_PROTOCOL_TAG);
+            let _ = writeln!(
+                out,
+                "    // {:?}, right-aligned",
+                String::from_utf8_lossy(FIAT_SHAMIR_PROTOCOL_TAG)
+            );
+            let _ = writeln!(
+                out,
+                "    bytes32 internal constant PROTOCOL_TAG = 0x{};",
+                hex_encode(&tag)
+            );
+        }
+        out.push_str("    // Absorption order:\n");
+        if self.fiat_shamir == FiatShamirMode::StrongV1 {
+            let _ = writeln!(out, "    //   absorb {}", STATEMENT_LAYOUT.join(", "));
+        }
+        for (absorbed, squeezed) in TRANSCRIPT_LAYOUT {
+            let _ = writeln!(
+                out,
+                "    //   absorb {}; squeeze {}",
+                absorbed.join(", "),
+                squeezed.join(", ")
+            );
+        }
+
+        let mut digest = sigma.digest().to_bytes_le();
+        digest.reverse();
+        push_lines(&mut out, &["", "    // SigmaVerify::digest() of the CRS"]);
+        let _ = writeln!(
+            out,
+            "    bytes32 internal constant SIGMA_DIGEST = 0x{};",
+            hex_encode(&digest)
+        );
+
+        if self.fiat_shamir == FiatShamirMode::StrongV1 {
+            out.push_str("\n    // Setup parameters absorbed by the strong-v1 statement\n");
+            let params = self.setup_params;
+            for (name, value) in [
+                ("L_FREE", params.l_free),
+                ("L", params.l),
+                ("L_USER_OUT", params.l_user_out),
+                ("L_USER", params.l_user),
+                ("L_D", params.l_D),
+                ("M_D", params.m_D),
+                ("N", params.n),
+                ("S_D", params.s_D),
+                ("S_MAX", params.s_max),
+            ] {
+                push_constant(&mut out, name, &value.to_string());
+            }
+        }
+
+        out.push_str("\n    // sigma_verify G1 points\n");
+        for (name, point) in [
+            ("G", &sigma.G),
+            ("SIGMA1_X", &sigma.sigma_1.x),
+            ("SIGMA1_Y", &sigma.sigma_1.y),
+            ("LAGRANGE_KL", &sigma.lagrange_KL),
+        ] {
+            push_g1(&mut out, name, point);
+        }
+
+        push_lines(
+            &mut out,
+            &[
+                "",
+                "    // sigma_verify G2 points. The final pairing check pairs H, ALPHA4, ALPHA, ALPHA2 and",
+                "    // ALPHA3 with its left-hand G1 terms, and GAMMA, ETA, DELTA, X and Y with the",
+                "    // right-hand ones.",
+            ],
+        );
+        for (name, point) in [
+            ("H", &sigma.H),
+            ("ALPHA", &sigma_2.alpha),
+            ("ALPHA2", &sigma_2.alpha2),
+            ("ALPHA3", &sigma_2.alpha3),
+            ("ALPHA4", &sigma_2.alpha4),
+            ("GAMMA", &sigma_2.gamma),
+            ("DELTA", &sigma_2.delta),
+            ("ETA", &sigma_2.eta),
+            ("X", &sigma_2.x),
+            ("Y", &sigma_2.y),
+        ] {
+            push_g2(&mut out, name, point);
+        }
+
+        out.push_str("\n    // Preprocess commitments\n");
+        for (name, point) in [
+            ("S0", self.s0),
+            ("S1", self.s1),
+            ("O_PUB_FIX", self.O_pub_fix),
+        ] {
+            push_g1(&mut out, name, point);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Writes the rendered library, creating the parent directory like `write_into_json` does.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.render())
+    }
+}
+
+fn push_lines(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        let _ = writeln!(out, "{line}");
+    }
+}
+
+fn push_constant(out: &mut String, name: &str, value: &str) {
+    let _ = writeln!(out, "    uint256 internal constant {name} = {value};");
+}
+
+fn push_g1(out: &mut String, name: &str, point: &G1serde) {
+    let (x_part1, x_part2, y_part1, y_part2) = split_g1(point);
+    push_constant(out, &format!("{name}_X_PART1"), &x_part1);
+    push_constant(out, &format!("{name}_X_PART2"), &x_part2);
+    push_constant(out, &format!("{name}_Y_PART1"), &y_part1);
+    push_constant(out, &format!("{name}_Y_PART2"), &y_part2);
+}
+
+fn push_g2(out: &mut String, name: &str, point: &G2serde) {
+    for (coordinate, bytes) in [
+        ("X", point.0.x.to_bytes_le()),
+        ("Y", point.0.y.to_bytes_le()),
+    ] {
+        // icicle lays out an Fp2 element as c0 then c1, each 48 little-endian bytes.
+        for (index, le) in bytes.chunks(48).enumerate() {
+            let (part1, part2) = split_base_field(le);
+            push_constant(out, &format!("{name}_{coordinate}{index}_PART1"), &part1);
+            push_constant(out, &format!("{name}_{coordinate}{index}_PART2"), &part2);
+        }
+    }
+}
+
+// The part1/part2 split of `split_g1` for one 48-byte little-endian base field element.
+fn split_base_field(le: &[u8]) -> (String, String) {
+    let be: Vec<u8> = le.iter().rev().copied().collect();
+    (
+        format!("0x{}", hex_encode(&be[0..16])),
+        format!("0x{}", hex_encode(&be[16..48])),
+    )
+}
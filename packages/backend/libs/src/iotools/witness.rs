@@ -0,0 +1,184 @@
+use super::inputs::R1csSource;
+use super::{
+    count_subcircuit_uses, load_subcircuit_r1cs, scalar_from_hex, scalar_to_hex,
+    PlacementVariables, SetupParams, SubcircuitInfo, SubcircuitR1CS,
+};
+use crate::error::{TokamakError, TokamakResult};
+use icicle_bls12_381::curve::ScalarField;
+use icicle_core::traits::FieldImpl;
+use rayon::prelude::*;
+use std::fmt;
+
+/// Why a placement does not satisfy its subcircuit.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WitnessFailureKind {
+    /// The placement has a different number of variables than the subcircuit has wires, so none
+    /// of its constraints were evaluated.
+    VariableCount { expected: usize, found: usize },
+    /// `a * b != c`, where `a`, `b` and `c` are the rows of `A * z`, `B * z` and `C * z` for the
+    /// placement's variables `z`.
+    Constraint {
+        row: usize,
+        a: ScalarField,
+        b: ScalarField,
+        c: ScalarField,
+    },
+}
+
+/// A constraint, or the variable count, of one placement that does not hold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WitnessFailure {
+    /// Index of the placement in `placementVariables.json`.
+    pub placement: usize,
+    pub subcircuit_id: usize,
+    pub subcircuit_name: String,
+    pub kind: WitnessFailureKind,
+}
+
+impl fmt::Display for WitnessFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "placement {} (subcircuit {} {}): ",
+            self.placement, self.subcircuit_id, self.subcircuit_name
+        )?;
+        match &self.kind {
+            WitnessFailureKind::VariableCount { expected, found } => {
+                write!(f, "{found} variables, the subcircuit has {expected} wires")
+            }
+            WitnessFailureKind::Constraint { row, a, b, c } => write!(
+                f,
+                "constraint {row} is unsatisfied: a = {}, b = {}, a * b = {}, c = {}",
+                scalar_to_hex(a),
+                scalar_to_hex(b),
+                scalar_to_hex(&(*a * *b)),
+                scalar_to_hex(c)
+            ),
+        }
+    }
+}
+
+/// Outcome of [`check_witness`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WitnessReport {
+    pub placements: usize,
+    /// Number of constraint rows evaluated over all placements.
+    pub constraints: usize,
+    /// Every failure, ordered by placement and then by row.
+    pub failures: Vec<WitnessFailure>,
+}
+
+impl WitnessReport {
+    pub fn is_satisfied(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Evaluates the R1CS of every placement's subcircuit against the placement's variables and
+/// reports each constraint row with `(A z)(B z) != C z`. Needs only the subcircuit library and
+/// the synthesizer output, not the CRS.
+///
+/// Inputs the prover could not use at all (an unknown subcircuit id, more placements than
+/// `s_max`, a subcircuit with more constraints than `n`, an unreadable R1CS file or a variable
+/// that is not a hex scalar) are returned as errors instead.
+pub fn check_witness(
+    r1cs: &(impl R1csSource + ?Sized),
+    placement_variables: &[PlacementVariables],
+    subcircuit_infos: &[SubcircuitInfo],
+    setup_params: &SetupParams,
+) -> TokamakResult<WitnessReport> {
+    let usage_counts = count_subcircuit_uses(placement_variables, subcircuit_infos, setup_params)?;
+    let r1cs_by_id = usage_counts
+        .iter()
+        .zip(subcircuit_infos)
+        .map(|(&count, info)| {
+            (count > 0)
+                .then(|| load_subcircuit_r1cs(r1cs, info, setup_params))
+                .transpose()
+        })
+        .collect::<TokamakResult<Vec<_>>>()?;
+
+    let per_placement = placement_variables
+        .par_iter()
+        .enumerate()
+        .map(|(placement_idx, placement)| {
+            let info = &subcircuit_infos[placement.subcircuitId];
+            let compact_r1cs = r1cs_by_id[placement.subcircuitId]
+                .as_ref()
+                .expect("the R1CS of every used subcircuit is loaded");
+            check_placement(placement_idx, placement, info, compact_r1cs)
+        })
+        .collect::<TokamakResult<Vec<_>>>()?;
+
+    let mut report = WitnessReport {
+        placements: placement_variables.len(),
+        ..WitnessReport::default()
+    };
+    for (constraints, failures) in per_placement {
+        report.constraints += constraints;
+        report.failures.extend(failures);
+    }
+    Ok(report)
+}
+
+// Returns the number of evaluated rows and the failures of one placement.
+fn check_placement(
+    placement_idx: usize,
+    placement: &PlacementVariables,
+    info: &SubcircuitInfo,
+    compact_r1cs: &SubcircuitR1CS,
+) -> TokamakResult<(usize, Vec<WitnessFailure>)> {
+    let failure = |kind| WitnessFailure {
+        placement: placement_idx,
+        subcircuit_id: info.id,
+        subcircuit_name: info.name.clone(),
+        kind,
+    };
+    if placement.variables.len() != info.Nwires {
+        let kind = WitnessFailureKind::VariableCount {
+            expected: info.Nwires,
+            found: placement.variables.len(),
+        };
+        return Ok((0, vec![failure(kind)]));
+    }
+
+    let z = placement
+        .variables
+        .iter()
+        .enumerate()
+        .map(|(wire_idx, value)| {
+            scalar_from_hex(&value.0).map_err(|err| {
+                TokamakError::parse(
+                    format!("variable {wire_idx} of placement {placement_idx}"),
+                    err.to_string(),
+                )
+            })
+        })
+        .collect::<TokamakResult<Vec<_>>>()?;
+    let eval_row = |active_wires: &[usize], row: &[(usize, ScalarField)]| {
+        row.iter()
+            .fold(ScalarField::zero(), |acc, (compact_idx, coeff)| {
+                acc + *coeff * z[active_wires[*compact_idx]]
+            })
+    };
+
+    let mut failures = Vec::new();
+    for row in 0..info.Nconsts {
+        let a = eval_row(
+            &compact_r1cs.A_active_wires,
+            &compact_r1cs.A_sparse_rows[row],
+        );
+        let b = eval_row(
+            &compact_r1cs.B_active_wires,
+            &compact_r1cs.B_sparse_rows[row],
+        );
+        let c = eval_row(
+            &compact_r1cs.C_active_wires,
+            &compact_r1cs.C_sparse_rows[row],
+        );
+        if a * b != c {
+            failures.push(failure(WitnessFailureKind::Constraint { row, a, b, c }));
+        }
+    }
+    Ok((info.Nconsts, failures))
+}
@@ -1,5 +1,7 @@
 #![allow(non_snake_case)]
 pub mod bivariate_polynomial;
+pub mod compute_backend;
+pub mod error;
 pub mod field_structures;
 pub mod group_structures;
 pub mod iotools;
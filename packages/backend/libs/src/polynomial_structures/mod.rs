@@ -1,4 +1,4 @@
-use crate::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt};
+use crate::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt, SparsePolynomialExt};
 use crate::iotools::{Instance, PlacementVariables, SetupParams, SubcircuitInfo, SubcircuitR1CS};
 use crate::vector_operations::*;
 use icicle_bls12_381::curve::ScalarField;
@@ -102,10 +102,11 @@ macro_rules! define_gen_qapXY {
 }
 
 impl Instance {
-    pub fn gen_a_free_X(&self, setup_params: &SetupParams) -> DensePolynomialExt {
-        let l_free = setup_params.l_free;
+    /// The public inputs in the order the verifier interpolates them: the first `l_user` user
+    /// values followed by the first `l_free - l_user` block values.
+    pub fn public_inputs(&self, setup_params: &SetupParams) -> Vec<ScalarField> {
         let l_user = setup_params.l_user;
-        let m_block = l_free - l_user;
+        let m_block = setup_params.l_free - l_user;
 
         let mut user_instance = vec![ScalarField::zero(); l_user];
         for (i, value) in user_instance.iter_mut().enumerate().take(l_user) {
@@ -117,7 +118,12 @@ impl Instance {
             *value = ScalarField::from_hex(&self.a_pub_block[i]);
         }
 
-        let public_instance = [user_instance, block_instance].concat().into_boxed_slice();
+        [user_instance, block_instance].concat()
+    }
+
+    pub fn gen_a_free_X(&self, setup_params: &SetupParams) -> DensePolynomialExt {
+        let l_free = setup_params.l_free;
+        let public_instance = self.public_inputs(setup_params);
 
         DensePolynomialExt::from_rou_evals(
             HostSlice::from_slice(&public_instance),
@@ -161,6 +167,36 @@ pub fn gen_bXY(
     )
 }
 
+/// `gen_bXY` as a sparse polynomial: only the columns of the placements are built, and the
+/// `s_max - placement_variables.len()` empty slots cost nothing.
+pub fn gen_bXY_sparse(
+    placement_variables: &[PlacementVariables],
+    subcircuit_infos: &[SubcircuitInfo],
+    setup_params: &SetupParams,
+) -> SparsePolynomialExt {
+    let l = setup_params.l;
+    let l_d = setup_params.l_D;
+    let m_i = l_d - l;
+    let columns = placement_variables
+        .iter()
+        .enumerate()
+        .map(|(i, placement)| {
+            let local_variables = &placement.variables;
+            let global_idx_set = &subcircuit_infos[placement.subcircuitId].flattenMap;
+            if local_variables.len() != global_idx_set.len() {
+                panic!("Corrupted placement variables.")
+            }
+            let mut column = vec![ScalarField::zero(); m_i];
+            for (&global_idx, val_str) in global_idx_set.iter().zip(local_variables.iter()) {
+                if global_idx >= l && global_idx < l_d && val_str.as_ref() != "0x0" {
+                    column[global_idx - l] = ScalarField::from_hex(val_str.as_ref());
+                }
+            }
+            (i, column)
+        });
+    SparsePolynomialExt::from_column_evals(m_i, setup_params.s_max, columns)
+}
+
 define_gen_qapXY!(gen_uXY, A_compact_col_mat, A_active_wires);
 define_gen_qapXY!(gen_vXY, B_compact_col_mat, B_active_wires);
 define_gen_qapXY!(gen_wXY, C_compact_col_mat, C_active_wires);
@@ -8,6 +8,20 @@ use icicle_core::vec_ops::VecOps;
 // Assuming the implementation of DensePolynomialExt and BivariatePolynomial is already available
 // This mod tests can be placed in a separate file
 
+// The circuit shape the iotools tests share: four placements of subcircuits with 16 wires, 8 of
+// them interface wires. Tests change single fields with struct update syntax.
+const SMALL_SETUP_PARAMS: crate::iotools::SetupParams = crate::iotools::SetupParams {
+    l_free: 4,
+    l: 8,
+    l_user_out: 2,
+    l_user: 4,
+    l_D: 16,
+    m_D: 32,
+    n: 4,
+    s_D: 4,
+    s_max: 4,
+};
+
 #[cfg(test)]
 mod msm_vs_rayon_tests {
     use super::*;
@@ -1641,3 +1655,1456 @@ mod tests_iotools {
         gen_g1serde_vec_of_xy_monomials(x, y, &gen, x_size, y_size, &mut res);
     }
 }
+
+mod tests_errors {
+    use crate::error::TokamakError;
+    use crate::iotools::{next_point, scalar_from_hex, scalar_to_hex};
+    use crate::utils::{validate_setup_shape, SetupShape};
+    use icicle_bls12_381::curve::{ScalarCfg, ScalarField};
+    use icicle_core::traits::GenerateRandom;
+
+    #[test]
+    fn test_validate_setup_shape_returns_shape_error() {
+        let shape = SetupShape {
+            l_free: 4,
+            m_i: 8,
+            n: 3,
+            s_max: 16,
+        };
+        assert!(matches!(
+            validate_setup_shape(&shape),
+            Err(TokamakError::Shape(_))
+        ));
+        let shape = SetupShape { n: 4, ..shape };
+        assert!(validate_setup_shape(&shape).is_ok());
+    }
+
+    #[test]
+    fn test_scalar_from_hex_roundtrip_and_errors() {
+        let scalar: ScalarField = ScalarCfg::generate_random(1)[0];
+        let recovered = scalar_from_hex(&scalar_to_hex(&scalar)).unwrap();
+        assert_eq!(scalar, recovered);
+
+        assert!(matches!(
+            scalar_from_hex("0xzz"),
+            Err(TokamakError::Parse { .. })
+        ));
+        let too_long = format!("0x{}", "11".repeat(33));
+        assert!(scalar_from_hex(&too_long).is_err());
+    }
+
+    #[test]
+    fn test_next_point_rejects_malformed_entries() {
+        let part1 = vec!["0x00".to_string(), "0x00".to_string()];
+        let part2 = vec!["0x00".to_string(), "0x00".to_string()];
+        assert!(matches!(
+            next_point(0, &part1, &part2),
+            Err(TokamakError::Parse { .. })
+        ));
+        assert!(next_point(2, &part1, &part2).is_err());
+    }
+}
+
+mod tests_point_checks {
+    use crate::group_structures::{G1serde, PointCheck};
+    use icicle_bls12_381::curve::{BaseField, CurveCfg, G1Affine};
+    use icicle_core::curve::Curve;
+    use icicle_core::traits::FieldImpl;
+
+    #[test]
+    fn test_g1_point_check() {
+        let point = CurveCfg::generate_random_affine_points(1)[0];
+        assert_eq!(G1serde(point).check(), PointCheck::Valid);
+        assert_eq!(G1serde::zero().check(), PointCheck::Valid);
+
+        let off_curve = G1Affine {
+            x: point.x,
+            y: point.x,
+        };
+        assert_eq!(G1serde(off_curve).check(), PointCheck::NotOnCurve);
+
+        let unreduced = G1Affine {
+            x: BaseField::from_bytes_le(&[0xff; 48]),
+            y: point.y,
+        };
+        assert_eq!(G1serde(unreduced).check(), PointCheck::NonCanonical);
+    }
+}
+
+mod tests_binary {
+    use crate::error::TokamakError;
+    use crate::group_structures::G1serde;
+    use crate::iotools::binary::{BinaryReader, BinaryWriter, PointEncoding};
+    use icicle_bls12_381::curve::{CurveCfg, ScalarCfg};
+    use icicle_core::curve::Curve;
+    use icicle_core::traits::GenerateRandom;
+
+    const MAGIC: [u8; 4] = *b"TEST";
+
+    #[test]
+    fn test_binary_roundtrip_in_both_encodings() {
+        let mut points: Vec<G1serde> = CurveCfg::generate_random_affine_points(3)
+            .into_iter()
+            .map(G1serde)
+            .collect();
+        points.push(G1serde::zero());
+        let scalar = ScalarCfg::generate_random(1)[0];
+
+        for (encoding, point_len) in [
+            (PointEncoding::Uncompressed, 96),
+            (PointEncoding::Compressed, 48),
+        ] {
+            let mut writer = BinaryWriter::new(&MAGIC, encoding);
+            for point in &points {
+                writer.g1(point);
+            }
+            writer.scalar(&scalar);
+            let bytes = writer.finish();
+            assert_eq!(bytes.len(), 6 + points.len() * point_len + 32);
+
+            let mut reader = BinaryReader::new(&bytes, &MAGIC, "test").unwrap();
+            assert_eq!(reader.encoding(), encoding);
+            for point in &points {
+                assert_eq!(reader.g1().unwrap(), *point);
+            }
+            assert_eq!(reader.scalar().unwrap(), scalar);
+            reader.finish().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_binary_rejects_bad_headers_and_lengths() {
+        let mut writer = BinaryWriter::new(&MAGIC, PointEncoding::Uncompressed);
+        writer.g1(&G1serde::zero());
+        let bytes = writer.finish();
+
+        assert!(matches!(
+            BinaryReader::new(&bytes, b"NOPE", "test"),
+            Err(TokamakError::Parse { .. })
+        ));
+        let mut bad_version = bytes.clone();
+        bad_version[4] += 1;
+        assert!(BinaryReader::new(&bad_version, &MAGIC, "test").is_err());
+        let mut bad_flags = bytes.clone();
+        bad_flags[5] = 0x80;
+        assert!(BinaryReader::new(&bad_flags, &MAGIC, "test").is_err());
+
+        let mut reader = BinaryReader::new(&bytes[..bytes.len() - 1], &MAGIC, "test").unwrap();
+        assert!(reader.g1().is_err());
+
+        let reader = BinaryReader::new(&bytes, &MAGIC, "test").unwrap();
+        assert!(reader.finish().is_err());
+    }
+
+    #[test]
+    fn test_binary_rejects_non_canonical_scalars() {
+        let modulus =
+            hex::decode("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001")
+                .unwrap();
+        let mut below_modulus = modulus.clone();
+        below_modulus[31] -= 1;
+        for (scalar, canonical) in [
+            (below_modulus, true),
+            (modulus, false),
+            (vec![0xff; 32], false),
+        ] {
+            let mut bytes = BinaryWriter::new(&MAGIC, PointEncoding::Uncompressed).finish();
+            bytes.extend(scalar);
+            let mut reader = BinaryReader::new(&bytes, &MAGIC, "test").unwrap();
+            assert_eq!(reader.scalar().is_ok(), canonical);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_compressed {
+    use crate::group_structures::{G1serde, G2serde, Sigma, Sigma1, Sigma2, SigmaVerify};
+    use crate::iotools::compressed::{
+        compressed_sigma_bytes, compressed_sigma_verify_json, decompress_sigma_bytes,
+        G1_COMPRESSED_LEN,
+    };
+    use crate::iotools::SigmaRkyv;
+    use icicle_bls12_381::curve::{CurveCfg, G2CurveCfg};
+    use icicle_core::curve::Curve;
+
+    fn g1_points(n: usize) -> Box<[G1serde]> {
+        CurveCfg::generate_random_affine_points(n)
+            .into_iter()
+            .map(G1serde)
+            .collect()
+    }
+
+    fn g2_point() -> G2serde {
+        G2serde(G2CurveCfg::generate_random_affine_points(1)[0])
+    }
+
+    pub(super) fn small_sigma() -> Sigma {
+        let g1 = || g1_points(1)[0];
+        Sigma {
+            G: g1(),
+            H: g2_point(),
+            sigma_1: Sigma1 {
+                xy_powers: g1_points(4),
+                x: g1(),
+                y: g1(),
+                delta: g1(),
+                eta: g1(),
+                gamma_inv_o_inst: g1_points(2),
+                eta_inv_li_o_inter_alpha4_kj: vec![g1_points(2), g1_points(2)].into(),
+                delta_inv_li_o_prv: vec![g1_points(3)].into(),
+                delta_inv_alphak_xh_tx: vec![g1_points(1), g1_points(2)].into(),
+                delta_inv_alpha4_xj_tx: g1_points(2),
+                delta_inv_alphak_yi_ty: vec![g1_points(2)].into(),
+            },
+            sigma_2: Sigma2 {
+                alpha: g2_point(),
+                alpha2: g2_point(),
+                alpha3: g2_point(),
+                alpha4: g2_point(),
+                gamma: g2_point(),
+                delta: g2_point(),
+                eta: g2_point(),
+                x: g2_point(),
+                y: g2_point(),
+            },
+            lagrange_KL: G1serde::zero(),
+        }
+    }
+
+    #[test]
+    fn test_compressed_point_roundtrip() {
+        for point in g1_points(4).iter().chain([&G1serde::zero()]) {
+            let bytes = point.to_compressed();
+            assert_eq!(G1serde::from_compressed(&bytes).unwrap(), *point);
+        }
+        for point in [g2_point(), g2_point(), G2serde::zero()] {
+            let bytes = point.to_compressed();
+            assert_eq!(G2serde::from_compressed(&bytes).unwrap(), point);
+        }
+    }
+
+    #[test]
+    fn test_decompression_rejects_invalid_points() {
+        let valid = g1_points(1)[0].to_compressed();
+        assert!(G1serde::from_compressed(&valid[..G1_COMPRESSED_LEN - 1]).is_err());
+
+        // Small x coordinates are either off the curve or outside the prime-order subgroup.
+        for x in 1u8..=8 {
+            let mut bytes = [0u8; G1_COMPRESSED_LEN];
+            bytes[0] = 0x80;
+            bytes[G1_COMPRESSED_LEN - 1] = x;
+            assert!(G1serde::from_compressed(&bytes).is_err());
+        }
+
+        let mut bytes = [0u8; 96];
+        bytes[0] = 0x80;
+        bytes[95] = 1;
+        assert!(G2serde::from_compressed(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_json_accepts_both_point_forms() {
+        let point = g1_points(1)[0];
+        let coords = serde_json::to_string(&point).unwrap();
+        let compressed = format!("\"0x{}\"", hex::encode(point.to_compressed()));
+        assert_eq!(serde_json::from_str::<G1serde>(&coords).unwrap(), point);
+        assert_eq!(serde_json::from_str::<G1serde>(&compressed).unwrap(), point);
+        assert!(serde_json::from_str::<G1serde>("\"0x1234\"").is_err());
+
+        let point = g2_point();
+        let compressed = format!("\"0x{}\"", hex::encode(point.to_compressed()));
+        assert_eq!(serde_json::from_str::<G2serde>(&compressed).unwrap(), point);
+    }
+
+    #[test]
+    fn test_compressed_sigma_artifacts_roundtrip() {
+        let sigma = small_sigma();
+
+        let uncompressed = rkyv::to_bytes::<_, 256>(&SigmaRkyv::from_sigma(&sigma)).unwrap();
+        let compressed = compressed_sigma_bytes(&sigma).unwrap();
+        assert!(compressed.len() < uncompressed.len());
+        let recovered = decompress_sigma_bytes(&compressed).unwrap();
+        assert_eq!(
+            rkyv::to_bytes::<_, 256>(&recovered).unwrap().as_ref(),
+            uncompressed.as_ref()
+        );
+        assert!(decompress_sigma_bytes(uncompressed.as_ref()).is_err());
+
+        let json = compressed_sigma_verify_json(&sigma).unwrap();
+        let full = serde_json::to_vec_pretty(&sigma.sigma_verify()).unwrap();
+        assert!(json.len() * 3 < full.len() * 2);
+        let recovered: SigmaVerify = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&recovered).unwrap(),
+            serde_json::to_value(sigma.sigma_verify()).unwrap()
+        );
+    }
+}
+
+mod tests_point_validation {
+    use super::tests_compressed::small_sigma;
+    use crate::error::TokamakError;
+    use crate::group_structures::{
+        ark_g1_affine_to_icicle, ark_g2_affine_to_icicle, point_validation, with_point_validation,
+        G1serde, G2serde, PointCheck, PointValidation,
+    };
+    use crate::iotools::binary::{BinaryReader, BinaryWriter, PointEncoding};
+    use crate::iotools::{next_point, split_g1, SigmaRkyv};
+    use ark_bls12_381::{Fr, G1Affine as ArkG1Affine, G2Affine as ArkG2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::PrimeField;
+    use icicle_bls12_381::curve::{BaseField, G1Affine};
+    use icicle_core::traits::FieldImpl;
+
+    // Multiplying a random curve point by the group order r leaves a point whose order divides the
+    // cofactor: on the curve, but outside the prime-order subgroup.
+    fn small_order_g1() -> G1serde {
+        for k in 1u64.. {
+            let x = ark_bls12_381::Fq::from(k);
+            if let Some(point) = ArkG1Affine::get_point_from_x_unchecked(x, false) {
+                let torsion = point.mul_bigint(Fr::MODULUS).into_affine();
+                if !torsion.is_zero() {
+                    return G1serde(ark_g1_affine_to_icicle(&torsion));
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    fn small_order_g2() -> G2serde {
+        for k in 1u64.. {
+            let x = ark_bls12_381::Fq2::from(k);
+            if let Some(point) = ArkG2Affine::get_point_from_x_unchecked(x, false) {
+                let torsion = point.mul_bigint(Fr::MODULUS).into_affine();
+                if !torsion.is_zero() {
+                    return G2serde(ark_g2_affine_to_icicle(&torsion));
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    fn off_curve_g1() -> G1serde {
+        G1serde(G1Affine {
+            x: BaseField::from_u32(1),
+            y: BaseField::from_u32(2),
+        })
+    }
+
+    #[test]
+    fn test_checked_constructors_reject_invalid_points() {
+        let torsion = small_order_g1();
+        assert_eq!(torsion.check(), PointCheck::NotInSubgroup);
+        assert!(matches!(
+            G1serde::checked(torsion.0),
+            Err(TokamakError::InvalidPoint {
+                check: PointCheck::NotInSubgroup,
+                ..
+            })
+        ));
+        assert!(matches!(
+            G1serde::checked(off_curve_g1().0),
+            Err(TokamakError::InvalidPoint {
+                check: PointCheck::NotOnCurve,
+                ..
+            })
+        ));
+        assert!(G1serde::checked(G1serde::zero().0).is_ok());
+
+        let torsion = small_order_g2();
+        assert_eq!(torsion.check(), PointCheck::NotInSubgroup);
+        assert!(G2serde::checked(torsion.0).is_err());
+
+        // The opt-out keeps the raw coordinates.
+        let loaded = G1serde::load(off_curve_g1().0, PointValidation::Trusted).unwrap();
+        assert_eq!(loaded, off_curve_g1());
+    }
+
+    #[test]
+    fn test_load_paths_reject_invalid_points_by_default() {
+        for point in [off_curve_g1(), small_order_g1()] {
+            let json = serde_json::to_string(&point).unwrap();
+            assert!(serde_json::from_str::<G1serde>(&json).is_err());
+
+            let (x1, x2, y1, y2) = split_g1(&point);
+            let (part1, part2) = (vec![x1, y1], vec![x2, y2]);
+            assert!(next_point(0, &part1, &part2).is_err());
+
+            let mut writer = BinaryWriter::new(b"TEST", PointEncoding::Uncompressed);
+            writer.g1(&point);
+            let bytes = writer.finish();
+            let mut reader = BinaryReader::new(&bytes, b"TEST", "test").unwrap();
+            assert!(reader.g1().is_err());
+
+            // Trusted artifacts skip the checks.
+            with_point_validation(PointValidation::Trusted, || {
+                assert_eq!(serde_json::from_str::<G1serde>(&json).unwrap(), point);
+                assert_eq!(next_point(0, &part1, &part2).unwrap(), point);
+                let mut reader = BinaryReader::new(&bytes, b"TEST", "test").unwrap();
+                assert_eq!(reader.g1().unwrap(), point);
+            });
+        }
+
+        let json = serde_json::to_string(&small_order_g2()).unwrap();
+        assert!(serde_json::from_str::<G2serde>(&json).is_err());
+        assert_eq!(point_validation(), PointValidation::Checked);
+    }
+
+    #[test]
+    fn test_sigma_archive_validation() {
+        let mut sigma = small_sigma();
+        let archive = rkyv::to_bytes::<_, 256>(&SigmaRkyv::from_sigma(&sigma)).unwrap();
+        let archived = rkyv::check_archived_root::<SigmaRkyv>(&archive).unwrap();
+        assert!(archived.validate_points(PointValidation::Checked).is_ok());
+
+        sigma.sigma_1.delta_inv_li_o_prv[0][1] = small_order_g1();
+        let archive = rkyv::to_bytes::<_, 256>(&SigmaRkyv::from_sigma(&sigma)).unwrap();
+        let archived = rkyv::check_archived_root::<SigmaRkyv>(&archive).unwrap();
+        assert!(archived.validate_points(PointValidation::Checked).is_err());
+        assert!(archived.validate_points(PointValidation::Trusted).is_ok());
+
+        sigma.sigma_1.delta_inv_li_o_prv[0][1] = G1serde::zero();
+        sigma.sigma_2.gamma = small_order_g2();
+        let archive = rkyv::to_bytes::<_, 256>(&SigmaRkyv::from_sigma(&sigma)).unwrap();
+        let archived = rkyv::check_archived_root::<SigmaRkyv>(&archive).unwrap();
+        assert!(archived.validate_points(PointValidation::Checked).is_err());
+    }
+}
+
+mod tests_inputs {
+    use super::SMALL_SETUP_PARAMS;
+    use crate::error::TokamakError;
+    use crate::group_structures::PointValidation;
+    use crate::iotools::inputs::{CircuitInputs, MemoryInputs, R1csSource};
+    use crate::iotools::{SetupParams, SubcircuitInfo, SubcircuitR1CS};
+    use std::io;
+    use std::path::PathBuf;
+
+    fn setup_params() -> SetupParams {
+        SetupParams {
+            s_max: 16,
+            ..SMALL_SETUP_PARAMS
+        }
+    }
+
+    #[test]
+    fn test_memory_inputs_borrow_and_report_missing() {
+        let params = setup_params();
+        let mut inputs = MemoryInputs::new(());
+        inputs.setup_params = Some(&params);
+        inputs.point_validation = PointValidation::Trusted;
+
+        assert_eq!(inputs.setup_params().unwrap().s_max, 16);
+        assert_eq!(inputs.point_validation(), PointValidation::Trusted);
+        assert!(matches!(
+            inputs.instance(),
+            Err(TokamakError::MissingInput("instance"))
+        ));
+        assert!(matches!(
+            inputs.placement_variables(),
+            Err(TokamakError::MissingInput("placement_variables"))
+        ));
+        assert_eq!(
+            inputs.r1cs_bytes(0).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        let r1cs = vec![b"r1cs".to_vec()];
+        inputs.r1cs = Some(&r1cs);
+        assert_eq!(inputs.r1cs_bytes(0).unwrap().as_ref(), b"r1cs");
+        assert!(inputs.r1cs_bytes(1).is_err());
+    }
+
+    #[test]
+    fn test_r1cs_sources_report_the_failing_file() {
+        let library = PathBuf::from("/nonexistent/subcircuit/library");
+        let err = library.r1cs_bytes(3).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("r1cs/subcircuit3.r1cs"));
+
+        let info = SubcircuitInfo {
+            id: 0,
+            name: "test".to_string(),
+            Nwires: 1,
+            Nconsts: 1,
+            Out_idx: Box::new([]),
+            In_idx: Box::new([]),
+            flattenMap: Box::new([0]),
+        };
+        let err =
+            SubcircuitR1CS::from_r1cs_bytes_sparse_only(b"nope".to_vec(), &setup_params(), &info)
+                .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
+
+mod tests_solidity {
+    use super::tests_compressed::small_sigma;
+    use super::SMALL_SETUP_PARAMS;
+    use crate::iotools::fiat_shamir::FiatShamirMode;
+    use crate::iotools::solidity::{SolidityVerifierConstants, DEFAULT_LIBRARY_NAME};
+    use crate::iotools::{hex_encode, split_g1, SetupParams};
+
+    const PARAMS: SetupParams = SetupParams {
+        l_user_out: 1,
+        l_user: 2,
+        n: 8,
+        ..SMALL_SETUP_PARAMS
+    };
+
+    fn constant<'a>(rendered: &'a str, name: &str) -> &'a str {
+        let prefix = format!("uint256 internal constant {name} = ");
+        rendered
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(prefix.as_str()))
+            .and_then(|value| value.strip_suffix(';'))
+            .unwrap_or_else(|| panic!("missing constant {name}"))
+    }
+
+    #[test]
+    fn test_solidity_constants_use_the_calldata_split() {
+        let sigma = small_sigma().sigma_verify();
+        let constants = SolidityVerifierConstants {
+            library_name: DEFAULT_LIBRARY_NAME,
+            sigma: &sigma,
+            s0: &sigma.sigma_1.x,
+            s1: &sigma.sigma_1.y,
+            O_pub_fix: &sigma.G,
+            setup_params: &PARAMS,
+            fiat_shamir: FiatShamirMode::Legacy,
+        };
+        let rendered = constants.render();
+        assert!(rendered.contains("library TokamakVerifierConstants {"));
+        assert!(rendered.trim_end().ends_with('}'));
+        // 7 G1 points of four words and 10 G2 points of eight words, plus FR_MASK
+        assert_eq!(
+            rendered.matches("uint256 internal constant").count(),
+            7 * 4 + 10 * 8 + 1
+        );
+
+        let (x_part1, x_part2, y_part1, y_part2) = split_g1(&sigma.G);
+        assert_eq!(constant(&rendered, "G_X_PART1"), x_part1);
+        assert_eq!(constant(&rendered, "G_X_PART2"), x_part2);
+        assert_eq!(constant(&rendered, "O_PUB_FIX_Y_PART1"), y_part1);
+        assert_eq!(constant(&rendered, "O_PUB_FIX_Y_PART2"), y_part2);
+
+        // The compressed encoding holds x.c1 then x.c0 big-endian, with flags in the top 3 bits.
+        let mut compressed = sigma.H.to_compressed();
+        compressed[0] &= 0x1f;
+        let hex = |bytes: &[u8]| format!("0x{}", hex_encode(bytes));
+        assert_eq!(constant(&rendered, "H_X1_PART1"), hex(&compressed[0..16]));
+        assert_eq!(constant(&rendered, "H_X1_PART2"), hex(&compressed[16..48]));
+        assert_eq!(constant(&rendered, "H_X0_PART1"), hex(&compressed[48..64]));
+        assert_eq!(constant(&rendered, "H_X0_PART2"), hex(&compressed[64..96]));
+    }
+
+    #[test]
+    fn test_solidity_constants_record_the_fiat_shamir_mode() {
+        let sigma = small_sigma().sigma_verify();
+        let mut constants = SolidityVerifierConstants {
+            library_name: DEFAULT_LIBRARY_NAME,
+            sigma: &sigma,
+            s0: &sigma.sigma_1.x,
+            s1: &sigma.sigma_1.y,
+            O_pub_fix: &sigma.G,
+            setup_params: &PARAMS,
+            fiat_shamir: FiatShamirMode::Legacy,
+        };
+        let legacy = constants.render();
+        assert!(legacy.contains("uint8 internal constant FIAT_SHAMIR_MODE = 0;"));
+        assert!(!legacy.contains("PROTOCOL_TAG"));
+        assert!(!legacy.contains("absorb protocol_tag"));
+
+        constants.fiat_shamir = FiatShamirMode::StrongV1;
+        let strong = constants.render();
+        assert!(strong.contains("uint8 internal constant FIAT_SHAMIR_MODE = 1;"));
+        assert!(strong.contains(&format!(
+            "bytes32 internal constant PROTOCOL_TAG = 0x{}{};",
+            "00".repeat(3),
+            hex_encode(b"tokamak-zk-evm/fiat-shamir/v1")
+        )));
+        assert!(strong.contains("//   absorb protocol_tag, sigma_digest, l_free,"));
+        assert_eq!(constant(&strong, "L_FREE"), "4");
+        assert_eq!(constant(&strong, "S_MAX"), "4");
+        assert_eq!(
+            strong.matches("uint256 internal constant").count(),
+            legacy.matches("uint256 internal constant").count() + 9
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_encode_poly {
+    use super::tests_compressed::small_sigma;
+    use crate::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt};
+    use crate::group_structures::{with_msm_memory_budget, G1serde, MsmMemoryBudget};
+    use crate::iotools::{SetupParams, SigmaRkyv};
+    use icicle_bls12_381::curve::{CurveCfg, G1Affine, ScalarCfg, ScalarField};
+    use icicle_core::curve::Curve;
+    use icicle_core::traits::GenerateRandom;
+    use icicle_runtime::memory::HostSlice;
+
+    #[test]
+    fn test_tiled_commitment_matches_single_shot() {
+        // xy_powers is (2 * n) x (2 * s_max) = 8 x 4.
+        let params = SetupParams {
+            l_free: 1,
+            l: 2,
+            l_user_out: 0,
+            l_user: 1,
+            l_D: 4,
+            m_D: 6,
+            n: 4,
+            s_D: 1,
+            s_max: 2,
+        };
+        let mut sigma = small_sigma();
+        sigma.sigma_1.xy_powers = CurveCfg::generate_random_affine_points(8 * 4)
+            .into_iter()
+            .map(G1serde)
+            .collect();
+        let archive = rkyv::to_bytes::<_, 256>(&SigmaRkyv::from_sigma(&sigma)).unwrap();
+        let archived = unsafe { rkyv::archived_root::<SigmaRkyv>(&archive) };
+        let coeffs = ScalarCfg::generate_random(8 * 4);
+        let poly = DensePolynomialExt::from_coeffs(HostSlice::from_slice(&coeffs), 8, 4);
+
+        let row_bytes = 4 * (std::mem::size_of::<ScalarField>() + std::mem::size_of::<G1Affine>());
+        let commit = |budget| {
+            with_msm_memory_budget(budget, || {
+                (
+                    sigma.sigma_1.encode_poly(&mut poly.clone(), &params),
+                    archived.sigma_1.encode_poly(&mut poly.clone(), &params),
+                )
+            })
+        };
+        let (expected, archived_expected) = commit(MsmMemoryBudget::Unbounded);
+        assert_eq!(expected, archived_expected);
+        // One power of Y per tile, uneven runs of three powers within a row, one row per tile and
+        // uneven tiles of three rows.
+        let entry_bytes = row_bytes / 4;
+        for budget in [
+            MsmMemoryBudget::Bytes(1),
+            MsmMemoryBudget::Bytes(3 * entry_bytes),
+            MsmMemoryBudget::Bytes(row_bytes),
+            MsmMemoryBudget::Bytes(3 * row_bytes),
+        ] {
+            assert_eq!(commit(budget), (expected, expected));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_sparse_polynomial {
+    use super::tests_compressed::small_sigma;
+    use crate::bivariate_polynomial::{
+        init_ntt_domain_for_size, BivariatePolynomial, SparsePolynomialExt,
+    };
+    use crate::group_structures::G1serde;
+    use crate::iotools::SetupParams;
+    use icicle_bls12_381::curve::{CurveCfg, ScalarCfg, ScalarField};
+    use icicle_core::curve::Curve;
+    use icicle_core::ntt;
+    use icicle_core::traits::{Arithmetic, FieldImpl, GenerateRandom};
+
+    // An 8 x 4 polynomial with random columns at `occupied`, like placements in four slots.
+    fn random_sparse(occupied: &[usize]) -> SparsePolynomialExt {
+        init_ntt_domain_for_size(64).unwrap();
+        let columns = occupied.iter().map(|&j| (j, ScalarCfg::generate_random(8)));
+        SparsePolynomialExt::from_column_evals(8, 4, columns)
+    }
+
+    #[test]
+    fn test_dense_roundtrip_and_eval() {
+        let poly = random_sparse(&[0, 2]);
+        assert_eq!(poly.occupied().collect::<Vec<_>>(), vec![0, 2]);
+        let dense = poly.to_dense();
+        assert_eq!(SparsePolynomialExt::from_dense(&dense), poly);
+
+        let omega = ntt::get_root_of_unity::<ScalarField>(4);
+        let x = ScalarCfg::generate_random(1)[0];
+        for y in [ScalarCfg::generate_random(1)[0], omega, omega.pow(2)] {
+            assert_eq!(poly.eval(&x, &y), dense.eval(&x, &y));
+        }
+        assert_eq!(poly.eval(&x, &omega), ScalarField::zero());
+    }
+
+    #[test]
+    fn test_linear_operations_match_dense() {
+        let a = random_sparse(&[0, 2]);
+        let b = random_sparse(&[1, 2]);
+        let scale = ScalarCfg::generate_random(1)[0];
+        let (x, y) = (
+            ScalarCfg::generate_random(1)[0],
+            ScalarCfg::generate_random(1)[0],
+        );
+        let (a_eval, b_eval) = (a.eval(&x, &y), b.eval(&x, &y));
+
+        assert_eq!((&a + &b).to_dense().eval(&x, &y), a_eval + b_eval);
+        assert_eq!((&a - &b).to_dense().eval(&x, &y), a_eval - b_eval);
+        assert_eq!((&a * &scale).to_dense().eval(&x, &y), a_eval * scale);
+        assert!((&a - &a).is_zero());
+    }
+
+    #[test]
+    fn test_mul_reduced_matches_product_on_domain() {
+        let a = random_sparse(&[0, 2]);
+        let b = random_sparse(&[1, 2]);
+        let product = a.mul_reduced(&b);
+        // Only the column both factors occupy survives.
+        assert_eq!(product.occupied().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(product.x_size, 16);
+
+        let omega = ntt::get_root_of_unity::<ScalarField>(4);
+        let x = ScalarCfg::generate_random(1)[0];
+        for j in 0..4 {
+            let y = omega.pow(j);
+            assert_eq!(product.eval(&x, &y), a.eval(&x, &y) * b.eval(&x, &y));
+        }
+    }
+
+    #[test]
+    fn test_div_by_vanishing_identity() {
+        let poly = random_sparse(&[1, 3]);
+        let (quotient, remainder) = poly.div_by_vanishing(4);
+        for j in remainder.occupied() {
+            let column = remainder.column(j).unwrap();
+            assert!(column[4..]
+                .iter()
+                .all(|coeff| *coeff == ScalarField::zero()));
+        }
+
+        let (x, y) = (
+            ScalarCfg::generate_random(1)[0],
+            ScalarCfg::generate_random(1)[0],
+        );
+        let vanishing = x.pow(4) - ScalarField::one();
+        assert_eq!(
+            poly.eval(&x, &y),
+            quotient.eval(&x, &y) * vanishing + remainder.eval(&x, &y)
+        );
+    }
+
+    #[test]
+    fn test_sparse_commitment_matches_dense() {
+        // xy_powers is (2 * n) x (2 * s_max) = 8 x 4.
+        let params = SetupParams {
+            l_free: 1,
+            l: 2,
+            l_user_out: 0,
+            l_user: 1,
+            l_D: 4,
+            m_D: 6,
+            n: 4,
+            s_D: 1,
+            s_max: 2,
+        };
+        let mut sigma = small_sigma();
+        sigma.sigma_1.xy_powers = CurveCfg::generate_random_affine_points(8 * 4)
+            .into_iter()
+            .map(G1serde)
+            .collect();
+        let poly = random_sparse(&[0, 2]);
+        assert_eq!(
+            sigma.sigma_1.encode_sparse_poly(&poly, &params),
+            sigma.sigma_1.encode_poly(&mut poly.to_dense(), &params)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_coset_polynomial {
+    use crate::bivariate_polynomial::{
+        init_ntt_domain_for_size, BivariatePolynomial, CosetPolynomialExt, DensePolynomialExt,
+        PolyExpr,
+    };
+    use icicle_bls12_381::curve::{ScalarCfg, ScalarField};
+    use icicle_core::traits::{FieldImpl, GenerateRandom};
+    use icicle_runtime::memory::HostSlice;
+
+    fn random_poly(x_size: usize, y_size: usize) -> DensePolynomialExt {
+        init_ntt_domain_for_size(256).unwrap();
+        let coeffs = ScalarCfg::generate_random(x_size * y_size);
+        DensePolynomialExt::from_coeffs(HostSlice::from_slice(&coeffs), x_size, y_size)
+    }
+
+    fn random_point() -> (ScalarField, ScalarField) {
+        let point = ScalarCfg::generate_random(2);
+        (point[0], point[1])
+    }
+
+    #[test]
+    fn test_pointwise_chain_matches_dense() {
+        let coset = ScalarField::from_u32(7);
+        let (u, v, w) = (random_poly(4, 2), random_poly(4, 2), random_poly(4, 2));
+        let on_coset = |poly| CosetPolynomialExt::from_poly(poly, 8, 4, coset, coset);
+        let (u_c, v_c, w_c) = (on_coset(&u), on_coset(&v), on_coset(&w));
+        let scale = ScalarCfg::generate_random(1)[0];
+
+        let chain = &(&(&u_c * &v_c) - &w_c) * &scale;
+        let expr = PolyExpr::scale(
+            scale,
+            PolyExpr::sub(
+                PolyExpr::mul(PolyExpr::poly(&u), PolyExpr::poly(&v)),
+                PolyExpr::poly(&w),
+            ),
+        );
+        assert_eq!(expr.evaluate_on_coset(&coset, &coset), chain);
+
+        let (x, y) = random_point();
+        let expected = scale * (u.eval(&x, &y) * v.eval(&x, &y) - w.eval(&x, &y));
+        assert_eq!(chain.to_poly().eval(&x, &y), expected);
+        assert_eq!(expr.eval(&x, &y), expected);
+        assert_eq!((&u_c + &w_c).to_poly().eval(&x, &y), (&u + &w).eval(&x, &y));
+    }
+
+    #[test]
+    fn test_div_by_vanishing_matches_coefficient_division() {
+        // p = q_x (X^4 - 1) + q_y (Y^2 - 1) with the X-degree of q_y below 4.
+        let (q_x, q_y) = (random_poly(4, 2), random_poly(4, 2));
+        let mut t_x_coeffs = vec![ScalarField::zero(); 8];
+        t_x_coeffs[0] = ScalarField::zero() - ScalarField::one();
+        t_x_coeffs[4] = ScalarField::one();
+        let t_x = DensePolynomialExt::from_coeffs(HostSlice::from_slice(&t_x_coeffs), 8, 1);
+        let t_y_coeffs = [
+            ScalarField::zero() - ScalarField::one(),
+            ScalarField::zero(),
+            ScalarField::one(),
+            ScalarField::zero(),
+        ];
+        let t_y = DensePolynomialExt::from_coeffs(HostSlice::from_slice(&t_y_coeffs), 1, 4);
+        let mut p = &(&q_x * &t_x) + &(&q_y * &t_y);
+
+        let coset = ScalarField::from_u32(7);
+        let (coset_q_x, coset_q_y) =
+            CosetPolynomialExt::from_poly(&p, 8, 4, coset, coset).div_by_vanishing(4, 2);
+        let (opt_q_x, opt_q_y) = p.div_by_vanishing_opt(4, 2);
+
+        let (x, y) = random_point();
+        let (coset_q_x, coset_q_y) = (coset_q_x.to_poly(), coset_q_y.to_poly());
+        assert_eq!(coset_q_x.eval(&x, &y), q_x.eval(&x, &y));
+        assert_eq!(coset_q_y.eval(&x, &y), q_y.eval(&x, &y));
+        assert_eq!(coset_q_x.eval(&x, &y), opt_q_x.eval(&x, &y));
+        assert_eq!(coset_q_y.eval(&x, &y), opt_q_y.eval(&x, &y));
+    }
+}
+
+#[cfg(all(test, feature = "arkworks"))]
+mod tests_compute_backend {
+    use super::tests_compressed::small_sigma;
+    use crate::bivariate_polynomial::{
+        init_ntt_domain_for_size, BivariatePolynomial, CosetPolynomialExt, DensePolynomialExt,
+    };
+    use crate::compute_backend::{with_compute_backend, ComputeBackend};
+    use crate::group_structures::G1serde;
+    use crate::iotools::SetupParams;
+    use crate::vector_operations::*;
+    use icicle_bls12_381::curve::{CurveCfg, G1Affine, ScalarCfg, ScalarField};
+    use icicle_core::curve::Curve;
+    use icicle_core::traits::{FieldImpl, GenerateRandom};
+    use icicle_runtime::memory::HostSlice;
+    use std::fmt::Debug;
+
+    // Runs `f` on both backends and checks that they agree.
+    fn on_both<T: PartialEq + Debug>(f: impl Fn() -> T) -> T {
+        let icicle = with_compute_backend(ComputeBackend::Icicle, &f);
+        let arkworks = with_compute_backend(ComputeBackend::Arkworks, &f);
+        assert_eq!(icicle, arkworks);
+        arkworks
+    }
+
+    fn coeffs_of(poly: &DensePolynomialExt) -> Vec<ScalarField> {
+        let mut coeffs = vec![ScalarField::zero(); poly.x_size * poly.y_size];
+        poly.copy_coeffs(0, HostSlice::from_mut_slice(&mut coeffs));
+        coeffs
+    }
+
+    #[test]
+    fn test_ntt_matches_icicle() {
+        init_ntt_domain_for_size(256).unwrap();
+        let coeffs = ScalarCfg::generate_random(32);
+        let coset = ScalarField::from_u32(7);
+        for (x_size, y_size) in [(8, 4), (32, 1), (1, 32)] {
+            let (evals, round_trip) = on_both(|| {
+                let poly =
+                    DensePolynomialExt::from_coeffs(HostSlice::from_slice(&coeffs), x_size, y_size);
+                let mut evals = vec![ScalarField::zero(); 32];
+                poly.to_rou_evals(Some(&coset), None, HostSlice::from_mut_slice(&mut evals));
+                let back = DensePolynomialExt::from_rou_evals(
+                    HostSlice::from_slice(&evals),
+                    x_size,
+                    y_size,
+                    Some(&coset),
+                    None,
+                );
+                (evals, coeffs_of(&back))
+            });
+            assert_ne!(evals, coeffs);
+            assert_eq!(round_trip, coeffs);
+        }
+    }
+
+    #[test]
+    fn test_polynomial_ops_match_icicle() {
+        init_ntt_domain_for_size(256).unwrap();
+        let u = ScalarCfg::generate_random(8);
+        let v = ScalarCfg::generate_random(8);
+        let coset = ScalarField::from_u32(7);
+        on_both(|| {
+            let u = DensePolynomialExt::from_coeffs(HostSlice::from_slice(&u), 4, 2);
+            let v = DensePolynomialExt::from_coeffs(HostSlice::from_slice(&v), 4, 2);
+            let mut product = &u * &v;
+            let (quo_x, quo_y) = product.div_by_vanishing_opt(2, 2);
+            let (coset_quo_x, coset_quo_y) =
+                CosetPolynomialExt::from_poly(&product, 8, 4, coset, coset).div_by_vanishing(2, 2);
+            (
+                coeffs_of(&product),
+                coeffs_of(&quo_x),
+                coeffs_of(&quo_y),
+                coset_quo_x,
+                coset_quo_y,
+            )
+        });
+    }
+
+    #[test]
+    fn test_vector_ops_match_icicle() {
+        let lhs = ScalarCfg::generate_random(6);
+        let rhs = ScalarCfg::generate_random(6);
+        let scalar = ScalarCfg::generate_random(1)[0];
+        on_both(|| {
+            let mut results = vec![vec![ScalarField::zero(); 6]; 7];
+            point_mul_two_vecs(&lhs, &rhs, &mut results[0]);
+            point_div_two_vecs(&lhs, &rhs, &mut results[1]);
+            point_add_two_vecs(&lhs, &rhs, &mut results[2]);
+            scale_vec(scalar, &lhs, &mut results[3]);
+            scalar_vec_sub(scalar, &lhs, &mut results[4]);
+            scalar_vec_add(scalar, &lhs, &mut results[5]);
+            results[6].copy_from_slice(&lhs);
+            transpose_inplace(&mut results[6], 2, 3);
+            let mut outer = vec![ScalarField::zero(); 12];
+            outer_product_two_vecs_rayon(&lhs[..3], &rhs[..4], &mut outer);
+            let mut matmul = vec![ScalarField::zero(); 4];
+            matrix_matrix_mul(&lhs, &rhs, 2, 3, 2, &mut matmul);
+            (results, outer, matmul, inner_product_two_vecs(&lhs, &rhs))
+        });
+    }
+
+    #[test]
+    fn test_commitment_matches_icicle() {
+        init_ntt_domain_for_size(256).unwrap();
+        // xy_powers is (2 * n) x (2 * s_max) = 8 x 4.
+        let params = SetupParams {
+            l_free: 1,
+            l: 2,
+            l_user_out: 0,
+            l_user: 1,
+            l_D: 4,
+            m_D: 6,
+            n: 4,
+            s_D: 1,
+            s_max: 2,
+        };
+        let mut sigma = small_sigma();
+        sigma.sigma_1.xy_powers = CurveCfg::generate_random_affine_points(8 * 4)
+            .into_iter()
+            .map(G1serde)
+            .collect();
+        // icicle's encoding of the point at infinity must map to the ark identity.
+        sigma.sigma_1.xy_powers[5] = G1serde(G1Affine::zero());
+        let coeffs = ScalarCfg::generate_random(8 * 4);
+        let poly = DensePolynomialExt::from_coeffs(HostSlice::from_slice(&coeffs), 8, 4);
+        on_both(|| sigma.sigma_1.encode_poly(&mut poly.clone(), &params));
+    }
+}
+
+mod tests_witness {
+    use super::SMALL_SETUP_PARAMS;
+    use crate::bivariate_polynomial::BivariatePolynomial;
+    use crate::error::TokamakError;
+    use crate::iotools::witness::{check_witness, WitnessFailureKind};
+    use crate::iotools::{
+        read_R1CS_gen_uvwXY, read_R1CS_gen_uvwXY_sparse, scalar_to_hex, HexString,
+        PlacementVariables, SubcircuitInfo,
+    };
+    use icicle_bls12_381::curve::{ScalarCfg, ScalarField};
+    use icicle_core::traits::{FieldImpl, GenerateRandom};
+
+    type LinearCombination = Vec<(u32, u32)>;
+
+    // A circom binary R1CS with a 32-byte field: the header section, then the constraints section.
+    fn r1cs_binary(n_wires: u32, constraints: &[[LinearCombination; 3]]) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend(32u32.to_le_bytes());
+        header.extend([0u8; 32]);
+        header.extend(n_wires.to_le_bytes());
+        header.extend([0u8; 12]);
+        header.extend(0u64.to_le_bytes());
+        header.extend((constraints.len() as u32).to_le_bytes());
+
+        let mut body = Vec::new();
+        for constraint in constraints {
+            for lc in constraint {
+                body.extend((lc.len() as u32).to_le_bytes());
+                for &(wire, coeff) in lc {
+                    body.extend(wire.to_le_bytes());
+                    body.extend(ScalarField::from_u32(coeff).to_bytes_le());
+                }
+            }
+        }
+
+        let mut data = b"r1cs".to_vec();
+        data.extend(1u32.to_le_bytes());
+        data.extend(2u32.to_le_bytes());
+        for (section_type, section) in [(1u32, header), (2u32, body)] {
+            data.extend(section_type.to_le_bytes());
+            data.extend((section.len() as u64).to_le_bytes());
+            data.extend(section);
+        }
+        data
+    }
+
+    // Wires (1, x, y, z, t) with x * y = z and (z + 1) * 1 = t.
+    fn subcircuit() -> (Vec<Vec<u8>>, Vec<SubcircuitInfo>) {
+        let r1cs = r1cs_binary(
+            5,
+            &[
+                [vec![(1, 1)], vec![(2, 1)], vec![(3, 1)]],
+                [vec![(3, 1), (0, 1)], vec![(0, 1)], vec![(4, 1)]],
+            ],
+        );
+        let info = SubcircuitInfo {
+            id: 0,
+            name: "mul_inc".to_string(),
+            Nwires: 5,
+            Nconsts: 2,
+            Out_idx: Box::new([]),
+            In_idx: Box::new([]),
+            flattenMap: Box::new([0, 1, 2, 3, 4]),
+        };
+        (vec![r1cs], vec![info])
+    }
+
+    fn placement(values: &[u32]) -> PlacementVariables {
+        PlacementVariables {
+            subcircuitId: 0,
+            variables: values
+                .iter()
+                .map(|&value| HexString(scalar_to_hex(&ScalarField::from_u32(value))))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_check_witness_reports_the_failing_row() {
+        let (r1cs, infos) = subcircuit();
+        let placements = [
+            placement(&[1, 3, 5, 15, 16]),
+            placement(&[1, 3, 5, 14, 15]),
+            placement(&[1, 2, 2, 4, 4]),
+            placement(&[1, 2, 2]),
+        ];
+        let report = check_witness(&r1cs, &placements, &infos, &SMALL_SETUP_PARAMS).unwrap();
+        assert!(!report.is_satisfied());
+        assert_eq!(report.placements, 4);
+        assert_eq!(report.constraints, 6);
+
+        let failures: Vec<_> = report
+            .failures
+            .iter()
+            .map(|failure| (failure.placement, failure.kind.clone()))
+            .collect();
+        assert_eq!(
+            failures,
+            vec![
+                (
+                    1,
+                    WitnessFailureKind::Constraint {
+                        row: 0,
+                        a: ScalarField::from_u32(3),
+                        b: ScalarField::from_u32(5),
+                        c: ScalarField::from_u32(14),
+                    }
+                ),
+                (
+                    2,
+                    WitnessFailureKind::Constraint {
+                        row: 1,
+                        a: ScalarField::from_u32(5),
+                        b: ScalarField::one(),
+                        c: ScalarField::from_u32(4),
+                    }
+                ),
+                (
+                    3,
+                    WitnessFailureKind::VariableCount {
+                        expected: 5,
+                        found: 3
+                    }
+                ),
+            ]
+        );
+        assert!(report.failures[0]
+            .to_string()
+            .starts_with("placement 1 (subcircuit 0 mul_inc): constraint 0 is unsatisfied"));
+
+        let report = check_witness(&r1cs, &placements[..1], &infos, &SMALL_SETUP_PARAMS).unwrap();
+        assert!(report.is_satisfied());
+    }
+
+    #[test]
+    fn test_check_witness_rejects_unusable_inputs() {
+        let (r1cs, infos) = subcircuit();
+        let mut unknown = placement(&[1, 3, 5, 15, 16]);
+        unknown.subcircuitId = 1;
+        assert!(matches!(
+            check_witness(&r1cs, &[unknown], &infos, &SMALL_SETUP_PARAMS),
+            Err(TokamakError::Parse { .. })
+        ));
+
+        let too_many = vec![placement(&[1, 3, 5, 15, 16]); 5];
+        assert!(matches!(
+            check_witness(&r1cs, &too_many, &infos, &SMALL_SETUP_PARAMS),
+            Err(TokamakError::Shape(_))
+        ));
+
+        let mut bad_hex = placement(&[1, 3, 5, 15, 16]);
+        bad_hex.variables[2] = HexString("0xzz".to_string());
+        assert!(matches!(
+            check_witness(&r1cs, &[bad_hex], &infos, &SMALL_SETUP_PARAMS),
+            Err(TokamakError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sparse_uvwXY_matches_dense() {
+        let (r1cs, infos) = subcircuit();
+        let placements = [placement(&[1, 3, 5, 15, 16]), placement(&[1, 2, 7, 14, 15])];
+        let (u, v, w) =
+            read_R1CS_gen_uvwXY(&r1cs, &placements, &infos, &SMALL_SETUP_PARAMS).unwrap();
+        let (u_sparse, v_sparse, w_sparse) =
+            read_R1CS_gen_uvwXY_sparse(&r1cs, &placements, &infos, &SMALL_SETUP_PARAMS).unwrap();
+        let x = ScalarCfg::generate_random(1)[0];
+        let y = ScalarCfg::generate_random(1)[0];
+        for (dense, sparse) in [(u, u_sparse), (v, v_sparse), (w, w_sparse)] {
+            assert!(sparse.occupied().all(|j| j < placements.len()));
+            assert_eq!(sparse.eval(&x, &y), dense.eval(&x, &y));
+            assert_eq!(sparse.to_dense().eval(&x, &y), dense.eval(&x, &y));
+        }
+    }
+
+    #[test]
+    fn test_read_R1CS_gen_uvwXY_rejects_unusable_inputs() {
+        let (r1cs, infos) = subcircuit();
+        let gen = |r1cs: &Vec<Vec<u8>>, placements: &[PlacementVariables]| {
+            read_R1CS_gen_uvwXY(r1cs, placements, &infos, &SMALL_SETUP_PARAMS).map(|_| ())
+        };
+        assert!(gen(&r1cs, &[placement(&[1, 3, 5, 15, 16])]).is_ok());
+
+        let mut unknown = placement(&[1, 3, 5, 15, 16]);
+        unknown.subcircuitId = 1;
+        assert!(matches!(
+            gen(&r1cs, &[unknown]),
+            Err(TokamakError::Parse { .. })
+        ));
+        let too_many = vec![placement(&[1, 3, 5, 15, 16]); 5];
+        assert!(matches!(gen(&r1cs, &too_many), Err(TokamakError::Shape(_))));
+        assert!(matches!(
+            gen(&r1cs, &[placement(&[1, 3, 5])]),
+            Err(TokamakError::Parse { .. })
+        ));
+        assert!(matches!(
+            gen(&Vec::new(), &[placement(&[1, 3, 5, 15, 16])]),
+            Err(TokamakError::MissingInput(_))
+        ));
+        let corrupt = vec![r1cs[0][..r1cs[0].len() - 8].to_vec()];
+        assert!(matches!(
+            gen(&corrupt, &[placement(&[1, 3, 5, 15, 16])]),
+            Err(TokamakError::Parse { .. })
+        ));
+    }
+}
+
+mod tests_permutation {
+    use crate::error::TokamakError;
+    use crate::iotools::permutation::{
+        check_copy_constraints, validate_permutation, PermutationSummary,
+    };
+    use crate::iotools::{
+        scalar_to_hex, HexString, Permutation, PlacementVariables, SetupParams, SubcircuitInfo,
+    };
+    use icicle_bls12_381::curve::ScalarField;
+    use icicle_core::traits::FieldImpl;
+
+    fn links(pairs: &[((usize, usize), (usize, usize))]) -> Vec<Permutation> {
+        pairs
+            .iter()
+            .map(|&((row, col), (x, y))| Permutation {
+                row,
+                col,
+                X: x,
+                Y: y,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_permutation_checks_bounds_and_bijection() {
+        let perm = links(&[
+            ((0, 0), (1, 2)),
+            ((1, 2), (0, 0)),
+            ((2, 1), (3, 3)),
+            ((3, 3), (0, 1)),
+            ((0, 1), (2, 1)),
+            ((1, 1), (1, 1)),
+        ]);
+        assert_eq!(
+            validate_permutation(&perm, 4, 4).unwrap(),
+            PermutationSummary {
+                entries: 6,
+                cycles: 2,
+                longest_cycle: 3,
+            }
+        );
+        assert_eq!(
+            validate_permutation(&[], 4, 4).unwrap(),
+            PermutationSummary::default()
+        );
+
+        for invalid in [
+            links(&[((4, 0), (0, 0)), ((0, 0), (4, 0))]),
+            links(&[((0, 0), (0, 4)), ((0, 4), (0, 0))]),
+            links(&[((0, 0), (1, 0)), ((0, 0), (2, 0)), ((1, 0), (0, 0))]),
+            links(&[((0, 0), (1, 0)), ((1, 0), (1, 0))]),
+            links(&[((0, 0), (1, 0))]),
+        ] {
+            assert!(matches!(
+                validate_permutation(&invalid, 4, 4),
+                Err(TokamakError::Parse { .. })
+            ));
+        }
+    }
+
+    // Interface rows 0 and 1 are wires 2 and 3 of subcircuit 0, rows 2 and 3 are wires 1 and 2 of
+    // subcircuit 1.
+    fn circuit() -> (SetupParams, Vec<SubcircuitInfo>, Vec<Box<[usize]>>) {
+        let setup_params = SetupParams {
+            l_free: 1,
+            l: 2,
+            l_user_out: 0,
+            l_user: 1,
+            l_D: 6,
+            m_D: 6,
+            n: 4,
+            s_D: 2,
+            s_max: 4,
+        };
+        let info = |id: usize, name: &str, flatten_map: &[usize]| SubcircuitInfo {
+            id,
+            name: name.to_string(),
+            Nwires: flatten_map.len(),
+            Nconsts: 1,
+            Out_idx: Box::new([]),
+            In_idx: Box::new([]),
+            flattenMap: flatten_map.into(),
+        };
+        let infos = vec![
+            info(0, "producer", &[0, 1, 2, 3]),
+            info(1, "consumer", &[0, 4, 5]),
+        ];
+        let global_wire_list = [[0, 0], [0, 1], [0, 2], [0, 3], [1, 1], [1, 2]]
+            .iter()
+            .map(|pair| Box::from(&pair[..]))
+            .collect();
+        (setup_params, infos, global_wire_list)
+    }
+
+    fn placement(subcircuit_id: usize, values: &[u32]) -> PlacementVariables {
+        PlacementVariables {
+            subcircuitId: subcircuit_id,
+            variables: values
+                .iter()
+                .map(|&value| HexString(scalar_to_hex(&ScalarField::from_u32(value))))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_check_copy_constraints_reports_the_first_mismatch() {
+        let (setup_params, infos, global_wire_list) = circuit();
+        let perm = links(&[
+            ((0, 0), (2, 1)),
+            ((2, 1), (0, 0)),
+            ((1, 0), (3, 1)),
+            ((3, 1), (1, 0)),
+        ]);
+        let placements = [placement(0, &[1, 7, 9, 4]), placement(1, &[1, 9, 4])];
+        let (summary, mismatch) =
+            check_copy_constraints(&perm, &placements, &infos, &global_wire_list, &setup_params)
+                .unwrap();
+        assert_eq!(summary.cycles, 2);
+        assert_eq!(mismatch, None);
+
+        let placements = [placement(0, &[1, 7, 9, 4]), placement(1, &[1, 9, 5])];
+        let (_, mismatch) =
+            check_copy_constraints(&perm, &placements, &infos, &global_wire_list, &setup_params)
+                .unwrap();
+        let mismatch = mismatch.unwrap();
+        assert_eq!(mismatch.entry, 2);
+        assert_eq!(mismatch.source.wire, Some((0, "producer".to_string(), 3)));
+        assert_eq!(mismatch.source.value, ScalarField::from_u32(4));
+        assert_eq!(mismatch.target.wire, Some((1, "consumer".to_string(), 2)));
+        assert_eq!(mismatch.target.value, ScalarField::from_u32(5));
+        assert!(mismatch.to_string().starts_with(
+            "permutation entry 2 links (1, 0) placement 0 subcircuit 0 producer wire 3"
+        ));
+
+        // Row 0 of placement 1 belongs to subcircuit 0, so the cell is empty and holds zero.
+        let perm = links(&[((0, 0), (0, 1)), ((0, 1), (0, 0))]);
+        let (_, mismatch) =
+            check_copy_constraints(&perm, &placements, &infos, &global_wire_list, &setup_params)
+                .unwrap();
+        let mismatch = mismatch.unwrap();
+        assert_eq!(mismatch.target.wire, None);
+        assert_eq!(mismatch.target.value, ScalarField::zero());
+    }
+}
+
+mod tests_public_io {
+    use crate::iotools::public_io::{decode_public_io, PublicIoMismatch, PublicRegion};
+    use crate::iotools::{HexString, Instance, InstanceDescription, SetupParams};
+
+    // The setup parameters of the qap-compiler's subcircuit library, whose global wire list
+    // places the public output buffer at a_pub_user[..65], the public input buffer at
+    // a_pub_user[65..85] and the block buffer at a_pub_block[..24].
+    fn setup_params() -> SetupParams {
+        SetupParams {
+            l_free: 128,
+            l: 728,
+            l_user_out: 65,
+            l_user: 85,
+            l_D: 4824,
+            m_D: 26591,
+            n: 4096,
+            s_D: 14,
+            s_max: 256,
+        }
+    }
+
+    // instance.json and instance_description.json laid out as the synthesizer writes them for an
+    // ERC-20 transfer: a Transfer event and the resulting Merkle root as outputs, the signature,
+    // contract, selector and initial Merkle root as public inputs, then the block buffer.
+    fn fixture() -> (Instance, InstanceDescription) {
+        (
+            serde_json::from_str(include_str!("../tests/fixtures/public_io/instance.json"))
+                .unwrap(),
+            serde_json::from_str(include_str!(
+                "../tests/fixtures/public_io/instance_description.json"
+            ))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_decode_public_io_labels_synthesizer_output() {
+        let (instance, description) = fixture();
+        let public_io = decode_public_io(&instance, &description, &setup_params());
+        assert_eq!(public_io.mismatches, []);
+
+        let outputs: Vec<_> = public_io
+            .outputs
+            .iter()
+            .map(|value| (value.description.as_str(), value.index, value.limbs))
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                ("Log topic for LOG3 instruction, topic index: 0", 0, 2),
+                ("Log topic for LOG3 instruction, topic index: 1", 2, 2),
+                ("Log topic for LOG3 instruction, topic index: 2", 4, 2),
+                ("Log value for LOG3 instruction, data index: 0", 6, 2),
+                (
+                    "Resulting Merkle tree root hash of 0x2be5e8c109e2197d077d13a82daead6a9b3433c5",
+                    8,
+                    2
+                ),
+            ]
+        );
+        assert_eq!(
+            public_io.outputs[0].value,
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+        assert_eq!(
+            public_io.outputs[4].value,
+            "0x0dbd9d7381e74ef5e8e25d940ed904759531985d5d9dc9f81818e811892f902b"
+        );
+
+        assert_eq!(public_io.inputs.len(), 16);
+        let contract = &public_io.inputs[1];
+        assert_eq!(
+            (
+                contract.description.as_str(),
+                contract.region,
+                contract.index
+            ),
+            ("Contract address to call", PublicRegion::UserInput, 67)
+        );
+        assert_eq!(
+            contract.value,
+            format!("0x{:0>64}", "2be5e8c109e2197d077d13a82daead6a9b3433c5")
+        );
+        let chain_id = &public_io.inputs[9];
+        assert_eq!(
+            (
+                chain_id.description.as_str(),
+                chain_id.region,
+                chain_id.index
+            ),
+            ("CHAINID", PublicRegion::Block, 10)
+        );
+        assert_eq!(chain_id.value, format!("0x{:0>64}", "1"));
+    }
+
+    #[test]
+    fn test_decode_public_io_reports_mismatches() {
+        let (instance, description) = fixture();
+
+        // The Merkle root loses its upper half.
+        let mut unpaired = description.clone();
+        unpaired.a_pub_user_description[9] = String::new();
+        let public_io = decode_public_io(&instance, &unpaired, &setup_params());
+        assert_eq!(public_io.outputs.len(), 4);
+        assert_eq!(
+            public_io.mismatches,
+            [PublicIoMismatch::UnpairedHalf {
+                vector: "a_pub_user",
+                index: 8,
+                description:
+                    "Resulting Merkle tree root hash of 0x2be5e8c109e2197d077d13a82daead6a9b3433c5"
+                        .to_string(),
+            }]
+        );
+
+        // An upper half of 17 bytes.
+        let mut wide = instance.clone();
+        wide.a_pub_user[9] = HexString(format!("0x01{}", "00".repeat(16)));
+        let public_io = decode_public_io(&wide, &description, &setup_params());
+        assert_eq!(public_io.outputs.len(), 4);
+        assert!(matches!(
+            public_io.mismatches[..],
+            [PublicIoMismatch::WideHalf { index: 9, .. }]
+        ));
+
+        let mut short = description;
+        short.a_pub_block_description = short.a_pub_block_description[..24].into();
+        let public_io = decode_public_io(&instance, &short, &setup_params());
+        assert_eq!(public_io.inputs.len(), 16);
+        assert_eq!(
+            public_io.mismatches,
+            [PublicIoMismatch::Length {
+                vector: "a_pub_block",
+                elements: 43,
+                descriptions: 24,
+            }]
+        );
+    }
+}
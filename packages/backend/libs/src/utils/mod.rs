@@ -2,6 +2,7 @@ use icicle_runtime::{self, Device};
 use std::path::PathBuf;
 
 use crate::bivariate_polynomial::init_ntt_domain_for_size;
+use crate::error::{TokamakError, TokamakResult};
 use crate::iotools::SetupParams;
 
 #[derive(Clone, Copy, Debug)]
@@ -12,34 +13,42 @@ pub struct SetupShape {
     pub s_max: usize,
 }
 
-pub fn load_setup_params_from_qap_path(qap_path: &str) -> SetupParams {
+pub fn load_setup_params_from_qap_path(qap_path: &str) -> TokamakResult<SetupParams> {
     let setup_path = PathBuf::from(qap_path).join("setupParams.json");
-    SetupParams::read_from_json(setup_path).expect("Failed to read setupParams.json")
+    SetupParams::read_from_json(setup_path.clone())
+        .map_err(|err| TokamakError::from_read(&setup_path, err))
 }
 
-pub fn setup_shape(params: &SetupParams) -> SetupShape {
-    let m_i = params
-        .l_D
-        .checked_sub(params.l)
-        .expect("Invalid setup params: l_D must be >= l.");
-    SetupShape {
+pub fn setup_shape(params: &SetupParams) -> TokamakResult<SetupShape> {
+    let m_i = params.l_D.checked_sub(params.l).ok_or_else(|| {
+        TokamakError::Shape(format!(
+            "l_D must be >= l (l_D = {}, l = {}).",
+            params.l_D, params.l
+        ))
+    })?;
+    Ok(SetupShape {
         l_free: params.l_free,
         m_i,
         n: params.n,
         s_max: params.s_max,
-    }
+    })
 }
 
-pub fn validate_setup_shape(shape: &SetupShape) {
+pub fn validate_setup_shape(shape: &SetupShape) -> TokamakResult<()> {
     if !shape.n.is_power_of_two() {
-        panic!("n is not a power of two.");
+        return Err(TokamakError::Shape("n is not a power of two.".to_string()));
     }
     if !shape.s_max.is_power_of_two() {
-        panic!("s_max is not a power of two.");
+        return Err(TokamakError::Shape(
+            "s_max is not a power of two.".to_string(),
+        ));
     }
     if !shape.m_i.is_power_of_two() {
-        panic!("m_I is not a power of two.");
+        return Err(TokamakError::Shape(
+            "m_I is not a power of two.".to_string(),
+        ));
     }
+    Ok(())
 }
 
 pub fn validate_public_wire_size(l: usize) {
@@ -70,8 +79,8 @@ pub fn trusted_setup_testing_ntt_domain_size(shape: &SetupShape) -> usize {
         .expect("max(n, m_i) * s_max overflow")
 }
 
-pub fn init_ntt_domain(size: usize) {
-    init_ntt_domain_for_size(size).expect("Failed to initialize NTT domain");
+pub fn init_ntt_domain(size: usize) -> TokamakResult<()> {
+    init_ntt_domain_for_size(size).map_err(|err| TokamakError::NttDomain(format!("{err:?}")))
 }
 
 /// Returns true if CUDA or METAL GPU is available.
@@ -85,13 +94,19 @@ pub fn check_gpu() -> bool {
 }
 
 /// Sets the best available device and returns the selected device name ("CUDA", "METAL", or "CPU").
+/// With the `arkworks` feature the CPU is always selected.
 pub fn check_device() -> &'static str {
     let _ = icicle_runtime::load_backend_from_env_or_default();
     let device_cpu = Device::new("CPU", 0);
     let device_cuda = Device::new("CUDA", 0);
     let device_metal = Device::new("METAL", 0);
 
-    if icicle_runtime::is_device_available(&device_cuda) {
+    if cfg!(feature = "arkworks") {
+        // The arkworks backend reads icicle buffers in host memory.
+        println!("Built with the arkworks compute backend, using CPU only");
+        icicle_runtime::set_device(&device_cpu).expect("Failed to set CPU device");
+        "CPU"
+    } else if icicle_runtime::is_device_available(&device_cuda) {
         println!("CUDA is available");
         icicle_runtime::set_device(&device_cuda).expect("Failed to set CUDA device");
         "CUDA"
@@ -1,7 +1,8 @@
 use super::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt};
-use icicle_bls12_381::curve::{ScalarCfg, ScalarField};
+use crate::compute_backend;
+use icicle_bls12_381::curve::ScalarField;
 use icicle_core::traits::FieldImpl;
-use icicle_core::vec_ops::{VecOps, VecOpsConfig};
+use icicle_core::vec_ops::VecOpsConfig;
 use icicle_runtime::errors::eIcicleError;
 use icicle_runtime::memory::{DeviceSlice, DeviceVec, HostOrDeviceSlice, HostSlice};
 use std::env;
@@ -40,7 +41,7 @@ pub fn point_mul_two_vecs(lhs: &[ScalarField], rhs: &[ScalarField], res: &mut [S
     let lhs_buff = HostSlice::from_slice(lhs);
     let rhs_buff = HostSlice::from_slice(rhs);
     let res_buff = HostSlice::from_mut_slice(res);
-    ScalarCfg::mul(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
+    compute_backend::mul(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
 }
 
 pub fn point_div_two_vecs(numer: &[ScalarField], denom: &[ScalarField], res: &mut [ScalarField]) {
@@ -51,7 +52,7 @@ pub fn point_div_two_vecs(numer: &[ScalarField], denom: &[ScalarField], res: &mu
     let lhs_buff = HostSlice::from_slice(numer);
     let rhs_buff = HostSlice::from_slice(denom);
     let res_buff = HostSlice::from_mut_slice(res);
-    ScalarCfg::div(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
+    compute_backend::div(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
 }
 
 pub fn point_add_two_vecs(lhs: &[ScalarField], rhs: &[ScalarField], res: &mut [ScalarField]) {
@@ -62,7 +63,7 @@ pub fn point_add_two_vecs(lhs: &[ScalarField], rhs: &[ScalarField], res: &mut [S
     let lhs_buff = HostSlice::from_slice(lhs);
     let rhs_buff = HostSlice::from_slice(rhs);
     let res_buff = HostSlice::from_mut_slice(res);
-    ScalarCfg::add(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
+    compute_backend::add(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
 }
 
 pub fn scale_vec(scaler: ScalarField, vec: &[ScalarField], res: &mut [ScalarField]) {
@@ -76,7 +77,7 @@ pub fn scale_vec(scaler: ScalarField, vec: &[ScalarField], res: &mut [ScalarFiel
     // let scaler = vec![lhs; rhs.len()];
     // point_mul_two_vecs(&scaler, rhs, res);
     let res_buff = HostSlice::from_mut_slice(res);
-    ScalarCfg::scalar_mul(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
+    compute_backend::scalar_mul(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
 }
 
 pub fn scalar_vec_sub(lhs: ScalarField, rhs: &[ScalarField], res: &mut [ScalarField]) {
@@ -88,7 +89,7 @@ pub fn scalar_vec_sub(lhs: ScalarField, rhs: &[ScalarField], res: &mut [ScalarFi
     let lhs_buff = HostSlice::from_slice(&lhs_v);
     let rhs_buff = HostSlice::from_slice(rhs);
     let res_buff = HostSlice::from_mut_slice(res);
-    ScalarCfg::scalar_sub(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
+    compute_backend::scalar_sub(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
 }
 
 pub fn scalar_vec_add(scalar: ScalarField, vec: &[ScalarField], res: &mut [ScalarField]) {
@@ -100,7 +101,7 @@ pub fn scalar_vec_add(scalar: ScalarField, vec: &[ScalarField], res: &mut [Scala
     let lhs_buff = HostSlice::from_slice(&lhs_v);
     let rhs_buff = HostSlice::from_slice(vec);
     let res_buff = HostSlice::from_mut_slice(res);
-    ScalarCfg::scalar_add(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
+    compute_backend::scalar_add(lhs_buff, rhs_buff, res_buff, &vec_ops_cfg).unwrap();
 }
 
 pub fn inner_product_two_vecs(lhs_vec: &[ScalarField], rhs_vec: &[ScalarField]) -> ScalarField {
@@ -112,7 +113,7 @@ pub fn inner_product_two_vecs(lhs_vec: &[ScalarField], rhs_vec: &[ScalarField])
     let vec_ops_cfg = VecOpsConfig::default();
     let mut mul_res_vec = vec![ScalarField::zero(); len];
     let mul_res_buff = HostSlice::from_mut_slice(&mut mul_res_vec);
-    ScalarCfg::mul(
+    compute_backend::mul(
         HostSlice::from_slice(lhs_vec),
         HostSlice::from_slice(rhs_vec),
         mul_res_buff,
@@ -121,7 +122,7 @@ pub fn inner_product_two_vecs(lhs_vec: &[ScalarField], rhs_vec: &[ScalarField])
     .unwrap();
     let mut res_vec = vec![ScalarField::zero()];
     let res = HostSlice::from_mut_slice(&mut res_vec);
-    ScalarCfg::sum(mul_res_buff, res, &vec_ops_cfg).unwrap();
+    compute_backend::sum(mul_res_buff, res, &vec_ops_cfg).unwrap();
     res_vec[0]
 }
 
@@ -136,7 +137,7 @@ pub fn transpose_inplace(a_vec: &mut [ScalarField], row_size: usize, col_size: u
     let a = HostSlice::from_slice(a_vec);
     let mut res_vec = vec![ScalarField::zero(); row_size * col_size];
     let res = HostSlice::from_mut_slice(&mut res_vec);
-    ScalarCfg::transpose(a, row_size as u32, col_size as u32, res, &vec_ops_cfg).unwrap();
+    compute_backend::transpose(a, row_size as u32, col_size as u32, res, &vec_ops_cfg).unwrap();
     a_vec.clone_from_slice(&res_vec);
 }
 
@@ -165,7 +166,7 @@ fn transpose_device_inplace_checked(
 
     let mut res_vec = DeviceVec::device_malloc(row_size * col_size)?;
 
-    ScalarCfg::transpose(
+    compute_backend::transpose(
         a_vec,
         row_size as u32,
         col_size as u32,
@@ -271,7 +272,7 @@ fn matrix_matrix_mul_checked(
     let mut vec_ops_cfg = VecOpsConfig::default();
     vec_ops_cfg.is_a_on_device = true;
     vec_ops_cfg.is_result_on_device = true;
-    ScalarCfg::transpose(
+    compute_backend::transpose(
         &lhs_device,
         m as u32,
         n as u32,
@@ -311,7 +312,7 @@ fn matrix_matrix_mul_with_transposed_lhs(
     transpose_device_inplace_checked(&mut extended_lhs, l * n, m)?;
 
     let mut transposed_rhs = DeviceVec::device_malloc(n * l)?;
-    ScalarCfg::transpose(
+    compute_backend::transpose(
         &rhs_device,
         n as u32,
         l as u32,
@@ -323,7 +324,7 @@ fn matrix_matrix_mul_with_transposed_lhs(
     vec_ops_cfg.is_b_on_device = true;
 
     let mut mul_res_device = DeviceVec::device_malloc(m * n * l)?;
-    ScalarCfg::mul(
+    compute_backend::mul(
         &extended_lhs,
         &extended_rhs,
         &mut mul_res_device,
@@ -333,7 +334,7 @@ fn matrix_matrix_mul_with_transposed_lhs(
     vec_ops_cfg.batch_size = (m * l) as i32;
     vec_ops_cfg.columns_batch = false;
     vec_ops_cfg.is_result_on_device = false; // Result goes to host
-    ScalarCfg::sum(
+    compute_backend::sum(
         &mul_res_device,
         HostSlice::from_mut_slice(res_mat),
         &vec_ops_cfg,
@@ -429,7 +430,7 @@ fn matrix_matrix_mul_tiled_checked(
     let mut vec_ops_cfg = VecOpsConfig::default();
     vec_ops_cfg.is_a_on_device = true;
     vec_ops_cfg.is_result_on_device = true;
-    ScalarCfg::transpose(
+    compute_backend::transpose(
         &lhs_device,
         m as u32,
         n as u32,
@@ -609,7 +610,7 @@ pub fn outer_product_two_vecs_rayon(
             let scaler_vec = vec![scaler; max_len];
             let mut res_vec = vec![ScalarField::zero(); max_len];
 
-            ScalarCfg::mul(
+            compute_backend::mul(
                 HostSlice::from_slice(&scaler_vec),
                 HostSlice::from_slice(base_vec),
                 HostSlice::from_mut_slice(&mut res_vec),
@@ -623,7 +624,7 @@ pub fn outer_product_two_vecs_rayon(
         let res_untranposed_buf = HostSlice::from_slice(&res_untransposed);
         let res_buf = HostSlice::from_mut_slice(res);
 
-        ScalarCfg::transpose(
+        compute_backend::transpose(
             res_untranposed_buf,
             min_len as u32,
             max_len as u32,
@@ -1,5 +1,14 @@
 #![allow(non_snake_case)]
-use libs::group_structures::G1serde;
+use libs::error::{TokamakError, TokamakResult};
+use libs::group_structures::{G1serde, PointValidation, SigmaVerify};
+use libs::impl_circuit_inputs_for_paths;
+use libs::iotools::binary::{
+    read_binary, write_binary, ArtifactEncoding, BinaryReader, BinaryWriter, PointEncoding,
+};
+use libs::iotools::fiat_shamir::FiatShamirMode;
+use libs::iotools::inputs::{CircuitInputs, MemoryInputs};
+use libs::iotools::permutation::validate_permutation;
+use libs::iotools::solidity::{SolidityVerifierConstants, DEFAULT_LIBRARY_NAME};
 use libs::iotools::ArchivedSigmaPreprocessRkyv;
 use libs::iotools::*;
 use libs::utils::{
@@ -7,7 +16,9 @@ use libs::utils::{
 };
 use libs::{impl_read_from_json, impl_write_into_json, pop_recover, split_push};
 
+use memmap2::Mmap;
 use serde::{Deserialize, Serialize};
+use std::fs::File;
 use std::path::PathBuf;
 
 pub struct PreprocessInputPaths<'a> {
@@ -15,9 +26,55 @@ pub struct PreprocessInputPaths<'a> {
     pub synthesizer_path: &'a str,
     pub setup_path: &'a str,
     pub output_path: &'a str,
+    pub point_validation: PointValidation,
+}
+
+impl_circuit_inputs_for_paths!(PreprocessInputPaths<'_>);
+
+/// Inputs of [`Preprocess::gen`]: the circuit and synthesizer inputs (`setup_params`,
+/// `permutation` and `instance` are read) plus the preprocessing part of the reference string.
+pub trait PreprocessInputs: CircuitInputs {
+    /// Calls `f` with the `sigma_preprocess` archive after checking its points according to
+    /// `point_validation`.
+    fn with_sigma_preprocess<R>(
+        &self,
+        f: impl FnOnce(&ArchivedSigmaPreprocessRkyv) -> R,
+    ) -> TokamakResult<R>;
+}
+
+/// Maps `sigma_preprocess.rkyv` from `setup_path`.
+impl PreprocessInputs for PreprocessInputPaths<'_> {
+    fn with_sigma_preprocess<R>(
+        &self,
+        f: impl FnOnce(&ArchivedSigmaPreprocessRkyv) -> R,
+    ) -> TokamakResult<R> {
+        let sigma_path = PathBuf::from(self.setup_path).join("sigma_preprocess.rkyv");
+        let file = File::open(&sigma_path).map_err(|err| TokamakError::Io {
+            path: sigma_path.clone(),
+            source: err,
+        })?;
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|err| TokamakError::Io {
+            path: sigma_path.clone(),
+            source: err,
+        })?;
+        let sigma = rkyv::check_archived_root::<SigmaPreprocessRkyv>(&mmap)
+            .map_err(|err| TokamakError::archive(&sigma_path, format!("{err:?}")))?;
+        sigma.validate_points(self.point_validation)?;
+        Ok(f(sigma))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl PreprocessInputs for MemoryInputs<'_, &ArchivedSigmaPreprocessRkyv> {
+    fn with_sigma_preprocess<R>(
+        &self,
+        f: impl FnOnce(&ArchivedSigmaPreprocessRkyv) -> R,
+    ) -> TokamakResult<R> {
+        self.crs.validate_points(self.point_validation)?;
+        Ok(f(self.crs))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Preprocess {
     pub s0: G1serde,
     pub s1: G1serde,
@@ -28,18 +85,28 @@ pub struct Preprocess {
 }
 
 impl Preprocess {
-    pub fn gen(
+    pub fn gen<I: PreprocessInputs + ?Sized>(inputs: &I) -> TokamakResult<Self> {
+        let setup_params = inputs.setup_params()?;
+        let permutation_raw = inputs.permutation()?;
+        let instance = inputs.instance()?;
+        inputs.with_sigma_preprocess(|sigma| {
+            Self::gen_with_sigma(sigma, &permutation_raw, &instance, &setup_params)
+        })?
+    }
+
+    pub fn gen_with_sigma(
         sigma: &ArchivedSigmaPreprocessRkyv,
         permutation_raw: &[Permutation],
         instance: &Instance,
         setup_params: &SetupParams,
-    ) -> Self {
-        let shape = setup_shape(setup_params);
-        validate_setup_shape(&shape);
+    ) -> TokamakResult<Self> {
+        let shape = setup_shape(setup_params)?;
+        validate_setup_shape(&shape)?;
         let m_i = shape.m_i;
         let s_max = shape.s_max;
+        validate_permutation(permutation_raw, m_i, s_max)?;
         let ntt_domain_size = prover_verifier_ntt_domain_size(&shape);
-        init_ntt_domain(ntt_domain_size);
+        init_ntt_domain(ntt_domain_size)?;
         // Generating permutation polynomials
         println!("Converting the permutation matrices into polynomials s^0 and s^1...");
         let (mut s0XY, mut s1XY) = Permutation::to_poly(permutation_raw, m_i, s_max);
@@ -72,13 +139,13 @@ impl Preprocess {
         // };
         // let lagrange_KL = sigma.sigma_1.encode_poly(&mut lagrange_KL_XY, &setup_params);
         // return Preprocess {s0, s1, lagrange_KL}
-        return Preprocess {
+        return Ok(Preprocess {
             s0,
             s1,
             O_pub_fix,
             // O_function_inst,
             // O_block_inst,
-        };
+        });
     }
 
     pub fn convert_format_for_solidity_verifier(&self) -> FormattedPreprocess {
@@ -105,6 +172,72 @@ impl Preprocess {
     }
 }
 
+impl Preprocess {
+    pub const BINARY_MAGIC: [u8; 4] = *b"TKPP";
+
+    /// Encodes s0, s1 and O_pub_fix in the versioned binary format.
+    pub fn to_bytes(&self, encoding: PointEncoding) -> Vec<u8> {
+        let mut writer = BinaryWriter::new(&Self::BINARY_MAGIC, encoding);
+        writer.g1(&self.s0).g1(&self.s1).g1(&self.O_pub_fix);
+        writer.finish()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> TokamakResult<Self> {
+        let mut reader = BinaryReader::new(bytes, &Self::BINARY_MAGIC, "binary preprocess")?;
+        let s0 = reader.g1()?;
+        let s1 = reader.g1()?;
+        let O_pub_fix = reader.g1()?;
+        reader.finish()?;
+        Ok(Self { s0, s1, O_pub_fix })
+    }
+
+    /// Writes `preprocess.json` or `preprocess.bin` into `dir` and returns the written path.
+    pub fn write_into_dir(
+        &self,
+        dir: &str,
+        encoding: ArtifactEncoding,
+    ) -> std::io::Result<PathBuf> {
+        let path = PathBuf::from(dir).join(encoding.file_name("preprocess"));
+        match encoding.point_encoding() {
+            None => self
+                .convert_format_for_solidity_verifier()
+                .write_into_json(path.clone())?,
+            Some(points) => write_binary(&path, &self.to_bytes(points))?,
+        }
+        Ok(path)
+    }
+
+    /// Reads `preprocess.json` or `preprocess.bin` from `dir`.
+    pub fn read_from_dir(dir: &str, encoding: ArtifactEncoding) -> TokamakResult<Self> {
+        let path = PathBuf::from(dir).join(encoding.file_name("preprocess"));
+        match encoding.point_encoding() {
+            None => FormattedPreprocess::read_from_json(path.clone())
+                .map_err(|err| TokamakError::from_read(&path, err))?
+                .recover_proof_from_format(),
+            Some(_) => Self::from_bytes(&read_binary(&path)?),
+        }
+    }
+
+    /// The constants library of a Solidity verifier for this preprocess and the CRS `sigma`, for
+    /// proofs made in `fiat_shamir`.
+    pub fn solidity_constants<'a>(
+        &'a self,
+        sigma: &'a SigmaVerify,
+        setup_params: &'a SetupParams,
+        fiat_shamir: FiatShamirMode,
+    ) -> SolidityVerifierConstants<'a> {
+        SolidityVerifierConstants {
+            library_name: DEFAULT_LIBRARY_NAME,
+            sigma,
+            s0: &self.s0,
+            s1: &self.s1,
+            O_pub_fix: &self.O_pub_fix,
+            setup_params,
+            fiat_shamir,
+        }
+    }
+}
+
 impl_read_from_json!(Preprocess);
 impl_write_into_json!(Preprocess);
 
@@ -118,13 +251,22 @@ impl_read_from_json!(FormattedPreprocess);
 impl_write_into_json!(FormattedPreprocess);
 
 impl FormattedPreprocess {
-    pub fn recover_proof_from_format(&self) -> Preprocess {
+    pub fn recover_proof_from_format(&self) -> TokamakResult<Preprocess> {
         let p1 = &self.preprocess_entries_part1;
         let p2 = &self.preprocess_entries_part2;
 
         const G1_CNT: usize = 3; // The number of G1 points
-        assert_eq!(p1.len(), G1_CNT * 2);
-        assert_eq!(p2.len(), G1_CNT * 2);
+        if p1.len() != G1_CNT * 2 || p2.len() != G1_CNT * 2 {
+            return Err(TokamakError::parse(
+                "formatted preprocess",
+                format!(
+                    "expected {} entries per part, got {} and {}",
+                    G1_CNT * 2,
+                    p1.len(),
+                    p2.len()
+                ),
+            ));
+        }
 
         let mut idx = 0;
 
@@ -135,12 +277,12 @@ impl FormattedPreprocess {
             // O_block_inst,
         );
 
-        return Preprocess {
+        return Ok(Preprocess {
             s0,
             s1,
             O_pub_fix,
             // O_function_inst,
             // O_block_inst,
-        };
+        });
     }
 }
@@ -1,13 +1,13 @@
 use clap::Parser;
-use std::fs::File;
-use std::path::PathBuf;
 
-use libs::iotools::SigmaPreprocessRkyv;
-use libs::iotools::{Instance, Permutation};
+use libs::error::TokamakError;
+use libs::group_structures::{with_point_validation, PointValidation, SigmaVerify};
+use libs::iotools::binary::ArtifactEncoding;
+use libs::iotools::fiat_shamir::FiatShamirMode;
 use libs::subcircuit_library::{resolve_subcircuit_library_path, SubcircuitLibraryArg};
 use libs::utils::{check_device, load_setup_params_from_qap_path};
-use memmap2::Mmap;
 use preprocess::{Preprocess, PreprocessInputPaths};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -26,6 +26,23 @@ struct Config {
     /// Output directory for preprocess.json
     #[arg(long, value_name = "PATH")]
     output: String,
+
+    /// Encoding of the preprocess output
+    #[arg(long, value_enum, default_value_t = ArtifactEncoding::Json)]
+    encoding: ArtifactEncoding,
+
+    /// Skip on-curve and subgroup checks of the CRS points (only for locally generated CRS)
+    #[arg(long, default_value_t = false)]
+    trusted_artifacts: bool,
+
+    /// Also render the Solidity verifier constants for this preprocess and the CRS's
+    /// sigma_verify.json into this file
+    #[arg(long, value_name = "PATH")]
+    solidity: Option<PathBuf>,
+
+    /// Fiat-Shamir mode of the proofs the Solidity verifier constants are rendered for
+    #[arg(long, value_enum, default_value_t = FiatShamirMode::Legacy, requires = "solidity")]
+    fiat_shamir: FiatShamirMode,
 }
 
 fn main() {
@@ -39,25 +56,44 @@ fn main() {
         synthesizer_path: &config.synthesizer_stat,
         setup_path: &config.crs,
         output_path: &config.output,
+        point_validation: if config.trusted_artifacts {
+            PointValidation::Trusted
+        } else {
+            PointValidation::Checked
+        },
     };
 
     check_device();
 
-    let setup_params = load_setup_params_from_qap_path(paths.qap_path);
-    let sigma_path = PathBuf::from(paths.setup_path).join("sigma_preprocess.rkyv");
-    let file = File::open(&sigma_path).expect(
-        "No reference string is found. Run the Setup first (expected sigma_preprocess.rkyv).",
-    );
-    let mmap = unsafe { Mmap::map(&file).expect("Failed to map sigma_preprocess.rkyv") };
-    let sigma = rkyv::check_archived_root::<SigmaPreprocessRkyv>(&mmap)
-        .expect("Invalid sigma_preprocess.rkyv archive");
-
-    let permutation_path = PathBuf::from(paths.synthesizer_path).join("permutation.json");
-    let permutation_raw = Permutation::read_box_from_json(permutation_path).unwrap();
-    let instance_path = PathBuf::from(paths.synthesizer_path).join("instance.json");
-    let instance = Instance::read_from_json(instance_path).unwrap();
-    let preprocess = Preprocess::gen(&sigma, &permutation_raw, &instance, &setup_params);
-    let formatted_preprocess = preprocess.convert_format_for_solidity_verifier();
-    let output_path = PathBuf::from(paths.output_path).join("preprocess.json");
-    formatted_preprocess.write_into_json(output_path).unwrap();
+    let preprocess = Preprocess::gen(&paths).unwrap_or_else(|err| {
+        eprintln!("Preprocess failed: {err}");
+        std::process::exit(1);
+    });
+    preprocess
+        .write_into_dir(paths.output_path, config.encoding)
+        .unwrap();
+
+    if let Some(solidity_path) = &config.solidity {
+        let sigma_path = PathBuf::from(paths.setup_path).join("sigma_verify.json");
+        let sigma = with_point_validation(paths.point_validation, || {
+            SigmaVerify::read_from_json(sigma_path.clone())
+                .map_err(|err| TokamakError::from_read(&sigma_path, err))
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("Loading the verifier CRS failed: {err}");
+            std::process::exit(1);
+        });
+        let setup_params = load_setup_params_from_qap_path(paths.qap_path).unwrap_or_else(|err| {
+            eprintln!("Loading the setup parameters failed: {err}");
+            std::process::exit(1);
+        });
+        preprocess
+            .solidity_constants(&sigma, &setup_params, config.fiat_shamir)
+            .write(solidity_path)
+            .unwrap();
+        println!(
+            "Solidity verifier constants saved at {}",
+            solidity_path.display()
+        );
+    }
 }
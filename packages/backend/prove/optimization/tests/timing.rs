@@ -10,9 +10,10 @@ use std::path::PathBuf;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "timing")]
+use libs::group_structures::PointValidation;
 use libs::utils::check_device;
 #[cfg(feature = "timing")]
-use prove::{ProveInputPaths, Prover, TranscriptManager};
+use prove::{ProveInputPaths, Prover, QuotientEngine, TranscriptManager};
 
 #[cfg(feature = "timing")]
 use prove::timing;
@@ -131,6 +132,8 @@ fn timing_prove_stages() {
         synthesizer_path: &synthesizer_path,
         setup_path: &setup_path,
         output_path: &output_path,
+        point_validation: PointValidation::Trusted,
+        quotient_engine: QuotientEngine::default(),
     };
 
     check_device();
@@ -138,7 +141,7 @@ fn timing_prove_stages() {
     libs::timing::reset();
     let wall_start = Instant::now();
 
-    let (mut prover, _binding) = Prover::init(&paths);
+    let (mut prover, _binding) = Prover::init(&paths).expect("Prover initialization failed");
     let setup_params = SetupParamsSummary {
         l_free: prover.setup_params.l_free,
         l: prover.setup_params.l,
@@ -272,10 +275,12 @@ fn benchmark_fused_expression_candidates() {
         synthesizer_path: &synthesizer_path,
         setup_path: &setup_path,
         output_path: &output_path,
+        point_validation: PointValidation::Trusted,
+        quotient_engine: QuotientEngine::default(),
     };
 
     check_device();
-    let (mut prover, _binding) = Prover::init(&paths);
+    let (mut prover, _binding) = Prover::init(&paths).expect("Prover initialization failed");
     let setup_params = SetupParamsSummary {
         l_free: prover.setup_params.l_free,
         l: prover.setup_params.l,
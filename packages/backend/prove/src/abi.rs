@@ -0,0 +1,192 @@
+use crate::FormattedProof;
+use libs::error::{TokamakError, TokamakResult};
+use libs::iotools::hex_encode;
+use tiny_keccak::Keccak;
+
+/// Solidity signature of the verifier's entry point. The arguments are the proof part1 and part2
+/// entries, the preprocess part1 and part2 entries and the public inputs, in that order.
+pub const VERIFY_SIGNATURE: &str = "verify(uint128[],uint256[],uint128[],uint256[],uint256[])";
+
+const WORD: usize = 32;
+const UINT128: usize = 16;
+
+/// The arguments of the verifier's `verify` call as the hex strings of [`FormattedProof`] and the
+/// formatted preprocess.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyCalldata {
+    pub proof_entries_part1: Vec<String>,
+    pub proof_entries_part2: Vec<String>,
+    pub preprocess_entries_part1: Vec<String>,
+    pub preprocess_entries_part2: Vec<String>,
+    pub public_inputs: Vec<String>,
+}
+
+impl VerifyCalldata {
+    /// The first four bytes of the Keccak-256 hash of [`VERIFY_SIGNATURE`].
+    pub fn selector() -> [u8; 4] {
+        let mut hasher = Keccak::new_keccak256();
+        hasher.update(VERIFY_SIGNATURE.as_bytes());
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    fn arrays(&self) -> [(&'static str, &[String], usize); 5] {
+        [
+            ("proof part1", &self.proof_entries_part1, UINT128),
+            ("proof part2", &self.proof_entries_part2, WORD),
+            ("preprocess part1", &self.preprocess_entries_part1, UINT128),
+            ("preprocess part2", &self.preprocess_entries_part2, WORD),
+            ("public input", &self.public_inputs, WORD),
+        ]
+    }
+
+    /// ABI-encodes the call: the selector, one offset word per array, then each array as its
+    /// length followed by one left-padded word per entry.
+    pub fn encode(&self) -> TokamakResult<Vec<u8>> {
+        let arrays = self
+            .arrays()
+            .into_iter()
+            .map(|(what, entries, width)| {
+                entries
+                    .iter()
+                    .map(|entry| parse_word(entry, width, what))
+                    .collect::<TokamakResult<Vec<_>>>()
+            })
+            .collect::<TokamakResult<Vec<_>>>()?;
+
+        let body_len: usize = arrays.iter().map(|words| WORD * (1 + words.len())).sum();
+        let mut calldata = Vec::with_capacity(4 + WORD * arrays.len() + body_len);
+        calldata.extend_from_slice(&Self::selector());
+        let mut offset = WORD * arrays.len();
+        for words in &arrays {
+            calldata.extend_from_slice(&usize_word(offset));
+            offset += WORD * (1 + words.len());
+        }
+        for words in &arrays {
+            calldata.extend_from_slice(&usize_word(words.len()));
+            for word in words {
+                calldata.extend_from_slice(word);
+            }
+        }
+        Ok(calldata)
+    }
+
+    /// Decodes calldata produced by [`Self::encode`]. uint128 entries come back as 16-byte and
+    /// uint256 entries as 32-byte `0x` hex strings, the widths `split_g1` and `scalar_to_hex`
+    /// produce.
+    pub fn decode(calldata: &[u8]) -> TokamakResult<Self> {
+        if calldata.len() < 4 {
+            return Err(calldata_error("shorter than the selector".to_string()));
+        }
+        let (selector, args) = calldata.split_at(4);
+        if selector != Self::selector() {
+            return Err(calldata_error(format!(
+                "selector 0x{} is not {VERIFY_SIGNATURE}",
+                hex_encode(selector)
+            )));
+        }
+
+        let mut decoded = [UINT128, WORD, UINT128, WORD, WORD]
+            .into_iter()
+            .enumerate()
+            .map(|(index, width)| {
+                let offset = read_usize(args, WORD * index)?;
+                let len = read_usize(args, offset)?;
+                (0..len)
+                    .map(|i| {
+                        let word = read_word(args, offset + WORD * (1 + i))?;
+                        if word[..WORD - width].iter().any(|byte| *byte != 0) {
+                            return Err(calldata_error(format!(
+                                "entry {i} of argument {index} overflows {} bits",
+                                width * 8
+                            )));
+                        }
+                        Ok(format!("0x{}", hex_encode(&word[WORD - width..])))
+                    })
+                    .collect::<TokamakResult<Vec<_>>>()
+            })
+            .collect::<TokamakResult<Vec<_>>>()?
+            .into_iter();
+
+        let mut next = || decoded.next().unwrap_or_default();
+        Ok(Self {
+            proof_entries_part1: next(),
+            proof_entries_part2: next(),
+            preprocess_entries_part1: next(),
+            preprocess_entries_part2: next(),
+            public_inputs: next(),
+        })
+    }
+}
+
+impl FormattedProof {
+    /// ABI-encodes a `verify` call for this proof, the formatted preprocess commitments and the
+    /// public inputs (as 32-byte hex).
+    pub fn to_abi_calldata(
+        &self,
+        preprocess_entries_part1: &[String],
+        preprocess_entries_part2: &[String],
+        public_inputs: &[String],
+    ) -> TokamakResult<Vec<u8>> {
+        VerifyCalldata {
+            proof_entries_part1: self.proof_entries_part1.clone(),
+            proof_entries_part2: self.proof_entries_part2.clone(),
+            preprocess_entries_part1: preprocess_entries_part1.to_vec(),
+            preprocess_entries_part2: preprocess_entries_part2.to_vec(),
+            public_inputs: public_inputs.to_vec(),
+        }
+        .encode()
+    }
+}
+
+fn calldata_error(reason: String) -> TokamakError {
+    TokamakError::parse("ABI calldata", reason)
+}
+
+// Parses a hex entry into a left-padded word, rejecting values wider than `width` bytes.
+fn parse_word(value: &str, width: usize, what: &str) -> TokamakResult<[u8; WORD]> {
+    let hex = value.trim_start_matches("0x");
+    let padded = if hex.len() % 2 == 1 {
+        format!("0{hex}")
+    } else {
+        hex.to_string()
+    };
+    let bytes = hex::decode(&padded)
+        .map_err(|err| TokamakError::parse(format!("{what} entry {value}"), err.to_string()))?;
+    let significant = &bytes[bytes.iter().take_while(|byte| **byte == 0).count()..];
+    if significant.len() > width {
+        return Err(TokamakError::parse(
+            format!("{what} entry {value}"),
+            format!("does not fit in {} bits", width * 8),
+        ));
+    }
+    let mut word = [0u8; WORD];
+    word[WORD - significant.len()..].copy_from_slice(significant);
+    Ok(word)
+}
+
+fn usize_word(value: usize) -> [u8; WORD] {
+    let mut word = [0u8; WORD];
+    word[WORD - 8..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+fn read_word(args: &[u8], at: usize) -> TokamakResult<&[u8]> {
+    at.checked_add(WORD)
+        .and_then(|end| args.get(at..end))
+        .ok_or_else(|| calldata_error(format!("no word at offset {at}")))
+}
+
+fn read_usize(args: &[u8], at: usize) -> TokamakResult<usize> {
+    let word = read_word(args, at)?;
+    if word[..WORD - 8].iter().any(|byte| *byte != 0) {
+        return Err(calldata_error(format!(
+            "word at offset {at} is not a length"
+        )));
+    }
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&word[WORD - 8..]);
+    usize::try_from(u64::from_be_bytes(value))
+        .map_err(|_| calldata_error(format!("word at offset {at} is not a length")))
+}
@@ -0,0 +1,100 @@
+use clap::Parser;
+use libs::error::{TokamakError, TokamakResult};
+use libs::iotools::inputs::{load_permutation, load_placement_variables, load_subcircuit_infos};
+use libs::iotools::permutation::check_copy_constraints;
+use libs::iotools::read_global_wire_list_as_boxed_boxed_numbers;
+use libs::iotools::witness::check_witness;
+use libs::subcircuit_library::{resolve_subcircuit_library_path, SubcircuitLibraryArg};
+use libs::utils::load_setup_params_from_qap_path;
+use std::path::Path;
+
+/// Checks every placement in `placementVariables.json` against the R1CS of its subcircuit and
+/// the copy constraints of `permutation.json`, and reports the constraints that do not hold.
+/// Needs no CRS.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Config {
+    #[command(flatten)]
+    subcircuit_library: SubcircuitLibraryArg,
+
+    /// Synthesizer output directory containing placementVariables.json and permutation.json
+    #[arg(long, value_name = "PATH")]
+    synthesizer_stat: String,
+
+    /// Print at most this many failures (all are counted)
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    max_failures: usize,
+}
+
+fn exit_on_error<T>(result: TokamakResult<T>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("Witness check failed: {err}");
+        std::process::exit(2);
+    })
+}
+
+fn main() {
+    let config = Config::parse();
+    let qap_path = resolve_subcircuit_library_path(config.subcircuit_library.as_deref());
+    let qap_path = qap_path.to_string_lossy();
+
+    let setup_params = exit_on_error(load_setup_params_from_qap_path(&qap_path));
+    let subcircuit_infos = exit_on_error(load_subcircuit_infos(&qap_path));
+    let placement_variables = exit_on_error(load_placement_variables(&config.synthesizer_stat));
+    let mut satisfied = true;
+
+    let report = exit_on_error(check_witness(
+        Path::new(qap_path.as_ref()),
+        &placement_variables,
+        &subcircuit_infos,
+        &setup_params,
+    ));
+    if report.is_satisfied() {
+        println!(
+            "All {} constraints of {} placements are satisfied",
+            report.constraints, report.placements
+        );
+    } else {
+        satisfied = false;
+        println!(
+            "{} failures in {} placements ({} constraints checked)",
+            report.failures.len(),
+            report.placements,
+            report.constraints
+        );
+        for failure in report.failures.iter().take(config.max_failures) {
+            println!("  {failure}");
+        }
+        if report.failures.len() > config.max_failures {
+            println!("  ... {} more", report.failures.len() - config.max_failures);
+        }
+    }
+
+    let global_wire_list_path = Path::new(qap_path.as_ref()).join("globalWireList.json");
+    let global_wire_list = exit_on_error(
+        read_global_wire_list_as_boxed_boxed_numbers(global_wire_list_path.clone())
+            .map_err(|err| TokamakError::from_read(&global_wire_list_path, err)),
+    );
+    let permutation = exit_on_error(load_permutation(&config.synthesizer_stat));
+    let (summary, mismatch) = exit_on_error(check_copy_constraints(
+        &permutation,
+        &placement_variables,
+        &subcircuit_infos,
+        &global_wire_list,
+        &setup_params,
+    ));
+    match mismatch {
+        None => println!(
+            "All {} copy constraints in {} cycles are satisfied (longest cycle: {})",
+            summary.entries, summary.cycles, summary.longest_cycle
+        ),
+        Some(mismatch) => {
+            satisfied = false;
+            println!("Copy constraint unsatisfied: {mismatch}");
+        }
+    }
+
+    if !satisfied {
+        std::process::exit(1);
+    }
+}
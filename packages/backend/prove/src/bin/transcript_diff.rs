@@ -0,0 +1,70 @@
+use clap::Parser;
+use prove::{TraceStep, TranscriptTrace};
+use std::path::PathBuf;
+
+/// Compares two transcript traces written with `--transcript-trace` and reports the first step
+/// at which they diverge.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Config {
+    /// Reference trace, e.g. from the prover
+    #[arg(value_name = "PATH")]
+    left: PathBuf,
+
+    /// Trace to compare against it, e.g. from the verifier or another implementation
+    #[arg(value_name = "PATH")]
+    right: PathBuf,
+
+    /// Compare only the bytes and states, not the step labels
+    #[arg(long, default_value_t = false)]
+    ignore_labels: bool,
+}
+
+fn read_trace(path: &PathBuf) -> TranscriptTrace {
+    TranscriptTrace::read_from_json(path.clone()).unwrap_or_else(|err| {
+        eprintln!("Failed to read the trace {}: {err}", path.display());
+        std::process::exit(2);
+    })
+}
+
+fn print_step(side: &str, step: &Option<TraceStep>) {
+    match step {
+        Some(step) => {
+            println!("  {side}: {:?} {}", step.op, step.label);
+            println!("    bytes:        {}", step.bytes);
+            println!("    state_before: {}", step.state_before);
+            println!("    state_after:  {}", step.state_after);
+        }
+        None => println!("  {side}: <end of trace>"),
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let left = read_trace(&config.left);
+    let right = read_trace(&config.right);
+
+    if left.transcript != right.transcript {
+        println!(
+            "The traces use different transcripts: {:?} and {:?}",
+            left.transcript, right.transcript
+        );
+    }
+    match left.first_divergence(&right, config.ignore_labels) {
+        None => println!("The traces agree on all {} steps", left.steps.len()),
+        Some(divergence) => {
+            if divergence.fields.is_empty() {
+                println!("The traces diverge at step {}", divergence.step);
+            } else {
+                println!(
+                    "The traces diverge at step {} ({})",
+                    divergence.step,
+                    divergence.fields.join(", ")
+                );
+            }
+            print_step("left", &divergence.left);
+            print_step("right", &divergence.right);
+            std::process::exit(1);
+        }
+    }
+}
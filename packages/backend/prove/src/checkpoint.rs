@@ -0,0 +1,466 @@
+use std::path::Path;
+
+use icicle_bls12_381::curve::ScalarField;
+use icicle_core::traits::FieldImpl;
+use icicle_runtime::memory::HostSlice;
+use libs::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt};
+use libs::error::{TokamakError, TokamakResult};
+use libs::field_structures::FieldSerde;
+use libs::iotools::binary::{read_binary, write_binary, BinaryReader, BinaryWriter, PointEncoding};
+use libs::iotools::{Instance, Permutation, PlacementVariables, SetupParams, SubcircuitInfo};
+use libs::utils::{
+    init_ntt_domain, prover_verifier_ntt_domain_size, setup_shape, validate_setup_shape,
+};
+use tiny_keccak::Keccak;
+
+use crate::{
+    Binding, FiatShamirMode, InstancePolynomials, Mixer, Proof0, Proof1, Proof2, Proof3, Prover,
+    ProverCache, ProverInputs, Quotients, SigmaHolder, TranscriptKind, Witness,
+};
+
+/// The messages of the rounds a prover has completed so far.
+pub struct PartialProof {
+    pub binding: Binding,
+    pub proof0: Option<Proof0>,
+    pub proof1: Option<Proof1>,
+    pub proof2: Option<Proof2>,
+    pub proof3: Option<Proof3>,
+}
+
+impl PartialProof {
+    pub fn new(binding: Binding) -> Self {
+        Self {
+            binding,
+            proof0: None,
+            proof1: None,
+            proof2: None,
+            proof3: None,
+        }
+    }
+
+    /// Number of rounds completed, from 0 right after `Prover::init` to 4 before `prove4`.
+    pub fn rounds_done(&self) -> usize {
+        [
+            self.proof0.is_some(),
+            self.proof1.is_some(),
+            self.proof2.is_some(),
+            self.proof3.is_some(),
+        ]
+        .iter()
+        .take_while(|done| **done)
+        .count()
+    }
+}
+
+struct DigestWriter(Keccak);
+
+impl DigestWriter {
+    fn usize(&mut self, value: usize) {
+        self.0.update(&(value as u64).to_be_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.usize(value.len());
+        self.0.update(value.as_bytes());
+    }
+
+    fn usizes(&mut self, values: &[usize]) {
+        self.usize(values.len());
+        for value in values {
+            self.usize(*value);
+        }
+    }
+}
+
+/// Keccak-256 over the setup parameters, subcircuit infos, placement variables, permutation and
+/// instance, followed by the reference string fingerprint.
+pub(crate) fn input_digest(
+    setup_params: &SetupParams,
+    subcircuit_infos: &[SubcircuitInfo],
+    placement_variables: &[PlacementVariables],
+    permutation_raw: &[Permutation],
+    instance: &Instance,
+    sigma: &SigmaHolder,
+) -> [u8; 32] {
+    let mut digest = DigestWriter(Keccak::new_keccak256());
+    digest.usizes(&[
+        setup_params.l_free,
+        setup_params.l,
+        setup_params.l_user_out,
+        setup_params.l_user,
+        setup_params.l_D,
+        setup_params.m_D,
+        setup_params.n,
+        setup_params.s_D,
+        setup_params.s_max,
+    ]);
+    digest.usize(subcircuit_infos.len());
+    for info in subcircuit_infos {
+        digest.usizes(&[info.id, info.Nwires, info.Nconsts]);
+        digest.str(&info.name);
+        digest.usizes(&info.Out_idx);
+        digest.usizes(&info.In_idx);
+        digest.usizes(&info.flattenMap);
+    }
+    digest.usize(placement_variables.len());
+    for placement in placement_variables {
+        digest.usize(placement.subcircuitId);
+        digest.usize(placement.variables.len());
+        for variable in placement.variables.iter() {
+            digest.str(&variable.0);
+        }
+    }
+    digest.usize(permutation_raw.len());
+    for entry in permutation_raw {
+        digest.usizes(&[entry.row, entry.col, entry.X, entry.Y]);
+    }
+    for values in [
+        &instance.a_pub_user,
+        &instance.a_pub_block,
+        &instance.a_pub_function,
+    ] {
+        digest.usize(values.len());
+        for value in values.iter() {
+            digest.str(&value.0);
+        }
+    }
+    digest.0.update(&sigma.fingerprint());
+    let mut out = [0u8; 32];
+    digest.0.finalize(&mut out);
+    out
+}
+
+const WHAT: &str = "prover checkpoint";
+
+fn write_poly(writer: &mut BinaryWriter, poly: &DensePolynomialExt) {
+    let mut coeffs = vec![ScalarField::zero(); poly.x_size * poly.y_size];
+    poly.copy_coeffs(0, HostSlice::from_mut_slice(&mut coeffs));
+    writer.u64(poly.x_size as u64).u64(poly.y_size as u64);
+    for coeff in &coeffs {
+        writer.scalar(coeff);
+    }
+}
+
+fn read_poly(reader: &mut BinaryReader) -> TokamakResult<DensePolynomialExt> {
+    let x_size = reader.u64()? as usize;
+    let y_size = reader.u64()? as usize;
+    if !x_size.is_power_of_two() || !y_size.is_power_of_two() {
+        return Err(TokamakError::parse(
+            WHAT,
+            format!("polynomial size {x_size}x{y_size} is not a power of two"),
+        ));
+    }
+    let coeffs = (0..x_size * y_size)
+        .map(|_| reader.scalar())
+        .collect::<TokamakResult<Vec<_>>>()?;
+    Ok(DensePolynomialExt::from_coeffs(
+        HostSlice::from_slice(&coeffs),
+        x_size,
+        y_size,
+    ))
+}
+
+fn read_flag(reader: &mut BinaryReader) -> TokamakResult<bool> {
+    match reader.u64()? {
+        0 => Ok(false),
+        1 => Ok(true),
+        flag => Err(TokamakError::parse(
+            WHAT,
+            format!("invalid presence flag {flag}"),
+        )),
+    }
+}
+
+fn write_optional_poly(writer: &mut BinaryWriter, poly: &Option<DensePolynomialExt>) {
+    writer.u64(poly.is_some() as u64);
+    if let Some(poly) = poly {
+        write_poly(writer, poly);
+    }
+}
+
+fn read_optional_poly(reader: &mut BinaryReader) -> TokamakResult<Option<DensePolynomialExt>> {
+    if read_flag(reader)? {
+        read_poly(reader).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn write_scalars(writer: &mut BinaryWriter, scalars: &[ScalarField]) {
+    writer.u64(scalars.len() as u64);
+    for scalar in scalars {
+        writer.scalar(scalar);
+    }
+}
+
+fn read_scalars(reader: &mut BinaryReader) -> TokamakResult<Vec<ScalarField>> {
+    let len = reader.u64()? as usize;
+    (0..len).map(|_| reader.scalar()).collect()
+}
+
+impl Prover {
+    pub const CHECKPOINT_MAGIC: [u8; 4] = *b"TKCK";
+
+    /// Encodes everything needed to resume after the rounds completed in `partial`: the input
+    /// digest, the transcript kind and Fiat-Shamir mode, the round messages, the blinding scalars, and the instance, witness, quotient and
+    /// cached polynomials. The transcript is not stored; it is replayed from the round messages.
+    pub fn checkpoint(&self, partial: &PartialProof) -> Vec<u8> {
+        let mut writer = BinaryWriter::new(&Self::CHECKPOINT_MAGIC, PointEncoding::Uncompressed);
+        writer
+            .bytes(&self.input_digest)
+            .u8(self.transcript.tag())
+            .u8(self.fiat_shamir.tag());
+
+        let binding = &partial.binding;
+        for point in [
+            &binding.A_free,
+            &binding.O_pub_free,
+            &binding.O_mid,
+            &binding.O_prv,
+        ] {
+            writer.g1(point);
+        }
+        writer.u64(partial.proof0.is_some() as u64);
+        if let Some(proof0) = &partial.proof0 {
+            for point in [
+                &proof0.U,
+                &proof0.V,
+                &proof0.W,
+                &proof0.Q_AX,
+                &proof0.Q_AY,
+                &proof0.B,
+            ] {
+                writer.g1(point);
+            }
+        }
+        writer.u64(partial.proof1.is_some() as u64);
+        if let Some(proof1) = &partial.proof1 {
+            writer.g1(&proof1.R);
+        }
+        writer.u64(partial.proof2.is_some() as u64);
+        if let Some(proof2) = &partial.proof2 {
+            writer.g1(&proof2.Q_CX).g1(&proof2.Q_CY);
+        }
+        writer.u64(partial.proof3.is_some() as u64);
+        if let Some(proof3) = &partial.proof3 {
+            writer
+                .scalar(&proof3.V_eval.0)
+                .scalar(&proof3.R_eval.0)
+                .scalar(&proof3.R_omegaX_eval.0)
+                .scalar(&proof3.R_omegaX_omegaY_eval.0);
+        }
+
+        let mixer = &self.mixer;
+        for scalar in [
+            &mixer.rU_X,
+            &mixer.rU_Y,
+            &mixer.rV_X,
+            &mixer.rV_Y,
+            &mixer.rR_X,
+            &mixer.rR_Y,
+            &mixer.rO_mid,
+        ] {
+            writer.scalar(scalar);
+        }
+        for scalars in [&mixer.rW_X, &mixer.rW_Y, &mixer.rB_X, &mixer.rB_Y] {
+            write_scalars(&mut writer, scalars);
+        }
+
+        let instance = &self.instance;
+        let witness = &self.witness;
+        let quotients = &self.quotients;
+        for poly in [
+            &instance.s0XY,
+            &instance.s1XY,
+            &instance.t_n,
+            &instance.t_mi,
+            &instance.t_smax,
+            &instance.a_free_X,
+            &witness.bXY,
+            &witness.uXY,
+            &witness.vXY,
+            &witness.wXY,
+            &witness.rXY,
+            &quotients.q0XY,
+            &quotients.q1XY,
+            &quotients.q2XY,
+            &quotients.q3XY,
+            &quotients.q4XY,
+            &quotients.q5XY,
+            &quotients.q6XY,
+            &quotients.q7XY,
+        ] {
+            write_poly(&mut writer, poly);
+        }
+        write_optional_poly(&mut writer, &self.cache.w_zk);
+        write_optional_poly(&mut writer, &self.cache.term_b_zk);
+        write_optional_poly(&mut writer, &self.cache.lagrange_kl_xy);
+        writer.finish()
+    }
+
+    /// Writes `checkpoint(partial)` to `path` through a temporary file, so that a crash while
+    /// writing leaves the previous checkpoint intact.
+    pub fn write_checkpoint(&self, partial: &PartialProof, path: &Path) -> TokamakResult<()> {
+        let tmp_path = path.with_extension("tmp");
+        write_binary(&tmp_path, &self.checkpoint(partial))
+            .and_then(|_| std::fs::rename(&tmp_path, path))
+            .map_err(|source| TokamakError::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Rebuilds the prover and its completed rounds from a checkpoint, without redoing the work
+    /// of `init`. Only the setup parameters, subcircuit infos, placement variables, permutation,
+    /// instance and reference string are loaded from `inputs`, and the checkpoint is rejected
+    /// with `CheckpointMismatch` unless their digest matches the one it was written with.
+    pub fn resume<I: ProverInputs + ?Sized>(
+        inputs: &I,
+        checkpoint: &[u8],
+    ) -> TokamakResult<(Self, PartialProof)> {
+        let mut reader = BinaryReader::new(checkpoint, &Self::CHECKPOINT_MAGIC, WHAT)?;
+        let stored_digest = reader.bytes(32)?;
+        let tag = reader.u8()?;
+        let transcript = TranscriptKind::from_tag(tag)
+            .ok_or_else(|| TokamakError::parse(WHAT, format!("unknown transcript tag {tag}")))?;
+        let tag = reader.u8()?;
+        let fiat_shamir = FiatShamirMode::from_tag(tag).ok_or_else(|| {
+            TokamakError::parse(WHAT, format!("unknown Fiat-Shamir mode tag {tag}"))
+        })?;
+
+        let setup_params = inputs.setup_params()?.into_owned();
+        let sigma = inputs.sigma()?;
+        sigma.validate_points(inputs.point_validation())?;
+        let public_instance = inputs.instance()?.into_owned();
+        let input_digest = input_digest(
+            &setup_params,
+            &inputs.subcircuit_infos()?,
+            &inputs.placement_variables()?,
+            &inputs.permutation()?,
+            &public_instance,
+            &sigma,
+        );
+        if stored_digest != input_digest.as_slice() {
+            return Err(TokamakError::CheckpointMismatch(
+                "it was written for different inputs or a different reference string".to_string(),
+            ));
+        }
+
+        let shape = setup_shape(&setup_params)?;
+        validate_setup_shape(&shape)?;
+        init_ntt_domain(prover_verifier_ntt_domain_size(&shape))?;
+
+        let binding = Binding {
+            A_free: reader.g1()?,
+            O_pub_free: reader.g1()?,
+            O_mid: reader.g1()?,
+            O_prv: reader.g1()?,
+        };
+        let mut partial = PartialProof::new(binding);
+        if read_flag(&mut reader)? {
+            partial.proof0 = Some(Proof0 {
+                U: reader.g1()?,
+                V: reader.g1()?,
+                W: reader.g1()?,
+                Q_AX: reader.g1()?,
+                Q_AY: reader.g1()?,
+                B: reader.g1()?,
+            });
+        }
+        if read_flag(&mut reader)? {
+            partial.proof1 = Some(Proof1 { R: reader.g1()? });
+        }
+        if read_flag(&mut reader)? {
+            partial.proof2 = Some(Proof2 {
+                Q_CX: reader.g1()?,
+                Q_CY: reader.g1()?,
+            });
+        }
+        if read_flag(&mut reader)? {
+            partial.proof3 = Some(Proof3 {
+                V_eval: FieldSerde(reader.scalar()?),
+                R_eval: FieldSerde(reader.scalar()?),
+                R_omegaX_eval: FieldSerde(reader.scalar()?),
+                R_omegaX_omegaY_eval: FieldSerde(reader.scalar()?),
+            });
+        }
+
+        let rU_X = reader.scalar()?;
+        let rU_Y = reader.scalar()?;
+        let rV_X = reader.scalar()?;
+        let rV_Y = reader.scalar()?;
+        let rR_X = reader.scalar()?;
+        let rR_Y = reader.scalar()?;
+        let rO_mid = reader.scalar()?;
+        let mixer = Mixer {
+            rU_X,
+            rU_Y,
+            rV_X,
+            rV_Y,
+            rW_X: read_scalars(&mut reader)?,
+            rW_Y: read_scalars(&mut reader)?,
+            rB_X: read_scalars(&mut reader)?,
+            rB_Y: read_scalars(&mut reader)?,
+            rR_X,
+            rR_Y,
+            rO_mid,
+        };
+
+        let instance = InstancePolynomials {
+            s0XY: read_poly(&mut reader)?,
+            s1XY: read_poly(&mut reader)?,
+            t_n: read_poly(&mut reader)?,
+            t_mi: read_poly(&mut reader)?,
+            t_smax: read_poly(&mut reader)?,
+            a_free_X: read_poly(&mut reader)?,
+        };
+        let witness = Witness {
+            bXY: read_poly(&mut reader)?,
+            uXY: read_poly(&mut reader)?,
+            vXY: read_poly(&mut reader)?,
+            wXY: read_poly(&mut reader)?,
+            rXY: read_poly(&mut reader)?,
+        };
+        let quotients = Quotients {
+            q0XY: read_poly(&mut reader)?,
+            q1XY: read_poly(&mut reader)?,
+            q2XY: read_poly(&mut reader)?,
+            q3XY: read_poly(&mut reader)?,
+            q4XY: read_poly(&mut reader)?,
+            q5XY: read_poly(&mut reader)?,
+            q6XY: read_poly(&mut reader)?,
+            q7XY: read_poly(&mut reader)?,
+        };
+        let cache = ProverCache {
+            w_zk: read_optional_poly(&mut reader)?,
+            term_b_zk: read_optional_poly(&mut reader)?,
+            lagrange_kl_xy: read_optional_poly(&mut reader)?,
+        };
+        reader.finish()?;
+
+        let prover = Self {
+            setup_params,
+            sigma,
+            instance,
+            witness,
+            mixer,
+            quotients,
+            cache,
+            input_digest,
+            transcript,
+            fiat_shamir,
+            public_instance,
+            transcript_trace: None,
+            quotient_engine: inputs.quotient_engine(),
+        };
+        Ok((prover, partial))
+    }
+
+    /// Reads a checkpoint written by `write_checkpoint` and resumes from it.
+    pub fn resume_from_file<I: ProverInputs + ?Sized>(
+        inputs: &I,
+        path: &Path,
+    ) -> TokamakResult<(Self, PartialProof)> {
+        Self::resume(inputs, &read_binary(path)?)
+    }
+}
@@ -1,31 +1,53 @@
 #![allow(non_snake_case)]
+use clap::ValueEnum;
 use icicle_bls12_381::curve::{ScalarCfg, ScalarField};
 use icicle_core::ntt;
 use icicle_core::traits::{Arithmetic, FieldImpl, GenerateRandom};
 use icicle_runtime::memory::HostSlice;
 use libs::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt, PolyExpr};
+use libs::error::{TokamakError, TokamakResult};
 use libs::field_structures::FieldSerde;
-use libs::group_structures::G1serde;
+use libs::group_structures::{G1serde, PointValidation};
+use libs::iotools::binary::{
+    read_binary, write_binary, ArtifactEncoding, BinaryReader, BinaryWriter, PointEncoding,
+};
+use libs::iotools::inputs::{CircuitInputs, MemoryInputs, R1csSource};
+use libs::iotools::permutation::validate_permutation;
 use libs::iotools::*;
-use libs::polynomial_structures::gen_bXY;
+use libs::polynomial_structures::gen_bXY_sparse;
 use libs::utils::{
-    init_ntt_domain, load_setup_params_from_qap_path, prover_verifier_ntt_domain_size, setup_shape,
-    validate_setup_shape,
+    init_ntt_domain, prover_verifier_ntt_domain_size, setup_shape, validate_setup_shape,
 };
 #[cfg(feature = "testing-mode")]
 use libs::vector_operations::point_mul_two_vecs;
 use libs::vector_operations::{point_div_two_vecs, resize, transpose_inplace};
-use libs::{impl_read_from_json, impl_write_into_json, pop_recover, split_push};
+use libs::{
+    impl_circuit_inputs_for_paths, impl_read_from_json, impl_write_into_json, pop_recover,
+    split_push,
+};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "timing")]
 use std::time::Instant;
 
-use std::path::PathBuf;
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
 use std::vec;
 use tiny_keccak::Keccak;
 
+mod abi;
+mod checkpoint;
+mod pool;
 mod sigma_source;
-use sigma_source::SigmaHolder;
+mod transcript;
+pub use abi::{VerifyCalldata, VERIFY_SIGNATURE};
+use checkpoint::input_digest;
+pub use checkpoint::PartialProof;
+pub use pool::{ProverJob, ProverPool};
+pub use sigma_source::SigmaHolder;
+pub use transcript::{
+    FiatShamirMode, PoseidonTranscript, ProtocolStatement, TraceDivergence, TraceOp, TraceStep,
+    Transcript, TranscriptKind, TranscriptTrace, FIAT_SHAMIR_PROTOCOL_TAG,
+};
 
 macro_rules! poly_comb {
         (($c:expr, $p:expr), $(($rest_c:expr, $rest_p:expr)),+ $(,)?) => {{
@@ -246,6 +268,54 @@ pub struct ProveInputPaths<'a> {
     pub synthesizer_path: &'a str,
     pub setup_path: &'a str,
     pub output_path: &'a str,
+    pub point_validation: PointValidation,
+    pub quotient_engine: QuotientEngine,
+}
+
+impl_circuit_inputs_for_paths!(ProveInputPaths<'_>);
+
+/// Inputs of [`Prover::init`]: the circuit and synthesizer inputs plus the combined reference
+/// string.
+pub trait ProverInputs: CircuitInputs {
+    fn sigma(&self) -> TokamakResult<SigmaHolder>;
+
+    /// Size of the file the reference string is read from, for the timing report.
+    fn sigma_file_bytes(&self) -> usize {
+        0
+    }
+
+    /// How the prover built by `Prover::init` or `Prover::resume` computes the quotients.
+    fn quotient_engine(&self) -> QuotientEngine {
+        QuotientEngine::default()
+    }
+}
+
+/// Reads `combined_sigma.rkyv` from `setup_path`.
+impl ProverInputs for ProveInputPaths<'_> {
+    fn sigma(&self) -> TokamakResult<SigmaHolder> {
+        let sigma_path = PathBuf::from(self.setup_path).join("combined_sigma.rkyv");
+        SigmaHolder::load(&sigma_path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::InvalidData => TokamakError::archive(&sigma_path, err.to_string()),
+            _ => TokamakError::Io {
+                path: sigma_path.clone(),
+                source: err,
+            },
+        })
+    }
+
+    fn sigma_file_bytes(&self) -> usize {
+        libs::iotools::inputs::file_bytes(self.setup_path, "combined_sigma.rkyv")
+    }
+
+    fn quotient_engine(&self) -> QuotientEngine {
+        self.quotient_engine
+    }
+}
+
+impl ProverInputs for MemoryInputs<'_, SigmaHolder> {
+    fn sigma(&self) -> TokamakResult<SigmaHolder> {
+        Ok(self.crs.clone())
+    }
 }
 
 pub struct Mixer {
@@ -300,6 +370,24 @@ pub struct ProverCache {
     lagrange_kl_xy: Option<DensePolynomialExt>,
 }
 
+/// How `prove0` and `prove2` divide their constraint polynomials by the vanishing polynomials.
+/// Both give the same quotients, so the proof does not depend on it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum QuotientEngine {
+    /// Interpolate the numerator and divide its coefficients
+    #[default]
+    Coefficient,
+    /// Evaluate the numerator on an extended coset and divide there, interpolating only the
+    /// quotients
+    Coset,
+}
+
+// 7 generates the multiplicative group of the scalar field, so no power of it up to the NTT sizes
+// is a root of unity and the vanishing polynomials have no root on its coset.
+fn quotient_coset() -> ScalarField {
+    ScalarField::from_u32(7)
+}
+
 pub struct Prover {
     pub setup_params: SetupParams,
     pub sigma: SigmaHolder,
@@ -308,6 +396,21 @@ pub struct Prover {
     pub mixer: Mixer,
     pub quotients: Quotients,
     pub cache: ProverCache,
+    /// Digest of the inputs, written into checkpoints so that they are only resumed against the
+    /// same inputs.
+    pub input_digest: [u8; 32],
+    /// Fiat-Shamir transcript the rounds run against. `init` selects Keccak.
+    pub transcript: TranscriptKind,
+    /// Whether the transcript is bound to the protocol statement first. `init` selects `Legacy`.
+    pub fiat_shamir: FiatShamirMode,
+    /// The public instance, absorbed in `FiatShamirMode::StrongV1`.
+    pub public_instance: Instance,
+    /// Set to `Some` to record the transcript of the next run; the rounds replace it with the
+    /// recorded `TranscriptTrace`. `init` leaves it `None`.
+    pub transcript_trace: Option<TranscriptTrace>,
+    /// How the quotients of `prove0` and `prove2` are computed. `init` and `resume` take it from
+    /// `ProverInputs::quotient_engine`; it is not part of the checkpoint.
+    pub quotient_engine: QuotientEngine,
 }
 
 #[cfg(feature = "timing")]
@@ -436,7 +539,7 @@ fn y_monomial() -> DensePolynomialExt {
     DensePolynomialExt::from_coeffs(HostSlice::from_slice(&coeffs), 1, 2)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Proof {
     pub binding: Binding,
     pub proof0: Proof0,
@@ -444,6 +547,14 @@ pub struct Proof {
     pub proof2: Proof2,
     pub proof3: Proof3,
     pub proof4: Proof4,
+    /// The transcript the challenges were squeezed from. Proofs written before it was recorded
+    /// read as Keccak.
+    #[serde(default)]
+    pub transcript: TranscriptKind,
+    /// Whether the transcript was bound to the protocol statement. Proofs written before it was
+    /// recorded read as `Legacy`.
+    #[serde(default)]
+    pub fiat_shamir: FiatShamirMode,
 }
 
 impl_read_from_json!(Proof);
@@ -508,6 +619,8 @@ impl Proof {
         return FormattedProof {
             proof_entries_part1,
             proof_entries_part2,
+            transcript: self.transcript,
+            fiat_shamir: self.fiat_shamir,
         };
     }
 }
@@ -515,21 +628,35 @@ impl Proof {
 pub struct FormattedProof {
     pub proof_entries_part1: Vec<String>,
     pub proof_entries_part2: Vec<String>,
+    #[serde(default)]
+    pub transcript: TranscriptKind,
+    #[serde(default)]
+    pub fiat_shamir: FiatShamirMode,
 }
 
 impl_read_from_json!(FormattedProof);
 impl_write_into_json!(FormattedProof);
 
 impl FormattedProof {
-    pub fn recover_proof_from_format(&self) -> Proof {
+    pub fn recover_proof_from_format(&self) -> TokamakResult<Proof> {
         let p1 = &self.proof_entries_part1;
         let p2 = &self.proof_entries_part2;
 
         const G1_CNT: usize = 19; // The number of G1 points
         const SCALAR_CNT: usize = 4; // The number of Scalars
 
-        assert_eq!(p1.len(), G1_CNT * 2);
-        assert_eq!(p2.len(), G1_CNT * 2 + SCALAR_CNT);
+        if p1.len() != G1_CNT * 2 || p2.len() != G1_CNT * 2 + SCALAR_CNT {
+            return Err(TokamakError::parse(
+                "formatted proof",
+                format!(
+                    "expected {} part1 and {} part2 entries, got {} and {}",
+                    G1_CNT * 2,
+                    G1_CNT * 2 + SCALAR_CNT,
+                    p1.len(),
+                    p2.len()
+                ),
+            ));
+        }
 
         let mut idx = 0;
 
@@ -563,25 +690,173 @@ impl FormattedProof {
             N_Y,
         };
         let scalar_slice = &p2[G1_CNT * 2..];
-        assert_eq!(scalar_slice.len(), SCALAR_CNT);
         let proof3 = Proof3 {
-            R_eval: FieldSerde(ScalarField::from_hex(&scalar_slice[0])),
-            R_omegaX_eval: FieldSerde(ScalarField::from_hex(&scalar_slice[1])),
-            R_omegaX_omegaY_eval: FieldSerde(ScalarField::from_hex(&scalar_slice[2])),
-            V_eval: FieldSerde(ScalarField::from_hex(&scalar_slice[3])),
+            R_eval: FieldSerde(scalar_from_hex(&scalar_slice[0])?),
+            R_omegaX_eval: FieldSerde(scalar_from_hex(&scalar_slice[1])?),
+            R_omegaX_omegaY_eval: FieldSerde(scalar_from_hex(&scalar_slice[2])?),
+            V_eval: FieldSerde(scalar_from_hex(&scalar_slice[3])?),
         };
-        return Proof {
+        return Ok(Proof {
             binding,
             proof0,
             proof1,
             proof2,
             proof3,
             proof4,
+            transcript: self.transcript,
+            fiat_shamir: self.fiat_shamir,
+        });
+    }
+}
+
+impl Proof {
+    pub const BINARY_MAGIC: [u8; 4] = *b"TKPF";
+
+    /// Encodes the proof in the versioned binary format: the 19 G1 points in the order of
+    /// `convert_format_for_solidity_verifier`, then R_eval, R_omegaX_eval, R_omegaX_omegaY_eval
+    /// and V_eval, then the `TranscriptKind::tag` and `FiatShamirMode::tag` bytes.
+    pub fn to_bytes(&self, encoding: PointEncoding) -> Vec<u8> {
+        let mut writer = BinaryWriter::new(&Self::BINARY_MAGIC, encoding);
+        for point in [
+            &self.proof0.U,
+            &self.proof0.V,
+            &self.proof0.W,
+            &self.binding.O_mid,
+            &self.binding.O_prv,
+            &self.proof0.Q_AX,
+            &self.proof0.Q_AY,
+            &self.proof2.Q_CX,
+            &self.proof2.Q_CY,
+            &self.proof4.Pi_X,
+            &self.proof4.Pi_Y,
+            &self.proof0.B,
+            &self.proof1.R,
+            &self.proof4.M_Y,
+            &self.proof4.M_X,
+            &self.proof4.N_Y,
+            &self.proof4.N_X,
+            &self.binding.O_pub_free,
+            &self.binding.A_free,
+        ] {
+            writer.g1(point);
+        }
+        writer
+            .scalar(&self.proof3.R_eval.0)
+            .scalar(&self.proof3.R_omegaX_eval.0)
+            .scalar(&self.proof3.R_omegaX_omegaY_eval.0)
+            .scalar(&self.proof3.V_eval.0)
+            .u8(self.transcript.tag())
+            .u8(self.fiat_shamir.tag());
+        writer.finish()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> TokamakResult<Self> {
+        let mut reader = BinaryReader::new(bytes, &Self::BINARY_MAGIC, "binary proof")?;
+        // Must follow the same order as `to_bytes`
+        let U = reader.g1()?;
+        let V = reader.g1()?;
+        let W = reader.g1()?;
+        let O_mid = reader.g1()?;
+        let O_prv = reader.g1()?;
+        let Q_AX = reader.g1()?;
+        let Q_AY = reader.g1()?;
+        let Q_CX = reader.g1()?;
+        let Q_CY = reader.g1()?;
+        let Pi_X = reader.g1()?;
+        let Pi_Y = reader.g1()?;
+        let B = reader.g1()?;
+        let R = reader.g1()?;
+        let M_Y = reader.g1()?;
+        let M_X = reader.g1()?;
+        let N_Y = reader.g1()?;
+        let N_X = reader.g1()?;
+        let O_pub_free = reader.g1()?;
+        let A_free = reader.g1()?;
+        let proof3 = Proof3 {
+            R_eval: FieldSerde(reader.scalar()?),
+            R_omegaX_eval: FieldSerde(reader.scalar()?),
+            R_omegaX_omegaY_eval: FieldSerde(reader.scalar()?),
+            V_eval: FieldSerde(reader.scalar()?),
         };
+        // Version 1 proofs predate the tags and were all Keccak and Legacy.
+        let (transcript, fiat_shamir) = if reader.version() < 2 {
+            (TranscriptKind::Keccak, FiatShamirMode::Legacy)
+        } else {
+            let tag = reader.u8()?;
+            let transcript = TranscriptKind::from_tag(tag).ok_or_else(|| {
+                TokamakError::parse("binary proof", format!("unknown transcript tag {tag}"))
+            })?;
+            let tag = reader.u8()?;
+            let fiat_shamir = FiatShamirMode::from_tag(tag).ok_or_else(|| {
+                TokamakError::parse(
+                    "binary proof",
+                    format!("unknown Fiat-Shamir mode tag {tag}"),
+                )
+            })?;
+            (transcript, fiat_shamir)
+        };
+        reader.finish()?;
+
+        Ok(Proof {
+            binding: Binding {
+                A_free,
+                O_pub_free,
+                O_mid,
+                O_prv,
+            },
+            proof0: Proof0 {
+                U,
+                V,
+                W,
+                Q_AX,
+                Q_AY,
+                B,
+            },
+            proof1: Proof1 { R },
+            proof2: Proof2 { Q_CX, Q_CY },
+            proof3,
+            proof4: Proof4 {
+                Pi_X,
+                Pi_Y,
+                M_X,
+                M_Y,
+                N_X,
+                N_Y,
+            },
+            transcript,
+            fiat_shamir,
+        })
+    }
+
+    /// Writes `proof.json` or `proof.bin` into `dir` and returns the written path.
+    pub fn write_into_dir(
+        &self,
+        dir: &str,
+        encoding: ArtifactEncoding,
+    ) -> std::io::Result<PathBuf> {
+        let path = PathBuf::from(dir).join(encoding.file_name("proof"));
+        match encoding.point_encoding() {
+            None => self
+                .convert_format_for_solidity_verifier()
+                .write_into_json(path.clone())?,
+            Some(points) => write_binary(&path, &self.to_bytes(points))?,
+        }
+        Ok(path)
+    }
+
+    /// Reads `proof.json` or `proof.bin` from `dir`. Binary files carry their own point encoding.
+    pub fn read_from_dir(dir: &str, encoding: ArtifactEncoding) -> TokamakResult<Self> {
+        let path = PathBuf::from(dir).join(encoding.file_name("proof"));
+        match encoding.point_encoding() {
+            None => FormattedProof::read_from_json(path.clone())
+                .map_err(|err| TokamakError::from_read(&path, err))?
+                .recover_proof_from_format(),
+            Some(_) => Self::from_bytes(&read_binary(&path)?),
+        }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Binding {
     pub A_free: G1serde,
     pub O_pub_free: G1serde,
@@ -589,7 +864,7 @@ pub struct Binding {
     pub O_prv: G1serde,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Proof0 {
     pub U: G1serde,
     pub V: G1serde,
@@ -599,39 +874,45 @@ pub struct Proof0 {
     pub B: G1serde,
 }
 impl Proof0 {
-    pub fn verify0_with_manager(&self, manager: &mut TranscriptManager) -> Vec<ScalarField> {
+    pub fn verify0_with_manager<T: Transcript>(
+        &self,
+        manager: &mut TranscriptManager<T>,
+    ) -> Vec<ScalarField> {
         manager.add_proof0(self);
         manager.get_thetas()
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Proof1 {
     pub R: G1serde,
 }
 impl Proof1 {
-    pub fn verify1_with_manager(&self, manager: &mut TranscriptManager) -> ScalarField {
+    pub fn verify1_with_manager<T: Transcript>(
+        &self,
+        manager: &mut TranscriptManager<T>,
+    ) -> ScalarField {
         manager.add_proof1(self);
         manager.get_kappa0()
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Proof2 {
     pub Q_CX: G1serde,
     pub Q_CY: G1serde,
 }
 impl Proof2 {
-    pub fn verify2_with_manager(
+    pub fn verify2_with_manager<T: Transcript>(
         &self,
-        manager: &mut TranscriptManager,
+        manager: &mut TranscriptManager<T>,
     ) -> (ScalarField, ScalarField) {
         manager.add_proof2(self);
         manager.get_chi_zeta()
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Proof3 {
     pub V_eval: FieldSerde,
     pub R_eval: FieldSerde,
@@ -639,14 +920,17 @@ pub struct Proof3 {
     pub R_omegaX_omegaY_eval: FieldSerde,
 }
 impl Proof3 {
-    pub fn verify3_with_manager(&self, manager: &mut TranscriptManager) -> ScalarField {
+    pub fn verify3_with_manager<T: Transcript>(
+        &self,
+        manager: &mut TranscriptManager<T>,
+    ) -> ScalarField {
         manager.add_proof3(self);
         let kappa1 = manager.get_kappa1();
         kappa1 // Only return kappa1
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Proof4 {
     pub Pi_X: G1serde,
     pub Pi_Y: G1serde,
@@ -671,79 +955,299 @@ pub struct Proof4Test {
     pub N_Y: G1serde,
 }
 
+/// Runs the whole prover on `inputs` and returns the proof. Nothing is written to disk, not even
+/// to `ProveInputPaths::output_path`.
+pub fn prove<I: ProverInputs + ?Sized>(inputs: &I) -> TokamakResult<Proof> {
+    let (mut prover, binding) = Prover::init(inputs)?;
+    let (proof, _proof4_test) = prover.prove_rounds(binding);
+    Ok(proof)
+}
+
+/// Builds a [`Prover`] from in-memory inputs instead of the synthesizer and setup output
+/// directories. The R1CS constraints are still read from the subcircuit library at `qap_path`.
+pub struct ProverBuilder<'a> {
+    qap_path: &'a str,
+    setup_params: Option<SetupParams>,
+    subcircuit_infos: Option<Box<[SubcircuitInfo]>>,
+    placement_variables: Option<Box<[PlacementVariables]>>,
+    permutation: Option<Box<[Permutation]>>,
+    instance: Option<Instance>,
+    sigma: Option<SigmaHolder>,
+    point_validation: PointValidation,
+    transcript: TranscriptKind,
+    fiat_shamir: FiatShamirMode,
+    quotient_engine: QuotientEngine,
+}
+
+impl<'a> ProverBuilder<'a> {
+    pub fn new(qap_path: &'a str) -> Self {
+        Self {
+            qap_path,
+            setup_params: None,
+            subcircuit_infos: None,
+            placement_variables: None,
+            permutation: None,
+            instance: None,
+            sigma: None,
+            point_validation: PointValidation::default(),
+            transcript: TranscriptKind::default(),
+            fiat_shamir: FiatShamirMode::default(),
+            quotient_engine: QuotientEngine::default(),
+        }
+    }
+
+    pub fn setup_params(mut self, setup_params: SetupParams) -> Self {
+        self.setup_params = Some(setup_params);
+        self
+    }
+
+    pub fn subcircuit_infos(mut self, subcircuit_infos: impl Into<Box<[SubcircuitInfo]>>) -> Self {
+        self.subcircuit_infos = Some(subcircuit_infos.into());
+        self
+    }
+
+    pub fn placement_variables(
+        mut self,
+        placement_variables: impl Into<Box<[PlacementVariables]>>,
+    ) -> Self {
+        self.placement_variables = Some(placement_variables.into());
+        self
+    }
+
+    pub fn permutation(mut self, permutation: impl Into<Box<[Permutation]>>) -> Self {
+        self.permutation = Some(permutation.into());
+        self
+    }
+
+    pub fn instance(mut self, instance: Instance) -> Self {
+        self.instance = Some(instance);
+        self
+    }
+
+    /// Sets the reference string, either loaded with [`SigmaHolder::load`] or archived from memory
+    /// with [`SigmaHolder::from_sigma`].
+    pub fn sigma(mut self, sigma: SigmaHolder) -> Self {
+        self.sigma = Some(sigma);
+        self
+    }
+
+    /// Whether the points of the reference string are checked in `build`. Defaults to `Checked`.
+    pub fn point_validation(mut self, point_validation: PointValidation) -> Self {
+        self.point_validation = point_validation;
+        self
+    }
+
+    /// Fiat-Shamir transcript of the proof. Defaults to Keccak, the only one the Solidity verifier
+    /// accepts.
+    pub fn transcript(mut self, transcript: TranscriptKind) -> Self {
+        self.transcript = transcript;
+        self
+    }
+
+    /// Whether the transcript is bound to the protocol statement first. Defaults to `Legacy`, the
+    /// only mode the Solidity verifier accepts.
+    pub fn fiat_shamir(mut self, fiat_shamir: FiatShamirMode) -> Self {
+        self.fiat_shamir = fiat_shamir;
+        self
+    }
+
+    /// How the quotients of `prove0` and `prove2` are computed. Defaults to `Coefficient`.
+    pub fn quotient_engine(mut self, quotient_engine: QuotientEngine) -> Self {
+        self.quotient_engine = quotient_engine;
+        self
+    }
+
+    pub fn build(self) -> TokamakResult<(Prover, Binding)> {
+        let setup_params = self
+            .setup_params
+            .ok_or(TokamakError::MissingInput("setup_params"))?;
+        let subcircuit_infos = self
+            .subcircuit_infos
+            .ok_or(TokamakError::MissingInput("subcircuit_infos"))?;
+        let placement_variables = self
+            .placement_variables
+            .ok_or(TokamakError::MissingInput("placement_variables"))?;
+        let permutation = self
+            .permutation
+            .ok_or(TokamakError::MissingInput("permutation"))?;
+        let instance = self
+            .instance
+            .ok_or(TokamakError::MissingInput("instance"))?;
+        let sigma = self.sigma.ok_or(TokamakError::MissingInput("sigma"))?;
+        sigma.validate_points(self.point_validation)?;
+
+        let (mut prover, binding) = Prover::from_inputs(
+            Path::new(self.qap_path),
+            setup_params,
+            &subcircuit_infos,
+            &placement_variables,
+            &permutation,
+            &instance,
+            sigma,
+        )?;
+        prover.transcript = self.transcript;
+        prover.fiat_shamir = self.fiat_shamir;
+        prover.quotient_engine = self.quotient_engine;
+        Ok((prover, binding))
+    }
+
+    pub fn prove(self) -> TokamakResult<Proof> {
+        let (mut prover, binding) = self.build()?;
+        let (proof, _proof4_test) = prover.prove_rounds(binding);
+        Ok(proof)
+    }
+
+    /// Resumes from a checkpoint of a prover built from the same inputs. The transcript and the
+    /// Fiat-Shamir mode come from the checkpoint, the quotient engine from the builder.
+    pub fn resume(self, checkpoint: &[u8]) -> TokamakResult<(Prover, PartialProof)> {
+        let sigma = self.sigma.ok_or(TokamakError::MissingInput("sigma"))?;
+        let mut inputs = MemoryInputs::new(sigma);
+        inputs.setup_params = self.setup_params.as_ref();
+        inputs.subcircuit_infos = self.subcircuit_infos.as_deref();
+        inputs.placement_variables = self.placement_variables.as_deref();
+        inputs.permutation = self.permutation.as_deref();
+        inputs.instance = self.instance.as_ref();
+        inputs.point_validation = self.point_validation;
+
+        let (mut prover, partial) = Prover::resume(&inputs, checkpoint)?;
+        prover.quotient_engine = self.quotient_engine;
+        Ok((prover, partial))
+    }
+}
+
 impl Prover {
-    pub fn init(paths: &ProveInputPaths) -> (Self, Binding) {
+    /// Loads the inputs from `inputs` and runs everything before `prove0`: the witness, the
+    /// quotient polynomials, the blinding scalars and the binding commitments.
+    pub fn init<I: ProverInputs + ?Sized>(inputs: &I) -> TokamakResult<(Self, Binding)> {
         #[cfg(feature = "timing")]
         let init_start = Instant::now();
-        // Load setup parameters from JSON file
-        let setup_params_path = PathBuf::from(paths.qap_path).join("setupParams.json");
-        let _setup_params_file_bytes = std::fs::metadata(&setup_params_path)
-            .map(|m| m.len() as usize)
-            .unwrap_or(0);
+        #[cfg(feature = "timing")]
+        let file_bytes = |dims: usize| {
+            vec![crate::timing::SizeInfo {
+                label: "file_bytes",
+                dims: vec![dims],
+            }]
+        };
         let setup_params = crate::time_block!(
             "init.load.setup_params",
             "load",
-            vec![crate::timing::SizeInfo {
-                label: "file_bytes",
-                dims: vec![_setup_params_file_bytes]
-            },],
-            { load_setup_params_from_qap_path(paths.qap_path) }
+            file_bytes(inputs.file_bytes("setupParams.json")),
+            { inputs.setup_params()? }
         );
-
-        let shape = setup_shape(&setup_params);
-        validate_setup_shape(&shape);
-        let _l = setup_params.l;
-        let m_i = shape.m_i;
-        let n = shape.n;
-        let s_max = shape.s_max;
-        #[cfg(feature = "timing")]
-        let s_d = setup_params.s_D;
-
-        let ntt_domain_size = prover_verifier_ntt_domain_size(&shape);
-        init_ntt_domain(ntt_domain_size);
-
-        // Load subcircuit information
-        let subcircuit_infos_path = PathBuf::from(paths.qap_path).join("subcircuitInfo.json");
-        let _subcircuit_infos_file_bytes = std::fs::metadata(&subcircuit_infos_path)
-            .map(|m| m.len() as usize)
-            .unwrap_or(0);
         let subcircuit_infos = crate::time_block!(
             "init.load.subcircuit_infos",
             "load",
-            vec![crate::timing::SizeInfo {
-                label: "file_bytes",
-                dims: vec![_subcircuit_infos_file_bytes]
-            },],
-            { SubcircuitInfo::read_box_from_json(subcircuit_infos_path).unwrap() }
+            file_bytes(inputs.file_bytes("subcircuitInfo.json")),
+            { inputs.subcircuit_infos()? }
         );
-
-        // Load local variables of placements (public instance + interface witness + internal witness)
-        let placement_variables_path =
-            PathBuf::from(paths.synthesizer_path).join("placementVariables.json");
-        let _placement_variables_file_bytes = std::fs::metadata(&placement_variables_path)
-            .map(|m| m.len() as usize)
-            .unwrap_or(0);
+        // Local variables of placements (public instance + interface witness + internal witness)
         let placement_variables = crate::time_block!(
             "init.load.placement_variables",
             "load",
-            vec![crate::timing::SizeInfo {
-                label: "file_bytes",
-                dims: vec![_placement_variables_file_bytes]
-            },],
-            { PlacementVariables::read_box_from_json(placement_variables_path).unwrap() }
+            file_bytes(inputs.file_bytes("placementVariables.json")),
+            { inputs.placement_variables()? }
+        );
+        // Permutation (copy constraints of the variables)
+        let permutation_raw = crate::time_block!(
+            "init.load.permutation",
+            "load",
+            file_bytes(inputs.file_bytes("permutation.json")),
+            { inputs.permutation()? }
+        );
+        let instance = crate::time_block!(
+            "init.load.instance",
+            "load",
+            file_bytes(inputs.file_bytes("instance.json")),
+            { inputs.instance()? }
         );
+        // Sigma (reference string)
+        let sigma = crate::time_block!(
+            "init.load.sigma",
+            "load",
+            file_bytes(inputs.sigma_file_bytes()),
+            { inputs.sigma()? }
+        );
+        sigma.validate_points(inputs.point_validation())?;
+
+        let (mut prover, binding) = Self::from_inputs(
+            inputs,
+            setup_params.into_owned(),
+            &subcircuit_infos,
+            &placement_variables,
+            &permutation_raw,
+            &instance,
+            sigma,
+        )?;
+        prover.quotient_engine = inputs.quotient_engine();
 
-        let witness: Witness = {
-            // Parsing the variables
-            let bXY = crate::time_block!(
-                "init.build.witness.bXY",
-                "build",
-                vec![crate::timing::SizeInfo {
-                    label: "bXY",
-                    dims: vec![m_i, s_max]
-                },],
-                { gen_bXY(&placement_variables, &subcircuit_infos, &setup_params) }
+        #[cfg(feature = "timing")]
+        {
+            let setup_params = &prover.setup_params;
+            let shape = setup_shape(setup_params)?;
+            let (n, m_i, s_max) = (shape.n, shape.m_i, shape.s_max);
+            let (_l, s_d) = (setup_params.l, setup_params.s_D);
+            crate::timing::record(
+                "init.total",
+                "init",
+                init_start.elapsed(),
+                vec![
+                    crate::timing::SizeInfo {
+                        label: "n_s_max",
+                        dims: vec![n, s_max],
+                    },
+                    crate::timing::SizeInfo {
+                        label: "m_i_s_max",
+                        dims: vec![m_i, s_max],
+                    },
+                    crate::timing::SizeInfo {
+                        label: "l",
+                        dims: vec![_l],
+                    },
+                    crate::timing::SizeInfo {
+                        label: "s_D",
+                        dims: vec![s_d],
+                    },
+                ],
             );
+        }
+
+        Ok((prover, binding))
+    }
+
+    /// Builds the prover from inputs already in memory, reading the R1CS constraints from `r1cs`.
+    pub(crate) fn from_inputs(
+        r1cs: &(impl R1csSource + ?Sized),
+        setup_params: SetupParams,
+        subcircuit_infos: &[SubcircuitInfo],
+        placement_variables: &[PlacementVariables],
+        permutation_raw: &[Permutation],
+        instance: &Instance,
+        sigma: SigmaHolder,
+    ) -> TokamakResult<(Self, Binding)> {
+        let public_instance = instance;
+        let input_digest = input_digest(
+            &setup_params,
+            subcircuit_infos,
+            placement_variables,
+            permutation_raw,
+            public_instance,
+            &sigma,
+        );
+        let shape = setup_shape(&setup_params)?;
+        validate_setup_shape(&shape)?;
+        let m_i = shape.m_i;
+        let n = shape.n;
+        let s_max = shape.s_max;
+        validate_permutation(permutation_raw, m_i, s_max)?;
+
+        let ntt_domain_size = prover_verifier_ntt_domain_size(&shape);
+        init_ntt_domain(ntt_domain_size)?;
+
+        let witness: Witness = {
+            // Parsing the variables. The witness polynomials are built with one column per
+            // placement and expanded once the columns are filled, so the `s_max` slots left
+            // empty are never evaluated. uvwXY goes first: it checks the placements against the
+            // subcircuit library before gen_bXY_sparse indexes into it.
             let (uXY, vXY, wXY) = crate::time_block!(
                 "init.build.witness.uvwXY",
                 "build",
@@ -752,14 +1256,24 @@ impl Prover {
                     dims: vec![n, s_max]
                 },],
                 {
-                    read_R1CS_gen_uvwXY(
-                        &paths.qap_path,
-                        &placement_variables,
-                        &subcircuit_infos,
+                    let (uXY, vXY, wXY) = read_R1CS_gen_uvwXY_sparse(
+                        r1cs,
+                        placement_variables,
+                        subcircuit_infos,
                         &setup_params,
-                    )
+                    )?;
+                    (uXY.to_dense(), vXY.to_dense(), wXY.to_dense())
                 }
             );
+            let bXY = crate::time_block!(
+                "init.build.witness.bXY",
+                "build",
+                vec![crate::timing::SizeInfo {
+                    label: "bXY",
+                    dims: vec![m_i, s_max]
+                },],
+                { gen_bXY_sparse(placement_variables, subcircuit_infos, &setup_params).to_dense() }
+            );
             let rXY = DensePolynomialExt::from_coeffs(
                 HostSlice::from_slice(&vec![ScalarField::zero()]),
                 1,
@@ -804,37 +1318,7 @@ impl Prover {
             lagrange_kl_xy: None,
         };
 
-        // Load permutation (copy constraints of the variables)
-        let permutation_path = PathBuf::from(paths.synthesizer_path).join("permutation.json");
-        let _permutation_file_bytes = std::fs::metadata(&permutation_path)
-            .map(|m| m.len() as usize)
-            .unwrap_or(0);
-        let permutation_raw = crate::time_block!(
-            "init.load.permutation",
-            "load",
-            vec![crate::timing::SizeInfo {
-                label: "file_bytes",
-                dims: vec![_permutation_file_bytes]
-            },],
-            { Permutation::read_box_from_json(permutation_path).unwrap() }
-        );
-
         let mut instance: InstancePolynomials = {
-            // Load instance
-            let instance_path = PathBuf::from(paths.synthesizer_path).join("instance.json");
-            let _instance_file_bytes = std::fs::metadata(&instance_path)
-                .map(|m| m.len() as usize)
-                .unwrap_or(0);
-            let _instance = crate::time_block!(
-                "init.load.instance",
-                "load",
-                vec![crate::timing::SizeInfo {
-                    label: "file_bytes",
-                    dims: vec![_instance_file_bytes]
-                },],
-                { Instance::read_from_json(instance_path).unwrap() }
-            );
-
             // Parsing the inputs
             let a_free_X = crate::time_block!(
                 "init.build.instance.a_free_X",
@@ -843,7 +1327,7 @@ impl Prover {
                     label: "a_free_X",
                     dims: vec![setup_params.l_free, 1]
                 },],
-                { _instance.gen_a_free_X(&setup_params) }
+                { public_instance.gen_a_free_X(&setup_params) }
             );
             // Fixed polynomials
             let t_n = crate::time_block!(
@@ -900,7 +1384,7 @@ impl Prover {
                     label: "s0/s1",
                     dims: vec![m_i, s_max]
                 },],
-                { Permutation::to_poly(&permutation_raw, m_i, s_max) }
+                { Permutation::to_poly(permutation_raw, m_i, s_max) }
             );
 
             InstancePolynomials {
@@ -968,7 +1452,7 @@ impl Prover {
             let mut flag_s0 = true;
             let mut flag_s1 = true;
             let mut flag_r = true;
-            for permEntry in &permutation_raw {
+            for permEntry in permutation_raw {
                 let this_wire_idx = permEntry.row;
                 let this_placement_idx = permEntry.col;
                 let next_wire_idx = permEntry.X as usize;
@@ -1018,25 +1502,6 @@ impl Prover {
             println!("Checked: Lemma 3");
         }
 
-        // Load Sigma (reference string)
-        let sigma_path = PathBuf::from(paths.setup_path).join("combined_sigma.rkyv");
-        let _sigma_file_bytes = std::fs::metadata(&sigma_path)
-            .map(|m| m.len() as usize)
-            .unwrap_or(0);
-        let sigma = crate::time_block!(
-            "init.load.sigma",
-            "load",
-            vec![crate::timing::SizeInfo {
-                label: "file_bytes",
-                dims: vec![_sigma_file_bytes]
-            },],
-            {
-                let sigma = SigmaHolder::load(&sigma_path)
-                        .expect("No reference string is found. Run the Setup first (expected combined_sigma.rkyv).");
-                sigma
-            }
-        );
-
         let mixer: Mixer = {
             let rU_X = ScalarCfg::generate_random(1)[0];
             let rU_Y = ScalarCfg::generate_random(1)[0];
@@ -1105,8 +1570,8 @@ impl Prover {
                 },],
                 {
                     sigma.sigma1().encode_O_pub_free(
-                        &placement_variables,
-                        &subcircuit_infos,
+                        placement_variables,
+                        subcircuit_infos,
                         &setup_params,
                     )
                 }
@@ -1121,8 +1586,8 @@ impl Prover {
                 },],
                 {
                     sigma.sigma1().encode_O_mid_no_zk(
-                        &placement_variables,
-                        &subcircuit_infos,
+                        placement_variables,
+                        subcircuit_infos,
                         &setup_params,
                     )
                 }
@@ -1137,8 +1602,8 @@ impl Prover {
                 },],
                 {
                     sigma.sigma1().encode_O_prv_no_zk(
-                        &placement_variables,
-                        &subcircuit_infos,
+                        placement_variables,
+                        subcircuit_infos,
                         &setup_params,
                     )
                 }
@@ -1166,32 +1631,7 @@ impl Prover {
             }
         };
 
-        #[cfg(feature = "timing")]
-        crate::timing::record(
-            "init.total",
-            "init",
-            init_start.elapsed(),
-            vec![
-                crate::timing::SizeInfo {
-                    label: "n_s_max",
-                    dims: vec![n, s_max],
-                },
-                crate::timing::SizeInfo {
-                    label: "m_i_s_max",
-                    dims: vec![m_i, s_max],
-                },
-                crate::timing::SizeInfo {
-                    label: "l",
-                    dims: vec![_l],
-                },
-                crate::timing::SizeInfo {
-                    label: "s_D",
-                    dims: vec![s_d],
-                },
-            ],
-        );
-
-        return (
+        return Ok((
             Self {
                 sigma,
                 setup_params,
@@ -1200,9 +1640,15 @@ impl Prover {
                 mixer,
                 quotients,
                 cache,
+                input_digest,
+                transcript: TranscriptKind::default(),
+                fiat_shamir: FiatShamirMode::default(),
+                public_instance: public_instance.clone(),
+                transcript_trace: None,
+                quotient_engine: QuotientEngine::default(),
             },
             binding,
-        );
+        ));
     }
 
     #[cfg(feature = "timing")]
@@ -1269,6 +1715,29 @@ impl Prover {
                 .evaluate_fused_with_domain(target_x_size, target_y_size)
             },
         ));
+        let n = self.setup_params.n;
+        results.push(benchmark_candidate_pair(
+            "prove0.q0XY.coset",
+            repeats,
+            || {
+                let mut p0XY = &(&self.witness.uXY * &self.witness.vXY) - &self.witness.wXY;
+                p0XY.div_by_vanishing_opt(n as i64, s_max as i64).0
+            },
+            |_, _| {
+                let coset = quotient_coset();
+                PolyExpr::sub(
+                    PolyExpr::mul(
+                        PolyExpr::poly(&self.witness.uXY),
+                        PolyExpr::poly(&self.witness.vXY),
+                    ),
+                    PolyExpr::poly(&self.witness.wXY),
+                )
+                .evaluate_on_coset_with_domain(2 * n, 2 * s_max, &coset, &coset)
+                .div_by_vanishing(n, s_max)
+                .0
+                .to_poly()
+            },
+        ));
         results.push(benchmark_candidate_pair(
             "prove2.Q_CX",
             repeats,
@@ -1443,6 +1912,135 @@ impl Prover {
         results
     }
 
+    /// Runs the five rounds against a fresh transcript, squeezing the challenges in the order the
+    /// verifier replays them, and assembles the proof.
+    pub fn prove_rounds(&mut self, binding: Binding) -> (Proof, Proof4Test) {
+        match self.resume_rounds(PartialProof::new(binding), |_, _| Ok::<(), Infallible>(())) {
+            Ok(result) => result,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Runs the rounds `partial` is still missing, replaying the transcript over the ones it
+    /// already holds. `after_round` is called once each of `prove0`–`prove3` completes, e.g. to
+    /// write a checkpoint, and its error aborts the run.
+    pub fn resume_rounds<E>(
+        &mut self,
+        partial: PartialProof,
+        after_round: impl FnMut(&Self, &PartialProof) -> Result<(), E>,
+    ) -> Result<(Proof, Proof4Test), E> {
+        match self.transcript {
+            TranscriptKind::Keccak => self.run_rounds(
+                TranscriptManager::<RollingKeccakTranscript>::default(),
+                partial,
+                after_round,
+            ),
+            TranscriptKind::Poseidon => self.run_rounds(
+                TranscriptManager::<PoseidonTranscript>::default(),
+                partial,
+                after_round,
+            ),
+        }
+    }
+
+    fn run_rounds<T: Transcript, E>(
+        &mut self,
+        mut manager: TranscriptManager<T>,
+        mut partial: PartialProof,
+        mut after_round: impl FnMut(&Self, &PartialProof) -> Result<(), E>,
+    ) -> Result<(Proof, Proof4Test), E> {
+        if self.transcript_trace.is_some() {
+            manager = manager.with_trace();
+        }
+        if self.fiat_shamir == FiatShamirMode::StrongV1 {
+            let [s0, s1, O_pub_fix] = self.preprocess_commitments();
+            manager.add_statement(&ProtocolStatement {
+                sigma_digest: self.sigma.sigma_verify().digest(),
+                setup_params: &self.setup_params,
+                s0: &s0,
+                s1: &s1,
+                O_pub_fix: &O_pub_fix,
+                instance: &self.public_instance,
+            });
+        }
+
+        if partial.proof0.is_none() {
+            partial.proof0 = Some(self.prove0());
+            after_round(self, &partial)?;
+        }
+        let proof0 = partial.proof0.as_ref().expect("prove0 is done");
+        let thetas = proof0.verify0_with_manager(&mut manager);
+
+        if partial.proof1.is_none() {
+            partial.proof1 = Some(self.prove1(&thetas));
+            after_round(self, &partial)?;
+        }
+        let proof1 = partial.proof1.as_ref().expect("prove1 is done");
+        let kappa0 = proof1.verify1_with_manager(&mut manager);
+
+        if partial.proof2.is_none() {
+            partial.proof2 = Some(self.prove2(&thetas, kappa0));
+            after_round(self, &partial)?;
+        }
+        let proof2 = partial.proof2.as_ref().expect("prove2 is done");
+        let (chi, zeta) = proof2.verify2_with_manager(&mut manager);
+
+        if partial.proof3.is_none() {
+            partial.proof3 = Some(self.prove3(chi, zeta));
+            after_round(self, &partial)?;
+        }
+        let proof3 = partial.proof3.as_ref().expect("prove3 is done");
+        let kappa1 = proof3.verify3_with_manager(&mut manager);
+
+        let (proof4, proof4_test) = self.prove4(proof3, &thetas, kappa0, chi, zeta, kappa1);
+
+        #[cfg(feature = "testing-mode")]
+        {
+            println!("kappa1: {}", kappa1.to_string());
+            println!("chi: {}", chi.to_string());
+        }
+
+        let PartialProof {
+            binding,
+            proof0,
+            proof1,
+            proof2,
+            proof3,
+        } = partial;
+        if self.transcript_trace.is_some() {
+            // The verifier goes on to squeeze kappa2, so the trace does too to stay comparable.
+            let [s0, s1, O_pub_fix] = self.preprocess_commitments();
+            manager.add_proof4(&proof4);
+            manager.add_binding(&binding);
+            manager.add_preprocess(&s0, &s1, &O_pub_fix);
+            manager.get_kappa2();
+            self.transcript_trace = manager.take_trace();
+        }
+
+        let proof = Proof {
+            binding,
+            proof0: proof0.expect("prove0 is done"),
+            proof1: proof1.expect("prove1 is done"),
+            proof2: proof2.expect("prove2 is done"),
+            proof3: proof3.expect("prove3 is done"),
+            proof4,
+            transcript: T::KIND,
+            fiat_shamir: self.fiat_shamir,
+        };
+        Ok((proof, proof4_test))
+    }
+
+    /// `s0`, `s1` and `O_pub_fix` as `Preprocess::gen` commits them, so that the prover binds the
+    /// strong Fiat-Shamir statement without reading the preprocess output.
+    pub fn preprocess_commitments(&self) -> [G1serde; 3] {
+        let sigma1 = self.sigma.sigma1();
+        let s0 = sigma1.encode_poly(&mut self.instance.s0XY.clone(), &self.setup_params);
+        let s1 = sigma1.encode_poly(&mut self.instance.s1XY.clone(), &self.setup_params);
+        let O_pub_fix =
+            sigma1.encode_O_pub_fix(&self.public_instance.a_pub_function, &self.setup_params);
+        [s0, s1, O_pub_fix]
+    }
+
     pub fn prove0(&mut self) -> Proof0 {
         #[cfg(feature = "timing")]
         let _total = crate::timing::SpanGuard::new(
@@ -1460,14 +2058,12 @@ impl Prover {
             ],
         );
         // Arithmetic constraints argument polynomials
-        let mut p0XY = crate::time_block!(
-            "poly.combine.prove0.p0XY",
-            "poly",
-            vec![crate::timing::SizeInfo {
-                label: "p0XY",
-                dims: vec![self.witness.uXY.x_size, self.witness.uXY.y_size]
-            },],
-            { &(&self.witness.uXY * &self.witness.vXY) - &self.witness.wXY }
+        let p0_expr = PolyExpr::sub(
+            PolyExpr::mul(
+                PolyExpr::poly(&self.witness.uXY),
+                PolyExpr::poly(&self.witness.vXY),
+            ),
+            PolyExpr::poly(&self.witness.wXY),
         );
         #[cfg(feature = "testing-mode")]
         {
@@ -1516,25 +2112,54 @@ impl Prover {
                 panic!("Evaluations of u(X,Y), v(X,Y), and w(X,Y) do not satisfy R1CS.")
             }
         }
-        (self.quotients.q0XY, self.quotients.q1XY) = crate::time_block!(
-            "poly.div_by_vanishing_opt.prove0.q0q1",
-            "poly",
-            vec![crate::timing::SizeInfo {
-                label: "vanishing",
-                dims: vec![self.setup_params.n, self.setup_params.s_max]
-            },],
-            {
-                p0XY.div_by_vanishing_opt(
-                    self.setup_params.n as i64,
-                    self.setup_params.s_max as i64,
+        (self.quotients.q0XY, self.quotients.q1XY) = match self.quotient_engine {
+            QuotientEngine::Coefficient => {
+                let mut p0XY = crate::time_block!(
+                    "poly.combine.prove0.p0XY",
+                    "poly",
+                    vec![crate::timing::SizeInfo {
+                        label: "p0XY",
+                        dims: vec![self.witness.uXY.x_size, self.witness.uXY.y_size]
+                    },],
+                    { &(&self.witness.uXY * &self.witness.vXY) - &self.witness.wXY }
+                );
+                crate::time_block!(
+                    "poly.div_by_vanishing_opt.prove0.q0q1",
+                    "poly",
+                    vec![crate::timing::SizeInfo {
+                        label: "vanishing",
+                        dims: vec![self.setup_params.n, self.setup_params.s_max]
+                    },],
+                    {
+                        p0XY.div_by_vanishing_opt(
+                            self.setup_params.n as i64,
+                            self.setup_params.s_max as i64,
+                        )
+                    }
                 )
             }
-        );
+            QuotientEngine::Coset => crate::time_block!(
+                "poly.coset_quotients.prove0.q0q1",
+                "poly",
+                vec![crate::timing::SizeInfo {
+                    label: "vanishing",
+                    dims: vec![self.setup_params.n, self.setup_params.s_max]
+                },],
+                {
+                    let (n, s_max) = (self.setup_params.n, self.setup_params.s_max);
+                    let coset = quotient_coset();
+                    let (q0XY, q1XY) = p0_expr
+                        .evaluate_on_coset_with_domain(2 * n, 2 * s_max, &coset, &coset)
+                        .div_by_vanishing(n, s_max);
+                    (q0XY.to_poly(), q1XY.to_poly())
+                }
+            ),
+        };
         #[cfg(feature = "testing-mode")]
         {
             let x_e = ScalarCfg::generate_random(1)[0];
             let y_e = ScalarCfg::generate_random(1)[0];
-            let p_0_eval = p0XY.eval(&x_e, &y_e);
+            let p_0_eval = p0_expr.eval(&x_e, &y_e);
             let q_0_eval = self.quotients.q0XY.eval(&x_e, &y_e);
             let q_1_eval = self.quotients.q1XY.eval(&x_e, &y_e);
             let t_n_eval = x_e.pow(self.setup_params.n) - ScalarField::one();
@@ -2099,67 +2724,88 @@ impl Prover {
             }
         );
 
-        let mut p_comb = crate::time_block!(
-            "poly.combine.prove2.p_comb",
-            "poly",
-            vec![crate::timing::SizeInfo {
-                label: "p_comb",
-                dims: vec![m_i, s_max]
-            },],
-            {
-                let r_gXY = PolyExpr::mul(PolyExpr::poly(&self.witness.rXY), PolyExpr::poly(&gXY));
-                let p1XY = PolyExpr::mul(
-                    PolyExpr::sub(
-                        PolyExpr::poly(&self.witness.rXY),
-                        PolyExpr::scalar(ScalarField::one()),
-                    ),
-                    PolyExpr::poly(&lagrange_KL_XY),
-                );
-                let p2XY = PolyExpr::mul_x_minus_one(PolyExpr::sub(
-                    r_gXY.clone(),
-                    PolyExpr::mul(PolyExpr::poly(&r_omegaX), PolyExpr::poly(&fXY)),
-                ));
-                let p3XY = PolyExpr::mul(
-                    PolyExpr::poly(&lagrange_K0_XY),
-                    PolyExpr::sub(
-                        r_gXY,
-                        PolyExpr::mul(PolyExpr::poly(&r_omegaX_omegaY), PolyExpr::poly(&fXY)),
-                    ),
-                );
+        let p_comb_expr = {
+            let r_gXY = PolyExpr::mul(PolyExpr::poly(&self.witness.rXY), PolyExpr::poly(&gXY));
+            let p1XY = PolyExpr::mul(
+                PolyExpr::sub(
+                    PolyExpr::poly(&self.witness.rXY),
+                    PolyExpr::scalar(ScalarField::one()),
+                ),
+                PolyExpr::poly(&lagrange_KL_XY),
+            );
+            let p2XY = PolyExpr::mul_x_minus_one(PolyExpr::sub(
+                r_gXY.clone(),
+                PolyExpr::mul(PolyExpr::poly(&r_omegaX), PolyExpr::poly(&fXY)),
+            ));
+            let p3XY = PolyExpr::mul(
+                PolyExpr::poly(&lagrange_K0_XY),
+                PolyExpr::sub(
+                    r_gXY,
+                    PolyExpr::mul(PolyExpr::poly(&r_omegaX_omegaY), PolyExpr::poly(&fXY)),
+                ),
+            );
 
-                let expr = PolyExpr::weighted_sum(vec![
-                    (ScalarField::one(), p1XY),
-                    (kappa0, p2XY),
-                    (kappa0_sq, p3XY),
-                ]);
-                #[cfg(feature = "timing")]
-                {
-                    if use_coeff_backend_for_prove2_p_comb_timing() {
-                        expr.evaluate_coeffs()
-                    } else {
-                        expr.evaluate_fused_with_domain(4 * m_i, 2 * s_max)
+            PolyExpr::weighted_sum(vec![
+                (ScalarField::one(), p1XY),
+                (kappa0, p2XY),
+                (kappa0_sq, p3XY),
+            ])
+        };
+        (self.quotients.q2XY, self.quotients.q3XY) = match self.quotient_engine {
+            QuotientEngine::Coefficient => {
+                let mut p_comb = crate::time_block!(
+                    "poly.combine.prove2.p_comb",
+                    "poly",
+                    vec![crate::timing::SizeInfo {
+                        label: "p_comb",
+                        dims: vec![m_i, s_max]
+                    },],
+                    {
+                        #[cfg(feature = "timing")]
+                        {
+                            if use_coeff_backend_for_prove2_p_comb_timing() {
+                                p_comb_expr.evaluate_coeffs()
+                            } else {
+                                p_comb_expr.evaluate_fused_with_domain(4 * m_i, 2 * s_max)
+                            }
+                        }
+                        #[cfg(not(feature = "timing"))]
+                        {
+                            p_comb_expr.evaluate_fused_with_domain(4 * m_i, 2 * s_max)
+                        }
                     }
-                }
-                #[cfg(not(feature = "timing"))]
+                );
+                crate::time_block!(
+                    "poly.div_by_vanishing_opt.prove2.qCXqCY",
+                    "poly",
+                    vec![crate::timing::SizeInfo {
+                        label: "vanishing",
+                        dims: vec![m_i, s_max]
+                    },],
+                    { p_comb.div_by_vanishing_opt(m_i as i64, s_max as i64) }
+                )
+            }
+            QuotientEngine::Coset => crate::time_block!(
+                "poly.coset_quotients.prove2.qCXqCY",
+                "poly",
+                vec![crate::timing::SizeInfo {
+                    label: "vanishing",
+                    dims: vec![m_i, s_max]
+                },],
                 {
-                    expr.evaluate_fused_with_domain(4 * m_i, 2 * s_max)
+                    let coset = quotient_coset();
+                    let (q2XY, q3XY) = p_comb_expr
+                        .evaluate_on_coset_with_domain(4 * m_i, 2 * s_max, &coset, &coset)
+                        .div_by_vanishing(m_i, s_max);
+                    (q2XY.to_poly(), q3XY.to_poly())
                 }
-            }
-        );
-        (self.quotients.q2XY, self.quotients.q3XY) = crate::time_block!(
-            "poly.div_by_vanishing_opt.prove2.qCXqCY",
-            "poly",
-            vec![crate::timing::SizeInfo {
-                label: "vanishing",
-                dims: vec![m_i, s_max]
-            },],
-            { p_comb.div_by_vanishing_opt(m_i as i64, s_max as i64) }
-        );
+            ),
+        };
         #[cfg(feature = "testing-mode")]
         {
             let x_e = ScalarCfg::generate_random(1)[0];
             let y_e = ScalarCfg::generate_random(1)[0];
-            let p_comb_eval = p_comb.eval(&x_e, &y_e);
+            let p_comb_eval = p_comb_expr.eval(&x_e, &y_e);
             let q_CX_eval = self.quotients.q2XY.eval(&x_e, &y_e);
             let q_CY_eval = self.quotients.q3XY.eval(&x_e, &y_e);
 
@@ -2168,6 +2814,7 @@ impl Prover {
             assert!(p_comb_eval.eq(&(q_CX_eval * t_mi_eval + q_CY_eval * t_smax_eval)));
             println!("Checked: combined copy-constraint quotient relation holds.")
         }
+        drop(p_comb_expr);
 
         // Adding zero-knowledge to the copy constraint argument
         let (r_D1, r_D2, g_D) = (
@@ -3513,220 +4160,127 @@ impl RollingKeccakTranscript {
     }
 }
 
+/// Absorbs the round messages and squeezes the challenges in the order the prover and the
+/// verifiers share. `TranscriptManager::new()` uses the Keccak transcript of the Solidity
+/// verifier; `TranscriptManager::<T>::default()` selects another backend. Every step is labelled
+/// so that `with_trace` can record it into a `TranscriptTrace`.
 #[derive(Clone)]
-pub struct TranscriptManager {
-    pub transcript: RollingKeccakTranscript,
+pub struct TranscriptManager<T: Transcript = RollingKeccakTranscript> {
+    pub transcript: T,
+    pub trace: Option<TranscriptTrace>,
 }
 
 impl TranscriptManager {
     pub fn new() -> Self {
         Self {
             transcript: RollingKeccakTranscript::new(),
+            trace: None,
         }
     }
+}
 
-    pub fn add_proof0(&mut self, proof: &Proof0) {
-        //println!("Adding proof0 commitments to transcript...");
-
-        // Add each field element individually to match the verifier exactly
-        // Order is critical: U_x, U_y, V_x, V_y, etc.
-        match self.transcript.commit_bls12_381_field_element(&proof.U.0.x) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit U.x: {}", e),
-        }
-
-        match self.transcript.commit_bls12_381_field_element(&proof.U.0.y) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit U.y: {}", e),
-        }
-
-        match self.transcript.commit_bls12_381_field_element(&proof.V.0.x) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit V.x: {}", e),
-        }
-
-        match self.transcript.commit_bls12_381_field_element(&proof.V.0.y) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit V.y: {}", e),
-        }
-
-        match self.transcript.commit_bls12_381_field_element(&proof.W.0.x) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit W.x: {}", e),
-        }
-
-        match self.transcript.commit_bls12_381_field_element(&proof.W.0.y) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit W.y: {}", e),
-        }
-
-        match self
-            .transcript
-            .commit_bls12_381_field_element(&proof.Q_AX.0.x)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit Q_AX.x: {}", e),
-        }
-
-        match self
-            .transcript
-            .commit_bls12_381_field_element(&proof.Q_AX.0.y)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit Q_AX.y: {}", e),
-        }
-
-        match self
-            .transcript
-            .commit_bls12_381_field_element(&proof.Q_AY.0.x)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit Q_AY.x: {}", e),
-        }
-
-        match self
-            .transcript
-            .commit_bls12_381_field_element(&proof.Q_AY.0.y)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit Q_AY.y: {}", e),
-        }
-
-        match self.transcript.commit_bls12_381_field_element(&proof.B.0.x) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit B.x: {}", e),
+impl<T: Transcript> Default for TranscriptManager<T> {
+    fn default() -> Self {
+        Self {
+            transcript: T::new(),
+            trace: None,
         }
+    }
+}
 
-        match self.transcript.commit_bls12_381_field_element(&proof.B.0.y) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit B.y: {}", e),
+impl<T: Transcript> TranscriptManager<T> {
+    pub fn add_proof0(&mut self, proof: &Proof0) {
+        // Order is critical: U, V, W, Q_AX, Q_AY, B, each as x then y
+        for (label, point) in [
+            ("U", &proof.U),
+            ("V", &proof.V),
+            ("W", &proof.W),
+            ("Q_AX", &proof.Q_AX),
+            ("Q_AY", &proof.Q_AY),
+            ("B", &proof.B),
+        ] {
+            self.absorb_g1(label, point);
         }
     }
 
     pub fn get_thetas(&mut self) -> Vec<ScalarField> {
-        //println!("Generating thetas from transcript...");
-        let thetas = self.transcript.get_challenges(3);
-
-        // Print challenges for debugging
-        /*
-        for (i, theta) in thetas.iter().enumerate() {
-            println!("Theta_{}: {}", i, hex_string(theta));
-        }
-        */
-        thetas
+        ["theta0", "theta1", "theta2"]
+            .into_iter()
+            .map(|label| self.squeeze(label))
+            .collect()
     }
 
     pub fn add_proof1(&mut self, proof: &Proof1) {
-        //println!("Adding proof1 commitments to transcript...");
-
-        match self.transcript.commit_bls12_381_field_element(&proof.R.0.x) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit R.x: {}", e),
-        }
-
-        match self.transcript.commit_bls12_381_field_element(&proof.R.0.y) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit R.y: {}", e),
-        }
+        self.absorb_g1("R", &proof.R);
     }
 
     pub fn get_kappa0(&mut self) -> ScalarField {
-        //println!("Generating kappa0 from transcript...");
-        let kappa0 = self.transcript.get_challenge();
-        //println!("Kappa0: {}", hex_string(&kappa0));
-        kappa0
+        self.squeeze("kappa0")
     }
 
     pub fn add_proof2(&mut self, proof: &Proof2) {
-        //println!("Adding proof2 commitments to transcript...");
-
-        match self
-            .transcript
-            .commit_bls12_381_field_element(&proof.Q_CX.0.x)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit Q_CX.x: {}", e),
-        }
-
-        match self
-            .transcript
-            .commit_bls12_381_field_element(&proof.Q_CX.0.y)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit Q_CX.y: {}", e),
-        }
-
-        match self
-            .transcript
-            .commit_bls12_381_field_element(&proof.Q_CY.0.x)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit Q_CY.x: {}", e),
-        }
-
-        match self
-            .transcript
-            .commit_bls12_381_field_element(&proof.Q_CY.0.y)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit Q_CY.y: {}", e),
-        }
+        self.absorb_g1("Q_CX", &proof.Q_CX);
+        self.absorb_g1("Q_CY", &proof.Q_CY);
     }
 
     pub fn get_chi_zeta(&mut self) -> (ScalarField, ScalarField) {
-        //println!("Generating chi and zeta from transcript...");
-        let chi = self.transcript.get_challenge();
-        let zeta = self.transcript.get_challenge();
-
-        //println!("Chi: {}", hex_string(&chi));
-        //println!("Zeta: {}", hex_string(&zeta));
-
+        let chi = self.squeeze("chi");
+        let zeta = self.squeeze("zeta");
         (chi, zeta)
     }
 
     pub fn add_proof3(&mut self, proof: &Proof3) {
-        //println!("Adding proof3 commitments to transcript...");
-
-        match self.transcript.commit_field_as_bytes(&proof.V_eval.0) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit V_eval: {}", e),
+        for (label, eval) in [
+            ("V_eval", &proof.V_eval),
+            ("R_eval", &proof.R_eval),
+            ("R_omegaX_eval", &proof.R_omegaX_eval),
+            ("R_omegaX_omegaY_eval", &proof.R_omegaX_omegaY_eval),
+        ] {
+            self.absorb_scalar(label, &eval.0);
         }
+    }
 
-        match self.transcript.commit_field_as_bytes(&proof.R_eval.0) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit R_eval: {}", e),
-        }
+    pub fn get_kappa1(&mut self) -> ScalarField {
+        self.squeeze("kappa1")
+    }
 
-        match self
-            .transcript
-            .commit_field_as_bytes(&proof.R_omegaX_eval.0)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit R_omegaX_eval: {}", e),
+    pub fn add_proof4(&mut self, proof: &Proof4) {
+        for (label, point) in [
+            ("Pi_X", &proof.Pi_X),
+            ("Pi_Y", &proof.Pi_Y),
+            ("M_X", &proof.M_X),
+            ("M_Y", &proof.M_Y),
+            ("N_X", &proof.N_X),
+            ("N_Y", &proof.N_Y),
+        ] {
+            self.absorb_g1(label, point);
         }
+    }
 
-        match self
-            .transcript
-            .commit_field_as_bytes(&proof.R_omegaX_omegaY_eval.0)
-        {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to commit R_omegaX_omegaY_eval: {}", e),
+    pub fn add_binding(&mut self, binding: &Binding) {
+        for (label, point) in [
+            ("A_free", &binding.A_free),
+            ("O_pub_free", &binding.O_pub_free),
+            ("O_mid", &binding.O_mid),
+            ("O_prv", &binding.O_prv),
+        ] {
+            self.absorb_g1(label, point);
         }
     }
 
-    pub fn get_kappa1(&mut self) -> ScalarField {
-        //println!("Generating kappa1 from transcript...");
-        let kappa1 = self.transcript.get_challenge();
-        //println!("Kappa1: {}", hex_string(&kappa1));
-        kappa1
-    }
-
-    // pub fn get_kappa2(&mut self) -> ScalarField {
-    //     //println!("Generating kappa2 from transcript...");
-    //     let kappa2 = self.transcript.get_challenge();
-    //     //println!("Kappa2: {}", hex_string(&kappa2));
-    //     kappa2
-    // }
+    // The preprocess commitments are passed individually because `prove` does not depend on `preprocess`.
+    pub fn add_preprocess(&mut self, s0: &G1serde, s1: &G1serde, O_pub_fix: &G1serde) {
+        self.absorb_g1("s0", s0);
+        self.absorb_g1("s1", s1);
+        self.absorb_g1("O_pub_fix", O_pub_fix);
+    }
+
+    /// Squeezes kappa2 after kappa1. The caller is expected to have absorbed Proof4, the binding
+    /// and the preprocess commitments, in that order, so that kappa2 depends on every element of
+    /// the final pairing check.
+    pub fn get_kappa2(&mut self) -> ScalarField {
+        self.squeeze("kappa2")
+    }
 }
 
 // Helper function to convert a scalar field element to a hex string
@@ -1,7 +1,12 @@
 use clap::Parser;
+use libs::group_structures::{with_msm_memory_budget, MsmMemoryBudget, PointValidation};
+use libs::iotools::binary::ArtifactEncoding;
 use libs::subcircuit_library::{resolve_subcircuit_library_path, SubcircuitLibraryArg};
 use libs::utils::check_device;
-use prove::{Proof, ProveInputPaths, Prover, TranscriptManager};
+use prove::{
+    FiatShamirMode, PartialProof, ProveInputPaths, Prover, QuotientEngine, TranscriptKind,
+    TranscriptTrace,
+};
 use std::path::PathBuf;
 use std::time::Instant;
 
@@ -22,11 +27,56 @@ struct Config {
     /// Output directory for proof.json
     #[arg(long, value_name = "PATH")]
     output: String,
+
+    /// Encoding of the proof output
+    #[arg(long, value_enum, default_value_t = ArtifactEncoding::Json)]
+    encoding: ArtifactEncoding,
+
+    /// Skip on-curve and subgroup checks of the CRS points (only for locally generated CRS)
+    #[arg(long, default_value_t = false)]
+    trusted_artifacts: bool,
+
+    /// Save the prover state to this file after initialization and each round, and resume from it
+    /// if it already exists. The file is removed once the proof is written.
+    #[arg(long, value_name = "PATH")]
+    checkpoint: Option<PathBuf>,
+
+    /// Fiat-Shamir transcript of the proof (the Solidity verifier only accepts keccak)
+    #[arg(long, value_enum, default_value_t = TranscriptKind::Keccak)]
+    transcript: TranscriptKind,
+
+    /// Bind the transcript to the CRS, preprocess, setup parameters and public instance before the
+    /// round messages (strong-v1), or absorb only the round messages (legacy, the Solidity verifier)
+    #[arg(long, value_enum, default_value_t = FiatShamirMode::Legacy)]
+    fiat_shamir: FiatShamirMode,
+
+    /// Record every transcript absorb and squeeze into this JSON file, for comparison with
+    /// `transcript_diff`
+    #[arg(long, value_name = "PATH")]
+    transcript_trace: Option<PathBuf>,
+
+    /// Cap the memory of each polynomial commitment's MSM inputs at this many MiB by committing in
+    /// tiles of rows. The commitments are the same as without a cap.
+    #[arg(long, value_name = "MIB")]
+    msm_memory_budget: Option<usize>,
+
+    /// Divide the prove0 and prove2 constraint polynomials by the vanishing polynomials on their
+    /// coefficients or on an extended coset. The proof is the same either way.
+    #[arg(long, value_enum, default_value_t = QuotientEngine::Coefficient)]
+    quotient_engine: QuotientEngine,
 }
 
 fn main() {
     let total_start = Instant::now();
     let config = Config::parse();
+    let budget = match config.msm_memory_budget {
+        Some(mib) => MsmMemoryBudget::Bytes(mib.saturating_mul(1 << 20)),
+        None => MsmMemoryBudget::Unbounded,
+    };
+    with_msm_memory_budget(budget, || run(&config, total_start));
+}
+
+fn run(config: &Config, total_start: Instant) {
     let qap_path = resolve_subcircuit_library_path(config.subcircuit_library.as_deref())
         .to_string_lossy()
         .into_owned();
@@ -36,57 +86,91 @@ fn main() {
         synthesizer_path: &config.synthesizer_stat,
         setup_path: &config.crs,
         output_path: &config.output,
+        point_validation: if config.trusted_artifacts {
+            PointValidation::Trusted
+        } else {
+            PointValidation::Checked
+        },
+        quotient_engine: config.quotient_engine,
     };
 
     check_device();
 
-    println!("Prover initialization...");
-    let (mut prover, binding) = Prover::init(&paths);
-
-    let mut manager = TranscriptManager::new();
-
-    println!("Running prove0...");
-    let proof0 = prover.prove0();
-    let thetas = proof0.verify0_with_manager(&mut manager);
-
-    println!("Running prove1...");
-    let proof1 = prover.prove1(&thetas);
-    let kappa0 = proof1.verify1_with_manager(&mut manager);
-
-    println!("Running prove2...");
-    let proof2 = prover.prove2(&thetas, kappa0);
-    let (chi, zeta) = proof2.verify2_with_manager(&mut manager);
-
-    println!("Running prove3...");
-    let proof3 = prover.prove3(chi, zeta);
-    let kappa1 = proof3.verify3_with_manager(&mut manager);
+    let (mut prover, partial) = match &config.checkpoint {
+        Some(path) if path.exists() => {
+            println!("Resuming the prover from {}...", path.display());
+            Prover::resume_from_file(&paths, path)
+        }
+        _ => {
+            println!("Prover initialization...");
+            Prover::init(&paths).map(|(mut prover, binding)| {
+                prover.transcript = config.transcript;
+                prover.fiat_shamir = config.fiat_shamir;
+                (prover, PartialProof::new(binding))
+            })
+        }
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("Prover initialization failed: {err}");
+        std::process::exit(1);
+    });
+    if prover.transcript != config.transcript {
+        eprintln!(
+            "The checkpoint was written with the {:?} transcript, not {:?}",
+            prover.transcript, config.transcript
+        );
+        std::process::exit(1);
+    }
+    if prover.fiat_shamir != config.fiat_shamir {
+        eprintln!(
+            "The checkpoint was written in the {:?} Fiat-Shamir mode, not {:?}",
+            prover.fiat_shamir, config.fiat_shamir
+        );
+        std::process::exit(1);
+    }
+    if config.transcript_trace.is_some() {
+        prover.transcript_trace = Some(TranscriptTrace::new(prover.transcript));
+    }
 
-    println!("Running prove4...");
-    let (proof4, proof4_test) = prover.prove4(&proof3, &thetas, kappa0, chi, zeta, kappa1);
+    let checkpoint = config.checkpoint.as_deref();
+    let saved = match checkpoint {
+        Some(path) if partial.rounds_done() == 0 => prover.write_checkpoint(&partial, path),
+        _ => Ok(()),
+    };
+    let (proof, proof4_test) = saved
+        .and_then(|_| {
+            println!("Running prove{}...", partial.rounds_done());
+            prover.resume_rounds(partial, |prover, partial| {
+                if let Some(path) = checkpoint {
+                    prover.write_checkpoint(partial, path)?;
+                }
+                println!("Running prove{}...", partial.rounds_done());
+                Ok(())
+            })
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("Writing the prover checkpoint failed: {err}");
+            std::process::exit(1);
+        });
     #[cfg(not(feature = "testing-mode"))]
     let _ = &proof4_test;
 
-    let proof = Proof {
-        binding,
-        proof0,
-        proof1,
-        proof2,
-        proof3,
-        proof4,
-    };
-
-    println!("Writing the proof into JSON (formatted for Solidity verifier)...");
-    let formatted_proof = proof.convert_format_for_solidity_verifier();
-    let output_path = PathBuf::from(paths.output_path).join("proof.json");
-    formatted_proof.write_into_json(output_path).unwrap();
+    println!("Writing the proof ({:?} encoding)...", config.encoding);
+    proof
+        .write_into_dir(paths.output_path, config.encoding)
+        .unwrap();
+    if let Some(path) = &config.checkpoint {
+        let _ = std::fs::remove_file(path);
+    }
+    if let (Some(path), Some(trace)) = (&config.transcript_trace, &prover.transcript_trace) {
+        println!("Writing the transcript trace to {}...", path.display());
+        trace.write_into_json(path.clone()).unwrap();
+    }
 
     #[cfg(feature = "testing-mode")]
     {
         let test_output_path = PathBuf::from(paths.output_path).join("proof4_test.json");
         proof4_test.write_into_json(test_output_path).unwrap();
-
-        println!("kappa1: {}", kappa1.to_string());
-        println!("chi: {}", chi.to_string());
     }
 
     let total_elapsed_secs = total_start.elapsed().as_secs_f64();
@@ -0,0 +1,174 @@
+use crate::{FiatShamirMode, Proof, ProverBuilder, SigmaHolder, TranscriptKind};
+use icicle_runtime::Device;
+use libs::compute_backend::{compute_backend, with_compute_backend};
+use libs::error::{TokamakError, TokamakResult};
+use libs::group_structures::{msm_memory_budget, with_msm_memory_budget, PointValidation};
+use libs::iotools::inputs::CircuitInputs;
+use libs::iotools::{Instance, Permutation, PlacementVariables, SetupParams, SubcircuitInfo};
+use libs::utils::{
+    init_ntt_domain, load_setup_params_from_qap_path, prover_verifier_ntt_domain_size, setup_shape,
+    validate_setup_shape,
+};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+
+/// The circuit and synthesizer inputs of one proof run by a [`ProverPool`]. The reference string
+/// and the R1CS constraints are shared by the pool.
+pub struct ProverJob {
+    pub setup_params: SetupParams,
+    pub subcircuit_infos: Box<[SubcircuitInfo]>,
+    pub placement_variables: Box<[PlacementVariables]>,
+    pub permutation: Box<[Permutation]>,
+    pub instance: Instance,
+    pub transcript: TranscriptKind,
+    pub fiat_shamir: FiatShamirMode,
+}
+
+impl ProverJob {
+    /// Reads the job from `inputs`, e.g. the `ProveInputPaths` of one synthesizer output, with
+    /// the default transcript and Fiat-Shamir mode.
+    pub fn load<I: CircuitInputs + ?Sized>(inputs: &I) -> TokamakResult<Self> {
+        Ok(Self {
+            setup_params: inputs.setup_params()?.into_owned(),
+            subcircuit_infos: inputs.subcircuit_infos()?.into_owned().into(),
+            placement_variables: inputs.placement_variables()?.into_owned().into(),
+            permutation: inputs.permutation()?.into_owned().into(),
+            instance: inputs.instance()?.into_owned(),
+            transcript: TranscriptKind::default(),
+            fiat_shamir: FiatShamirMode::default(),
+        })
+    }
+}
+
+/// Proves independent instances in parallel on CPU worker threads of one process.
+///
+/// `init_ntt_domain` and the icicle device are process-wide, so two `Prover`s may only run side
+/// by side if neither of them resizes the NTT domain. The pool initializes the domain once for the
+/// shape of the subcircuit library and rejects jobs that would need a larger one. The reference
+/// string is validated once and shared by every worker.
+pub struct ProverPool {
+    qap_path: String,
+    sigma: SigmaHolder,
+    ntt_domain_size: usize,
+    workers: usize,
+}
+
+impl ProverPool {
+    /// Loads the setup parameters from `qap_path`, checks the points of `sigma` unless
+    /// `point_validation` is `Trusted` and initializes the NTT domain of the CPU backend.
+    /// `workers` is clamped to at least one.
+    pub fn new(
+        qap_path: &str,
+        sigma: SigmaHolder,
+        point_validation: PointValidation,
+        workers: usize,
+    ) -> TokamakResult<Self> {
+        let setup_params = load_setup_params_from_qap_path(qap_path)?;
+        let shape = setup_shape(&setup_params)?;
+        validate_setup_shape(&shape)?;
+        let ntt_domain_size = prover_verifier_ntt_domain_size(&shape);
+        sigma.validate_points(point_validation)?;
+
+        // The domain belongs to the device of the initializing thread, so it is set up on the CPU
+        // like the workers rather than on whatever device the caller selected.
+        thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    use_cpu_device();
+                    init_ntt_domain(ntt_domain_size)
+                })
+                .join()
+                .expect("NTT domain initialization panicked")
+        })?;
+
+        Ok(Self {
+            qap_path: qap_path.to_string(),
+            sigma,
+            ntt_domain_size,
+            workers: workers.max(1),
+        })
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Proves every job and returns the results in the order of `jobs`.
+    ///
+    /// Jobs are pulled from the iterator only as workers free up, so at most `2 * workers` of them
+    /// are held in memory at once: one running per worker and one waiting per worker. Pass a lazy
+    /// iterator, e.g. one that calls [`ProverJob::load`], to bound memory for long batches. The
+    /// workers use the caller's `msm_memory_budget` and `compute_backend`.
+    pub fn prove_all<J>(&self, jobs: J) -> Vec<TokamakResult<Proof>>
+    where
+        J: IntoIterator<Item = ProverJob>,
+    {
+        let (job_sender, job_receiver) = mpsc::sync_channel::<(usize, ProverJob)>(self.workers);
+        let job_receiver = Mutex::new(job_receiver);
+        let (result_sender, result_receiver) = mpsc::channel();
+        let budget = msm_memory_budget();
+        let backend = compute_backend();
+
+        thread::scope(|scope| {
+            for _ in 0..self.workers {
+                let job_receiver = &job_receiver;
+                let result_sender = result_sender.clone();
+                scope.spawn(move || {
+                    use_cpu_device();
+                    loop {
+                        let next = job_receiver.lock().unwrap().recv();
+                        let Ok((index, job)) = next else {
+                            break;
+                        };
+                        let result = with_compute_backend(backend, || {
+                            with_msm_memory_budget(budget, || self.prove_job(job))
+                        });
+                        let _ = result_sender.send((index, result));
+                    }
+                });
+            }
+            drop(result_sender);
+
+            for indexed in jobs.into_iter().enumerate() {
+                if job_sender.send(indexed).is_err() {
+                    break;
+                }
+            }
+            drop(job_sender);
+        });
+
+        let mut results = result_receiver.into_iter().collect::<Vec<_>>();
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    fn prove_job(&self, job: ProverJob) -> TokamakResult<Proof> {
+        let shape = setup_shape(&job.setup_params)?;
+        let ntt_domain_size = prover_verifier_ntt_domain_size(&shape);
+        if ntt_domain_size > self.ntt_domain_size {
+            return Err(TokamakError::Shape(format!(
+                "the job needs an NTT domain of {ntt_domain_size}, but the pool was initialized \
+                 for {}",
+                self.ntt_domain_size
+            )));
+        }
+        ProverBuilder::new(&self.qap_path)
+            .setup_params(job.setup_params)
+            .subcircuit_infos(job.subcircuit_infos)
+            .placement_variables(job.placement_variables)
+            .permutation(job.permutation)
+            .instance(job.instance)
+            .sigma(self.sigma.clone())
+            // Checked once in `new`.
+            .point_validation(PointValidation::Trusted)
+            .transcript(job.transcript)
+            .fiat_shamir(job.fiat_shamir)
+            .prove()
+    }
+}
+
+// The icicle device is selected per thread.
+fn use_cpu_device() {
+    icicle_runtime::set_device(&Device::new("CPU", 0)).expect("Failed to set CPU device");
+}
@@ -1,45 +1,225 @@
-use std::io;
+use std::io::{self, BufWriter, Write};
 use std::path::Path;
 
-use libs::bivariate_polynomial::DensePolynomialExt;
-use libs::group_structures::G1serde;
+use libs::bivariate_polynomial::{DensePolynomialExt, SparsePolynomialExt};
+use libs::error::TokamakResult;
+use libs::group_structures::{
+    G1serde, PartialSigma1Verify, PointValidation, Sigma, Sigma2, SigmaVerify,
+};
+use libs::iotools::compressed::{decompress_sigma_bytes, is_compressed_sigma};
 use libs::iotools::{
-    ArchivedSigma1Rkyv, ArchivedSigmaRkyv, HexString, PlacementVariables, SetupParams, SigmaRkyv,
-    SubcircuitInfo,
+    ArchivedG1SerdeRkyv, ArchivedSigma1Rkyv, ArchivedSigmaRkyv, HexString, PlacementVariables,
+    SetupParams, SigmaRkyv, SubcircuitInfo,
 };
 use memmap2::Mmap;
+use rkyv::ser::serializers::{
+    AllocScratch, CompositeSerializer, FallbackScratch, HeapScratch, SharedSerializeMap,
+    WriteSerializer,
+};
+use rkyv::ser::Serializer;
+use rkyv::AlignedVec;
 use std::fs::File;
+use std::ops::Deref;
+use std::sync::Arc;
+use tiny_keccak::Keccak;
 
+/// Cheap to clone: clones share the same mapped or decompressed archive.
+#[derive(Clone)]
 pub struct SigmaHolder {
-    inner: SigmaZeroCopy,
+    inner: Arc<SigmaZeroCopy>,
 }
 
 pub struct SigmaZeroCopy {
-    mmap: Mmap,
+    bytes: SigmaBytes,
+    // Set when every point was already checked on load, by decompression.
+    points_checked: bool,
+}
+
+// An uncompressed archive is read in place; a compressed one is decompressed once on load into an
+// anonymous temporary file, which is then mapped like an uncompressed one.
+enum SigmaBytes {
+    Mapped(Mmap),
+    Owned(AlignedVec),
+}
+
+impl Deref for SigmaBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            SigmaBytes::Mapped(mmap) => mmap,
+            SigmaBytes::Owned(bytes) => bytes,
+        }
+    }
 }
 
 impl SigmaZeroCopy {
     pub fn load(path: &Path) -> std::io::Result<Self> {
         let file = File::open(path)?;
         let mmap = unsafe { Mmap::map(&file)? };
+        if is_compressed_sigma(&mmap) {
+            // Decompression validates the archive and every point, so neither is checked again.
+            let sigma = decompress_sigma_bytes(&mmap)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            drop(mmap);
+            return Ok(Self {
+                bytes: SigmaBytes::Mapped(archive_to_temp_file(&sigma)?),
+                points_checked: true,
+            });
+        }
         rkyv::check_archived_root::<SigmaRkyv>(&mmap).map_err(|err| {
             io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("Invalid sigma archive: {err:?}"),
             )
         })?;
-        Ok(Self { mmap })
+        Ok(Self {
+            bytes: SigmaBytes::Mapped(mmap),
+            points_checked: false,
+        })
+    }
+
+    pub fn from_sigma(sigma: &Sigma) -> std::io::Result<Self> {
+        let archived = SigmaRkyv::from_sigma(sigma);
+        let bytes = rkyv::to_bytes::<_, 256>(&archived).map_err(io::Error::other)?;
+        Ok(Self {
+            bytes: SigmaBytes::Owned(bytes),
+            points_checked: false,
+        })
     }
 
     pub fn sigma(&self) -> &ArchivedSigmaRkyv {
-        // Safe because we validated the archive on load and the mmap lives with self.
-        unsafe { rkyv::archived_root::<SigmaRkyv>(&self.mmap) }
+        // Safe because the archive was validated, or written by us, on load and the bytes live
+        // with self.
+        unsafe { rkyv::archived_root::<SigmaRkyv>(&self.bytes) }
+    }
+}
+
+// Archives `sigma` into an unnamed temporary file, removed by the OS once the map is dropped, so
+// the decompressed table is paged from disk instead of held on the heap.
+fn archive_to_temp_file(sigma: &SigmaRkyv) -> io::Result<Mmap> {
+    let file = tempfile::tempfile()?;
+    let mut serializer = CompositeSerializer::new(
+        WriteSerializer::new(BufWriter::new(&file)),
+        FallbackScratch::<HeapScratch<256>, AllocScratch>::default(),
+        SharedSerializeMap::new(),
+    );
+    serializer
+        .serialize_value(sigma)
+        .map_err(|err| io::Error::other(format!("{err:?}")))?;
+    let (writer, _, _) = serializer.into_components();
+    writer.into_inner().flush()?;
+    unsafe { Mmap::map(&file) }
+}
+
+fn hash_g1_points(hasher: &mut Keccak, points: &[ArchivedG1SerdeRkyv]) {
+    hasher.update(&(points.len() as u64).to_be_bytes());
+    for point in points {
+        hasher.update(&point.x);
+        hasher.update(&point.y);
     }
 }
 
 impl SigmaHolder {
     pub fn load(path: &Path) -> std::io::Result<Self> {
-        SigmaZeroCopy::load(path).map(|inner| SigmaHolder { inner })
+        SigmaZeroCopy::load(path).map(|inner| SigmaHolder {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Archives an in-memory reference string, e.g. one produced by a setup run in the same
+    /// process.
+    pub fn from_sigma(sigma: &Sigma) -> std::io::Result<Self> {
+        SigmaZeroCopy::from_sigma(sigma).map(|inner| SigmaHolder {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Checks every point of the reference string unless `validation` is `Trusted` or the points
+    /// were already checked while decompressing it.
+    pub fn validate_points(&self, validation: PointValidation) -> TokamakResult<()> {
+        if self.inner.points_checked {
+            return Ok(());
+        }
+        self.inner.sigma().validate_points(validation)
+    }
+
+    /// Keccak-256 of every point of the reference string, with the length of each sigma_1 table
+    /// and row, so two strings that differ anywhere get different fingerprints.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let sigma = self.inner.sigma();
+        let sigma_1 = &sigma.sigma_1;
+        let sigma_2 = &sigma.sigma_2;
+        let mut hasher = Keccak::new_keccak256();
+        for point in [
+            &sigma.G,
+            &sigma.lagrange_KL,
+            &sigma_1.x,
+            &sigma_1.y,
+            &sigma_1.delta,
+            &sigma_1.eta,
+        ] {
+            hasher.update(&point.x);
+            hasher.update(&point.y);
+        }
+        hash_g1_points(&mut hasher, &sigma_1.xy_powers);
+        hash_g1_points(&mut hasher, &sigma_1.gamma_inv_o_inst);
+        hash_g1_points(&mut hasher, &sigma_1.delta_inv_alpha4_xj_tx);
+        for rows in [
+            &sigma_1.eta_inv_li_o_inter_alpha4_kj,
+            &sigma_1.delta_inv_li_o_prv,
+            &sigma_1.delta_inv_alphak_xh_tx,
+            &sigma_1.delta_inv_alphak_yi_ty,
+        ] {
+            hasher.update(&(rows.len() as u64).to_be_bytes());
+            for row in rows.iter() {
+                hash_g1_points(&mut hasher, row);
+            }
+        }
+        for point in [
+            &sigma.H,
+            &sigma_2.alpha,
+            &sigma_2.alpha2,
+            &sigma_2.alpha3,
+            &sigma_2.alpha4,
+            &sigma_2.gamma,
+            &sigma_2.delta,
+            &sigma_2.eta,
+            &sigma_2.x,
+            &sigma_2.y,
+        ] {
+            hasher.update(&point.x);
+            hasher.update(&point.y);
+        }
+        let mut digest = [0u8; 32];
+        hasher.finalize(&mut digest);
+        digest
+    }
+
+    /// The points `sigma_verify.json` holds, extracted from the combined reference string.
+    pub fn sigma_verify(&self) -> SigmaVerify {
+        let sigma = self.inner.sigma();
+        let sigma_2 = &sigma.sigma_2;
+        SigmaVerify {
+            G: sigma.G.to_g1serde(),
+            H: sigma.H.to_g2serde(),
+            sigma_1: PartialSigma1Verify {
+                x: sigma.sigma_1.x.to_g1serde(),
+                y: sigma.sigma_1.y.to_g1serde(),
+            },
+            sigma_2: Sigma2 {
+                alpha: sigma_2.alpha.to_g2serde(),
+                alpha2: sigma_2.alpha2.to_g2serde(),
+                alpha3: sigma_2.alpha3.to_g2serde(),
+                alpha4: sigma_2.alpha4.to_g2serde(),
+                gamma: sigma_2.gamma.to_g2serde(),
+                delta: sigma_2.delta.to_g2serde(),
+                eta: sigma_2.eta.to_g2serde(),
+                x: sigma_2.x.to_g2serde(),
+                y: sigma_2.y.to_g2serde(),
+            },
+            lagrange_KL: sigma.lagrange_KL.to_g1serde(),
+        }
     }
 
     pub fn sigma1(&self) -> Sigma1Handle<'_> {
@@ -54,6 +234,10 @@ impl<'a> Sigma1Handle<'a> {
         self.0.encode_poly(poly, params)
     }
 
+    pub fn encode_sparse_poly(&self, poly: &SparsePolynomialExt, params: &SetupParams) -> G1serde {
+        self.0.encode_sparse_poly(poly, params)
+    }
+
     pub fn encode_poly_timed(
         &self,
         poly: &mut DensePolynomialExt,
@@ -0,0 +1,423 @@
+use crate::{hex_encode, RollingKeccakTranscript, TranscriptManager};
+use clap::ValueEnum;
+use icicle_bls12_381::curve::{BaseField, ScalarField};
+use icicle_core::hash::{HashConfig, Hasher};
+use icicle_core::poseidon::Poseidon;
+use icicle_core::traits::FieldImpl;
+use icicle_runtime::memory::HostSlice;
+use libs::group_structures::G1serde;
+pub use libs::iotools::fiat_shamir::{FiatShamirMode, FIAT_SHAMIR_PROTOCOL_TAG};
+use libs::iotools::{HexString, Instance, SetupParams};
+use libs::{impl_read_from_json, impl_write_into_json};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Fiat-Shamir transcript that `TranscriptManager` absorbs the round messages into and squeezes
+/// the verifier challenges from.
+pub trait Transcript: Clone {
+    const KIND: TranscriptKind;
+
+    fn new() -> Self;
+    fn absorb_g1(&mut self, point: &G1serde);
+    fn absorb_scalar(&mut self, scalar: &ScalarField);
+    /// Squeezes one challenge, never zero.
+    fn squeeze_challenge(&mut self) -> ScalarField;
+    /// The raw state, recorded before and after every step of a `TranscriptTrace`.
+    fn state(&self) -> Vec<u8>;
+
+    fn squeeze_challenges(&mut self, count: usize) -> Vec<ScalarField> {
+        (0..count).map(|_| self.squeeze_challenge()).collect()
+    }
+}
+
+/// Which transcript a proof was produced with. Recorded in the proof file, and selectable from
+/// the prover CLI with `--transcript`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptKind {
+    /// Rolling Keccak-256 with the memory layout of the Solidity verifier
+    #[default]
+    Keccak,
+    /// Poseidon sponge over the BLS12-381 scalar field, for verifying inside other circuits
+    Poseidon,
+}
+
+impl TranscriptKind {
+    /// Byte identifying the transcript in binary artifacts.
+    pub fn tag(&self) -> u8 {
+        match self {
+            TranscriptKind::Keccak => 0,
+            TranscriptKind::Poseidon => 1,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(TranscriptKind::Keccak),
+            1 => Some(TranscriptKind::Poseidon),
+            _ => None,
+        }
+    }
+}
+
+/// The statement a strong Fiat-Shamir transcript is bound to. The prover and the verifier build
+/// it from their own copies of the inputs; any difference changes every challenge.
+pub struct ProtocolStatement<'a> {
+    /// `SigmaVerify::digest` of the reference string
+    pub sigma_digest: ScalarField,
+    pub setup_params: &'a SetupParams,
+    pub s0: &'a G1serde,
+    pub s1: &'a G1serde,
+    pub O_pub_fix: &'a G1serde,
+    pub instance: &'a Instance,
+}
+
+fn protocol_tag() -> ScalarField {
+    let mut le_bytes = [0u8; 32];
+    for (byte, tag_byte) in le_bytes
+        .iter_mut()
+        .zip(FIAT_SHAMIR_PROTOCOL_TAG.iter().rev())
+    {
+        *byte = *tag_byte;
+    }
+    ScalarField::from_bytes_le(&le_bytes)
+}
+
+// A dimension or length as a scalar, all 64 bits of it.
+fn length_scalar(len: usize) -> ScalarField {
+    let mut le_bytes = [0u8; 32];
+    le_bytes[..8].copy_from_slice(&(len as u64).to_le_bytes());
+    ScalarField::from_bytes_le(&le_bytes)
+}
+
+impl<T: Transcript> TranscriptManager<T> {
+    /// Absorbs the protocol tag, the reference string digest, the setup parameters in declaration
+    /// order, `s0`, `s1`, `O_pub_fix`, and `a_pub_user`, `a_pub_block` and `a_pub_function`, each
+    /// preceded by its length. Called before `add_proof0` in `FiatShamirMode::StrongV1`.
+    pub fn add_statement(&mut self, statement: &ProtocolStatement) {
+        self.absorb_scalar("protocol_tag", &protocol_tag());
+        self.absorb_scalar("sigma_digest", &statement.sigma_digest);
+        let params = statement.setup_params;
+        for (name, dim) in [
+            ("l_free", params.l_free),
+            ("l", params.l),
+            ("l_user_out", params.l_user_out),
+            ("l_user", params.l_user),
+            ("l_D", params.l_D),
+            ("m_D", params.m_D),
+            ("n", params.n),
+            ("s_D", params.s_D),
+            ("s_max", params.s_max),
+        ] {
+            self.absorb_scalar(name, &length_scalar(dim));
+        }
+        self.absorb_g1("s0", statement.s0);
+        self.absorb_g1("s1", statement.s1);
+        self.absorb_g1("O_pub_fix", statement.O_pub_fix);
+        let instance = statement.instance;
+        for (name, values) in [
+            ("a_pub_user", &instance.a_pub_user),
+            ("a_pub_block", &instance.a_pub_block),
+            ("a_pub_function", &instance.a_pub_function),
+        ] {
+            self.absorb_public_values(name, values);
+        }
+    }
+
+    fn absorb_public_values(&mut self, name: &str, values: &[HexString]) {
+        self.absorb_scalar(&format!("{name}.len"), &length_scalar(values.len()));
+        for (i, value) in values.iter().enumerate() {
+            self.absorb_scalar(&format!("{name}[{i}]"), &ScalarField::from_hex(value));
+        }
+    }
+}
+
+impl Transcript for RollingKeccakTranscript {
+    const KIND: TranscriptKind = TranscriptKind::Keccak;
+
+    fn new() -> Self {
+        RollingKeccakTranscript::new()
+    }
+
+    // Each coordinate is committed as a 16-byte and a 32-byte word, so neither call can fail.
+    fn absorb_g1(&mut self, point: &G1serde) {
+        self.commit_g1_point(point)
+            .expect("G1 coordinates are split into 32-byte words");
+    }
+
+    fn absorb_scalar(&mut self, scalar: &ScalarField) {
+        self.commit_field_as_bytes(scalar)
+            .expect("scalars are 32 bytes");
+    }
+
+    fn squeeze_challenge(&mut self) -> ScalarField {
+        self.get_challenge()
+    }
+
+    // state_0 || state_1 || the big-endian challenge counter
+    fn state(&self) -> Vec<u8> {
+        let mut state = Vec::with_capacity(68);
+        state.extend_from_slice(&self.state_part_0);
+        state.extend_from_slice(&self.state_part_1);
+        state.extend_from_slice(&self.challenge_counter.to_be_bytes());
+        state
+    }
+}
+
+/// Poseidon (width 3) sponge over the BLS12-381 scalar field. Every absorbed element replaces
+/// the state with `Poseidon_absorb(state, element)`, and the `i`-th challenge is
+/// `Poseidon_squeeze(state, i)`; the two permutations differ in their domain tag. A G1 coordinate
+/// does not fit in a scalar, so it is absorbed as its low and high 192-bit halves.
+#[derive(Clone)]
+pub struct PoseidonTranscript {
+    state: ScalarField,
+    challenge_counter: u32,
+}
+
+const POSEIDON_WIDTH: u32 = 3;
+const ABSORB_DOMAIN_TAG: u32 = 1;
+const SQUEEZE_DOMAIN_TAG: u32 = 2;
+const HALF_BASE_FIELD_LEN: usize = 24;
+
+thread_local! {
+    // Creating a hasher loads the round constants, so each thread keeps one per domain tag.
+    static POSEIDON_HASHERS: (Hasher, Hasher) = (
+        poseidon_hasher(ABSORB_DOMAIN_TAG),
+        poseidon_hasher(SQUEEZE_DOMAIN_TAG),
+    );
+}
+
+fn poseidon_hasher(domain_tag: u32) -> Hasher {
+    Poseidon::new::<ScalarField>(POSEIDON_WIDTH, Some(&ScalarField::from_u32(domain_tag)))
+        .expect("failed to create the Poseidon hasher")
+}
+
+fn poseidon(hasher: &Hasher, inputs: [ScalarField; 2]) -> ScalarField {
+    let mut output = [ScalarField::zero()];
+    hasher
+        .hash(
+            HostSlice::from_slice(&inputs),
+            &HashConfig::default(),
+            HostSlice::from_mut_slice(&mut output),
+        )
+        .expect("Poseidon hashing failed");
+    output[0]
+}
+
+fn base_field_halves(element: &BaseField) -> [ScalarField; 2] {
+    let le_bytes = element.to_bytes_le();
+    let mut halves = [ScalarField::zero(); 2];
+    for (half, chunk) in halves.iter_mut().zip(le_bytes.chunks(HALF_BASE_FIELD_LEN)) {
+        let mut bytes = [0u8; 32];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        *half = ScalarField::from_bytes_le(&bytes);
+    }
+    halves
+}
+
+impl PoseidonTranscript {
+    fn absorb(&mut self, element: ScalarField) {
+        self.state = POSEIDON_HASHERS.with(|(absorb, _)| poseidon(absorb, [self.state, element]));
+    }
+}
+
+impl Transcript for PoseidonTranscript {
+    const KIND: TranscriptKind = TranscriptKind::Poseidon;
+
+    fn new() -> Self {
+        Self {
+            state: ScalarField::zero(),
+            challenge_counter: 0,
+        }
+    }
+
+    fn absorb_g1(&mut self, point: &G1serde) {
+        for coordinate in [&point.0.x, &point.0.y] {
+            for half in base_field_halves(coordinate) {
+                self.absorb(half);
+            }
+        }
+    }
+
+    fn absorb_scalar(&mut self, scalar: &ScalarField) {
+        self.absorb(*scalar);
+    }
+
+    fn squeeze_challenge(&mut self) -> ScalarField {
+        let counter = ScalarField::from_u32(self.challenge_counter);
+        self.challenge_counter += 1;
+        let challenge =
+            POSEIDON_HASHERS.with(|(_, squeeze)| poseidon(squeeze, [self.state, counter]));
+        if challenge == ScalarField::zero() {
+            return ScalarField::one();
+        }
+        challenge
+    }
+
+    // The big-endian state scalar || the big-endian challenge counter
+    fn state(&self) -> Vec<u8> {
+        let mut state = be_bytes(&self.state);
+        state.extend_from_slice(&self.challenge_counter.to_be_bytes());
+        state
+    }
+}
+
+// ===== TRACE =====
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceOp {
+    Absorb,
+    Squeeze,
+}
+
+/// One absorb or squeeze. Byte strings are `0x`-prefixed hex: `bytes` is the absorbed element
+/// (a G1 point as big-endian `x || y`, a scalar as 32 big-endian bytes) or the squeezed
+/// challenge, and the states are `Transcript::state`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub op: TraceOp,
+    pub label: String,
+    pub bytes: String,
+    pub state_before: String,
+    pub state_after: String,
+}
+
+/// Every step a `TranscriptManager` took while tracing was enabled, for comparison with a trace
+/// exported by another implementation of the transcript.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptTrace {
+    pub transcript: TranscriptKind,
+    pub steps: Vec<TraceStep>,
+}
+
+impl_read_from_json!(TranscriptTrace);
+impl_write_into_json!(TranscriptTrace);
+
+/// The first step at which two traces disagree. A side is `None` when its trace ended earlier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceDivergence {
+    pub step: usize,
+    pub left: Option<TraceStep>,
+    pub right: Option<TraceStep>,
+    /// Names of the differing fields, or empty when one trace ended.
+    pub fields: Vec<&'static str>,
+}
+
+impl TranscriptTrace {
+    pub fn new(transcript: TranscriptKind) -> Self {
+        Self {
+            transcript,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Compares the traces step by step. Hex strings are compared case-insensitively, and labels
+    /// are skipped when `ignore_labels` is set, e.g. for an implementation that names the
+    /// commitments differently.
+    pub fn first_divergence(&self, other: &Self, ignore_labels: bool) -> Option<TraceDivergence> {
+        let len = self.steps.len().max(other.steps.len());
+        (0..len).find_map(|step| {
+            let (left, right) = match (self.steps.get(step), other.steps.get(step)) {
+                (Some(left), Some(right)) => (left, right),
+                (left, right) => {
+                    return Some(TraceDivergence {
+                        step,
+                        left: left.cloned(),
+                        right: right.cloned(),
+                        fields: Vec::new(),
+                    })
+                }
+            };
+            let mut fields = Vec::new();
+            if left.op != right.op {
+                fields.push("op");
+            }
+            if !ignore_labels && left.label != right.label {
+                fields.push("label");
+            }
+            for (name, lhs, rhs) in [
+                ("bytes", &left.bytes, &right.bytes),
+                ("state_before", &left.state_before, &right.state_before),
+                ("state_after", &left.state_after, &right.state_after),
+            ] {
+                if !lhs.eq_ignore_ascii_case(rhs) {
+                    fields.push(name);
+                }
+            }
+            (!fields.is_empty()).then(|| TraceDivergence {
+                step,
+                left: Some(left.clone()),
+                right: Some(right.clone()),
+                fields,
+            })
+        })
+    }
+}
+
+fn be_bytes<F: FieldImpl>(field: &F) -> Vec<u8> {
+    let mut bytes = field.to_bytes_le();
+    bytes.reverse();
+    bytes
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex_encode(bytes))
+}
+
+impl<T: Transcript> TranscriptManager<T> {
+    /// Records every following absorb and squeeze into `trace`.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(TranscriptTrace::new(T::KIND));
+        self
+    }
+
+    pub fn take_trace(&mut self) -> Option<TranscriptTrace> {
+        self.trace.take()
+    }
+
+    pub fn absorb_g1(&mut self, label: &str, point: &G1serde) {
+        let state_before = self.trace.as_ref().map(|_| self.transcript.state());
+        self.transcript.absorb_g1(point);
+        if let Some(state_before) = state_before {
+            let mut bytes = be_bytes(&point.0.x);
+            bytes.extend(be_bytes(&point.0.y));
+            self.record(TraceOp::Absorb, label, &bytes, &state_before);
+        }
+    }
+
+    pub fn absorb_scalar(&mut self, label: &str, scalar: &ScalarField) {
+        let state_before = self.trace.as_ref().map(|_| self.transcript.state());
+        self.transcript.absorb_scalar(scalar);
+        if let Some(state_before) = state_before {
+            self.record(TraceOp::Absorb, label, &be_bytes(scalar), &state_before);
+        }
+    }
+
+    pub fn squeeze(&mut self, label: &str) -> ScalarField {
+        let state_before = self.trace.as_ref().map(|_| self.transcript.state());
+        let challenge = self.transcript.squeeze_challenge();
+        if let Some(state_before) = state_before {
+            self.record(
+                TraceOp::Squeeze,
+                label,
+                &be_bytes(&challenge),
+                &state_before,
+            );
+        }
+        challenge
+    }
+
+    fn record(&mut self, op: TraceOp, label: &str, bytes: &[u8], state_before: &[u8]) {
+        let state_after = self.transcript.state();
+        if let Some(trace) = &mut self.trace {
+            trace.steps.push(TraceStep {
+                op,
+                label: label.to_string(),
+                bytes: to_hex(bytes),
+                state_before: to_hex(state_before),
+                state_after: to_hex(&state_after),
+            });
+        }
+    }
+}
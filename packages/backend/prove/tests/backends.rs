@@ -0,0 +1,95 @@
+mod common;
+
+use common::Fixture;
+use libs::compute_backend::{with_compute_backend, ComputeBackend};
+use libs::group_structures::PointValidation;
+use libs::iotools::binary::PointEncoding;
+use libs::utils::check_device;
+use prove::{PartialProof, ProveInputPaths, Prover, QuotientEngine};
+use std::convert::Infallible;
+use std::env;
+
+fn read_env(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .and_then(|v| if v.trim().is_empty() { None } else { Some(v) })
+}
+
+/// Proves a small in-memory instance from one checkpoint with both compute backends and checks
+/// that the preprocess commitments and the proof bytes are identical.
+#[test]
+fn proofs_match_across_compute_backends() {
+    check_device();
+    let fixture = Fixture::new();
+
+    // `build` draws the blinding scalars, so it runs once and both backends resume from its
+    // checkpoint.
+    let (prover, binding) =
+        with_compute_backend(ComputeBackend::Icicle, || fixture.builder().build())
+            .expect("Prover initialization failed");
+    let checkpoint = prover.checkpoint(&PartialProof::new(binding));
+    drop(prover);
+
+    let prove = |backend| {
+        with_compute_backend(backend, || {
+            let (mut prover, partial) = fixture
+                .builder()
+                .resume(&checkpoint)
+                .expect("Failed to resume the prover");
+            let preprocess = prover.preprocess_commitments();
+            let (proof, _) = match prover.resume_rounds(partial, |_, _| Ok::<(), Infallible>(())) {
+                Ok(result) => result,
+                Err(never) => match never {},
+            };
+            (preprocess, proof.to_bytes(PointEncoding::Uncompressed))
+        })
+    };
+    assert_eq!(
+        prove(ComputeBackend::Icicle),
+        prove(ComputeBackend::Arkworks)
+    );
+}
+
+/// The same comparison on the instance given by the inputs of the timing test. Run it with
+/// `PROVE_QAP_PATH`, `PROVE_SYNTHESIZER_PATH` and `PROVE_SETUP_PATH` set and `--ignored`.
+#[test]
+#[ignore = "needs PROVE_QAP_PATH, PROVE_SYNTHESIZER_PATH and PROVE_SETUP_PATH"]
+fn proofs_match_across_compute_backends_on_synthesizer_output() {
+    let qap_path = read_env("PROVE_QAP_PATH").expect("PROVE_QAP_PATH is not set");
+    let synthesizer_path =
+        read_env("PROVE_SYNTHESIZER_PATH").expect("PROVE_SYNTHESIZER_PATH is not set");
+    let setup_path = read_env("PROVE_SETUP_PATH").expect("PROVE_SETUP_PATH is not set");
+    let paths = ProveInputPaths {
+        qap_path: &qap_path,
+        synthesizer_path: &synthesizer_path,
+        setup_path: &setup_path,
+        output_path: "",
+        point_validation: PointValidation::Trusted,
+        quotient_engine: QuotientEngine::default(),
+    };
+    check_device();
+
+    // `init` draws the blinding scalars, so it runs once and both backends resume from its
+    // checkpoint.
+    let (prover, binding) = with_compute_backend(ComputeBackend::Icicle, || Prover::init(&paths))
+        .expect("Prover initialization failed");
+    let checkpoint = prover.checkpoint(&PartialProof::new(binding));
+    drop(prover);
+
+    let prove = |backend| {
+        with_compute_backend(backend, || {
+            let (mut prover, partial) =
+                Prover::resume(&paths, &checkpoint).expect("Failed to resume the prover");
+            let preprocess = prover.preprocess_commitments();
+            let (proof, _) = match prover.resume_rounds(partial, |_, _| Ok::<(), Infallible>(())) {
+                Ok(result) => result,
+                Err(never) => match never {},
+            };
+            (preprocess, proof.to_bytes(PointEncoding::Uncompressed))
+        })
+    };
+    assert_eq!(
+        prove(ComputeBackend::Icicle),
+        prove(ComputeBackend::Arkworks)
+    );
+}
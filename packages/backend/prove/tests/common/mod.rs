@@ -0,0 +1,177 @@
+//! A small in-memory prover instance shared by the integration tests: one `mul_inc` subcircuit
+//! placed three times, written to a temporary subcircuit library, and a random reference string.
+
+use icicle_bls12_381::curve::{CurveCfg, G2CurveCfg, ScalarCfg, ScalarField};
+use icicle_core::curve::Curve;
+use icicle_core::traits::{FieldImpl, GenerateRandom};
+use libs::field_structures::Tau;
+use libs::group_structures::{PointValidation, Sigma};
+use libs::iotools::inputs::{MemoryInputs, R1csSource};
+use libs::iotools::{
+    scalar_to_hex, HexString, Instance, Permutation, PlacementVariables, SetupParams,
+    SubcircuitInfo,
+};
+use prove::{ProverBuilder, SigmaHolder};
+use std::fs;
+use tempfile::TempDir;
+
+type LinearCombination = Vec<(u32, u32)>;
+
+fn setup_params() -> SetupParams {
+    SetupParams {
+        l_free: 4,
+        l: 8,
+        l_user_out: 2,
+        l_user: 4,
+        l_D: 16,
+        m_D: 32,
+        n: 4,
+        s_D: 4,
+        s_max: 4,
+    }
+}
+
+// A circom binary R1CS with a 32-byte field: the header section, then the constraints section.
+fn r1cs_binary(n_wires: u32, constraints: &[[LinearCombination; 3]]) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend(32u32.to_le_bytes());
+    header.extend([0u8; 32]);
+    header.extend(n_wires.to_le_bytes());
+    header.extend([0u8; 12]);
+    header.extend(0u64.to_le_bytes());
+    header.extend((constraints.len() as u32).to_le_bytes());
+
+    let mut body = Vec::new();
+    for constraint in constraints {
+        for lc in constraint {
+            body.extend((lc.len() as u32).to_le_bytes());
+            for &(wire, coeff) in lc {
+                body.extend(wire.to_le_bytes());
+                body.extend(ScalarField::from_u32(coeff).to_bytes_le());
+            }
+        }
+    }
+
+    let mut data = b"r1cs".to_vec();
+    data.extend(1u32.to_le_bytes());
+    data.extend(2u32.to_le_bytes());
+    for (section_type, section) in [(1u32, header), (2u32, body)] {
+        data.extend(section_type.to_le_bytes());
+        data.extend((section.len() as u64).to_le_bytes());
+        data.extend(section);
+    }
+    data
+}
+
+fn hex(values: &[u32]) -> Box<[HexString]> {
+    values
+        .iter()
+        .map(|&value| HexString(scalar_to_hex(&ScalarField::from_u32(value))))
+        .collect()
+}
+
+// Wires (1, t, x, y, z) with x * y = z and (z + 1) * 1 = t. The constant, the output and the
+// inputs are interface wires, z is private.
+fn subcircuit() -> (Vec<u8>, SubcircuitInfo) {
+    let r1cs = r1cs_binary(
+        5,
+        &[
+            [vec![(2, 1)], vec![(3, 1)], vec![(4, 1)]],
+            [vec![(4, 1), (0, 1)], vec![(0, 1)], vec![(1, 1)]],
+        ],
+    );
+    let info = SubcircuitInfo {
+        id: 0,
+        name: "mul_inc".to_string(),
+        Nwires: 5,
+        Nconsts: 2,
+        Out_idx: Box::new([1, 1]),
+        In_idx: Box::new([2, 2]),
+        flattenMap: Box::new([8, 9, 10, 11, 16]),
+    };
+    (r1cs, info)
+}
+
+fn placement(x: u32, y: u32) -> PlacementVariables {
+    PlacementVariables {
+        subcircuitId: 0,
+        variables: hex(&[1, x * y + 1, x, y, x * y]),
+    }
+}
+
+fn sigma(params: &SetupParams) -> SigmaHolder {
+    let m_i = params.l_D - params.l;
+    let sigma = Sigma::gen(
+        params,
+        &Tau::gen(),
+        &ScalarCfg::generate_random(params.m_D),
+        &ScalarCfg::generate_random(params.s_max),
+        &ScalarCfg::generate_random(m_i),
+        &ScalarCfg::generate_random(params.l_free),
+        &CurveCfg::generate_random_affine_points(1)[0],
+        &G2CurveCfg::generate_random_affine_points(1)[0],
+    );
+    SigmaHolder::from_sigma(&sigma).expect("Failed to archive the reference string")
+}
+
+pub struct Fixture {
+    library: TempDir,
+    params: SetupParams,
+    info: SubcircuitInfo,
+    placements: Vec<PlacementVariables>,
+    instance: Instance,
+    sigma: SigmaHolder,
+}
+
+impl Fixture {
+    /// Writes the subcircuit library and generates the reference string. The caller selects the
+    /// device first.
+    pub fn new() -> Self {
+        let params = setup_params();
+        let (r1cs, info) = subcircuit();
+        let library = tempfile::tempdir().expect("Failed to create the subcircuit library dir");
+        fs::create_dir(library.path().join("r1cs")).expect("Failed to create the r1cs dir");
+        fs::write(library.path().join("r1cs/subcircuit0.r1cs"), r1cs)
+            .expect("Failed to write the R1CS");
+        let sigma = sigma(&params);
+        Self {
+            library,
+            params,
+            info,
+            placements: vec![placement(3, 5), placement(2, 7), placement(4, 4)],
+            instance: Instance {
+                a_pub_user: hex(&[16, 15, 3, 5]),
+                a_pub_block: hex(&[]),
+                a_pub_function: hex(&[0, 0, 0, 0]),
+            },
+            sigma,
+        }
+    }
+
+    /// A builder with every input of the instance set.
+    pub fn builder(&self) -> ProverBuilder<'_> {
+        ProverBuilder::new(self.library.path().to_str().expect("Non UTF-8 temp dir"))
+            .setup_params(self.params.clone())
+            .subcircuit_infos(vec![self.info.clone()])
+            .placement_variables(self.placements.clone())
+            .permutation(Vec::<Permutation>::new())
+            .instance(self.instance.clone())
+            .sigma(self.sigma.clone())
+            .point_validation(PointValidation::Trusted)
+    }
+
+    /// In-memory inputs of the instance that read the R1CS constraints from `r1cs`.
+    #[allow(dead_code)] // Not every test crate that includes this module uses it.
+    pub fn memory_inputs<'a>(&'a self, r1cs: &'a dyn R1csSource) -> MemoryInputs<'a, SigmaHolder> {
+        MemoryInputs {
+            setup_params: Some(&self.params),
+            subcircuit_infos: Some(std::slice::from_ref(&self.info)),
+            placement_variables: Some(&self.placements),
+            permutation: Some(&[]),
+            instance: Some(&self.instance),
+            r1cs: Some(r1cs),
+            point_validation: PointValidation::Trusted,
+            ..MemoryInputs::new(self.sigma.clone())
+        }
+    }
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::Fixture;
+use libs::error::TokamakError;
+use libs::utils::check_device;
+use prove::Prover;
+
+/// `Prover::init` on in-memory inputs returns an error instead of panicking when the R1CS source
+/// lacks a used subcircuit or holds a corrupt one.
+#[test]
+fn init_reports_unusable_in_memory_r1cs() {
+    check_device();
+    let fixture = Fixture::new();
+
+    let missing: Vec<Vec<u8>> = Vec::new();
+    assert!(matches!(
+        Prover::init(&fixture.memory_inputs(&missing)),
+        Err(TokamakError::MissingInput(_))
+    ));
+    let corrupt = vec![b"r1cs".to_vec()];
+    assert!(matches!(
+        Prover::init(&fixture.memory_inputs(&corrupt)),
+        Err(TokamakError::Parse { .. })
+    ));
+    let empty_library = tempfile::tempdir().expect("Failed to create the subcircuit library dir");
+    let empty_library = empty_library.path().to_path_buf();
+    assert!(matches!(
+        Prover::init(&fixture.memory_inputs(&empty_library)),
+        Err(TokamakError::MissingInput(_))
+    ));
+}
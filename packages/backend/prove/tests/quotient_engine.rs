@@ -0,0 +1,42 @@
+mod common;
+
+use common::Fixture;
+use libs::iotools::binary::PointEncoding;
+use libs::utils::check_device;
+use prove::{PartialProof, QuotientEngine};
+use std::convert::Infallible;
+
+/// Proves a small in-memory instance from one checkpoint with both quotient engines and checks
+/// that the proof bytes are identical.
+#[test]
+fn proofs_match_across_quotient_engines() {
+    check_device();
+    let fixture = Fixture::new();
+
+    // `build` draws the blinding scalars, so it runs once and both engines resume from its
+    // checkpoint.
+    let (prover, binding) = fixture
+        .builder()
+        .build()
+        .expect("Prover initialization failed");
+    let checkpoint = prover.checkpoint(&PartialProof::new(binding));
+    drop(prover);
+
+    let prove = |engine| {
+        let (mut prover, partial) = fixture
+            .builder()
+            .quotient_engine(engine)
+            .resume(&checkpoint)
+            .expect("Failed to resume the prover");
+        assert_eq!(prover.quotient_engine, engine);
+        let (proof, _) = match prover.resume_rounds(partial, |_, _| Ok::<(), Infallible>(())) {
+            Ok(result) => result,
+            Err(never) => match never {},
+        };
+        proof.to_bytes(PointEncoding::Uncompressed)
+    };
+    assert_eq!(
+        prove(QuotientEngine::Coefficient),
+        prove(QuotientEngine::Coset)
+    );
+}
@@ -532,11 +532,11 @@ fn process_prepare(config: &Phase2PrepareConfig, _is_gpu_enabled: bool) -> Sigma
     let setup_file_name = "setupParams.json";
     let setup_params = SetupParams::read_from_json(qap_path.join(&setup_file_name))
         .expect("cannot SetupParams read file");
-    let shape = setup_shape(&setup_params);
-    validate_setup_shape(&shape);
+    let shape = setup_shape(&setup_params).expect("Invalid setup params");
+    validate_setup_shape(&shape).expect("Invalid setup shape");
     validate_public_wire_size(shape.l_free);
     let ntt_domain_size = trusted_setup_ntt_domain_size(&shape);
-    init_ntt_domain(ntt_domain_size);
+    init_ntt_domain(ntt_domain_size).expect("Failed to initialize NTT domain");
     let segments = public_wire_segments(&setup_params);
     let n = setup_params.n; // Number of constraints per subcircuit
     let s_max = setup_params.s_max;
@@ -42,6 +42,10 @@ struct Config {
     /// Use the hardcoded testing tau and generators instead of random sampling
     #[arg(long, default_value_t = false)]
     fixed_tau: bool,
+
+    /// Store the points of combined_sigma.rkyv and sigma_verify.json compressed
+    #[arg(long, default_value_t = false)]
+    compressed_points: bool,
 }
 
 fn main() {
@@ -86,9 +90,10 @@ fn main() {
         )
     };
 
-    let setup_params: SetupParams = load_setup_params_from_qap_path(paths.qap_path);
-    let shape = setup_shape(&setup_params);
-    validate_setup_shape(&shape);
+    let setup_params: SetupParams =
+        load_setup_params_from_qap_path(paths.qap_path).expect("Failed to read setupParams.json");
+    let shape = setup_shape(&setup_params).expect("Invalid setup params");
+    validate_setup_shape(&shape).expect("Invalid setup shape");
     validate_public_wire_size(shape.l_free);
 
     let m_d = setup_params.m_D;
@@ -104,7 +109,7 @@ fn main() {
     let ntt_domain_size = trusted_setup_testing_ntt_domain_size(&shape);
     #[cfg(not(feature = "testing-mode"))]
     let ntt_domain_size = trusted_setup_ntt_domain_size(&shape);
-    init_ntt_domain(ntt_domain_size);
+    init_ntt_domain(ntt_domain_size).expect("Failed to initialize NTT domain");
 
     let subcircuit_infos_path = PathBuf::from(paths.qap_path).join("subcircuitInfo.json");
     let subcircuit_infos = SubcircuitInfo::read_box_from_json(subcircuit_infos_path).unwrap();
@@ -254,11 +259,12 @@ fn main() {
         let mut a_free_X = public_instance.gen_a_free_X(&setup_params);
         let mut bXY = gen_bXY(&placement_variables, &subcircuit_infos, &setup_params);
         let (mut uXY, mut vXY, mut wXY) = read_R1CS_gen_uvwXY(
-            &paths.qap_path,
+            std::path::Path::new(paths.qap_path),
             &placement_variables,
             &subcircuit_infos,
             &setup_params,
-        );
+        )
+        .unwrap();
         let a_free_encoding = sigma.sigma_1.encode_poly(&mut a_free_X, &setup_params);
         let O_pub_fix = sigma
             .sigma_1
@@ -475,9 +481,15 @@ fn main() {
     let output_dir_path = PathBuf::from(paths.output_path);
     std::fs::create_dir_all(&output_dir_path).expect("Failed to create output directory");
     {
-        use libs::iotools::write_final_crs_artifacts;
+        use libs::iotools::binary::PointEncoding;
+        use libs::iotools::write_final_crs_artifacts_with_encoding;
         println!("Writing final CRS artifacts...");
-        write_final_crs_artifacts(&output_dir_path, &sigma)
+        let encoding = if config.compressed_points {
+            PointEncoding::Compressed
+        } else {
+            PointEncoding::Uncompressed
+        };
+        write_final_crs_artifacts_with_encoding(&output_dir_path, &sigma, encoding)
             .expect("Failed to write final CRS artifacts");
     }
     let lap = start.elapsed();
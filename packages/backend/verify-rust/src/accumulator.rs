@@ -0,0 +1,156 @@
+use crate::{snark_pairing_bases, SnarkPairingTerms};
+use icicle_bls12_381::curve::ScalarField;
+use icicle_core::traits::FieldImpl;
+use libs::error::{TokamakError, TokamakResult};
+use libs::field_structures::FieldSerde;
+use libs::group_structures::{pairing_product_is_identity, G1serde, SigmaVerify};
+use libs::iotools::binary::{BinaryReader, BinaryWriter, PointEncoding};
+use libs::{impl_read_from_json, impl_write_into_json};
+use prove::TranscriptManager;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The deferred final pairing check of one or more proofs made against the same CRS.
+///
+/// A proof is accepted when `e(lhs, lhs_bases) == e(rhs, rhs_bases)`, where both sides pair five
+/// G1 points with five fixed G2 points of `sigma_verify`. Because the G2 bases are shared, any
+/// number of checks fold into a single pair of G1 vectors with random weights, and the whole batch
+/// is then decided with one multi-pairing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnarkAccumulator {
+    /// `SigmaVerify::digest` of the CRS whose G2 bases decide the accumulator.
+    pub sigma_digest: FieldSerde,
+    /// Number of proofs folded in. Only the fold transcript binds it, see `decide`.
+    pub count: u64,
+    pub lhs: [G1serde; 5],
+    pub rhs: [G1serde; 5],
+}
+
+impl_read_from_json!(SnarkAccumulator);
+impl_write_into_json!(SnarkAccumulator);
+
+impl SnarkAccumulator {
+    pub const BINARY_MAGIC: [u8; 4] = *b"TKAC";
+
+    pub(crate) fn from_terms(sigma_digest: ScalarField, terms: SnarkPairingTerms) -> Self {
+        Self {
+            sigma_digest: FieldSerde(sigma_digest),
+            count: 1,
+            lhs: terms.lhs,
+            rhs: terms.rhs,
+        }
+    }
+
+    /// Folds `other` into `self` as `self + r * other`. The weight `r` is squeezed from a Keccak
+    /// transcript over both accumulators and their counts, so anyone holding the two inputs can
+    /// recompute the fold. Degenerate accumulators, which would only add to the count, are
+    /// rejected.
+    pub fn fold(&self, other: &Self) -> TokamakResult<Self> {
+        if self.sigma_digest != other.sigma_digest {
+            return Err(TokamakError::AccumulatorMismatch(
+                "the accumulators were made against different CRSs".to_string(),
+            ));
+        }
+        if self.is_degenerate() || other.is_degenerate() {
+            return Err(TokamakError::AccumulatorMismatch(
+                "an accumulator holds no proof or an identity side".to_string(),
+            ));
+        }
+        let weight = self.fold_weight(other);
+        let fold_points = |acc: &[G1serde; 5], points: &[G1serde; 5]| {
+            let mut folded = *acc;
+            for (acc, point) in folded.iter_mut().zip(points) {
+                *acc = *acc + *point * weight;
+            }
+            folded
+        };
+        Ok(Self {
+            sigma_digest: self.sigma_digest,
+            count: self.count + other.count,
+            lhs: fold_points(&self.lhs, &other.lhs),
+            rhs: fold_points(&self.rhs, &other.rhs),
+        })
+    }
+
+    /// Folds the accumulators left to right.
+    pub fn fold_all(accumulators: &[Self]) -> TokamakResult<Self> {
+        let (first, rest) = accumulators
+            .split_first()
+            .ok_or(TokamakError::MissingInput("accumulator"))?;
+        rest.iter()
+            .try_fold(first.clone(), |acc, next| acc.fold(next))
+    }
+
+    // No proof, or a side of identity points, which satisfies the pairing check trivially.
+    fn is_degenerate(&self) -> bool {
+        let is_identity =
+            |points: &[G1serde; 5]| points.iter().all(|point| *point == G1serde::zero());
+        self.count == 0 || is_identity(&self.lhs) || is_identity(&self.rhs)
+    }
+
+    fn fold_weight(&self, other: &Self) -> ScalarField {
+        let mut manager = TranscriptManager::new();
+        manager.absorb_scalar("sigma_digest", &self.sigma_digest.0);
+        for (side, acc) in [("acc", self), ("next", other)] {
+            let count = ScalarField::from_hex(&format!("{:#x}", acc.count));
+            manager.absorb_scalar(&format!("{side}.count"), &count);
+            for (i, point) in acc.lhs.iter().enumerate() {
+                manager.absorb_g1(&format!("{side}.lhs[{i}]"), point);
+            }
+            for (i, point) in acc.rhs.iter().enumerate() {
+                manager.absorb_g1(&format!("{side}.rhs[{i}]"), point);
+            }
+        }
+        manager.squeeze("fold")
+    }
+
+    /// Runs the deferred pairing check. Every folded proof is accepted when it holds.
+    ///
+    /// An accumulator with a zero count or an identity side is rejected, as it passes the check
+    /// without any proof. The check does not cover `count`: it is the number of accepted proofs
+    /// only for a caller that re-derives the fold from the accumulators of those proofs.
+    pub fn decide(&self, sigma: &SigmaVerify) -> TokamakResult<bool> {
+        if self.sigma_digest.0 != sigma.digest() {
+            return Err(TokamakError::AccumulatorMismatch(
+                "the accumulator was made against a different CRS".to_string(),
+            ));
+        }
+        if self.is_degenerate() {
+            return Ok(false);
+        }
+        let (lhs_bases, rhs_bases) = snark_pairing_bases(sigma);
+        let mut g1 = self.lhs.to_vec();
+        g1.extend(self.rhs.iter().map(|point| G1serde::zero() - *point));
+        let mut g2 = lhs_bases.to_vec();
+        g2.extend(rhs_bases);
+        Ok(pairing_product_is_identity(&g1, &g2))
+    }
+
+    /// Encodes the digest, the count and the ten points in the versioned binary format.
+    pub fn to_bytes(&self, encoding: PointEncoding) -> Vec<u8> {
+        let mut writer = BinaryWriter::new(&Self::BINARY_MAGIC, encoding);
+        writer.scalar(&self.sigma_digest.0).u64(self.count);
+        for point in self.lhs.iter().chain(&self.rhs) {
+            writer.g1(point);
+        }
+        writer.finish()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> TokamakResult<Self> {
+        let mut reader = BinaryReader::new(bytes, &Self::BINARY_MAGIC, "binary accumulator")?;
+        let sigma_digest = FieldSerde(reader.scalar()?);
+        let count = reader.u64()?;
+        let mut lhs = [G1serde::zero(); 5];
+        let mut rhs = [G1serde::zero(); 5];
+        for point in lhs.iter_mut().chain(rhs.iter_mut()) {
+            *point = reader.g1()?;
+        }
+        reader.finish()?;
+        Ok(Self {
+            sigma_digest,
+            count,
+            lhs,
+            rhs,
+        })
+    }
+}
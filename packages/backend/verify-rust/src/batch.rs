@@ -0,0 +1,271 @@
+use crate::{
+    fiat_shamir_failure, proof_input_checks, shared_input_checks, snark_pairing_bases, Kappa2Mode,
+    ProofCheck, SnarkPairingTerms,
+};
+use icicle_bls12_381::curve::ScalarCfg;
+use icicle_core::traits::GenerateRandom;
+use libs::bivariate_polynomial::DensePolynomialExt;
+use libs::error::{TokamakError, TokamakResult};
+use libs::group_structures::{
+    pairing_product_is_identity, with_point_validation, G1serde, PointValidation, SigmaVerify,
+};
+use libs::iotools::binary::ArtifactEncoding;
+use libs::iotools::public_io::{decode_public_io, PublicIo};
+use libs::iotools::{Instance, InstanceDescription, SetupParams};
+use libs::utils::{
+    init_ntt_domain, load_setup_params_from_qap_path, prover_verifier_ntt_domain_size, setup_shape,
+    validate_setup_shape,
+};
+use preprocess::Preprocess;
+use prove::{FiatShamirMode, FormattedProof, Proof};
+use std::path::PathBuf;
+
+pub struct BatchVerifyInputPaths<'a> {
+    pub qap_path: &'a str,
+    pub setup_path: &'a str,
+    pub preprocess_path: &'a str,
+    pub preprocess_encoding: ArtifactEncoding,
+    pub point_validation: PointValidation,
+}
+
+/// Outcome of `BatchVerifier::verify`. `invalid` holds the insertion indices of the rejected
+/// proofs in ascending order. An empty batch is not verified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchVerification {
+    pub verified: bool,
+    pub invalid: Vec<usize>,
+}
+
+/// Verifies many proofs that share one CRS and one preprocess. The setup is loaded and the NTT
+/// domain is initialized once, and the final pairing checks of all proofs are folded with random
+/// weights into a single multi-pairing.
+pub struct BatchVerifier {
+    sigma: SigmaVerify,
+    preprocess: Preprocess,
+    setup_params: SetupParams,
+    kappa2_mode: Kappa2Mode,
+    min_fiat_shamir: FiatShamirMode,
+    point_validation: PointValidation,
+    entries: Vec<BatchEntry>,
+}
+
+// What one queued proof adds to the shared CRS, preprocess and setup parameters.
+struct BatchEntry {
+    a_pub_X: DensePolynomialExt,
+    instance: Instance,
+    proof: Proof,
+    public_io: Option<PublicIo>,
+}
+
+impl BatchVerifier {
+    pub fn init(paths: &BatchVerifyInputPaths) -> TokamakResult<Self> {
+        let setup_params = load_setup_params_from_qap_path(paths.qap_path)?;
+        let shape = setup_shape(&setup_params)?;
+        validate_setup_shape(&shape)?;
+        let ntt_domain_size = prover_verifier_ntt_domain_size(&shape);
+        init_ntt_domain(ntt_domain_size)?;
+
+        let (sigma, preprocess) = with_point_validation(paths.point_validation, || {
+            // Load Sigma (reference string)
+            let sigma_path = PathBuf::from(paths.setup_path).join("sigma_verify.json");
+            let sigma = SigmaVerify::read_from_json(sigma_path.clone())
+                .map_err(|err| TokamakError::from_read(&sigma_path, err))?;
+
+            // Load Verifier preprocess
+            let preprocess =
+                Preprocess::read_from_dir(paths.preprocess_path, paths.preprocess_encoding)?;
+            Ok::<_, TokamakError>((sigma, preprocess))
+        })?;
+
+        Ok(Self {
+            sigma,
+            preprocess,
+            setup_params,
+            kappa2_mode: Kappa2Mode::default(),
+            min_fiat_shamir: FiatShamirMode::StrongV1,
+            point_validation: paths.point_validation,
+            entries: Vec::new(),
+        })
+    }
+
+    pub fn with_kappa2_mode(mut self, kappa2_mode: Kappa2Mode) -> Self {
+        self.kappa2_mode = kappa2_mode;
+        self
+    }
+
+    /// Rejects proofs made in a weaker Fiat-Shamir mode than `min_fiat_shamir`, like
+    /// `Verifier::with_min_fiat_shamir`.
+    pub fn with_min_fiat_shamir(mut self, min_fiat_shamir: FiatShamirMode) -> Self {
+        self.min_fiat_shamir = min_fiat_shamir;
+        self
+    }
+
+    /// Queues a proof together with the instance it was generated for and returns its index.
+    pub fn add(&mut self, instance: &Instance, proof: &FormattedProof) -> TokamakResult<usize> {
+        self.push(instance, None, proof)
+    }
+
+    /// Like `add`, and decodes the public values of the instance with the synthesizer's
+    /// description of it.
+    pub fn add_described(
+        &mut self,
+        instance: &Instance,
+        description: &InstanceDescription,
+        proof: &FormattedProof,
+    ) -> TokamakResult<usize> {
+        self.push(instance, Some(description), proof)
+    }
+
+    fn push(
+        &mut self,
+        instance: &Instance,
+        description: Option<&InstanceDescription>,
+        proof: &FormattedProof,
+    ) -> TokamakResult<usize> {
+        let proof =
+            with_point_validation(self.point_validation, || proof.recover_proof_from_format())?;
+        let a_pub_X = instance.gen_a_free_X(&self.setup_params);
+        let public_io = description
+            .map(|description| decode_public_io(instance, description, &self.setup_params));
+        self.entries.push(BatchEntry {
+            a_pub_X,
+            instance: instance.clone(),
+            proof,
+            public_io,
+        });
+        Ok(self.entries.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The decoded public values of the proof at `index`, if it was queued with `add_described`.
+    /// They only hold for proofs that `verify` does not reject.
+    pub fn public_io(&self, index: usize) -> Option<&PublicIo> {
+        self.entries.get(index)?.public_io.as_ref()
+    }
+
+    fn check<'a>(&'a self, entry: &'a BatchEntry) -> ProofCheck<'a> {
+        ProofCheck {
+            sigma: &self.sigma,
+            preprocess: &self.preprocess,
+            setup_params: &self.setup_params,
+            a_pub_X: &entry.a_pub_X,
+            instance: &entry.instance,
+            proof: &entry.proof,
+            kappa2_mode: self.kappa2_mode,
+        }
+    }
+
+    /// Verifies every queued proof. Proofs in too weak a Fiat-Shamir mode or with malformed group
+    /// elements are rejected up front; the rest are checked with one folded multi-pairing, and if
+    /// that fails the batch is bisected to identify the invalid proofs.
+    pub fn verify(&self) -> BatchVerification {
+        let shared_inputs_valid = shared_input_checks(&self.sigma, &self.preprocess)
+            .iter()
+            .all(|check| check.status.is_valid());
+        if !shared_inputs_valid {
+            return BatchVerification {
+                verified: false,
+                invalid: (0..self.entries.len()).collect(),
+            };
+        }
+
+        let mut invalid = Vec::new();
+        let mut candidates = Vec::new();
+        let mut terms = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            let proof_inputs_valid = fiat_shamir_failure(self.min_fiat_shamir, &entry.proof)
+                .is_none()
+                && proof_input_checks(&entry.proof)
+                    .iter()
+                    .all(|check| check.status.is_valid());
+            if !proof_inputs_valid {
+                invalid.push(index);
+                continue;
+            }
+            let check = self.check(entry);
+            let challenges = check.collect_challenges();
+            candidates.push(index);
+            terms.push(check.snark_pairing_terms(&challenges));
+        }
+
+        let positions: Vec<usize> = (0..terms.len()).collect();
+        let failures = bisect_failures(&positions, &mut |subset: &[usize]| {
+            self.folded_pairing_holds(subset.iter().map(|&position| &terms[position]))
+        });
+        invalid.extend(failures.into_iter().map(|position| candidates[position]));
+        invalid.sort_unstable();
+
+        BatchVerification {
+            verified: !self.entries.is_empty() && invalid.is_empty(),
+            invalid,
+        }
+    }
+
+    // Folds `e(lhs_i, lhs_bases) == e(rhs_i, rhs_bases)` over all i with fresh random weights r_i
+    // into `e(sum r_i lhs_i, lhs_bases) * e(-sum r_i rhs_i, rhs_bases) == 1`.
+    fn folded_pairing_holds<'a>(
+        &self,
+        terms: impl ExactSizeIterator<Item = &'a SnarkPairingTerms>,
+    ) -> bool {
+        let weights = ScalarCfg::generate_random(terms.len());
+        let mut lhs = [G1serde::zero(); 5];
+        let mut rhs = [G1serde::zero(); 5];
+        for (term, weight) in terms.zip(weights) {
+            for (acc, point) in lhs.iter_mut().zip(term.lhs) {
+                *acc = *acc + point * weight;
+            }
+            for (acc, point) in rhs.iter_mut().zip(term.rhs) {
+                *acc = *acc + point * weight;
+            }
+        }
+
+        let (lhs_bases, rhs_bases) = snark_pairing_bases(&self.sigma);
+        let mut g1 = lhs.to_vec();
+        g1.extend(rhs.iter().map(|point| G1serde::zero() - *point));
+        let mut g2 = lhs_bases.to_vec();
+        g2.extend(rhs_bases);
+        pairing_product_is_identity(&g1, &g2)
+    }
+}
+
+/// Returns the members of `indices` for which `holds` fails, given that `holds` accepts a set
+/// exactly when it accepts each of its members. Failing sets are halved until the offending
+/// members are isolated.
+pub(crate) fn bisect_failures<F>(indices: &[usize], holds: &mut F) -> Vec<usize>
+where
+    F: FnMut(&[usize]) -> bool,
+{
+    let mut failures = Vec::new();
+    if !indices.is_empty() && !holds(indices) {
+        bisect_failing(indices, holds, &mut failures);
+    }
+    failures
+}
+
+// `indices` is known to contain at least one failure.
+fn bisect_failing<F>(indices: &[usize], holds: &mut F, failures: &mut Vec<usize>)
+where
+    F: FnMut(&[usize]) -> bool,
+{
+    if indices.len() == 1 {
+        failures.push(indices[0]);
+        return;
+    }
+    let (left, right) = indices.split_at(indices.len() / 2);
+    if holds(left) {
+        // The failure must be in the right half, so it needs no check of its own.
+        bisect_failing(right, holds, failures);
+    } else {
+        bisect_failing(left, holds, failures);
+        if !holds(right) {
+            bisect_failing(right, holds, failures);
+        }
+    }
+}
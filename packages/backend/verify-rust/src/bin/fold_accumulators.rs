@@ -0,0 +1,59 @@
+use clap::Parser;
+use libs::error::TokamakError;
+use libs::group_structures::SigmaVerify;
+use std::path::PathBuf;
+use verify::SnarkAccumulator;
+
+/// Folds accumulators written by `verify --accumulator` into one and optionally decides it.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Config {
+    /// Accumulator JSON files, folded in the given order
+    #[arg(value_name = "PATH", required = true)]
+    accumulators: Vec<PathBuf>,
+
+    /// Optional path to write the folded accumulator as JSON
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// CRS output directory containing sigma_verify.json. When given, the folded accumulator is
+    /// decided with one pairing check and `true` or `false` is printed as the last line.
+    #[arg(long, value_name = "PATH")]
+    crs: Option<PathBuf>,
+}
+
+fn main() {
+    let config = Config::parse();
+    let folded = config
+        .accumulators
+        .iter()
+        .map(|path| {
+            SnarkAccumulator::read_from_json(path.clone())
+                .map_err(|err| TokamakError::from_read(path, err))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|accumulators| SnarkAccumulator::fold_all(&accumulators))
+        .unwrap_or_else(|err| {
+            eprintln!("Folding the accumulators failed: {err}");
+            std::process::exit(1);
+        });
+    println!("Folded {} proofs", folded.count);
+
+    if let Some(output) = &config.output {
+        folded
+            .write_into_json(output.clone())
+            .expect("Failed to write the folded accumulator");
+    }
+
+    if let Some(crs) = &config.crs {
+        let sigma_path = crs.join("sigma_verify.json");
+        let decided = SigmaVerify::read_from_json(sigma_path.clone())
+            .map_err(|err| TokamakError::from_read(&sigma_path, err))
+            .and_then(|sigma| folded.decide(&sigma))
+            .unwrap_or_else(|err| {
+                eprintln!("Deciding the accumulator failed: {err}");
+                std::process::exit(1);
+            });
+        println!("{decided}");
+    }
+}
@@ -0,0 +1,96 @@
+use clap::Parser;
+use libs::error::{TokamakError, TokamakResult};
+use libs::group_structures::{with_point_validation, PointValidation};
+use libs::iotools::binary::ArtifactEncoding;
+use libs::iotools::inputs::load_instance;
+use libs::iotools::{hex_encode, scalar_to_hex};
+use libs::subcircuit_library::{resolve_subcircuit_library_path, SubcircuitLibraryArg};
+use libs::utils::load_setup_params_from_qap_path;
+use preprocess::Preprocess;
+use prove::Proof;
+use std::fs;
+use std::path::PathBuf;
+
+/// Combines a proof, its preprocess commitments and the public instance into the ABI-encoded
+/// calldata of the Solidity verifier's `verify` entry point.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Config {
+    #[command(flatten)]
+    subcircuit_library: SubcircuitLibraryArg,
+
+    /// Synthesizer output directory containing instance.json
+    #[arg(long, value_name = "PATH")]
+    synthesizer_stat: String,
+
+    /// Preprocess output directory containing preprocess.json
+    #[arg(long, value_name = "PATH")]
+    preprocess: String,
+
+    /// Proof output directory containing proof.json
+    #[arg(long, value_name = "PATH")]
+    proof: String,
+
+    /// Encoding of the preprocess input (both binary encodings read preprocess.bin)
+    #[arg(long, value_enum, default_value_t = ArtifactEncoding::Json)]
+    preprocess_encoding: ArtifactEncoding,
+
+    /// Encoding of the proof input (both binary encodings read proof.bin)
+    #[arg(long, value_enum, default_value_t = ArtifactEncoding::Json)]
+    proof_encoding: ArtifactEncoding,
+
+    /// Skip on-curve and subgroup checks of loaded points (only for locally generated artifacts)
+    #[arg(long)]
+    trusted_artifacts: bool,
+
+    /// Optional path to write the calldata to instead of printing it
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+fn calldata(config: &Config, qap_path: &str) -> TokamakResult<Vec<u8>> {
+    let setup_params = load_setup_params_from_qap_path(qap_path)?;
+    let instance = load_instance(&config.synthesizer_stat)?;
+    let point_validation = if config.trusted_artifacts {
+        PointValidation::Trusted
+    } else {
+        PointValidation::Checked
+    };
+    let (preprocess, proof) = with_point_validation(point_validation, || {
+        Ok::<_, TokamakError>((
+            Preprocess::read_from_dir(&config.preprocess, config.preprocess_encoding)?,
+            Proof::read_from_dir(&config.proof, config.proof_encoding)?,
+        ))
+    })?;
+
+    let formatted_preprocess = preprocess.convert_format_for_solidity_verifier();
+    let public_inputs = instance
+        .public_inputs(&setup_params)
+        .iter()
+        .map(scalar_to_hex)
+        .collect::<Vec<_>>();
+    proof
+        .convert_format_for_solidity_verifier()
+        .to_abi_calldata(
+            &formatted_preprocess.preprocess_entries_part1,
+            &formatted_preprocess.preprocess_entries_part2,
+            &public_inputs,
+        )
+}
+
+fn main() {
+    let config = Config::parse();
+    let qap_path = resolve_subcircuit_library_path(config.subcircuit_library.as_deref())
+        .to_string_lossy()
+        .into_owned();
+
+    let calldata = calldata(&config, &qap_path).unwrap_or_else(|err| {
+        eprintln!("Encoding the calldata failed: {err}");
+        std::process::exit(1);
+    });
+    let encoded = format!("0x{}", hex_encode(&calldata));
+    match &config.output {
+        Some(output) => fs::write(output, encoded).expect("Failed to write the calldata"),
+        None => println!("{encoded}"),
+    }
+}
@@ -3,33 +3,129 @@ use icicle_bls12_381::curve::{ScalarCfg, ScalarField};
 use icicle_core::ntt;
 use icicle_core::traits::{Arithmetic, FieldImpl, GenerateRandom};
 use libs::bivariate_polynomial::{BivariatePolynomial, DensePolynomialExt};
+use libs::error::{TokamakError, TokamakResult};
 use libs::group_structures::pairing;
-use libs::group_structures::{G1serde, SigmaVerify};
+use libs::group_structures::{
+    with_point_validation, G1serde, G2serde, PointValidation, SigmaVerify,
+};
+use libs::impl_circuit_inputs_for_paths;
+use libs::iotools::binary::ArtifactEncoding;
+use libs::iotools::inputs::{CircuitInputs, MemoryInputs};
+use libs::iotools::public_io::{decode_public_io, PublicIo};
 use libs::iotools::{Instance, SetupParams};
 use libs::utils::{
-    init_ntt_domain, load_setup_params_from_qap_path, prover_verifier_ntt_domain_size, setup_shape,
-    validate_setup_shape,
+    init_ntt_domain, prover_verifier_ntt_domain_size, setup_shape, validate_setup_shape,
 };
-use preprocess::{FormattedPreprocess, Preprocess};
+use preprocess::Preprocess;
 use prove::*;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::path::PathBuf;
 
+mod accumulator;
+mod batch;
+mod report;
+#[cfg(test)]
+mod tests;
+pub use accumulator::SnarkAccumulator;
+pub use batch::{BatchVerification, BatchVerifier, BatchVerifyInputPaths};
+pub use report::{
+    ChallengeReport, InputCheck, InputSource, PairingCheck, PairingEquation, VerificationFailure,
+    VerificationReport,
+};
+
 pub struct VerifyInputPaths<'a> {
     pub qap_path: &'a str,
     pub synthesizer_path: &'a str,
     pub setup_path: &'a str,
     pub preprocess_path: &'a str,
     pub proof_path: &'a str,
+    pub preprocess_encoding: ArtifactEncoding,
+    pub proof_encoding: ArtifactEncoding,
+    pub point_validation: PointValidation,
+}
+
+impl_circuit_inputs_for_paths!(VerifyInputPaths<'_>);
+
+/// Inputs of [`Verifier::init`]: the circuit and synthesizer inputs (only `setup_params` and
+/// `instance` are read) plus the verifier's reference string, the preprocess commitments and the
+/// proof.
+pub trait VerifierInputs: CircuitInputs {
+    fn sigma_verify(&self) -> TokamakResult<Cow<'_, SigmaVerify>>;
+    fn preprocess(&self) -> TokamakResult<Cow<'_, Preprocess>>;
+    fn proof(&self) -> TokamakResult<Cow<'_, Proof>>;
+}
+
+/// Reads `sigma_verify.json` from `setup_path` and the preprocess and proof from their
+/// directories in the configured encodings.
+impl VerifierInputs for VerifyInputPaths<'_> {
+    fn sigma_verify(&self) -> TokamakResult<Cow<'_, SigmaVerify>> {
+        let sigma_path = PathBuf::from(self.setup_path).join("sigma_verify.json");
+        SigmaVerify::read_from_json(sigma_path.clone())
+            .map(Cow::Owned)
+            .map_err(|err| TokamakError::from_read(&sigma_path, err))
+    }
+
+    fn preprocess(&self) -> TokamakResult<Cow<'_, Preprocess>> {
+        Preprocess::read_from_dir(self.preprocess_path, self.preprocess_encoding).map(Cow::Owned)
+    }
+
+    fn proof(&self) -> TokamakResult<Cow<'_, Proof>> {
+        Proof::read_from_dir(self.proof_path, self.proof_encoding).map(Cow::Owned)
+    }
+}
+
+/// Verifier artifacts held in memory, for use as the `crs` of [`MemoryInputs`]. Their points
+/// are taken as they are; `point_validation` only applies to points parsed on load.
+pub struct VerifierArtifacts<'a> {
+    pub sigma_verify: &'a SigmaVerify,
+    pub preprocess: &'a Preprocess,
+    pub proof: &'a Proof,
+}
+
+impl VerifierInputs for MemoryInputs<'_, VerifierArtifacts<'_>> {
+    fn sigma_verify(&self) -> TokamakResult<Cow<'_, SigmaVerify>> {
+        Ok(Cow::Borrowed(self.crs.sigma_verify))
+    }
+
+    fn preprocess(&self) -> TokamakResult<Cow<'_, Preprocess>> {
+        Ok(Cow::Borrowed(self.crs.preprocess))
+    }
+
+    fn proof(&self) -> TokamakResult<Cow<'_, Proof>> {
+        Ok(Cow::Borrowed(self.crs.proof))
+    }
 }
 
 pub struct Verifier {
     pub sigma: SigmaVerify,
     pub a_pub_X: DensePolynomialExt,
-    // pub publicInputBuffer: PublicInputBuffer,
-    // pub publicOutputBuffer: PublicOutputBuffer,
+    /// The values of `a_pub_X` decoded with the synthesizer's instance description, when the
+    /// inputs provide one.
+    pub public_io: Option<PublicIo>,
     pub preprocess: Preprocess,
     pub setup_params: SetupParams,
+    /// The public instance, absorbed when the proof uses `FiatShamirMode::StrongV1`.
+    pub instance: Instance,
     pub proof: Proof,
+    pub kappa2_mode: Kappa2Mode,
+    /// The weakest `FiatShamirMode` accepted. The proof records its own mode, so without a
+    /// minimum a StrongV1 proof could be stripped down to Legacy. StrongV1 unless Legacy is
+    /// opted into with `with_min_fiat_shamir`.
+    pub min_fiat_shamir: FiatShamirMode,
+}
+
+/// How the verifier obtains kappa2, the challenge that batches the arithmetic, copy and binding
+/// equations into the single pairing check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kappa2Mode {
+    /// Squeezed from the Fiat-Shamir transcript after absorbing Proof4, the binding and the
+    /// preprocess commitments. Reproducible and identical to the on-chain derivation.
+    #[default]
+    Transcript,
+    /// Sampled fresh on every run. The verdict is the same, but the challenges are not reproducible.
+    Random,
 }
 
 struct VerificationChallenges {
@@ -50,70 +146,208 @@ struct VerificationDomainContext {
     t_smax_eval: ScalarField,
 }
 
-impl Verifier {
-    pub fn init(paths: &VerifyInputPaths) -> Self {
-        let setup_params = load_setup_params_from_qap_path(paths.qap_path);
-        let shape = setup_shape(&setup_params);
-        validate_setup_shape(&shape);
-        let ntt_domain_size = prover_verifier_ntt_domain_size(&shape);
-        init_ntt_domain(ntt_domain_size);
+fn proof_input_checks(proof: &Proof) -> Vec<InputCheck> {
+    let mut checks = Vec::new();
+    for (name, point) in [
+        ("U", &proof.proof0.U),
+        ("V", &proof.proof0.V),
+        ("W", &proof.proof0.W),
+        ("O_mid", &proof.binding.O_mid),
+        ("O_prv", &proof.binding.O_prv),
+        ("Q_AX", &proof.proof0.Q_AX),
+        ("Q_AY", &proof.proof0.Q_AY),
+        ("Q_CX", &proof.proof2.Q_CX),
+        ("Q_CY", &proof.proof2.Q_CY),
+        ("Pi_X", &proof.proof4.Pi_X),
+        ("Pi_Y", &proof.proof4.Pi_Y),
+        ("B", &proof.proof0.B),
+        ("R", &proof.proof1.R),
+        ("M_Y", &proof.proof4.M_Y),
+        ("M_X", &proof.proof4.M_X),
+        ("N_Y", &proof.proof4.N_Y),
+        ("N_X", &proof.proof4.N_X),
+        ("O_pub_free", &proof.binding.O_pub_free),
+        ("A_free", &proof.binding.A_free),
+    ] {
+        checks.push(InputCheck::g1(InputSource::Proof, name, point));
+    }
+    checks
+}
 
-        // Load instance
-        let instance_path = PathBuf::from(paths.synthesizer_path).join("instance.json");
-        let instance = Instance::read_from_json(instance_path).unwrap();
-        // Parsing the inputs
-        let a_pub_X = instance.gen_a_free_X(&setup_params);
+// Checks on the inputs a batch of proofs shares: the preprocess commitments and the CRS.
+fn shared_input_checks(sigma: &SigmaVerify, preprocess: &Preprocess) -> Vec<InputCheck> {
+    let mut checks = Vec::new();
+    for (name, point) in [
+        ("s0", &preprocess.s0),
+        ("s1", &preprocess.s1),
+        ("O_pub_fix", &preprocess.O_pub_fix),
+    ] {
+        checks.push(InputCheck::g1(InputSource::Preprocess, name, point));
+    }
+    for (name, point) in [
+        ("G", sigma.g()),
+        ("sigma_1.x", sigma.sigma1_x()),
+        ("sigma_1.y", sigma.sigma1_y()),
+        ("lagrange_KL", sigma.lagrange_kl()),
+    ] {
+        checks.push(InputCheck::g1(InputSource::SigmaVerify, name, &point));
+    }
+    let sigma2 = sigma.sigma2();
+    for (name, point) in [
+        ("H", sigma.h()),
+        ("sigma_2.alpha", sigma2.alpha),
+        ("sigma_2.alpha2", sigma2.alpha2),
+        ("sigma_2.alpha3", sigma2.alpha3),
+        ("sigma_2.alpha4", sigma2.alpha4),
+        ("sigma_2.gamma", sigma2.gamma),
+        ("sigma_2.delta", sigma2.delta),
+        ("sigma_2.eta", sigma2.eta),
+        ("sigma_2.x", sigma2.x),
+        ("sigma_2.y", sigma2.y),
+    ] {
+        checks.push(InputCheck::g2(InputSource::SigmaVerify, name, &point));
+    }
+    checks
+}
 
-        // Load Sigma (reference string)
-        let sigma_path = PathBuf::from(paths.setup_path).join("sigma_verify.json");
-        let sigma = SigmaVerify::read_from_json(sigma_path).expect(
-            "No reference string is found. Run the Setup first (expected sigma_verify.json).",
-        );
+/// G2 bases of the final pairing check, paired with `SnarkPairingTerms::lhs` and `rhs` respectively.
+fn snark_pairing_bases(sigma: &SigmaVerify) -> ([G2serde; 5], [G2serde; 5]) {
+    let sigma2 = sigma.sigma2();
+    (
+        [
+            sigma.h(),
+            sigma2.alpha4,
+            sigma2.alpha,
+            sigma2.alpha2,
+            sigma2.alpha3,
+        ],
+        [sigma2.gamma, sigma2.eta, sigma2.delta, sigma2.x, sigma2.y],
+    )
+}
 
-        // Load Verifier preprocess
-        let preprocess_path = PathBuf::from(paths.preprocess_path).join("preprocess.json");
-        let preprocess = FormattedPreprocess::read_from_json(preprocess_path)
-            .expect("No Verifier preprocess is found. Run the Preprocess first.")
-            .recover_proof_from_format();
-
-        // Load Proof
-        let proof_path = PathBuf::from(paths.proof_path).join("proof.json");
-        // let proof = Proof::read_from_json(&proof_path)
-        // .expect("No proof is found. Run the Prove first.");
-        let proof = FormattedProof::read_from_json(proof_path)
-            .expect("No proof is found. Run the Prove first.")
-            .recover_proof_from_format();
-
-        return Self {
-            sigma,
-            a_pub_X,
-            // publicInputBuffer: instance.publicInputBuffer,
-            // publicOutputBuffer: instance.publicOutputBuffer,
-            setup_params,
+/// G1 sides of the final pairing check `e(lhs, lhs_bases) == e(rhs, rhs_bases)`.
+struct SnarkPairingTerms {
+    lhs: [G1serde; 5],
+    rhs: [G1serde; 5],
+}
+
+// Rejects a proof made in a weaker mode than `required`.
+fn fiat_shamir_failure(required: FiatShamirMode, proof: &Proof) -> Option<VerificationFailure> {
+    (proof.fiat_shamir < required).then_some(VerificationFailure::FiatShamirMode {
+        required,
+        found: proof.fiat_shamir,
+    })
+}
+
+// `statement` is only absorbed when the proof was made in `FiatShamirMode::StrongV1`.
+fn derive_challenges(
+    proof: &Proof,
+    preprocess: &Preprocess,
+    kappa2_mode: Kappa2Mode,
+    statement: &ProtocolStatement,
+) -> VerificationChallenges {
+    derive_challenges_traced(proof, preprocess, kappa2_mode, statement, false).0
+}
+
+// Also returns the `TranscriptTrace` of the derivation when `trace` is set.
+fn derive_challenges_traced(
+    proof: &Proof,
+    preprocess: &Preprocess,
+    kappa2_mode: Kappa2Mode,
+    statement: &ProtocolStatement,
+    trace: bool,
+) -> (VerificationChallenges, Option<TranscriptTrace>) {
+    match proof.transcript {
+        TranscriptKind::Keccak => derive_challenges_with::<RollingKeccakTranscript>(
+            proof,
             preprocess,
+            kappa2_mode,
+            statement,
+            trace,
+        ),
+        TranscriptKind::Poseidon => derive_challenges_with::<PoseidonTranscript>(
             proof,
-        };
+            preprocess,
+            kappa2_mode,
+            statement,
+            trace,
+        ),
     }
+}
 
-    fn collect_challenges(&self) -> VerificationChallenges {
-        let proof0 = &self.proof.proof0;
-        let proof1 = &self.proof.proof1;
-        let proof2 = &self.proof.proof2;
-        let proof3 = &self.proof.proof3;
-        let mut transcript_manager = TranscriptManager::new();
-        let thetas = proof0.verify0_with_manager(&mut transcript_manager);
-        let kappa0 = proof1.verify1_with_manager(&mut transcript_manager);
-        let (chi, zeta) = proof2.verify2_with_manager(&mut transcript_manager);
-        let kappa1 = proof3.verify3_with_manager(&mut transcript_manager);
-        let kappa2 = ScalarCfg::generate_random(1)[0];
-        VerificationChallenges {
-            thetas,
-            kappa0,
-            chi,
-            zeta,
-            kappa1,
-            kappa2,
+fn derive_challenges_with<T: Transcript>(
+    proof: &Proof,
+    preprocess: &Preprocess,
+    kappa2_mode: Kappa2Mode,
+    statement: &ProtocolStatement,
+    trace: bool,
+) -> (VerificationChallenges, Option<TranscriptTrace>) {
+    let mut transcript_manager = TranscriptManager::<T>::default();
+    if trace {
+        transcript_manager = transcript_manager.with_trace();
+    }
+    if proof.fiat_shamir == FiatShamirMode::StrongV1 {
+        transcript_manager.add_statement(statement);
+    }
+    let thetas = proof.proof0.verify0_with_manager(&mut transcript_manager);
+    let kappa0 = proof.proof1.verify1_with_manager(&mut transcript_manager);
+    let (chi, zeta) = proof.proof2.verify2_with_manager(&mut transcript_manager);
+    let kappa1 = proof.proof3.verify3_with_manager(&mut transcript_manager);
+    let kappa2 = match kappa2_mode {
+        Kappa2Mode::Transcript => {
+            transcript_manager.add_proof4(&proof.proof4);
+            transcript_manager.add_binding(&proof.binding);
+            transcript_manager.add_preprocess(
+                &preprocess.s0,
+                &preprocess.s1,
+                &preprocess.O_pub_fix,
+            );
+            transcript_manager.get_kappa2()
         }
+        Kappa2Mode::Random => ScalarCfg::generate_random(1)[0],
+    };
+    let challenges = VerificationChallenges {
+        thetas,
+        kappa0,
+        chi,
+        zeta,
+        kappa1,
+        kappa2,
+    };
+    (challenges, transcript_manager.take_trace())
+}
+
+/// The inputs of one proof's verification, borrowed from a `Verifier`, or from a `BatchVerifier`
+/// and one of its entries.
+struct ProofCheck<'a> {
+    sigma: &'a SigmaVerify,
+    preprocess: &'a Preprocess,
+    setup_params: &'a SetupParams,
+    a_pub_X: &'a DensePolynomialExt,
+    instance: &'a Instance,
+    proof: &'a Proof,
+    kappa2_mode: Kappa2Mode,
+}
+
+impl ProofCheck<'_> {
+    fn statement(&self) -> ProtocolStatement<'_> {
+        ProtocolStatement {
+            sigma_digest: self.sigma.digest(),
+            setup_params: self.setup_params,
+            s0: &self.preprocess.s0,
+            s1: &self.preprocess.s1,
+            O_pub_fix: &self.preprocess.O_pub_fix,
+            instance: self.instance,
+        }
+    }
+
+    fn collect_challenges(&self) -> VerificationChallenges {
+        derive_challenges(
+            self.proof,
+            self.preprocess,
+            self.kappa2_mode,
+            &self.statement(),
+        )
     }
 
     fn build_domain_context(
@@ -222,6 +456,114 @@ impl Verifier {
         (AUX, AUX_X, AUX_Y)
     }
 
+    fn snark_pairing_terms(&self, challenges: &VerificationChallenges) -> SnarkPairingTerms {
+        let binding = &self.proof.binding;
+        let proof0 = &self.proof.proof0;
+        let proof4 = &self.proof.proof4;
+        let domain = self.build_domain_context(challenges);
+        let lagrange_k0_eval = self.eval_lagrange_k0(&domain, challenges);
+        let a_eval = self.eval_a_pub(challenges);
+        let lhs_a = self.lhs_arith(&domain, challenges);
+        let lhs_c = self.lhs_copy(&domain, challenges, lagrange_k0_eval);
+        let lhs_b = self.lhs_binding(challenges, a_eval);
+        let lhs = lhs_b + ((lhs_a + lhs_c) * challenges.kappa2);
+        let (aux, aux_x, aux_y) = self.snark_aux(proof4, &domain, challenges);
+
+        SnarkPairingTerms {
+            lhs: [lhs + aux, proof0.B, proof0.U, proof0.V, proof0.W],
+            rhs: [
+                self.preprocess.O_pub_fix + binding.O_pub_free,
+                binding.O_mid,
+                binding.O_prv,
+                aux_x,
+                aux_y,
+            ],
+        }
+    }
+}
+
+impl Verifier {
+    pub fn init<I: VerifierInputs + ?Sized>(inputs: &I) -> TokamakResult<Self> {
+        let setup_params = inputs.setup_params()?.into_owned();
+        let shape = setup_shape(&setup_params)?;
+        validate_setup_shape(&shape)?;
+        let ntt_domain_size = prover_verifier_ntt_domain_size(&shape);
+        init_ntt_domain(ntt_domain_size)?;
+
+        // Load instance
+        let instance = inputs.instance()?;
+        // Parsing the inputs
+        let a_pub_X = instance.gen_a_free_X(&setup_params);
+        // The description only labels the verified elements, so a missing or mismatched one is
+        // reported rather than rejected.
+        let public_io = match inputs.instance_description() {
+            Ok(description) => description
+                .map(|description| decode_public_io(&instance, &description, &setup_params)),
+            Err(err) => Some(PublicIo::unreadable(&err)),
+        };
+
+        let (sigma, preprocess, proof) = with_point_validation(inputs.point_validation(), || {
+            // Load Sigma (reference string)
+            let sigma = inputs.sigma_verify()?.into_owned();
+
+            // Load Verifier preprocess
+            let preprocess = inputs.preprocess()?.into_owned();
+
+            // Load Proof
+            let proof = inputs.proof()?.into_owned();
+            Ok::<_, TokamakError>((sigma, preprocess, proof))
+        })?;
+
+        return Ok(Self {
+            sigma,
+            a_pub_X,
+            public_io,
+            setup_params,
+            instance: instance.into_owned(),
+            preprocess,
+            proof,
+            kappa2_mode: Kappa2Mode::default(),
+            min_fiat_shamir: FiatShamirMode::StrongV1,
+        });
+    }
+
+    pub fn with_kappa2_mode(mut self, kappa2_mode: Kappa2Mode) -> Self {
+        self.kappa2_mode = kappa2_mode;
+        self
+    }
+
+    /// Rejects proofs made in a weaker Fiat-Shamir mode than `min_fiat_shamir`. Pass
+    /// `FiatShamirMode::Legacy` to accept proofs for the Solidity verifier's transcript.
+    pub fn with_min_fiat_shamir(mut self, min_fiat_shamir: FiatShamirMode) -> Self {
+        self.min_fiat_shamir = min_fiat_shamir;
+        self
+    }
+
+    fn check(&self) -> ProofCheck<'_> {
+        ProofCheck {
+            sigma: &self.sigma,
+            preprocess: &self.preprocess,
+            setup_params: &self.setup_params,
+            a_pub_X: &self.a_pub_X,
+            instance: &self.instance,
+            proof: &self.proof,
+            kappa2_mode: self.kappa2_mode,
+        }
+    }
+
+    /// Every absorb and squeeze of the challenge derivation. With `Kappa2Mode::Transcript` it
+    /// matches the trace the prover records step for step.
+    pub fn transcript_trace(&self) -> TranscriptTrace {
+        let (_, trace) = derive_challenges_traced(
+            &self.proof,
+            &self.preprocess,
+            self.kappa2_mode,
+            &self.check().statement(),
+            true,
+        );
+        trace.expect("tracing was enabled")
+    }
+
     fn copy_aux(
         &self,
         proof4: &Proof4Test,
@@ -245,53 +587,125 @@ impl Verifier {
         proof4.Pi_AX * challenges.chi + proof4.Pi_AY * challenges.zeta
     }
 
-    pub fn verify_snark(&self) -> bool {
-        let binding = &self.proof.binding;
-        let proof0 = &self.proof.proof0;
-        let proof4 = &self.proof.proof4;
-        let challenges = self.collect_challenges();
-        let domain = self.build_domain_context(&challenges);
-        let lagrange_k0_eval = self.eval_lagrange_k0(&domain, &challenges);
-        let a_eval = self.eval_a_pub(&challenges);
-        let lhs_a = self.lhs_arith(&domain, &challenges);
-        let lhs_c = self.lhs_copy(&domain, &challenges, lagrange_k0_eval);
-        let lhs_b = self.lhs_binding(&challenges, a_eval);
-        let lhs = lhs_b + ((lhs_a + lhs_c) * challenges.kappa2);
-        let (aux, aux_x, aux_y) = self.snark_aux(proof4, &domain, &challenges);
+    fn input_checks(&self) -> Vec<InputCheck> {
+        let mut checks = proof_input_checks(&self.proof);
+        checks.extend(shared_input_checks(&self.sigma, &self.preprocess));
+        checks
+    }
 
-        let left_pair = pairing(
-            &[lhs + aux, proof0.B, proof0.U, proof0.V, proof0.W],
-            &[
-                self.sigma.h(),
-                self.sigma.sigma2().alpha4,
-                self.sigma.sigma2().alpha,
-                self.sigma.sigma2().alpha2,
-                self.sigma.sigma2().alpha3,
-            ],
-        );
-        let right_pair = pairing(
-            &[
-                self.preprocess.O_pub_fix + binding.O_pub_free,
-                binding.O_mid,
-                binding.O_prv,
-                aux_x,
-                aux_y,
-            ],
-            &[
-                self.sigma.sigma2().gamma,
-                self.sigma.sigma2().eta,
-                self.sigma.sigma2().delta,
-                self.sigma.sigma2().x,
-                self.sigma.sigma2().y,
-            ],
-        );
+    fn snark_pairing_holds(&self, challenges: &VerificationChallenges) -> bool {
+        let terms = self.check().snark_pairing_terms(challenges);
+        let (lhs_bases, rhs_bases) = snark_pairing_bases(&self.sigma);
+        let left_pair = pairing(&terms.lhs, &lhs_bases);
+        let right_pair = pairing(&terms.rhs, &rhs_bases);
         left_pair.eq(&right_pair)
     }
 
+    /// Runs the full verification and records the recomputed challenges, the validity of every
+    /// input group element and the outcome of the final pairing equation.
+    pub fn verify_with_report(&self) -> VerificationReport {
+        let challenges = self.check().collect_challenges();
+        let challenge_report = ChallengeReport::new(
+            &challenges.thetas,
+            &challenges.kappa0,
+            &challenges.chi,
+            &challenges.zeta,
+            &challenges.kappa1,
+            &challenges.kappa2,
+            self.kappa2_mode,
+        );
+        let input_checks = self.input_checks();
+        let failure = fiat_shamir_failure(self.min_fiat_shamir, &self.proof).or_else(|| {
+            input_checks
+                .iter()
+                .find(|check| !check.status.is_valid())
+                .map(|invalid| VerificationFailure::InvalidInput {
+                    source: invalid.source,
+                    name: invalid.name,
+                })
+        });
+        if let Some(failure) = failure {
+            return VerificationReport {
+                verified: false,
+                challenges: challenge_report,
+                input_checks,
+                pairing_checks: Vec::new(),
+                failure: Some(failure),
+                public_io: self.public_io.clone(),
+            };
+        }
+
+        let passed = self.snark_pairing_holds(&challenges);
+        VerificationReport {
+            verified: passed,
+            challenges: challenge_report,
+            input_checks,
+            pairing_checks: vec![PairingCheck {
+                equation: PairingEquation::Snark,
+                passed,
+            }],
+            failure: (!passed).then_some(VerificationFailure::Pairing {
+                equation: PairingEquation::Snark,
+            }),
+            public_io: self.public_io.clone(),
+        }
+    }
+
+    /// Extends `verify_with_report` with the per-argument pairing equations, which need the
+    /// split opening proofs written by the prover in testing mode.
+    pub fn verify_with_test_report(&self, proof4: &Proof4Test) -> VerificationReport {
+        let mut report = self.verify_with_report();
+        if report.pairing_checks.is_empty() {
+            return report;
+        }
+        for (equation, passed) in [
+            (PairingEquation::Arith, self.verify_arith(proof4)),
+            (PairingEquation::Copy, self.verify_copy(proof4)),
+            (PairingEquation::Binding, self.verify_binding(proof4)),
+        ] {
+            report
+                .pairing_checks
+                .push(PairingCheck { equation, passed });
+            if !passed && report.failure.is_none() {
+                report.verified = false;
+                report.failure = Some(VerificationFailure::Pairing { equation });
+            }
+        }
+        report
+    }
+
+    /// Checks the inputs and derives the challenges like `verify_with_report`, but defers the final
+    /// pairing check to the returned accumulator instead of running it.
+    pub fn accumulate(&self) -> Result<SnarkAccumulator, VerificationFailure> {
+        if let Some(failure) = fiat_shamir_failure(self.min_fiat_shamir, &self.proof) {
+            return Err(failure);
+        }
+        if let Some(invalid) = self
+            .input_checks()
+            .into_iter()
+            .find(|check| !check.status.is_valid())
+        {
+            return Err(VerificationFailure::InvalidInput {
+                source: invalid.source,
+                name: invalid.name,
+            });
+        }
+        let challenges = self.check().collect_challenges();
+        Ok(SnarkAccumulator::from_terms(
+            self.sigma.digest(),
+            self.check().snark_pairing_terms(&challenges),
+        ))
+    }
+
+    pub fn verify_snark(&self) -> bool {
+        self.verify_with_report().verified
+    }
+
     pub fn verify_arith(&self, proof4: &Proof4Test) -> bool {
-        let challenges = self.collect_challenges();
-        let domain = self.build_domain_context(&challenges);
-        let lhs_a = self.lhs_arith(&domain, &challenges);
+        let check = self.check();
+        let challenges = check.collect_challenges();
+        let domain = check.build_domain_context(&challenges);
+        let lhs_a = check.lhs_arith(&domain, &challenges);
         let aux_a = self.arith_aux(proof4, &challenges);
 
         let left_pair = pairing(&[lhs_a + aux_a], &[self.sigma.h()]);
@@ -303,10 +717,11 @@ impl Verifier {
     }
 
     pub fn verify_copy(&self, proof4: &Proof4Test) -> bool {
-        let challenges = self.collect_challenges();
-        let domain = self.build_domain_context(&challenges);
-        let lagrange_k0_eval = self.eval_lagrange_k0(&domain, &challenges);
-        let lhs_c = self.lhs_copy(&domain, &challenges, lagrange_k0_eval);
+        let check = self.check();
+        let challenges = check.collect_challenges();
+        let domain = check.build_domain_context(&challenges);
+        let lagrange_k0_eval = check.eval_lagrange_k0(&domain, &challenges);
+        let lhs_c = check.lhs_copy(&domain, &challenges, lagrange_k0_eval);
         let (aux_c, aux_x, aux_y) = self.copy_aux(proof4, &domain, &challenges);
         let left_pair = pairing(&[lhs_c + aux_c], &[self.sigma.h()]);
         let right_pair = pairing(
@@ -317,11 +732,12 @@ impl Verifier {
     }
 
     pub fn verify_binding(&self, proof4: &Proof4Test) -> bool {
+        let check = self.check();
         let binding = &self.proof.binding;
         let proof0 = &self.proof.proof0;
-        let challenges = self.collect_challenges();
-        let a_eval = self.eval_a_pub(&challenges);
-        let lhs_b = self.lhs_binding(&challenges, a_eval);
+        let challenges = check.collect_challenges();
+        let a_eval = check.eval_a_pub(&challenges);
+        let lhs_b = check.lhs_binding(&challenges, a_eval);
         let aux_b = proof4.Pi_B * (challenges.kappa2 * challenges.chi);
         let left_pair = pairing(
             &[lhs_b + aux_b, proof0.B, proof0.U, proof0.V, proof0.W],
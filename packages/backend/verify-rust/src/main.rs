@@ -1,9 +1,13 @@
 use clap::Parser;
+use libs::group_structures::PointValidation;
+use libs::iotools::binary::ArtifactEncoding;
 use libs::subcircuit_library::{resolve_subcircuit_library_path, SubcircuitLibraryArg};
 use libs::utils::check_device;
+use prove::FiatShamirMode;
 #[cfg(feature = "testing-mode")]
 use prove::Proof4Test;
-use verify::{Verifier, VerifyInputPaths};
+use std::path::PathBuf;
+use verify::{Kappa2Mode, Verifier, VerifyInputPaths};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -26,6 +30,41 @@ struct Config {
     /// Proof output directory containing proof.json
     #[arg(long, value_name = "PATH")]
     proof: String,
+
+    /// Optional path to write the verification report (challenges and per-check results) as JSON
+    #[arg(long, value_name = "PATH")]
+    report: Option<String>,
+
+    /// Defer the final pairing check: write it as an accumulator JSON for `fold_accumulators`
+    /// instead of running it
+    #[arg(long, value_name = "PATH", conflicts_with = "report")]
+    accumulator: Option<PathBuf>,
+
+    /// Sample kappa2 at random instead of deriving it from the transcript
+    #[arg(long)]
+    random_kappa2: bool,
+
+    /// Reject proofs made in a weaker Fiat-Shamir mode than this one (pass legacy to accept the
+    /// proofs of `prove`'s default mode)
+    #[arg(long, value_enum, default_value_t = FiatShamirMode::StrongV1)]
+    min_fiat_shamir: FiatShamirMode,
+
+    /// Encoding of the preprocess input (both binary encodings read preprocess.bin)
+    #[arg(long, value_enum, default_value_t = ArtifactEncoding::Json)]
+    preprocess_encoding: ArtifactEncoding,
+
+    /// Encoding of the proof input (both binary encodings read proof.bin)
+    #[arg(long, value_enum, default_value_t = ArtifactEncoding::Json)]
+    proof_encoding: ArtifactEncoding,
+
+    /// Skip on-curve and subgroup checks of loaded points (only for locally generated artifacts)
+    #[arg(long)]
+    trusted_artifacts: bool,
+
+    /// Optional path to write every transcript absorb and squeeze as JSON, for comparison with
+    /// `transcript_diff`
+    #[arg(long, value_name = "PATH")]
+    transcript_trace: Option<PathBuf>,
 }
 
 fn main() {
@@ -40,30 +79,89 @@ fn main() {
         setup_path: &config.crs,
         preprocess_path: &config.preprocess,
         proof_path: &config.proof,
+        preprocess_encoding: config.preprocess_encoding,
+        proof_encoding: config.proof_encoding,
+        point_validation: if config.trusted_artifacts {
+            PointValidation::Trusted
+        } else {
+            PointValidation::Checked
+        },
     };
 
     check_device();
 
     println!("Verifier initialization...");
-    let verifier = Verifier::init(&paths);
+    let kappa2_mode = if config.random_kappa2 {
+        Kappa2Mode::Random
+    } else {
+        Kappa2Mode::Transcript
+    };
+    let verifier = Verifier::init(&paths)
+        .unwrap_or_else(|err| {
+            eprintln!("Verifier initialization failed: {err}");
+            std::process::exit(1);
+        })
+        .with_kappa2_mode(kappa2_mode)
+        .with_min_fiat_shamir(config.min_fiat_shamir);
 
-    println!("Verifying the proof...");
-    let res_snark = verifier.verify_snark();
-    println!("{}", res_snark);
+    if let Some(trace_path) = &config.transcript_trace {
+        verifier
+            .transcript_trace()
+            .write_into_json(trace_path.clone())
+            .expect("Failed to write the transcript trace");
+    }
+
+    if let Some(accumulator_path) = &config.accumulator {
+        println!("Accumulating the proof...");
+        let accumulated = match verifier.accumulate() {
+            Ok(accumulator) => {
+                accumulator
+                    .write_into_json(accumulator_path.clone())
+                    .expect("Failed to write the accumulator");
+                true
+            }
+            Err(failure) => {
+                eprintln!("Verification failed: {:?}", failure);
+                false
+            }
+        };
+        println!("{accumulated}");
+        return;
+    }
 
+    println!("Verifying the proof...");
+    #[cfg(not(feature = "testing-mode"))]
+    let report = verifier.verify_with_report();
     #[cfg(feature = "testing-mode")]
-    {
-        use std::path::PathBuf;
+    let report = {
         let test_proof_path = PathBuf::from(paths.proof_path).join("proof4_test.json");
         let proof4_test = Proof4Test::read_from_json(test_proof_path).unwrap();
-        println!(
-            "Verification arithmetic: {}",
-            verifier.verify_arith(&proof4_test)
-        );
-        println!("Verification copy: {}", verifier.verify_copy(&proof4_test));
-        println!(
-            "Verification binding: {}",
-            verifier.verify_binding(&proof4_test)
-        );
+        let report = verifier.verify_with_test_report(&proof4_test);
+        for check in &report.pairing_checks {
+            println!("Verification {:?}: {}", check.equation, check.passed);
+        }
+        report
+    };
+
+    if let Some(failure) = &report.failure {
+        eprintln!("Verification failed: {:?}", failure);
+    }
+    if let Some(public_io) = &report.public_io {
+        for mismatch in &public_io.mismatches {
+            eprintln!("Instance description mismatch: {:?}", mismatch);
+        }
+        if report.verified {
+            for (kind, values) in [("output", &public_io.outputs), ("input", &public_io.inputs)] {
+                for value in values {
+                    println!("Public {kind} {}: {}", value.description, value.value);
+                }
+            }
+        }
+    }
+    if let Some(report_path) = &config.report {
+        report
+            .write_into_json(PathBuf::from(report_path))
+            .expect("Failed to write the verification report");
     }
+    println!("{}", report.verified);
 }
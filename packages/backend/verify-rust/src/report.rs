@@ -0,0 +1,134 @@
+use crate::Kappa2Mode;
+use icicle_bls12_381::curve::ScalarField;
+use libs::group_structures::{G1serde, G2serde, PointCheck};
+use libs::impl_write_into_json;
+use libs::iotools::public_io::PublicIo;
+use libs::iotools::scalar_to_hex;
+use prove::FiatShamirMode;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Where an input element of the verification was loaded from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputSource {
+    Proof,
+    Preprocess,
+    SigmaVerify,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct InputCheck {
+    pub source: InputSource,
+    pub name: &'static str,
+    pub status: PointCheck,
+}
+
+impl InputCheck {
+    pub fn g1(source: InputSource, name: &'static str, point: &G1serde) -> Self {
+        Self {
+            source,
+            name,
+            status: point.check(),
+        }
+    }
+
+    pub fn g2(source: InputSource, name: &'static str, point: &G2serde) -> Self {
+        Self {
+            source,
+            name,
+            status: point.check(),
+        }
+    }
+}
+
+/// Fiat-Shamir challenges recomputed by the verifier, as big-endian hex strings.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChallengeReport {
+    pub thetas: Vec<String>,
+    pub kappa0: String,
+    pub chi: String,
+    pub zeta: String,
+    pub kappa1: String,
+    pub kappa2: String,
+    pub kappa2_mode: Kappa2Mode,
+}
+
+impl ChallengeReport {
+    pub fn new(
+        thetas: &[ScalarField],
+        kappa0: &ScalarField,
+        chi: &ScalarField,
+        zeta: &ScalarField,
+        kappa1: &ScalarField,
+        kappa2: &ScalarField,
+        kappa2_mode: Kappa2Mode,
+    ) -> Self {
+        Self {
+            thetas: thetas.iter().map(scalar_to_hex).collect(),
+            kappa0: scalar_to_hex(kappa0),
+            chi: scalar_to_hex(chi),
+            zeta: scalar_to_hex(zeta),
+            kappa1: scalar_to_hex(kappa1),
+            kappa2: scalar_to_hex(kappa2),
+            kappa2_mode,
+        }
+    }
+}
+
+/// The pairing equations the verifier can evaluate. Only `Snark` is available in production;
+/// the per-argument equations need the `Proof4Test` split of the opening proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairingEquation {
+    Snark,
+    Arith,
+    Copy,
+    Binding,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PairingCheck {
+    pub equation: PairingEquation,
+    pub passed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VerificationFailure {
+    /// A proof, preprocess or CRS element is not a valid group element.
+    InvalidInput {
+        source: InputSource,
+        name: &'static str,
+    },
+    /// A pairing equation did not hold.
+    Pairing { equation: PairingEquation },
+    /// The proof was made in a weaker Fiat-Shamir mode than the verifier requires.
+    FiatShamirMode {
+        required: FiatShamirMode,
+        found: FiatShamirMode,
+    },
+}
+
+/// Structured verdict of `Verifier::verify_with_report`.
+#[derive(Clone, Debug, Serialize)]
+pub struct VerificationReport {
+    pub verified: bool,
+    pub challenges: ChallengeReport,
+    pub input_checks: Vec<InputCheck>,
+    pub pairing_checks: Vec<PairingCheck>,
+    pub failure: Option<VerificationFailure>,
+    /// The public outputs and inputs read from the synthesizer's instance description, with
+    /// every mismatch against the instance. The proof attests to them only if `verified`.
+    pub public_io: Option<PublicIo>,
+}
+
+impl_write_into_json!(VerificationReport);
+
+impl VerificationReport {
+    pub fn failed_inputs(&self) -> impl Iterator<Item = &InputCheck> {
+        self.input_checks
+            .iter()
+            .filter(|check| !check.status.is_valid())
+    }
+}
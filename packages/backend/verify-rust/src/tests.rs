@@ -0,0 +1,751 @@
+use super::*;
+use icicle_bls12_381::curve::{BaseField, CurveCfg, G1Affine, G2CurveCfg};
+use icicle_core::curve::Curve;
+use libs::field_structures::{FieldSerde, Tau};
+use libs::group_structures::{PartialSigma1Verify, Sigma2};
+use libs::iotools::binary::PointEncoding;
+use libs::iotools::solidity::{STATEMENT_LAYOUT, TRANSCRIPT_LAYOUT};
+use libs::iotools::{scalar_from_hex, scalar_to_hex, HexString};
+use preprocess::FormattedPreprocess;
+
+// Fixture points are not on the curve; the transcript only absorbs their coordinates.
+fn point(k: u32) -> G1serde {
+    G1serde(G1Affine {
+        x: BaseField::from_u32(k),
+        y: BaseField::from_u32(1000 + k),
+    })
+}
+
+fn scalar(v: u32) -> FieldSerde {
+    FieldSerde(ScalarField::from_u32(v))
+}
+
+fn fixture() -> (Proof, Preprocess) {
+    let proof = Proof {
+        proof0: Proof0 {
+            U: point(1),
+            V: point(2),
+            W: point(3),
+            Q_AX: point(4),
+            Q_AY: point(5),
+            B: point(6),
+        },
+        proof1: Proof1 { R: point(7) },
+        proof2: Proof2 {
+            Q_CX: point(8),
+            Q_CY: point(9),
+        },
+        proof3: Proof3 {
+            V_eval: scalar(101),
+            R_eval: scalar(102),
+            R_omegaX_eval: scalar(103),
+            R_omegaX_omegaY_eval: scalar(104),
+        },
+        proof4: Proof4 {
+            Pi_X: point(10),
+            Pi_Y: point(11),
+            M_X: point(12),
+            M_Y: point(13),
+            N_X: point(14),
+            N_Y: point(15),
+        },
+        binding: Binding {
+            A_free: point(16),
+            O_pub_free: point(17),
+            O_mid: point(18),
+            O_prv: point(19),
+        },
+        transcript: TranscriptKind::Keccak,
+        fiat_shamir: FiatShamirMode::Legacy,
+    };
+    let preprocess = Preprocess {
+        s0: point(20),
+        s1: point(21),
+        O_pub_fix: point(22),
+    };
+    (proof, preprocess)
+}
+
+fn statement_fixture() -> (SetupParams, Instance) {
+    let setup_params = SetupParams {
+        l_free: 4,
+        l: 8,
+        l_user_out: 1,
+        l_user: 2,
+        l_D: 16,
+        m_D: 32,
+        n: 8,
+        s_D: 4,
+        s_max: 4,
+    };
+    let hex = |values: &[&str]| -> Box<[HexString]> {
+        values.iter().map(|v| HexString(v.to_string())).collect()
+    };
+    let instance = Instance {
+        a_pub_user: hex(&["0x01", "0x02"]),
+        a_pub_block: hex(&["0x03", "0x04"]),
+        a_pub_function: hex(&["0x05"]),
+    };
+    (setup_params, instance)
+}
+
+fn derive_bound(
+    proof: &Proof,
+    preprocess: &Preprocess,
+    kappa2_mode: Kappa2Mode,
+    sigma_digest: ScalarField,
+    instance: &Instance,
+) -> VerificationChallenges {
+    let (setup_params, _) = statement_fixture();
+    let statement = ProtocolStatement {
+        sigma_digest,
+        setup_params: &setup_params,
+        s0: &preprocess.s0,
+        s1: &preprocess.s1,
+        O_pub_fix: &preprocess.O_pub_fix,
+        instance,
+    };
+    derive_challenges(proof, preprocess, kappa2_mode, &statement)
+}
+
+fn derive(
+    proof: &Proof,
+    preprocess: &Preprocess,
+    kappa2_mode: Kappa2Mode,
+) -> VerificationChallenges {
+    let (_, instance) = statement_fixture();
+    derive_bound(
+        proof,
+        preprocess,
+        kappa2_mode,
+        ScalarField::from_u32(7),
+        &instance,
+    )
+}
+
+#[test]
+fn test_transcript_challenges_golden_vectors() {
+    let (proof, preprocess) = fixture();
+    let challenges = derive(&proof, &preprocess, Kappa2Mode::Transcript);
+    let thetas: Vec<String> = challenges.thetas.iter().map(scalar_to_hex).collect();
+    assert_eq!(
+        thetas,
+        [
+            "0x08d81b2b3d51267640882f3a0d0277b06ebbf8bc4aed69a49135057816aa4572",
+            "0x09befc192df9e88dae1ba0ea0a2417d85ca70e3ec9db36c53f3ef752b49b85e7",
+            "0x1e1720f94e320c728523defb9de53785efb69c653b2ddf1941b7eae766b548ad",
+        ]
+    );
+    assert_eq!(
+        scalar_to_hex(&challenges.kappa0),
+        "0x08f0387ff62a60958e91ac5f7e0ab165e2e8e312e9629dd4b859690d179513be"
+    );
+    assert_eq!(
+        scalar_to_hex(&challenges.chi),
+        "0x14e0894bea6783c1b819bdcc92cb2be2354078a7cb40503af8342b69153959a4"
+    );
+    assert_eq!(
+        scalar_to_hex(&challenges.zeta),
+        "0x185f6cfd984c2713eb8fb6d0b366950c82b52f5f153f3d275270dbafb507b3da"
+    );
+    assert_eq!(
+        scalar_to_hex(&challenges.kappa1),
+        "0x0e9f47b4dbe554f249869aa7398893ad1779d7f90f726dac950c113a2f7a879f"
+    );
+    assert_eq!(
+        scalar_to_hex(&challenges.kappa2),
+        "0x1cb0b3edb6cdd2cac68e44c35c82284926666b9d0a767182b3fbf959daa97894"
+    );
+}
+
+#[test]
+fn test_transcript_kappa2_binds_final_check_inputs() {
+    let (proof, preprocess) = fixture();
+    let base = derive(&proof, &preprocess, Kappa2Mode::Transcript).kappa2;
+
+    let (mut tampered_proof, preprocess) = fixture();
+    tampered_proof.proof4.N_Y = point(99);
+    let kappa2 = derive(&tampered_proof, &preprocess, Kappa2Mode::Transcript).kappa2;
+    assert_ne!(base, kappa2);
+
+    let (proof, mut tampered_preprocess) = fixture();
+    tampered_preprocess.O_pub_fix = point(99);
+    let kappa2 = derive(&proof, &tampered_preprocess, Kappa2Mode::Transcript).kappa2;
+    assert_ne!(base, kappa2);
+}
+
+#[test]
+fn test_random_kappa2_keeps_transcript_challenges() {
+    let (proof, preprocess) = fixture();
+    let transcript = derive(&proof, &preprocess, Kappa2Mode::Transcript);
+    let random = derive(&proof, &preprocess, Kappa2Mode::Random);
+    assert_eq!(transcript.thetas, random.thetas);
+    assert_eq!(transcript.kappa1, random.kappa1);
+    assert_ne!(transcript.kappa2, random.kappa2);
+}
+
+#[test]
+fn test_poseidon_transcript_is_deterministic_and_distinct() {
+    let (mut proof, preprocess) = fixture();
+    let keccak = derive(&proof, &preprocess, Kappa2Mode::Transcript);
+    proof.transcript = TranscriptKind::Poseidon;
+    let poseidon = derive(&proof, &preprocess, Kappa2Mode::Transcript);
+    let again = derive(&proof, &preprocess, Kappa2Mode::Transcript);
+    assert_eq!(poseidon.thetas, again.thetas);
+    assert_eq!(poseidon.kappa2, again.kappa2);
+    assert_ne!(poseidon.thetas, keccak.thetas);
+    assert_ne!(poseidon.thetas[0], poseidon.thetas[1]);
+
+    proof.proof4.N_Y = point(99);
+    let tampered = derive(&proof, &preprocess, Kappa2Mode::Transcript);
+    assert_eq!(poseidon.kappa1, tampered.kappa1);
+    assert_ne!(poseidon.kappa2, tampered.kappa2);
+}
+
+#[test]
+fn test_strong_fiat_shamir_binds_the_statement() {
+    let (mut proof, preprocess) = fixture();
+    let (_, instance) = statement_fixture();
+    let digest = ScalarField::from_u32(7);
+    let legacy = derive(&proof, &preprocess, Kappa2Mode::Transcript);
+
+    proof.fiat_shamir = FiatShamirMode::StrongV1;
+    let strong = derive_bound(
+        &proof,
+        &preprocess,
+        Kappa2Mode::Transcript,
+        digest,
+        &instance,
+    );
+    assert_ne!(strong.thetas, legacy.thetas);
+
+    let mut other_instance = instance.clone();
+    other_instance.a_pub_function[0] = HexString("0x06".to_string());
+    let rebound = derive_bound(
+        &proof,
+        &preprocess,
+        Kappa2Mode::Transcript,
+        digest,
+        &other_instance,
+    );
+    assert_ne!(strong.thetas, rebound.thetas);
+
+    let other_crs = derive_bound(
+        &proof,
+        &preprocess,
+        Kappa2Mode::Transcript,
+        ScalarField::from_u32(8),
+        &instance,
+    );
+    assert_ne!(strong.thetas, other_crs.thetas);
+
+    let (_, mut other_preprocess) = fixture();
+    other_preprocess.s1 = point(99);
+    let other_commitments = derive_bound(
+        &proof,
+        &other_preprocess,
+        Kappa2Mode::Transcript,
+        digest,
+        &instance,
+    );
+    assert_ne!(strong.thetas, other_commitments.thetas);
+
+    // The legacy transcript ignores the statement.
+    proof.fiat_shamir = FiatShamirMode::Legacy;
+    let legacy_rebound = derive_bound(
+        &proof,
+        &preprocess,
+        Kappa2Mode::Transcript,
+        digest,
+        &other_instance,
+    );
+    assert_eq!(legacy.thetas, legacy_rebound.thetas);
+}
+
+#[test]
+fn test_minimum_fiat_shamir_mode_rejects_weaker_proofs() {
+    let (mut proof, _) = fixture();
+    assert_eq!(fiat_shamir_failure(FiatShamirMode::Legacy, &proof), None);
+    assert_eq!(
+        fiat_shamir_failure(FiatShamirMode::StrongV1, &proof),
+        Some(VerificationFailure::FiatShamirMode {
+            required: FiatShamirMode::StrongV1,
+            found: FiatShamirMode::Legacy,
+        })
+    );
+    proof.fiat_shamir = FiatShamirMode::StrongV1;
+    assert_eq!(fiat_shamir_failure(FiatShamirMode::StrongV1, &proof), None);
+    assert_eq!(fiat_shamir_failure(FiatShamirMode::Legacy, &proof), None);
+}
+
+#[test]
+fn test_verifier_rejects_legacy_proofs_by_default() {
+    let (proof, preprocess) = fixture();
+    let (setup_params, instance) = statement_fixture();
+    let (sigma_verify, ..) = accumulator_sigma();
+    let inputs = MemoryInputs {
+        setup_params: Some(&setup_params),
+        instance: Some(&instance),
+        ..MemoryInputs::new(VerifierArtifacts {
+            sigma_verify: &sigma_verify,
+            preprocess: &preprocess,
+            proof: &proof,
+        })
+    };
+    let verifier = Verifier::init(&inputs).unwrap();
+    assert_eq!(verifier.min_fiat_shamir, FiatShamirMode::StrongV1);
+    let legacy_rejected = Some(VerificationFailure::FiatShamirMode {
+        required: FiatShamirMode::StrongV1,
+        found: FiatShamirMode::Legacy,
+    });
+    assert_eq!(verifier.verify_with_report().failure, legacy_rejected);
+    assert_eq!(verifier.accumulate().err(), legacy_rejected);
+
+    let verifier = verifier.with_min_fiat_shamir(FiatShamirMode::Legacy);
+    assert_ne!(verifier.verify_with_report().failure, legacy_rejected);
+}
+
+fn trace(proof: &Proof, preprocess: &Preprocess) -> (VerificationChallenges, TranscriptTrace) {
+    let (setup_params, instance) = statement_fixture();
+    let statement = ProtocolStatement {
+        sigma_digest: ScalarField::from_u32(7),
+        setup_params: &setup_params,
+        s0: &preprocess.s0,
+        s1: &preprocess.s1,
+        O_pub_fix: &preprocess.O_pub_fix,
+        instance: &instance,
+    };
+    let (challenges, trace) =
+        derive_challenges_traced(proof, preprocess, Kappa2Mode::Transcript, &statement, true);
+    (challenges, trace.unwrap())
+}
+
+#[test]
+fn test_transcript_trace_records_every_step() {
+    let (mut proof, preprocess) = fixture();
+    let (challenges, trace) = trace(&proof, &preprocess);
+    let untraced = derive(&proof, &preprocess, Kappa2Mode::Transcript);
+    assert_eq!(challenges.thetas, untraced.thetas);
+    assert_eq!(challenges.kappa2, untraced.kappa2);
+
+    assert_eq!(trace.transcript, TranscriptKind::Keccak);
+    assert_eq!(trace.steps.len(), 34);
+    assert_eq!(trace.steps[0].label, "U");
+    assert_eq!(trace.steps[6].op, TraceOp::Squeeze);
+    assert_eq!(trace.steps[6].label, "theta0");
+    assert_eq!(trace.steps[33].label, "kappa2");
+    for pair in trace.steps.windows(2) {
+        assert_eq!(pair[0].state_after, pair[1].state_before);
+    }
+
+    proof.fiat_shamir = FiatShamirMode::StrongV1;
+    proof.transcript = TranscriptKind::Poseidon;
+    let (_, strong) = trace(&proof, &preprocess);
+    assert_eq!(strong.transcript, TranscriptKind::Poseidon);
+    assert_eq!(strong.steps.len(), 34 + 22);
+    assert_eq!(strong.steps[0].label, "protocol_tag");
+    assert_eq!(strong.steps[21].label, "a_pub_function[0]");
+    assert_eq!(strong.steps[22].label, "U");
+}
+
+#[test]
+fn test_solidity_transcript_layout_matches_the_transcript() {
+    let (proof, preprocess) = fixture();
+    let (_, trace) = trace(&proof, &preprocess);
+    let labels: Vec<&str> = trace.steps.iter().map(|step| step.label.as_str()).collect();
+    let layout: Vec<&str> = TRANSCRIPT_LAYOUT
+        .iter()
+        .flat_map(|(absorbed, squeezed)| absorbed.iter().chain(squeezed.iter()).copied())
+        .collect();
+    assert_eq!(labels, layout);
+
+    // Strong-v1 prepends the statement, with `name[..]` expanded over the instance vectors.
+    let mut strong_proof = proof.clone();
+    strong_proof.fiat_shamir = FiatShamirMode::StrongV1;
+    let (_, strong) = trace(&strong_proof, &preprocess);
+    let (_, instance) = statement_fixture();
+    let mut expected: Vec<String> = Vec::new();
+    for label in STATEMENT_LAYOUT {
+        match label.strip_suffix("[..]") {
+            Some(name) => {
+                let len = match name {
+                    "a_pub_user" => instance.a_pub_user.len(),
+                    "a_pub_block" => instance.a_pub_block.len(),
+                    _ => instance.a_pub_function.len(),
+                };
+                expected.extend((0..len).map(|i| format!("{name}[{i}]")));
+            }
+            None => expected.push(label.to_string()),
+        }
+    }
+    expected.extend(layout.iter().map(|label| label.to_string()));
+    let strong_labels: Vec<&str> = strong
+        .steps
+        .iter()
+        .map(|step| step.label.as_str())
+        .collect();
+    assert_eq!(strong_labels, expected);
+}
+
+#[test]
+fn test_first_divergence_finds_the_tampered_step() {
+    let (mut proof, preprocess) = fixture();
+    let (_, reference) = trace(&proof, &preprocess);
+    assert_eq!(reference.first_divergence(&reference.clone(), false), None);
+
+    let mut relabelled = reference.clone();
+    relabelled.steps[3].label = "Q_A_X".to_string();
+    relabelled.steps[4].bytes = relabelled.steps[4].bytes.to_uppercase().replace("0X", "0x");
+    assert_eq!(reference.first_divergence(&relabelled, true), None);
+    let divergence = reference.first_divergence(&relabelled, false).unwrap();
+    assert_eq!(divergence.step, 3);
+    assert_eq!(divergence.fields, vec!["label"]);
+
+    proof.proof1.R = point(99);
+    let (_, tampered) = trace(&proof, &preprocess);
+    let divergence = reference.first_divergence(&tampered, false).unwrap();
+    assert_eq!(divergence.step, 9);
+    assert_eq!(divergence.fields, vec!["bytes", "state_after"]);
+
+    let mut truncated = reference.clone();
+    truncated.steps.truncate(5);
+    let divergence = reference.first_divergence(&truncated, false).unwrap();
+    assert_eq!(divergence.step, 5);
+    assert_eq!(divergence.left.as_ref(), Some(&reference.steps[5]));
+    assert_eq!(divergence.right, None);
+    assert!(divergence.fields.is_empty());
+}
+
+#[test]
+fn test_proof_files_record_the_transcript() {
+    with_point_validation(PointValidation::Trusted, || {
+        let (mut proof, _) = fixture();
+        proof.transcript = TranscriptKind::Poseidon;
+        proof.fiat_shamir = FiatShamirMode::StrongV1;
+        let from_json = proof
+            .convert_format_for_solidity_verifier()
+            .recover_proof_from_format()
+            .unwrap();
+        assert_eq!(from_json.transcript, TranscriptKind::Poseidon);
+        assert_eq!(from_json.fiat_shamir, FiatShamirMode::StrongV1);
+        let bytes = proof.to_bytes(PointEncoding::Uncompressed);
+        let from_binary = Proof::from_bytes(&bytes).unwrap();
+        assert_eq!(from_binary.transcript, TranscriptKind::Poseidon);
+        assert_eq!(from_binary.fiat_shamir, FiatShamirMode::StrongV1);
+        // Version 1 proofs have no tags and read as Keccak and Legacy; version 2 requires them.
+        assert!(Proof::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+        let mut untagged = bytes[..bytes.len() - 2].to_vec();
+        untagged[4] = 1;
+        let untagged = Proof::from_bytes(&untagged).unwrap();
+        assert_eq!(untagged.transcript, TranscriptKind::Keccak);
+        assert_eq!(untagged.fiat_shamir, FiatShamirMode::Legacy);
+    });
+}
+
+#[test]
+fn test_bisect_failures_isolates_bad_indices() {
+    let bad = [3usize, 4, 11];
+    let indices: Vec<usize> = (0..16).collect();
+    let failures = batch::bisect_failures(&indices, &mut |subset: &[usize]| {
+        subset.iter().all(|index| !bad.contains(index))
+    });
+    assert_eq!(failures, bad);
+
+    // A single bad proof costs at most two checks per halving.
+    let mut calls = 0;
+    let failures = batch::bisect_failures(&indices, &mut |subset: &[usize]| {
+        calls += 1;
+        !subset.contains(&9)
+    });
+    assert_eq!(failures, [9]);
+    assert!(calls <= 1 + 2 * 4);
+
+    let failures = batch::bisect_failures(&indices, &mut |_: &[usize]| true);
+    assert!(failures.is_empty());
+    assert!(batch::bisect_failures(&[], &mut |_: &[usize]| false).is_empty());
+}
+
+fn assert_same_format(lhs: &Proof, rhs: &Proof) {
+    let lhs = lhs.convert_format_for_solidity_verifier();
+    let rhs = rhs.convert_format_for_solidity_verifier();
+    assert_eq!(lhs.proof_entries_part1, rhs.proof_entries_part1);
+    assert_eq!(lhs.proof_entries_part2, rhs.proof_entries_part2);
+}
+
+#[test]
+fn test_binary_proof_roundtrips_with_json() {
+    // The fixture points are off the curve, so loading them needs the trusted opt-out.
+    with_point_validation(PointValidation::Trusted, || {
+        let (proof, preprocess) = fixture();
+        let from_json = proof
+            .convert_format_for_solidity_verifier()
+            .recover_proof_from_format()
+            .unwrap();
+        let from_binary =
+            Proof::from_bytes(&from_json.to_bytes(PointEncoding::Uncompressed)).unwrap();
+        assert_same_format(&proof, &from_binary);
+
+        let recovered = Preprocess::from_bytes(&preprocess.to_bytes(PointEncoding::Uncompressed))
+            .unwrap()
+            .convert_format_for_solidity_verifier();
+        let original = preprocess.convert_format_for_solidity_verifier();
+        assert_eq!(
+            recovered.preprocess_entries_part1,
+            original.preprocess_entries_part1
+        );
+        assert_eq!(
+            recovered.preprocess_entries_part2,
+            original.preprocess_entries_part2
+        );
+    });
+}
+
+#[test]
+fn test_loading_rejects_off_curve_points_by_default() {
+    let (proof, preprocess) = fixture();
+    assert!(matches!(
+        proof
+            .convert_format_for_solidity_verifier()
+            .recover_proof_from_format(),
+        Err(TokamakError::InvalidPoint { .. })
+    ));
+    assert!(Proof::from_bytes(&proof.to_bytes(PointEncoding::Uncompressed)).is_err());
+    assert!(preprocess
+        .convert_format_for_solidity_verifier()
+        .recover_proof_from_format()
+        .is_err());
+    assert!(Preprocess::from_bytes(&preprocess.to_bytes(PointEncoding::Uncompressed)).is_err());
+}
+
+// The fixture with every point replaced by a random curve point, for codecs that validate points.
+fn curve_fixture() -> (Proof, Preprocess) {
+    let (mut proof, mut preprocess) = fixture();
+    let mut points = CurveCfg::generate_random_affine_points(22)
+        .into_iter()
+        .map(G1serde);
+    for point in [
+        &mut proof.proof0.U,
+        &mut proof.proof0.V,
+        &mut proof.proof0.W,
+        &mut proof.proof0.Q_AX,
+        &mut proof.proof0.Q_AY,
+        &mut proof.proof0.B,
+        &mut proof.proof1.R,
+        &mut proof.proof2.Q_CX,
+        &mut proof.proof2.Q_CY,
+        &mut proof.proof4.Pi_X,
+        &mut proof.proof4.Pi_Y,
+        &mut proof.proof4.M_X,
+        &mut proof.proof4.M_Y,
+        &mut proof.proof4.N_X,
+        &mut proof.proof4.N_Y,
+        &mut proof.binding.A_free,
+        &mut proof.binding.O_pub_free,
+        &mut proof.binding.O_mid,
+        &mut proof.binding.O_prv,
+        &mut preprocess.s0,
+        &mut preprocess.s1,
+        &mut preprocess.O_pub_fix,
+    ] {
+        *point = points.next().unwrap();
+    }
+    (proof, preprocess)
+}
+
+#[test]
+fn test_compressed_proof_roundtrip() {
+    let (proof, _) = curve_fixture();
+    let bytes = proof.to_bytes(PointEncoding::Compressed);
+    // Header, points, evaluations, then the transcript and Fiat-Shamir tags.
+    assert_eq!(bytes.len(), 6 + 19 * 48 + 4 * 32 + 2);
+    assert_same_format(&proof, &Proof::from_bytes(&bytes).unwrap());
+
+    // The preprocess magic is rejected by the proof decoder.
+    assert!(Proof::from_bytes(&fixture().1.to_bytes(PointEncoding::Compressed)).is_err());
+}
+
+#[test]
+fn test_abi_calldata_decodes_to_the_same_points() {
+    let (proof, preprocess) = curve_fixture();
+    let (setup_params, instance) = statement_fixture();
+    let formatted = proof.convert_format_for_solidity_verifier();
+    let formatted_preprocess = preprocess.convert_format_for_solidity_verifier();
+    let public_inputs = instance
+        .public_inputs(&setup_params)
+        .iter()
+        .map(scalar_to_hex)
+        .collect::<Vec<_>>();
+    let calldata = formatted
+        .to_abi_calldata(
+            &formatted_preprocess.preprocess_entries_part1,
+            &formatted_preprocess.preprocess_entries_part2,
+            &public_inputs,
+        )
+        .unwrap();
+
+    // Selector, five offsets, then each array's length word and entries.
+    assert_eq!(calldata[..4], VerifyCalldata::selector());
+    assert_eq!(calldata.len(), 4 + 5 * 32 + (5 + 38 + 42 + 6 + 6 + 4) * 32);
+    assert_eq!(calldata[4 + 31], 5 * 32);
+    assert_eq!(calldata[4 + 5 * 32 + 31], 38);
+
+    let decoded = VerifyCalldata::decode(&calldata).unwrap();
+    assert_eq!(decoded.proof_entries_part1, formatted.proof_entries_part1);
+    assert_eq!(decoded.proof_entries_part2, formatted.proof_entries_part2);
+    assert_eq!(decoded.public_inputs, public_inputs);
+    let recovered = FormattedProof {
+        proof_entries_part1: decoded.proof_entries_part1,
+        proof_entries_part2: decoded.proof_entries_part2,
+        transcript: formatted.transcript,
+        fiat_shamir: formatted.fiat_shamir,
+    }
+    .recover_proof_from_format()
+    .unwrap();
+    assert_same_format(&proof, &recovered);
+    let recovered_preprocess = FormattedPreprocess {
+        preprocess_entries_part1: decoded.preprocess_entries_part1,
+        preprocess_entries_part2: decoded.preprocess_entries_part2,
+    }
+    .recover_proof_from_format()
+    .unwrap();
+    assert_eq!(recovered_preprocess.s0, preprocess.s0);
+    assert_eq!(recovered_preprocess.s1, preprocess.s1);
+    assert_eq!(recovered_preprocess.O_pub_fix, preprocess.O_pub_fix);
+    let inputs = decoded
+        .public_inputs
+        .iter()
+        .map(|value| scalar_from_hex(value).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(inputs, instance.public_inputs(&setup_params));
+
+    // A part1 entry wider than uint128 and a foreign selector are both rejected.
+    let mut wide = formatted.proof_entries_part1.clone();
+    wide[0] = formatted.proof_entries_part2[0].clone();
+    assert!(FormattedProof {
+        proof_entries_part1: wide,
+        proof_entries_part2: formatted.proof_entries_part2.clone(),
+        transcript: formatted.transcript,
+        fiat_shamir: formatted.fiat_shamir,
+    }
+    .to_abi_calldata(&[], &[], &[])
+    .is_err());
+    let mut foreign = calldata;
+    foreign[0] ^= 1;
+    assert!(VerifyCalldata::decode(&foreign).is_err());
+}
+
+// A CRS with known trapdoors, so that accumulators satisfying the final pairing check can be built
+// without a proof.
+fn accumulator_sigma() -> (SigmaVerify, [ScalarField; 5], [ScalarField; 5], G1serde) {
+    let tau = Tau::gen();
+    let g = G1serde(CurveCfg::generate_random_affine_points(1)[0]);
+    let h = G2CurveCfg::generate_random_affine_points(1)[0];
+    let sigma = SigmaVerify {
+        G: g,
+        H: G2serde(h),
+        sigma_1: PartialSigma1Verify { x: g, y: g },
+        sigma_2: Sigma2::gen(&tau, &h),
+        lagrange_KL: g,
+    };
+    let lhs_trapdoors = [
+        ScalarField::one(),
+        tau.alpha.pow(4),
+        tau.alpha,
+        tau.alpha.pow(2),
+        tau.alpha.pow(3),
+    ];
+    let rhs_trapdoors = [tau.gamma, tau.eta, tau.delta, tau.x, tau.y];
+    (sigma, lhs_trapdoors, rhs_trapdoors, g)
+}
+
+fn valid_accumulator(
+    sigma: &SigmaVerify,
+    lhs_trapdoors: &[ScalarField; 5],
+    rhs_trapdoors: &[ScalarField; 5],
+    g: G1serde,
+) -> SnarkAccumulator {
+    let lhs_scalars = ScalarCfg::generate_random(5);
+    let mut rhs_scalars = ScalarCfg::generate_random(5);
+    let mut balance = ScalarField::zero();
+    for (a, t) in lhs_scalars.iter().zip(lhs_trapdoors) {
+        balance = balance + *a * *t;
+    }
+    for (c, t) in rhs_scalars.iter().zip(rhs_trapdoors).take(4) {
+        balance = balance - *c * *t;
+    }
+    rhs_scalars[4] = balance * rhs_trapdoors[4].inv();
+    let terms = SnarkPairingTerms {
+        lhs: std::array::from_fn(|i| g * lhs_scalars[i]),
+        rhs: std::array::from_fn(|i| g * rhs_scalars[i]),
+    };
+    SnarkAccumulator::from_terms(sigma.digest(), terms)
+}
+
+#[test]
+fn test_folded_accumulator_decides_the_batch() {
+    let (sigma, lhs_trapdoors, rhs_trapdoors, g) = accumulator_sigma();
+    let first = valid_accumulator(&sigma, &lhs_trapdoors, &rhs_trapdoors, g);
+    let second = valid_accumulator(&sigma, &lhs_trapdoors, &rhs_trapdoors, g);
+    assert!(first.decide(&sigma).unwrap());
+
+    let folded = SnarkAccumulator::fold_all(&[first.clone(), second.clone()]).unwrap();
+    assert_eq!(folded.count, 2);
+    assert_eq!(folded, first.fold(&second).unwrap());
+    assert!(folded.decide(&sigma).unwrap());
+    let bytes = folded.to_bytes(PointEncoding::Uncompressed);
+    assert_eq!(SnarkAccumulator::from_bytes(&bytes).unwrap(), folded);
+
+    let mut invalid = valid_accumulator(&sigma, &lhs_trapdoors, &rhs_trapdoors, g);
+    invalid.rhs[4] = invalid.rhs[4] + g;
+    assert!(!invalid.decide(&sigma).unwrap());
+    let folded_invalid = SnarkAccumulator::fold_all(&[first, invalid, second]).unwrap();
+    assert_eq!(folded_invalid.count, 3);
+    assert!(!folded_invalid.decide(&sigma).unwrap());
+}
+
+#[test]
+fn test_degenerate_accumulators_are_rejected() {
+    let (sigma, lhs_trapdoors, rhs_trapdoors, g) = accumulator_sigma();
+    let identity = SnarkAccumulator {
+        sigma_digest: FieldSerde(sigma.digest()),
+        count: 5,
+        lhs: [G1serde::zero(); 5],
+        rhs: [G1serde::zero(); 5],
+    };
+    assert!(!identity.decide(&sigma).unwrap());
+
+    let valid = valid_accumulator(&sigma, &lhs_trapdoors, &rhs_trapdoors, g);
+    let mut empty = valid.clone();
+    empty.count = 0;
+    assert!(!empty.decide(&sigma).unwrap());
+    for degenerate in [&identity, &empty] {
+        assert!(matches!(
+            valid.fold(degenerate),
+            Err(TokamakError::AccumulatorMismatch(_))
+        ));
+        assert!(matches!(
+            degenerate.fold(&valid),
+            Err(TokamakError::AccumulatorMismatch(_))
+        ));
+    }
+}
+
+#[test]
+fn test_accumulators_are_bound_to_their_crs() {
+    let (sigma, lhs_trapdoors, rhs_trapdoors, g) = accumulator_sigma();
+    let accumulator = valid_accumulator(&sigma, &lhs_trapdoors, &rhs_trapdoors, g);
+    let mut foreign = accumulator.clone();
+    foreign.sigma_digest = scalar(1);
+    assert!(matches!(
+        accumulator.fold(&foreign),
+        Err(TokamakError::AccumulatorMismatch(_))
+    ));
+    assert!(matches!(
+        foreign.decide(&sigma),
+        Err(TokamakError::AccumulatorMismatch(_))
+    ));
+    assert!(matches!(
+        SnarkAccumulator::fold_all(&[]),
+        Err(TokamakError::MissingInput(_))
+    ));
+}